@@ -29,26 +29,31 @@ use std::{env, fs, io};
 use blockstack_lib::address::b58;
 use blockstack_lib::address::AddressHashMode;
 use blockstack_lib::burnchains::bitcoin::address::{
-    ADDRESS_VERSION_MAINNET_SINGLESIG, ADDRESS_VERSION_TESTNET_SINGLESIG,
+    BitcoinAddress, ADDRESS_VERSION_MAINNET_SINGLESIG, ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
+use blockstack_lib::burnchains::bitcoin::BitcoinNetworkType;
 use blockstack_lib::burnchains::Address;
+use blockstack_lib::burnchains::Txid;
 use blockstack_lib::chainstate::stacks::{
+    AssetInfo, FungibleConditionCode, NonfungibleConditionCode, PostConditionPrincipal,
     StacksBlock, StacksMicroblock, StacksPrivateKey, StacksPublicKey, StacksTransaction,
     StacksTransactionSigner, TokenTransferMemo, TransactionAnchorMode, TransactionAuth,
-    TransactionContractCall, TransactionPayload, TransactionSmartContract,
-    TransactionSpendingCondition, TransactionVersion, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
-    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+    TransactionAuthField, TransactionAuthFlags, TransactionContractCall, TransactionPayload,
+    TransactionPostCondition, TransactionPostConditionMode, TransactionPublicKeyEncoding,
+    TransactionSmartContract, TransactionSpendingCondition, TransactionVersion,
+    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
-use blockstack_lib::codec::{Error as CodecError, StacksMessageCodec};
-use blockstack_lib::core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
+use blockstack_lib::codec::{read_next, write_next, Error as CodecError, StacksMessageCodec};
+use blockstack_lib::core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET, POX_MAX_NUM_CYCLES};
 use blockstack_lib::net::Error as NetError;
 use blockstack_lib::types::chainstate::StacksAddress;
+use blockstack_lib::util::boot::boot_code_addr;
 use blockstack_lib::util::{
     hash::hex_bytes, hash::to_hex, log, retry::LogReader, strings::StacksString,
 };
 use blockstack_lib::vm::{
     errors::{Error as ClarityError, RuntimeErrorType},
-    types::PrincipalData,
+    types::{PrincipalData, QualifiedContractIdentifier},
     ClarityName, ContractName, Value,
 };
 
@@ -63,6 +68,13 @@ This CLI has these methods:
   contract-call      used to generate and sign a contract-call transaction
   generate-sk        used to generate a secret key for transaction signing
   token-transfer     used to generate and sign a transfer transaction
+  stack-stx          used to generate and sign a PoX stack-stx transaction
+  delegate-stx       used to generate and sign a PoX delegate-stx transaction
+  delegate-stack-stx used to generate and sign a PoX delegate-stack-stx transaction
+  unsigned-tx        used to generate an unsigned singlesig or multisig transaction
+  unsigned-sponsored-tx  used to generate an unsigned sponsored transaction
+  sign-partial       used to add one signature to a partially-signed transaction
+  finalize-tx        used to turn a fully-signed partially-signed transaction into a transaction
   addresses          used to get both Bitcoin and Stacks addresses from a private key
   decode-tx          used to decode a hex-encoded transaction into a human-readable representation
   decode-block       used to decode a hex-encoded Stacks block into a human-readable representation
@@ -101,6 +113,23 @@ is that the miner chooses, but you can decide which with the following options:
   --microblock-only  indicates to mine this transaction only in a microblock
   --block-only       indicates to mine this transaction only in a block
 
+Post-conditions protect the transaction's senders, and are given with the following options:
+
+  --post-condition-mode [allow|deny]
+      whether to allow asset transfers not covered by a post-condition (default: deny)
+  --stx-post-condition [principal] [condition-code] [amount]
+  --ft-post-condition [principal] [asset] [condition-code] [amount]
+  --nft-post-condition [principal] [asset] [asset-value] [nft-condition-code]
+
+A principal is `origin`, a standard address, or a contract principal.  An asset is written as
+`[contract-address].[contract-name]::[asset-name]`, and an NFT's asset value is evaluated as a
+Clarity expression.  The condition code is one of sent-eq, sent-gt, sent-ge, sent-lt, or sent-le,
+and the NFT condition code is sent or not-sent.  e.g.,
+
+   --stx-post-condition origin sent-le 1000 \\
+   --nft-post-condition SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4 \\
+      SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::foo-nft u1 sent
+
 Arguments are supplied in one of two ways: through script evaluation or via hex encoding
 of the value serialization format. The method for supplying arguments is chosen by
 prefacing each argument with a flag:
@@ -128,6 +157,126 @@ is that the miner chooses, but you can decide which with the following options:
 
   --microblock-only  indicates to mine this transaction only in a microblock
   --block-only       indicates to mine this transaction only in a block
+
+Post-conditions protect the transaction's senders, and are given with the following options:
+
+  --post-condition-mode [allow|deny]
+      whether to allow asset transfers not covered by a post-condition (default: deny)
+  --stx-post-condition [principal] [condition-code] [amount]
+  --ft-post-condition [principal] [asset] [condition-code] [amount]
+  --nft-post-condition [principal] [asset] [asset-value] [nft-condition-code]
+
+A principal is `origin`, a standard address, or a contract principal.  An asset is written as
+`[contract-address].[contract-name]::[asset-name]`, and an NFT's asset value is evaluated as a
+Clarity expression.  The condition code is one of sent-eq, sent-gt, sent-ge, sent-lt, or sent-le,
+and the NFT condition code is sent or not-sent.  e.g.,
+
+   --stx-post-condition origin sent-le 1000 \\
+   --nft-post-condition SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4 \\
+      SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::foo-nft u1 sent
+";
+
+const STACK_STX_USAGE: &str = "blockstack-cli (options) stack-stx [origin-secret-key-hex] [fee-rate] [nonce] [amount-ustx] [pox-address] [start-burn-height] [lock-period]
+
+The stack-stx command generates and signs a call to the PoX contract's stack-stx function, which
+locks the origin's STX for [lock-period] reward cycles and pays its reward slots to [pox-address].
+If successful, this command outputs the hex string encoding of the transaction to stdout, and
+exits with code 0.
+
+The PoX address is a Bitcoin address (P2PKH or P2SH) for the same network as the transaction.
+The start burn height must fall in the current reward cycle.
+
+Before the transaction is made, the call is checked against the same rules as the PoX contract's
+can-stack-stx: the amount must be positive, the lock period must be between 1 and 12 reward
+cycles, and the PoX address must be valid.  The stacking minimum depends on the chain state, so
+it is only checked if given with the following option (see the /v2/pox endpoint's
+`min_amount_ustx`):
+
+  --stacking-minimum [amount-ustx]
+
+It takes the same anchor mode and post-condition options as contract-call.  e.g.,
+
+   blockstack-cli --testnet stack-stx $secret_key 10 0 100000000000 \\
+      mvWRFPELmpCHSkFQ7o9EVdCd9eXeUTa9T8 2101 6
+";
+
+const DELEGATE_STX_USAGE: &str = "blockstack-cli (options) delegate-stx [origin-secret-key-hex] [fee-rate] [nonce] [amount-ustx] [delegate-to] [until-burn-height] [pox-address]
+
+The delegate-stx command generates and signs a call to the PoX contract's delegate-stx function,
+which allows [delegate-to] to stack up to [amount-ustx] of the origin's STX.  If successful, this
+command outputs the hex string encoding of the transaction to stdout, and exits with code 0.
+
+The delegation lasts until [until-burn-height], and the delegate must stack to [pox-address].
+Both are optional; either may be omitted from the end of the arguments, or given as `none`.
+The PoX address is a Bitcoin address (P2PKH or P2SH) for the same network as the transaction.
+
+It takes the same anchor mode and post-condition options as contract-call.
+";
+
+const DELEGATE_STACK_STX_USAGE: &str = "blockstack-cli (options) delegate-stack-stx [origin-secret-key-hex] [fee-rate] [nonce] [stacker] [amount-ustx] [pox-address] [start-burn-height] [lock-period]
+
+The delegate-stack-stx command generates and signs a call to the PoX contract's delegate-stack-stx
+function, with which a delegate locks [amount-ustx] of a [stacker]'s STX for [lock-period] reward
+cycles.  If successful, this command outputs the hex string encoding of the transaction to stdout,
+and exits with code 0.
+
+The PoX address is a Bitcoin address (P2PKH or P2SH) for the same network as the transaction.
+The start burn height must fall in the current reward cycle.
+
+Before the transaction is made, the call is checked against the same rules as the PoX contract's
+minimal-can-stack-stx: the amount must be positive, the lock period must be between 1 and 12
+reward cycles, and the PoX address must be valid.
+
+It takes the same anchor mode and post-condition options as contract-call.
+";
+
+const UNSIGNED_TX_USAGE: &str = "blockstack-cli (options) unsigned-tx [origin-signer] [fee-rate] [nonce] [method] [method-args...]
+
+The unsigned-tx command generates an unsigned transaction, to be signed with sign-partial and
+then turned into a transaction with finalize-tx.  If successful, this command outputs the hex
+string encoding of the partially-signed transaction to stdout, and exits with code 0.
+
+The signer is either a hex-encoded public key, for a single-signature account, or
+`M:pubkey1,pubkey2,...` for an M-of-N multi-signature account.  Multi-signature keys must be
+listed in the order that the account's address was generated with, and must sign in that order.
+
+The method is one of publish, contract-call, token-transfer, stack-stx, delegate-stx, or
+delegate-stack-stx, and its arguments are the ones that method takes after its nonce.  e.g.,
+
+   blockstack-cli unsigned-tx 2:$pubkey_1,$pubkey_2,$pubkey_3 10 0 \\
+      token-transfer ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV 1000
+
+The transaction can be mined in a Stacks block, a Stacks microblock, or either.  The default
+is that the miner chooses, but you can decide which with the following options:
+
+  --microblock-only  indicates to mine this transaction only in a microblock
+  --block-only       indicates to mine this transaction only in a block
+
+It also takes the same post-condition options as contract-call.
+";
+
+const UNSIGNED_SPONSORED_TX_USAGE: &str = "blockstack-cli (options) unsigned-sponsored-tx [origin-signer] [origin-nonce] [sponsor-signer] [fee-rate] [sponsor-nonce] [method] [method-args...]
+
+The unsigned-sponsored-tx command generates an unsigned sponsored transaction, whose fee is paid
+by the sponsor.  It takes the same signers, methods, and options as unsigned-tx.  The origin's
+signers sign first with sign-partial, and then the sponsor's.  If successful, this command
+outputs the hex string encoding of the partially-signed transaction to stdout, and exits with
+code 0.
+";
+
+const SIGN_PARTIAL_USAGE: &str =
+    "blockstack-cli (options) sign-partial [partially-signed-tx-hex] [secret-key-hex]
+
+The sign-partial command adds one signature to a partially-signed transaction.  If successful,
+this command outputs the hex string encoding of the partially-signed transaction to stdout, and
+exits with code 0.
+";
+
+const FINALIZE_TX_USAGE: &str = "blockstack-cli (options) finalize-tx [partially-signed-tx-hex]
+
+The finalize-tx command checks that a partially-signed transaction carries all of its signatures,
+and if so, outputs the hex string encoding of the signed transaction to stdout, and exits with
+code 0.
 ";
 
 const GENERATE_USAGE: &str = "blockstack-cli (options) generate-sk
@@ -347,6 +496,769 @@ fn parse_anchor_mode(
     }
 }
 
+fn parse_post_condition_principal(principal: &str) -> Result<PostConditionPrincipal, CliError> {
+    if principal == "origin" {
+        return Ok(PostConditionPrincipal::Origin);
+    }
+    match PrincipalData::parse(principal)
+        .map_err(|_e| CliError::Message(format!("Failed to parse principal '{}'", principal)))?
+    {
+        PrincipalData::Standard(data) => Ok(PostConditionPrincipal::Standard(data.into())),
+        PrincipalData::Contract(contract_id) => Ok(PostConditionPrincipal::Contract(
+            contract_id.issuer.into(),
+            contract_id.name,
+        )),
+    }
+}
+
+fn parse_asset_info(asset: &str) -> Result<AssetInfo, CliError> {
+    let parts: Vec<&str> = asset.splitn(2, "::").collect();
+    if parts.len() != 2 {
+        return Err(CliError::Message(format!(
+            "Failed to parse asset '{}': expected [contract-address].[contract-name]::[asset-name]",
+            asset
+        )));
+    }
+    let contract_id = QualifiedContractIdentifier::parse(parts[0]).map_err(|_e| {
+        CliError::Message(format!("Failed to parse asset contract '{}'", parts[0]))
+    })?;
+    let asset_name = ClarityName::try_from(parts[1].to_string())?;
+    Ok(AssetInfo {
+        contract_address: contract_id.issuer.into(),
+        contract_name: contract_id.name,
+        asset_name,
+    })
+}
+
+fn parse_fungible_condition_code(code: &str) -> Result<FungibleConditionCode, CliError> {
+    match code {
+        "sent-eq" => Ok(FungibleConditionCode::SentEq),
+        "sent-gt" => Ok(FungibleConditionCode::SentGt),
+        "sent-ge" => Ok(FungibleConditionCode::SentGe),
+        "sent-lt" => Ok(FungibleConditionCode::SentLt),
+        "sent-le" => Ok(FungibleConditionCode::SentLe),
+        _ => Err(CliError::Message(format!(
+            "Failed to parse fungible condition code '{}': expected one of sent-eq, sent-gt, sent-ge, sent-lt, or sent-le",
+            code
+        ))),
+    }
+}
+
+fn parse_nonfungible_condition_code(code: &str) -> Result<NonfungibleConditionCode, CliError> {
+    match code {
+        "sent" => Ok(NonfungibleConditionCode::Sent),
+        "not-sent" => Ok(NonfungibleConditionCode::NotSent),
+        _ => Err(CliError::Message(format!(
+            "Failed to parse nonfungible condition code '{}': expected sent or not-sent",
+            code
+        ))),
+    }
+}
+
+/// Remove the post-condition options from `args`, and return the post-condition mode and the
+/// post-conditions they describe.  The mode defaults to `deny`.
+fn parse_post_conditions(
+    args: &mut Vec<String>,
+    usage: &str,
+) -> Result<(TransactionPostConditionMode, Vec<TransactionPostCondition>), CliError> {
+    let mut mode = None;
+    let mut post_conditions = vec![];
+    let mut i = 0;
+    while i < args.len() {
+        let num_flag_args = match args[i].as_str() {
+            "--post-condition-mode" => 1,
+            "--stx-post-condition" => 3,
+            "--ft-post-condition" => 4,
+            "--nft-post-condition" => 4,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if i + num_flag_args >= args.len() {
+            return Err(CliError::Message(format!(
+                "Incorrect argument count supplied to {} \n\nUSAGE:\n {}",
+                &args[i], usage
+            )));
+        }
+
+        let flag_args: Vec<String> = args.drain(i..(i + num_flag_args + 1)).collect();
+        match flag_args[0].as_str() {
+            "--post-condition-mode" => {
+                if mode.is_some() {
+                    return Err(CliError::Message(format!("USAGE:\n {}", usage)));
+                }
+                mode = match flag_args[1].as_str() {
+                    "allow" => Some(TransactionPostConditionMode::Allow),
+                    "deny" => Some(TransactionPostConditionMode::Deny),
+                    _ => {
+                        return Err(CliError::Message(format!(
+                            "Failed to parse post-condition mode '{}': expected allow or deny",
+                            &flag_args[1]
+                        )))
+                    }
+                };
+            }
+            "--stx-post-condition" => {
+                post_conditions.push(TransactionPostCondition::STX(
+                    parse_post_condition_principal(&flag_args[1])?,
+                    parse_fungible_condition_code(&flag_args[2])?,
+                    flag_args[3].parse()?,
+                ));
+            }
+            "--ft-post-condition" => {
+                post_conditions.push(TransactionPostCondition::Fungible(
+                    parse_post_condition_principal(&flag_args[1])?,
+                    parse_asset_info(&flag_args[2])?,
+                    parse_fungible_condition_code(&flag_args[3])?,
+                    flag_args[4].parse()?,
+                ));
+            }
+            "--nft-post-condition" => {
+                let asset_value = blockstack_lib::clarity::vm_execute(&flag_args[3])?
+                    .ok_or("Supplied asset value did not evaluate to a Value")?;
+                post_conditions.push(TransactionPostCondition::Nonfungible(
+                    parse_post_condition_principal(&flag_args[1])?,
+                    parse_asset_info(&flag_args[2])?,
+                    asset_value,
+                    parse_nonfungible_condition_code(&flag_args[4])?,
+                ));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    Ok((
+        mode.unwrap_or(TransactionPostConditionMode::Deny),
+        post_conditions,
+    ))
+}
+
+fn post_condition_principal_to_string(principal: &PostConditionPrincipal) -> String {
+    match principal {
+        PostConditionPrincipal::Origin => "origin".to_string(),
+        PostConditionPrincipal::Standard(addr) => addr.to_string(),
+        PostConditionPrincipal::Contract(addr, contract_name) => {
+            format!("{}.{}", addr, contract_name)
+        }
+    }
+}
+
+fn asset_info_to_string(asset_info: &AssetInfo) -> String {
+    format!(
+        "{}.{}::{}",
+        &asset_info.contract_address, &asset_info.contract_name, &asset_info.asset_name
+    )
+}
+
+fn fungible_condition_code_to_str(code: &FungibleConditionCode) -> &'static str {
+    match code {
+        FungibleConditionCode::SentEq => "sent-eq",
+        FungibleConditionCode::SentGt => "sent-gt",
+        FungibleConditionCode::SentGe => "sent-ge",
+        FungibleConditionCode::SentLt => "sent-lt",
+        FungibleConditionCode::SentLe => "sent-le",
+    }
+}
+
+fn nonfungible_condition_code_to_str(code: &NonfungibleConditionCode) -> &'static str {
+    match code {
+        NonfungibleConditionCode::Sent => "sent",
+        NonfungibleConditionCode::NotSent => "not-sent",
+    }
+}
+
+/// Render a post-condition as JSON, using the same principal, asset, and condition code syntax
+/// that the post-condition options take.
+fn post_condition_to_json(post_condition: &TransactionPostCondition) -> serde_json::Value {
+    match post_condition {
+        TransactionPostCondition::STX(principal, code, amount) => serde_json::json!({
+            "type": "stx",
+            "principal": post_condition_principal_to_string(principal),
+            "condition_code": fungible_condition_code_to_str(code),
+            "amount": amount,
+        }),
+        TransactionPostCondition::Fungible(principal, asset_info, code, amount) => {
+            serde_json::json!({
+                "type": "fungible",
+                "principal": post_condition_principal_to_string(principal),
+                "asset": asset_info_to_string(asset_info),
+                "condition_code": fungible_condition_code_to_str(code),
+                "amount": amount,
+            })
+        }
+        TransactionPostCondition::Nonfungible(principal, asset_info, asset_value, code) => {
+            serde_json::json!({
+                "type": "nonfungible",
+                "principal": post_condition_principal_to_string(principal),
+                "asset": asset_info_to_string(asset_info),
+                "asset_value": format!("{}", asset_value),
+                "condition_code": nonfungible_condition_code_to_str(code),
+            })
+        }
+    }
+}
+
+/// Build a smart contract payload from `[contract-name] [file-name.clar]`, reading the contract
+/// from stdin if the file name is `-`.
+fn make_contract_publish_payload(args: &[String]) -> Result<TransactionPayload, CliError> {
+    if args.len() != 2 {
+        return Err("publish requires a contract name and a contract file".into());
+    }
+    let contract_name = &args[0];
+    let contract_file = &args[1];
+
+    let contract_contents = if contract_file == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        fs::read_to_string(contract_file)?
+    };
+
+    let payload = make_contract_publish(contract_name.clone(), contract_contents)?;
+    Ok(payload.into())
+}
+
+/// Build a contract-call payload from `[contract-publisher-address] [contract-name]
+/// [function-name] [args...]`, where each argument is a `-e` or `-x` pair.
+fn make_contract_call_payload(args: &[String]) -> Result<TransactionPayload, CliError> {
+    if args.len() < 3 {
+        return Err(
+            "contract-call requires a contract address, contract name, and function name".into(),
+        );
+    }
+    let contract_address = &args[0];
+    let contract_name = &args[1];
+    let function_name = &args[2];
+
+    let val_args = &args[3..];
+
+    if val_args.len() % 2 != 0 {
+        return Err(
+            "contract-call arguments must be supplied as a list of `-e ...` or `-x 0000...` pairs"
+                .into(),
+        );
+    }
+
+    let mut arg_iterator = 0;
+    let mut values = Vec::new();
+    while arg_iterator < val_args.len() {
+        let eval_method = &val_args[arg_iterator];
+        let input = &val_args[arg_iterator + 1];
+        let value = match eval_method.as_str() {
+            "-x" => {
+                Value::try_deserialize_hex_untyped(input)?
+            },
+            "-e" => {
+                blockstack_lib::clarity::vm_execute(input)?
+                    .ok_or("Supplied argument did not evaluate to a Value")?
+            },
+            _ => {
+                return Err("contract-call arguments must be supplied as a list of `-e ...` or `-x 0000...` pairs".into())
+            }
+        };
+
+        values.push(value);
+        arg_iterator += 2;
+    }
+
+    let payload = make_contract_call(
+        contract_address.clone(),
+        contract_name.clone(),
+        function_name.clone(),
+        values,
+    )?;
+    Ok(payload.into())
+}
+
+/// Build a token-transfer payload from `[recipient-address] [amount] [memo]`.  The memo is
+/// optional.
+fn make_token_transfer_payload(args: &[String]) -> Result<TransactionPayload, CliError> {
+    if args.len() < 2 {
+        return Err("token-transfer requires a recipient address and an amount".into());
+    }
+    let recipient_address =
+        PrincipalData::parse(&args[0]).map_err(|_e| "Failed to parse recipient")?;
+    let amount = args[1].parse()?;
+    let memo = {
+        let mut memo = [0; 34];
+        let mut bytes = if args.len() == 3 {
+            args[2].as_bytes().to_vec()
+        } else {
+            vec![]
+        };
+        bytes.resize(34, 0);
+        memo.copy_from_slice(&bytes);
+        TokenTransferMemo(memo)
+    };
+
+    Ok(TransactionPayload::TokenTransfer(
+        recipient_address,
+        amount,
+        memo,
+    ))
+}
+
+/// Parse a Bitcoin address into the PoX contract's `(tuple (version (buff 1)) (hashbytes (buff
+/// 20)))` representation.  The address must be for the same network as the transaction.
+fn parse_pox_address(address: &str, version: TransactionVersion) -> Result<Value, CliError> {
+    let btc_address = BitcoinAddress::from_b58(address)
+        .map_err(|_e| CliError::Message(format!("Failed to parse PoX address '{}'", address)))?;
+    let mainnet = version == TransactionVersion::Mainnet;
+    if (btc_address.network_id == BitcoinNetworkType::Mainnet) != mainnet {
+        return Err(CliError::Message(format!(
+            "PoX address '{}' is not a {} Bitcoin address",
+            address,
+            if mainnet { "mainnet" } else { "testnet" }
+        )));
+    }
+    let pox_addr = StacksAddress::from_bitcoin_address(&btc_address);
+    Ok(Value::Tuple(pox_addr.as_clarity_tuple()))
+}
+
+/// Parse an optional argument, which is either missing or given as `none` if it is absent.
+fn parse_optional_arg<'a>(args: &'a [String], index: usize) -> Option<&'a str> {
+    match args.get(index) {
+        Some(arg) if arg != "none" => Some(arg.as_str()),
+        _ => None,
+    }
+}
+
+/// Remove the `--stacking-minimum [amount-ustx]` option from the arguments, if present.
+fn parse_stacking_minimum(args: &mut Vec<String>, usage: &str) -> Result<Option<u128>, CliError> {
+    match args.iter().position(|arg| arg == "--stacking-minimum") {
+        Some(ix) => {
+            if ix + 1 >= args.len() {
+                return Err(CliError::Message(format!("USAGE:\n {}", usage)));
+            }
+            let stacking_minimum = args[ix + 1].parse()?;
+            args.drain(ix..ix + 2);
+            Ok(Some(stacking_minimum))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Check a stacking call against the same rules as the PoX contract's `can-stack-stx`, or
+/// `minimal-can-stack-stx` if no stacking minimum is given.  Whether or not the stacker has voted
+/// to reject PoX depends on the chain state, so that is left to the contract.
+fn check_can_stack_stx(
+    amount_ustx: u128,
+    lock_period: u128,
+    stacking_minimum: Option<u128>,
+) -> Result<(), CliError> {
+    if let Some(stacking_minimum) = stacking_minimum {
+        if amount_ustx < stacking_minimum {
+            return Err(CliError::Message(format!(
+                "Cannot stack: amount {} is below the stacking minimum {}",
+                amount_ustx, stacking_minimum
+            )));
+        }
+    }
+    if amount_ustx == 0 {
+        return Err("Cannot stack: amount must be positive".into());
+    }
+    if lock_period < 1 || lock_period > POX_MAX_NUM_CYCLES as u128 {
+        return Err(CliError::Message(format!(
+            "Cannot stack: lock period must be between 1 and {} reward cycles",
+            POX_MAX_NUM_CYCLES
+        )));
+    }
+    Ok(())
+}
+
+/// Build a contract-call payload for a function in the PoX boot contract.
+fn make_pox_call_payload(
+    function_name: &str,
+    function_args: Vec<Value>,
+    version: TransactionVersion,
+) -> Result<TransactionPayload, CliError> {
+    let payload = TransactionContractCall {
+        address: boot_code_addr(version == TransactionVersion::Mainnet),
+        contract_name: ContractName::try_from("pox".to_string())?,
+        function_name: ClarityName::try_from(function_name.to_string())?,
+        function_args,
+    };
+    Ok(payload.into())
+}
+
+/// Build a stack-stx payload from `[amount-ustx] [pox-address] [start-burn-height]
+/// [lock-period]`, checking it against `can-stack-stx` first.
+fn make_stack_stx_payload(
+    args: &[String],
+    version: TransactionVersion,
+    stacking_minimum: Option<u128>,
+) -> Result<TransactionPayload, CliError> {
+    if args.len() != 4 {
+        return Err(
+            "stack-stx requires an amount, a PoX address, a start burn height, and a lock period"
+                .into(),
+        );
+    }
+    let amount_ustx: u128 = args[0].parse()?;
+    let pox_addr = parse_pox_address(&args[1], version)?;
+    let start_burn_height: u128 = args[2].parse()?;
+    let lock_period: u128 = args[3].parse()?;
+
+    check_can_stack_stx(amount_ustx, lock_period, stacking_minimum)?;
+
+    make_pox_call_payload(
+        "stack-stx",
+        vec![
+            Value::UInt(amount_ustx),
+            pox_addr,
+            Value::UInt(start_burn_height),
+            Value::UInt(lock_period),
+        ],
+        version,
+    )
+}
+
+/// Build a delegate-stx payload from `[amount-ustx] [delegate-to] [until-burn-height]
+/// [pox-address]`.  The last two are optional.
+fn make_delegate_stx_payload(
+    args: &[String],
+    version: TransactionVersion,
+) -> Result<TransactionPayload, CliError> {
+    if args.len() < 2 || args.len() > 4 {
+        return Err("delegate-stx requires an amount and a delegate".into());
+    }
+    let amount_ustx: u128 = args[0].parse()?;
+    let delegate_to = PrincipalData::parse(&args[1]).map_err(|_e| "Failed to parse delegate")?;
+    let until_burn_height = match parse_optional_arg(args, 2) {
+        Some(height) => Value::some(Value::UInt(height.parse()?))?,
+        None => Value::none(),
+    };
+    let pox_addr = match parse_optional_arg(args, 3) {
+        Some(address) => Value::some(parse_pox_address(address, version)?)?,
+        None => Value::none(),
+    };
+
+    make_pox_call_payload(
+        "delegate-stx",
+        vec![
+            Value::UInt(amount_ustx),
+            Value::Principal(delegate_to),
+            until_burn_height,
+            pox_addr,
+        ],
+        version,
+    )
+}
+
+/// Build a delegate-stack-stx payload from `[stacker] [amount-ustx] [pox-address]
+/// [start-burn-height] [lock-period]`, checking it against `minimal-can-stack-stx` first.
+fn make_delegate_stack_stx_payload(
+    args: &[String],
+    version: TransactionVersion,
+) -> Result<TransactionPayload, CliError> {
+    if args.len() != 5 {
+        return Err("delegate-stack-stx requires a stacker, an amount, a PoX address, a start burn height, and a lock period".into());
+    }
+    let stacker = PrincipalData::parse(&args[0]).map_err(|_e| "Failed to parse stacker")?;
+    let amount_ustx: u128 = args[1].parse()?;
+    let pox_addr = parse_pox_address(&args[2], version)?;
+    let start_burn_height: u128 = args[3].parse()?;
+    let lock_period: u128 = args[4].parse()?;
+
+    check_can_stack_stx(amount_ustx, lock_period, None)?;
+
+    make_pox_call_payload(
+        "delegate-stack-stx",
+        vec![
+            Value::Principal(stacker),
+            Value::UInt(amount_ustx),
+            pox_addr,
+            Value::UInt(start_burn_height),
+            Value::UInt(lock_period),
+        ],
+        version,
+    )
+}
+
+/// Build a payload for one of the `publish`, `contract-call`, `token-transfer`, `stack-stx`,
+/// `delegate-stx`, or `delegate-stack-stx` methods, given that method's arguments after the
+/// secret key, fee, and nonce.
+fn make_payload(
+    method: &str,
+    args: &[String],
+    version: TransactionVersion,
+) -> Result<TransactionPayload, CliError> {
+    match method {
+        "publish" => make_contract_publish_payload(args),
+        "contract-call" => make_contract_call_payload(args),
+        "token-transfer" => make_token_transfer_payload(args),
+        "stack-stx" => make_stack_stx_payload(args, version, None),
+        "delegate-stx" => make_delegate_stx_payload(args, version),
+        "delegate-stack-stx" => make_delegate_stack_stx_payload(args, version),
+        _ => Err(CliError::Message(format!(
+            "Unsupported transaction method '{}': expected one of publish, contract-call, token-transfer, stack-stx, delegate-stx, or delegate-stack-stx",
+            method
+        ))),
+    }
+}
+
+/// Parse a signer into an unsigned spending condition.  A signer is either a single hex-encoded
+/// public key (a P2PKH singlesig account), or `M:pubkey1,pubkey2,...` (an M-of-N P2SH multisig
+/// account).  Multisig public keys must be listed in the order in which they will sign.
+fn parse_spending_condition(signer: &str) -> Result<TransactionSpendingCondition, CliError> {
+    let parts: Vec<&str> = signer.splitn(2, ':').collect();
+    if parts.len() == 1 {
+        let pubkey = StacksPublicKey::from_hex(parts[0])
+            .map_err(|e| CliError::Message(format!("Failed to parse public key: {}", e)))?;
+        return Ok(TransactionSpendingCondition::new_singlesig_p2pkh(pubkey)
+            .ok_or("Failed to create p2pkh spending condition from public key")?);
+    }
+
+    let num_sigs: u16 = parts[0].parse()?;
+    let mut pubkeys = vec![];
+    for pubkey_hex in parts[1].split(',') {
+        let pubkey = StacksPublicKey::from_hex(pubkey_hex)
+            .map_err(|e| CliError::Message(format!("Failed to parse public key: {}", e)))?;
+        pubkeys.push(pubkey);
+    }
+    if num_sigs == 0 || (num_sigs as usize) > pubkeys.len() {
+        return Err(CliError::Message(format!(
+            "Invalid multisig signer: {} signatures required of {} public keys",
+            num_sigs,
+            pubkeys.len()
+        )));
+    }
+
+    let mut spending_condition =
+        TransactionSpendingCondition::new_multisig_p2sh(num_sigs, pubkeys.clone())
+            .ok_or("Failed to create p2sh spending condition from public keys")?;
+    if let TransactionSpendingCondition::Multisig(ref mut data) = spending_condition {
+        data.fields = pubkeys
+            .into_iter()
+            .map(|pubkey| TransactionAuthField::PublicKey(pubkey))
+            .collect();
+    }
+    Ok(spending_condition)
+}
+
+/// Remove a multisig spending condition's auth fields and signature threshold, so that the
+/// condition can be serialized before it has all of its signatures.
+fn take_auth_fields(
+    spending_condition: &mut TransactionSpendingCondition,
+) -> (Vec<TransactionAuthField>, u16) {
+    match spending_condition {
+        TransactionSpendingCondition::Singlesig(_) => (vec![], 0),
+        TransactionSpendingCondition::Multisig(ref mut data) => {
+            let fields = std::mem::replace(&mut data.fields, vec![]);
+            let signatures_required = data.signatures_required;
+            data.signatures_required = 0;
+            (fields, signatures_required)
+        }
+    }
+}
+
+fn restore_auth_fields(
+    spending_condition: &mut TransactionSpendingCondition,
+    fields: Vec<TransactionAuthField>,
+    signatures_required: u16,
+) -> Result<(), CliError> {
+    match spending_condition {
+        TransactionSpendingCondition::Singlesig(_) => {
+            if fields.len() > 0 || signatures_required > 0 {
+                return Err("Malformed partially-signed transaction: auth fields given for a singlesig spending condition".into());
+            }
+        }
+        TransactionSpendingCondition::Multisig(ref mut data) => {
+            if data.fields.len() > 0 || data.signatures_required > 0 {
+                return Err("Malformed partially-signed transaction: multisig auth fields must follow the transaction".into());
+            }
+            data.fields = fields;
+            data.signatures_required = signatures_required;
+        }
+    }
+    Ok(())
+}
+
+/// Encode a transaction that may be missing signatures.  Multisig spending conditions cannot be
+/// serialized until they carry all of their signatures, so the transaction is written with its
+/// multisig auth fields removed, followed by the origin's and then the sponsor's auth fields and
+/// signature thresholds.
+fn encode_partial_tx(tx: &StacksTransaction) -> String {
+    let mut tx = tx.clone();
+    let (origin_fields, origin_required, sponsor_fields, sponsor_required) = match tx.auth {
+        TransactionAuth::Standard(ref mut origin) => {
+            let (origin_fields, origin_required) = take_auth_fields(origin);
+            (origin_fields, origin_required, vec![], 0)
+        }
+        TransactionAuth::Sponsored(ref mut origin, ref mut sponsor) => {
+            let (origin_fields, origin_required) = take_auth_fields(origin);
+            let (sponsor_fields, sponsor_required) = take_auth_fields(sponsor);
+            (
+                origin_fields,
+                origin_required,
+                sponsor_fields,
+                sponsor_required,
+            )
+        }
+    };
+
+    let mut bytes = vec![];
+    tx.consensus_serialize(&mut bytes)
+        .expect("FATAL: invalid transaction");
+    write_next(&mut bytes, &origin_fields).expect("FATAL: invalid auth fields");
+    write_next(&mut bytes, &origin_required).expect("FATAL: invalid auth fields");
+    write_next(&mut bytes, &sponsor_fields).expect("FATAL: invalid auth fields");
+    write_next(&mut bytes, &sponsor_required).expect("FATAL: invalid auth fields");
+    to_hex(&bytes)
+}
+
+/// Decode a transaction encoded with `encode_partial_tx()`.
+fn decode_partial_tx(partial_tx: &str) -> Result<StacksTransaction, CliError> {
+    let bytes = hex_bytes(partial_tx)?;
+    let mut cursor = io::Cursor::new(&bytes);
+    let mut tx = StacksTransaction::consensus_deserialize(&mut cursor)?;
+    let origin_fields: Vec<TransactionAuthField> = read_next(&mut cursor)?;
+    let origin_required: u16 = read_next(&mut cursor)?;
+    let sponsor_fields: Vec<TransactionAuthField> = read_next(&mut cursor)?;
+    let sponsor_required: u16 = read_next(&mut cursor)?;
+    if (cursor.position() as usize) != bytes.len() {
+        return Err("Malformed partially-signed transaction: trailing bytes".into());
+    }
+
+    match tx.auth {
+        TransactionAuth::Standard(ref mut origin) => {
+            if sponsor_fields.len() > 0 || sponsor_required > 0 {
+                return Err("Malformed partially-signed transaction: sponsor auth fields given for a standard transaction".into());
+            }
+            restore_auth_fields(origin, origin_fields, origin_required)?;
+        }
+        TransactionAuth::Sponsored(ref mut origin, ref mut sponsor) => {
+            restore_auth_fields(origin, origin_fields, origin_required)?;
+            restore_auth_fields(sponsor, sponsor_fields, sponsor_required)?;
+        }
+    }
+    Ok(tx)
+}
+
+/// Add one signature to a spending condition, given the sighash at the start of that spending
+/// condition.  Multisig signatures must be added in the order of the condition's public keys,
+/// since each signature commits to the signatures before it.
+fn sign_spending_condition(
+    spending_condition: &mut TransactionSpendingCondition,
+    initial_sighash: &Txid,
+    auth_flag: &TransactionAuthFlags,
+    privk: &StacksPrivateKey,
+) -> Result<(), CliError> {
+    let pubkey = StacksPublicKey::from_private(privk);
+    match spending_condition {
+        TransactionSpendingCondition::Singlesig(ref mut data) => {
+            let signer = StacksAddress::from_public_keys(
+                0,
+                &data.hash_mode.to_address_hash_mode(),
+                1,
+                &vec![pubkey],
+            )
+            .ok_or("Failed to hash public key")?;
+            if signer.bytes != data.signer {
+                return Err("Secret key does not match this transaction's signer".into());
+            }
+
+            let (sig, _) = TransactionSpendingCondition::next_signature(
+                initial_sighash,
+                auth_flag,
+                data.tx_fee,
+                data.nonce,
+                privk,
+            )?;
+            data.set_signature(sig);
+        }
+        TransactionSpendingCondition::Multisig(ref mut data) => {
+            let key_index = data
+                .fields
+                .iter()
+                .position(|field| match field {
+                    TransactionAuthField::PublicKey(ref field_pubkey) => *field_pubkey == pubkey,
+                    TransactionAuthField::Signature(..) => false,
+                })
+                .ok_or("Secret key does not match any unsigned public key of this transaction's signer")?;
+            if data.fields[(key_index + 1)..]
+                .iter()
+                .any(|field| field.is_signature())
+            {
+                return Err("Multisig signatures must be added in the order of the signer's public keys, but a later key has already signed".into());
+            }
+
+            let mut cur_sighash = initial_sighash.clone();
+            for field in data.fields[..key_index].iter() {
+                if let TransactionAuthField::Signature(ref key_encoding, ref sig) = field {
+                    let (_, next_sighash) = TransactionSpendingCondition::next_verification(
+                        &cur_sighash,
+                        auth_flag,
+                        data.tx_fee,
+                        data.nonce,
+                        key_encoding,
+                        sig,
+                    )?;
+                    cur_sighash = next_sighash;
+                }
+            }
+
+            let (sig, _) = TransactionSpendingCondition::next_signature(
+                &cur_sighash,
+                auth_flag,
+                data.tx_fee,
+                data.nonce,
+                privk,
+            )?;
+            let key_encoding = if privk.compress_public() {
+                TransactionPublicKeyEncoding::Compressed
+            } else {
+                TransactionPublicKeyEncoding::Uncompressed
+            };
+            data.fields[key_index] = TransactionAuthField::Signature(key_encoding, sig);
+        }
+    }
+    Ok(())
+}
+
+/// Add one signature to a partially-signed transaction.  The origin signs first; once it has all
+/// of its signatures, a sponsored transaction's sponsor signs over the origin's sighash.
+fn sign_partial_tx(tx: &mut StacksTransaction, privk: &StacksPrivateKey) -> Result<(), CliError> {
+    let origin_signed = {
+        let origin = tx.auth.origin();
+        origin.num_signatures() >= origin.signatures_required()
+    };
+
+    if !origin_signed {
+        let initial_sighash = {
+            let mut tx_copy = tx.clone();
+            tx_copy.auth = tx_copy.auth.into_initial_sighash_auth();
+            tx_copy.txid()
+        };
+        match tx.auth {
+            TransactionAuth::Standard(ref mut origin)
+            | TransactionAuth::Sponsored(ref mut origin, _) => sign_spending_condition(
+                origin,
+                &initial_sighash,
+                &TransactionAuthFlags::AuthStandard,
+                privk,
+            ),
+        }
+    } else {
+        let origin_sighash = tx.verify_origin()?;
+        match tx.auth {
+            TransactionAuth::Sponsored(_, ref mut sponsor)
+                if sponsor.num_signatures() < sponsor.signatures_required() =>
+            {
+                sign_spending_condition(
+                    sponsor,
+                    &origin_sighash,
+                    &TransactionAuthFlags::AuthSponsored,
+                    privk,
+                )
+            }
+            _ => Err("Transaction is already fully signed".into()),
+        }
+    }
+}
+
 fn handle_contract_publish(
     args_slice: &[String],
     version: TransactionVersion,
@@ -367,24 +1279,14 @@ fn handle_contract_publish(
     let sk_publisher = &args[0];
     let tx_fee = args[1].parse()?;
     let nonce = args[2].parse()?;
-    let contract_name = &args[3];
-    let contract_file = &args[4];
-
-    let contract_contents = if contract_file == "-" {
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        buffer
-    } else {
-        fs::read_to_string(contract_file)?
-    };
+    let payload = make_contract_publish_payload(&args[3..])?;
 
     let sk_publisher = StacksPrivateKey::from_hex(sk_publisher)?;
 
-    let payload = make_contract_publish(contract_name.clone(), contract_contents)?;
     let mut unsigned_tx = make_standard_single_sig_tx(
         version,
         chain_id,
-        payload.into(),
+        payload,
         &StacksPublicKey::from_private(&sk_publisher),
         nonce,
         tx_fee,
@@ -421,61 +1323,81 @@ fn handle_contract_call(
         )));
     }
     let anchor_mode = parse_anchor_mode(&mut args, CALL_USAGE)?;
+    let (post_condition_mode, post_conditions) = parse_post_conditions(&mut args, CALL_USAGE)?;
     let sk_origin = &args[0];
     let tx_fee = args[1].parse()?;
     let nonce = args[2].parse()?;
-    let contract_address = &args[3];
-    let contract_name = &args[4];
-    let function_name = &args[5];
+    let payload = make_contract_call_payload(&args[3..])?;
 
-    let val_args = &args[6..];
+    let sk_origin = StacksPrivateKey::from_hex(sk_origin)?;
 
-    if val_args.len() % 2 != 0 {
-        return Err(
-            "contract-call arguments must be supplied as a list of `-e ...` or `-x 0000...` pairs"
-                .into(),
-        );
+    let mut unsigned_tx = make_standard_single_sig_tx(
+        version,
+        chain_id,
+        payload,
+        &StacksPublicKey::from_private(&sk_origin),
+        nonce,
+        tx_fee,
+    );
+    unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    for post_condition in post_conditions.into_iter() {
+        unsigned_tx.add_post_condition(post_condition);
     }
 
-    let mut arg_iterator = 0;
-    let mut values = Vec::new();
-    while arg_iterator < val_args.len() {
-        let eval_method = &val_args[arg_iterator];
-        let input = &val_args[arg_iterator + 1];
-        let value = match eval_method.as_str() {
-            "-x" => {
-                Value::try_deserialize_hex_untyped(input)?
-            },
-            "-e" => {
-                blockstack_lib::clarity::vm_execute(input)?
-                    .ok_or("Supplied argument did not evaluate to a Value")?
-            },
-            _ => {
-                return Err("contract-call arguments must be supplied as a list of `-e ...` or `-x 0000...` pairs".into())
-            }
-        };
+    let mut unsigned_tx_bytes = vec![];
+    unsigned_tx
+        .consensus_serialize(&mut unsigned_tx_bytes)
+        .expect("FATAL: invalid transaction");
+    let signed_tx = sign_transaction_single_sig_standard(&to_hex(&unsigned_tx_bytes), &sk_origin)?;
 
-        values.push(value);
-        arg_iterator += 2;
+    let mut signed_tx_bytes = vec![];
+    signed_tx
+        .consensus_serialize(&mut signed_tx_bytes)
+        .expect("FATAL: invalid signed transaction");
+    Ok(to_hex(&signed_tx_bytes))
+}
+
+fn handle_token_transfer(
+    args_slice: &[String],
+    version: TransactionVersion,
+    chain_id: u32,
+) -> Result<String, CliError> {
+    let mut args = args_slice.to_vec();
+    if args.len() >= 1 && args[0] == "-h" {
+        return Err(CliError::Message(format!(
+            "USAGE:\n {}",
+            TOKEN_TRANSFER_USAGE
+        )));
+    }
+    if args.len() < 5 {
+        return Err(CliError::Message(format!(
+            "Incorrect argument count supplied \n\nUSAGE:\n {}",
+            TOKEN_TRANSFER_USAGE
+        )));
     }
 
-    let sk_origin = StacksPrivateKey::from_hex(sk_origin)?;
+    let anchor_mode = parse_anchor_mode(&mut args, TOKEN_TRANSFER_USAGE)?;
+    let (post_condition_mode, post_conditions) =
+        parse_post_conditions(&mut args, TOKEN_TRANSFER_USAGE)?;
+    let sk_origin = StacksPrivateKey::from_hex(&args[0])?;
+    let tx_fee = args[1].parse()?;
+    let nonce = args[2].parse()?;
+    let payload = make_token_transfer_payload(&args[3..])?;
 
-    let payload = make_contract_call(
-        contract_address.clone(),
-        contract_name.clone(),
-        function_name.clone(),
-        values,
-    )?;
     let mut unsigned_tx = make_standard_single_sig_tx(
         version,
         chain_id,
-        payload.into(),
+        payload,
         &StacksPublicKey::from_private(&sk_origin),
         nonce,
         tx_fee,
     );
     unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    for post_condition in post_conditions.into_iter() {
+        unsigned_tx.add_post_condition(post_condition);
+    }
 
     let mut unsigned_tx_bytes = vec![];
     unsigned_tx
@@ -490,45 +1412,40 @@ fn handle_contract_call(
     Ok(to_hex(&signed_tx_bytes))
 }
 
-fn handle_token_transfer(
+fn handle_stacking_call(
+    method: &str,
+    usage: &str,
     args_slice: &[String],
     version: TransactionVersion,
     chain_id: u32,
 ) -> Result<String, CliError> {
     let mut args = args_slice.to_vec();
     if args.len() >= 1 && args[0] == "-h" {
-        return Err(CliError::Message(format!(
-            "USAGE:\n {}",
-            TOKEN_TRANSFER_USAGE
-        )));
+        return Err(CliError::Message(format!("USAGE:\n {}", usage)));
     }
+    let anchor_mode = parse_anchor_mode(&mut args, usage)?;
+    let (post_condition_mode, post_conditions) = parse_post_conditions(&mut args, usage)?;
+    let stacking_minimum = parse_stacking_minimum(&mut args, usage)?;
     if args.len() < 5 {
         return Err(CliError::Message(format!(
             "Incorrect argument count supplied \n\nUSAGE:\n {}",
-            TOKEN_TRANSFER_USAGE
+            usage
         )));
     }
 
-    let anchor_mode = parse_anchor_mode(&mut args, TOKEN_TRANSFER_USAGE)?;
     let sk_origin = StacksPrivateKey::from_hex(&args[0])?;
     let tx_fee = args[1].parse()?;
     let nonce = args[2].parse()?;
-    let recipient_address =
-        PrincipalData::parse(&args[3]).map_err(|_e| "Failed to parse recipient")?;
-    let amount = &args[4].parse()?;
-    let memo = {
-        let mut memo = [0; 34];
-        let mut bytes = if args.len() == 6 {
-            args[5].as_bytes().to_vec()
-        } else {
-            vec![]
-        };
-        bytes.resize(34, 0);
-        memo.copy_from_slice(&bytes);
-        TokenTransferMemo(memo)
+    let payload = match method {
+        "stack-stx" => make_stack_stx_payload(&args[3..], version, stacking_minimum)?,
+        _ => {
+            if stacking_minimum.is_some() {
+                return Err(CliError::Message(format!("USAGE:\n {}", usage)));
+            }
+            make_payload(method, &args[3..], version)?
+        }
     };
 
-    let payload = TransactionPayload::TokenTransfer(recipient_address, *amount, memo);
     let mut unsigned_tx = make_standard_single_sig_tx(
         version,
         chain_id,
@@ -538,6 +1455,10 @@ fn handle_token_transfer(
         tx_fee,
     );
     unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    for post_condition in post_conditions.into_iter() {
+        unsigned_tx.add_post_condition(post_condition);
+    }
 
     let mut unsigned_tx_bytes = vec![];
     unsigned_tx
@@ -552,6 +1473,142 @@ fn handle_token_transfer(
     Ok(to_hex(&signed_tx_bytes))
 }
 
+fn handle_unsigned_tx(
+    args_slice: &[String],
+    version: TransactionVersion,
+    chain_id: u32,
+) -> Result<String, CliError> {
+    let mut args = args_slice.to_vec();
+    if args.len() >= 1 && args[0] == "-h" {
+        return Err(CliError::Message(format!("USAGE:\n {}", UNSIGNED_TX_USAGE)));
+    }
+    let anchor_mode = parse_anchor_mode(&mut args, UNSIGNED_TX_USAGE)?;
+    let (post_condition_mode, post_conditions) =
+        parse_post_conditions(&mut args, UNSIGNED_TX_USAGE)?;
+    if args.len() < 4 {
+        return Err(CliError::Message(format!(
+            "Incorrect argument count supplied \n\nUSAGE:\n {}",
+            UNSIGNED_TX_USAGE
+        )));
+    }
+
+    let mut origin = parse_spending_condition(&args[0])?;
+    let tx_fee = args[1].parse()?;
+    let nonce = args[2].parse()?;
+    let payload = make_payload(&args[3], &args[4..], version)?;
+
+    origin.set_nonce(nonce);
+    origin.set_tx_fee(tx_fee);
+
+    let mut unsigned_tx =
+        StacksTransaction::new(version, TransactionAuth::Standard(origin), payload);
+    unsigned_tx.chain_id = chain_id;
+    unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    for post_condition in post_conditions.into_iter() {
+        unsigned_tx.add_post_condition(post_condition);
+    }
+
+    Ok(encode_partial_tx(&unsigned_tx))
+}
+
+fn handle_unsigned_sponsored_tx(
+    args_slice: &[String],
+    version: TransactionVersion,
+    chain_id: u32,
+) -> Result<String, CliError> {
+    let mut args = args_slice.to_vec();
+    if args.len() >= 1 && args[0] == "-h" {
+        return Err(CliError::Message(format!(
+            "USAGE:\n {}",
+            UNSIGNED_SPONSORED_TX_USAGE
+        )));
+    }
+    let anchor_mode = parse_anchor_mode(&mut args, UNSIGNED_SPONSORED_TX_USAGE)?;
+    let (post_condition_mode, post_conditions) =
+        parse_post_conditions(&mut args, UNSIGNED_SPONSORED_TX_USAGE)?;
+    if args.len() < 6 {
+        return Err(CliError::Message(format!(
+            "Incorrect argument count supplied \n\nUSAGE:\n {}",
+            UNSIGNED_SPONSORED_TX_USAGE
+        )));
+    }
+
+    let mut origin = parse_spending_condition(&args[0])?;
+    let origin_nonce = args[1].parse()?;
+    let mut sponsor = parse_spending_condition(&args[2])?;
+    let tx_fee = args[3].parse()?;
+    let sponsor_nonce = args[4].parse()?;
+    let payload = make_payload(&args[5], &args[6..], version)?;
+
+    // the sponsor pays the fee
+    origin.set_nonce(origin_nonce);
+    origin.set_tx_fee(0);
+    sponsor.set_nonce(sponsor_nonce);
+    sponsor.set_tx_fee(tx_fee);
+
+    let mut unsigned_tx = StacksTransaction::new(
+        version,
+        TransactionAuth::Sponsored(origin, sponsor),
+        payload,
+    );
+    unsigned_tx.chain_id = chain_id;
+    unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    for post_condition in post_conditions.into_iter() {
+        unsigned_tx.add_post_condition(post_condition);
+    }
+
+    Ok(encode_partial_tx(&unsigned_tx))
+}
+
+fn handle_sign_partial(args: &[String], _version: TransactionVersion) -> Result<String, CliError> {
+    if (args.len() >= 1 && args[0] == "-h") || args.len() != 2 {
+        return Err(CliError::Message(format!(
+            "USAGE:\n {}",
+            SIGN_PARTIAL_USAGE
+        )));
+    }
+
+    let mut tx = decode_partial_tx(&args[0])?;
+    let privk = StacksPrivateKey::from_hex(&args[1])?;
+    sign_partial_tx(&mut tx, &privk)?;
+
+    Ok(encode_partial_tx(&tx))
+}
+
+fn handle_finalize_tx(args: &[String], _version: TransactionVersion) -> Result<String, CliError> {
+    if (args.len() >= 1 && args[0] == "-h") || args.len() != 1 {
+        return Err(CliError::Message(format!("USAGE:\n {}", FINALIZE_TX_USAGE)));
+    }
+
+    let tx = decode_partial_tx(&args[0])?;
+
+    let origin = tx.auth.origin();
+    if origin.num_signatures() < origin.signatures_required() {
+        return Err(CliError::Message(format!(
+            "Transaction is missing origin signatures: have {}, need {}",
+            origin.num_signatures(),
+            origin.signatures_required()
+        )));
+    }
+    if let Some(sponsor) = tx.auth.sponsor() {
+        if sponsor.num_signatures() < sponsor.signatures_required() {
+            return Err(CliError::Message(format!(
+                "Transaction is missing sponsor signatures: have {}, need {}",
+                sponsor.num_signatures(),
+                sponsor.signatures_required()
+            )));
+        }
+    }
+    tx.verify()?;
+
+    let mut signed_tx_bytes = vec![];
+    tx.consensus_serialize(&mut signed_tx_bytes)
+        .expect("FATAL: invalid signed transaction");
+    Ok(to_hex(&signed_tx_bytes))
+}
+
 fn generate_secret_key(args: &[String], version: TransactionVersion) -> Result<String, CliError> {
     if args.len() >= 1 && args[0] == "-h" {
         return Err(CliError::Message(format!("USAGE:\n {}", GENERATE_USAGE)));
@@ -646,7 +1703,17 @@ fn decode_transaction(args: &[String], _version: TransactionVersion) -> Result<S
     let mut debug_cursor = LogReader::from_reader(&mut cursor);
 
     match StacksTransaction::consensus_deserialize(&mut debug_cursor) {
-        Ok(tx) => Ok(serde_json::to_string(&tx).expect("Failed to serialize transaction to JSON")),
+        Ok(tx) => {
+            let mut tx_json =
+                serde_json::to_value(&tx).expect("Failed to serialize transaction to JSON");
+            tx_json["post_conditions"] = serde_json::Value::Array(
+                tx.post_conditions
+                    .iter()
+                    .map(|post_condition| post_condition_to_json(post_condition))
+                    .collect(),
+            );
+            Ok(tx_json.to_string())
+        }
         Err(e) => {
             let mut ret = String::new();
             ret.push_str(&format!("Failed to decode transaction: {:?}\n", &e));
@@ -771,6 +1838,27 @@ fn main_handler(mut argv: Vec<String>) -> Result<String, CliError> {
             "contract-call" => handle_contract_call(args, tx_version, chain_id),
             "publish" => handle_contract_publish(args, tx_version, chain_id),
             "token-transfer" => handle_token_transfer(args, tx_version, chain_id),
+            "stack-stx" => {
+                handle_stacking_call("stack-stx", STACK_STX_USAGE, args, tx_version, chain_id)
+            }
+            "delegate-stx" => handle_stacking_call(
+                "delegate-stx",
+                DELEGATE_STX_USAGE,
+                args,
+                tx_version,
+                chain_id,
+            ),
+            "delegate-stack-stx" => handle_stacking_call(
+                "delegate-stack-stx",
+                DELEGATE_STACK_STX_USAGE,
+                args,
+                tx_version,
+                chain_id,
+            ),
+            "unsigned-tx" => handle_unsigned_tx(args, tx_version, chain_id),
+            "unsigned-sponsored-tx" => handle_unsigned_sponsored_tx(args, tx_version, chain_id),
+            "sign-partial" => handle_sign_partial(args, tx_version),
+            "finalize-tx" => handle_finalize_tx(args, tx_version),
             "generate-sk" => generate_secret_key(args, tx_version),
             "addresses" => get_addresses(args, tx_version),
             "decode-tx" => decode_transaction(args, tx_version),
@@ -1067,6 +2155,399 @@ mod test {
         );
     }
 
+    #[test]
+    fn multisig_partial_signing() {
+        let sks: Vec<StacksPrivateKey> = (0..3).map(|_| StacksPrivateKey::new()).collect();
+        let signer = format!(
+            "2:{}",
+            sks.iter()
+                .map(|sk| StacksPublicKey::from_private(sk).to_hex())
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+
+        let unsigned_args = [
+            "--testnet",
+            "unsigned-tx",
+            &signer,
+            "1",
+            "0",
+            "token-transfer",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+        ];
+        let unsigned_tx = main_handler(to_string_vec(&unsigned_args)).unwrap();
+
+        let finalize_args = ["finalize-tx", &unsigned_tx];
+        assert!(format!(
+            "{}",
+            main_handler(to_string_vec(&finalize_args)).unwrap_err()
+        )
+        .contains("missing origin signatures"));
+
+        // signers skip the second key, and sign across separate invocations
+        let sign_args = ["sign-partial", &unsigned_tx, &sks[0].to_hex()];
+        let partial_tx = main_handler(to_string_vec(&sign_args)).unwrap();
+        let sign_args = ["sign-partial", &partial_tx, &sks[2].to_hex()];
+        let signed_tx = main_handler(to_string_vec(&sign_args)).unwrap();
+
+        let sign_args = ["sign-partial", &signed_tx, &sks[1].to_hex()];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&sign_args)).unwrap_err())
+                .contains("already fully signed")
+        );
+
+        let finalize_args = ["finalize-tx", &signed_tx];
+        let tx_hex = main_handler(to_string_vec(&finalize_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            &hex_bytes(&tx_hex).unwrap(),
+        ))
+        .unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.auth.origin().num_signatures(), 2);
+        assert_eq!(tx.version, TransactionVersion::Testnet);
+
+        // a key can't sign ahead of keys listed after it
+        let sign_args = ["sign-partial", &unsigned_tx, &sks[2].to_hex()];
+        let partial_tx = main_handler(to_string_vec(&sign_args)).unwrap();
+        let sign_args = ["sign-partial", &partial_tx, &sks[0].to_hex()];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&sign_args)).unwrap_err()).contains("order")
+        );
+
+        let sign_args = [
+            "sign-partial",
+            &unsigned_tx,
+            &StacksPrivateKey::new().to_hex(),
+        ];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&sign_args)).unwrap_err())
+                .contains("does not match")
+        );
+
+        let unsigned_args = [
+            "unsigned-tx",
+            &signer.replacen("2:", "4:", 1),
+            "1",
+            "0",
+            "token-transfer",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+        ];
+        assert!(main_handler(to_string_vec(&unsigned_args)).is_err());
+    }
+
+    #[test]
+    fn sponsored_partial_signing() {
+        let origin_sk = StacksPrivateKey::new();
+        let sponsor_sks: Vec<StacksPrivateKey> = (0..2).map(|_| StacksPrivateKey::new()).collect();
+        let origin_signer = StacksPublicKey::from_private(&origin_sk).to_hex();
+        let sponsor_signer = format!(
+            "2:{},{}",
+            StacksPublicKey::from_private(&sponsor_sks[0]).to_hex(),
+            StacksPublicKey::from_private(&sponsor_sks[1]).to_hex()
+        );
+
+        let unsigned_args = [
+            "unsigned-sponsored-tx",
+            &origin_signer,
+            "3",
+            &sponsor_signer,
+            "100",
+            "7",
+            "contract-call",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+            "foo-contract",
+            "transfer-fookens",
+            "-e",
+            "(+ 1 0)",
+        ];
+        let unsigned_tx = main_handler(to_string_vec(&unsigned_args)).unwrap();
+
+        // the sponsor can't sign before the origin
+        let sign_args = ["sign-partial", &unsigned_tx, &sponsor_sks[0].to_hex()];
+        assert!(main_handler(to_string_vec(&sign_args)).is_err());
+
+        let mut partial_tx = unsigned_tx;
+        for sk in [&origin_sk, &sponsor_sks[0]].iter() {
+            let sign_args = ["sign-partial", &partial_tx, &sk.to_hex()];
+            partial_tx = main_handler(to_string_vec(&sign_args)).unwrap();
+        }
+
+        let finalize_args = ["finalize-tx", &partial_tx];
+        assert!(format!(
+            "{}",
+            main_handler(to_string_vec(&finalize_args)).unwrap_err()
+        )
+        .contains("missing sponsor signatures"));
+
+        let sign_args = ["sign-partial", &partial_tx, &sponsor_sks[1].to_hex()];
+        let signed_tx = main_handler(to_string_vec(&sign_args)).unwrap();
+
+        let finalize_args = ["finalize-tx", &signed_tx];
+        let tx_hex = main_handler(to_string_vec(&finalize_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            &hex_bytes(&tx_hex).unwrap(),
+        ))
+        .unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.auth.origin().nonce(), 3);
+        assert_eq!(tx.auth.origin().tx_fee(), 0);
+        let sponsor = tx.auth.sponsor().unwrap();
+        assert_eq!(sponsor.nonce(), 7);
+        assert_eq!(sponsor.tx_fee(), 100);
+        assert_eq!(sponsor.num_signatures(), 2);
+    }
+
+    #[test]
+    fn post_conditions() {
+        let cc_args = [
+            "contract-call",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+            "foo-contract",
+            "transfer-fookens",
+            "--stx-post-condition",
+            "origin",
+            "sent-le",
+            "1000",
+            "-e",
+            "(+ 1 0)",
+            "--ft-post-condition",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::fookens",
+            "sent-eq",
+            "10",
+            "--nft-post-condition",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::foo-nft",
+            "u1",
+            "not-sent",
+            "--post-condition-mode",
+            "allow",
+        ];
+
+        let tx_hex = main_handler(to_string_vec(&cc_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            &hex_bytes(&tx_hex).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(tx.post_condition_mode, TransactionPostConditionMode::Allow);
+        assert_eq!(tx.post_conditions.len(), 3);
+        assert_eq!(
+            tx.post_conditions[0],
+            TransactionPostCondition::STX(
+                PostConditionPrincipal::Origin,
+                FungibleConditionCode::SentLe,
+                1000
+            )
+        );
+        match tx.payload {
+            TransactionPayload::ContractCall(ref cc) => {
+                assert_eq!(cc.function_args, vec![Value::Int(1)])
+            }
+            _ => panic!("expected a contract-call"),
+        }
+
+        let decoded: serde_json::Value =
+            serde_json::from_str(&main_handler(to_string_vec(&["decode-tx", &tx_hex])).unwrap())
+                .unwrap();
+        assert_eq!(
+            decoded["post_conditions"],
+            serde_json::json!([
+                {
+                    "type": "stx",
+                    "principal": "origin",
+                    "condition_code": "sent-le",
+                    "amount": 1000,
+                },
+                {
+                    "type": "fungible",
+                    "principal": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract",
+                    "asset": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::fookens",
+                    "condition_code": "sent-eq",
+                    "amount": 10,
+                },
+                {
+                    "type": "nonfungible",
+                    "principal": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+                    "asset": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::foo-nft",
+                    "asset_value": "u1",
+                    "condition_code": "not-sent",
+                },
+            ])
+        );
+
+        // token transfers keep their memo, and default to deny mode
+        let tt_args = [
+            "token-transfer",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+            "--stx-post-condition",
+            "origin",
+            "sent-eq",
+            "10",
+            "Memo",
+        ];
+        let tx_hex = main_handler(to_string_vec(&tt_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            &hex_bytes(&tx_hex).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(tx.post_condition_mode, TransactionPostConditionMode::Deny);
+        assert_eq!(tx.post_conditions.len(), 1);
+        match tx.payload {
+            TransactionPayload::TokenTransfer(_, amount, ref memo) => {
+                assert_eq!(amount, 10);
+                assert_eq!(&memo.0[..4], b"Memo");
+            }
+            _ => panic!("expected a token-transfer"),
+        }
+
+        let tt_args = [
+            "token-transfer",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+            "--stx-post-condition",
+            "origin",
+            "sent-most",
+            "10",
+        ];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&tt_args)).unwrap_err())
+                .contains("condition code")
+        );
+
+        let tt_args = [
+            "token-transfer",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+            "--ft-post-condition",
+            "origin",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract",
+            "sent-eq",
+            "10",
+        ];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&tt_args)).unwrap_err())
+                .contains("Failed to parse asset")
+        );
+
+        let tt_args = [
+            "token-transfer",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+            "--stx-post-condition",
+            "origin",
+        ];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&tt_args)).unwrap_err())
+                .contains("Incorrect argument count")
+        );
+    }
+
+    #[test]
+    fn simple_stacking_calls() {
+        let ss_args = [
+            "--testnet",
+            "stack-stx",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "100000000000",
+            "mvWRFPELmpCHSkFQ7o9EVdCd9eXeUTa9T8",
+            "2101",
+            "6",
+        ];
+        let result = main_handler(to_string_vec(&ss_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut &hex_bytes(&result).unwrap()[..])
+            .unwrap();
+        match tx.payload {
+            TransactionPayload::ContractCall(ref cc) => {
+                assert_eq!(cc.address, boot_code_addr(false));
+                assert_eq!(cc.contract_name.as_str(), "pox");
+                assert_eq!(cc.function_name.as_str(), "stack-stx");
+                assert_eq!(cc.function_args.len(), 4);
+            }
+            _ => panic!("Expected a contract-call payload"),
+        }
+
+        // mainnet PoX address for a testnet transaction
+        let mut ss_args = ss_args.to_vec();
+        ss_args[6] = "1KkL94EPD3mz7RFCZPmRBy3KjbWZ4qo58E";
+        assert!(
+            format!("{}", main_handler(to_string_vec(&ss_args)).unwrap_err())
+                .contains("is not a testnet Bitcoin address")
+        );
+
+        ss_args[6] = "mvWRFPELmpCHSkFQ7o9EVdCd9eXeUTa9T8";
+        ss_args[8] = "13";
+        assert!(
+            format!("{}", main_handler(to_string_vec(&ss_args)).unwrap_err())
+                .contains("lock period must be between 1 and 12")
+        );
+
+        ss_args[8] = "6";
+        ss_args.extend_from_slice(&["--stacking-minimum", "200000000000"]);
+        assert!(
+            format!("{}", main_handler(to_string_vec(&ss_args)).unwrap_err())
+                .contains("below the stacking minimum")
+        );
+
+        let ds_args = [
+            "--testnet",
+            "delegate-stx",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "100000000000",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "none",
+            "mvWRFPELmpCHSkFQ7o9EVdCd9eXeUTa9T8",
+        ];
+        let result = main_handler(to_string_vec(&ds_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut &hex_bytes(&result).unwrap()[..])
+            .unwrap();
+        match tx.payload {
+            TransactionPayload::ContractCall(ref cc) => {
+                assert_eq!(cc.function_name.as_str(), "delegate-stx");
+                assert_eq!(cc.function_args[2], Value::none());
+            }
+            _ => panic!("Expected a contract-call payload"),
+        }
+
+        let dss_args = [
+            "--testnet",
+            "delegate-stack-stx",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "0",
+            "mvWRFPELmpCHSkFQ7o9EVdCd9eXeUTa9T8",
+            "2101",
+            "6",
+        ];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&dss_args)).unwrap_err())
+                .contains("amount must be positive")
+        );
+    }
+
     #[test]
     fn simple_addresses() {
         let addr_args = [
@@ -41,6 +41,7 @@ pub mod keys;
 pub mod messages;
 pub mod network;
 pub mod spv;
+pub mod wallet;
 
 pub type PeerMessage = deps::bitcoin::network::message::NetworkMessage;
 
@@ -0,0 +1,1133 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+use std::collections::HashSet;
+use std::fs;
+
+use deps::bitcoin::blockdata::block::Block;
+use deps::bitcoin::blockdata::script::Script;
+use deps::bitcoin::blockdata::transaction::Transaction;
+use deps::bitcoin::network::message as btc_message;
+use deps::bitcoin::network::serialize::{serialize, BitcoinHash};
+use deps::bitcoin::util::hash::Sha256dHash;
+
+use burnchains::bitcoin::address::BitcoinAddress;
+use burnchains::bitcoin::blocks::{BitcoinBlockDownloader, BitcoinHeaderIPC};
+use burnchains::bitcoin::indexer::BitcoinIndexer;
+use burnchains::bitcoin::spv::SpvClient;
+use burnchains::bitcoin::BitcoinNetworkType;
+use burnchains::bitcoin::Error as btc_error;
+
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+use rusqlite::{OpenFlags, OptionalExtension, NO_PARAMS};
+
+use util::db::{
+    query_row, query_rows, sqlite_open, tx_begin_immediate, u64_to_sql, DBConn, DBTx,
+    Error as db_error, FromColumn, FromRow,
+};
+use util::get_epoch_time_secs;
+use util::hash::{hex_bytes, to_hex, Hash160};
+use util::log;
+
+pub const WALLET_DB_VERSION: &'static str = "1";
+
+/// Approximate size of a signed P2PKH input, used to decide whether a UTXO is worth consolidating
+pub const P2PKH_INPUT_SIZE: u64 = 148;
+
+/// Once the wallet holds more than this many spendable UTXOs, coin selection will add some of
+/// the smallest ones to each transaction so that they get merged into its change output.
+pub const CONSOLIDATION_THRESHOLD: usize = 10;
+
+/// Maximum number of extra UTXOs to consolidate into a single transaction
+pub const MAX_CONSOLIDATION_INPUTS: usize = 5;
+
+/// Metadata key under which the wallet's birth height is saved
+pub const WALLET_BIRTH_HEIGHT_KEY: &'static str = "birth_height";
+
+const WALLET_INITIAL_SCHEMA: &[&'static str] = &[
+    r#"
+    CREATE TABLE tracked_addresses(
+        address_hash TEXT PRIMARY KEY NOT NULL      -- hash160 of a P2PKH address we own
+    );
+    "#,
+    r#"
+    CREATE TABLE scanned_blocks(
+        height INTEGER PRIMARY KEY NOT NULL,
+        block_hash TEXT NOT NULL
+    );
+    "#,
+    r#"
+    CREATE TABLE utxos(
+        txid TEXT NOT NULL,
+        vout INTEGER NOT NULL,
+        amount INTEGER NOT NULL,
+        script_pubkey TEXT NOT NULL,
+        block_height INTEGER,           -- NULL if the creating transaction is not yet mined
+        spent_by TEXT,                  -- txid of the transaction that spends this output, if any
+        spent_height INTEGER,           -- NULL unless the spending transaction has been mined
+        PRIMARY KEY(txid,vout)
+    );
+    "#,
+    "CREATE INDEX utxos_spent_by ON utxos(spent_by);",
+    r#"
+    CREATE TABLE pending_txs(
+        txid TEXT PRIMARY KEY NOT NULL,
+        tx_hex TEXT NOT NULL,
+        replaces TEXT,                  -- txid of the pending transaction this one replaced by fee
+        sent_at INTEGER NOT NULL
+    );
+    "#,
+    r#"
+    CREATE TABLE metadata(
+        key TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL
+    );
+    "#,
+    "CREATE TABLE db_config(version TEXT NOT NULL);",
+];
+
+/// An output owned by one of the wallet's addresses
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletUTXO {
+    pub txid: Sha256dHash,
+    pub vout: u32,
+    pub amount: u64,
+    pub script_pubkey: Script,
+    /// Height of the block that mined the creating transaction, or None if still in the mempool
+    pub block_height: Option<u64>,
+}
+
+impl FromRow<WalletUTXO> for WalletUTXO {
+    fn from_row<'a>(row: &'a Row) -> Result<WalletUTXO, db_error> {
+        let txid = Sha256dHash::from_column(row, "txid")?;
+        let vout: u32 = row.get_unwrap("vout");
+        let amount = u64::from_column(row, "amount")?;
+        let script_hex: String = row.get_unwrap("script_pubkey");
+        let script_pubkey: Script = hex_bytes(&script_hex)
+            .map_err(|_e| db_error::ParseError)?
+            .into();
+        let block_height: Option<u64> = u64::from_column(row, "block_height")?;
+
+        Ok(WalletUTXO {
+            txid,
+            vout,
+            amount,
+            script_pubkey,
+            block_height,
+        })
+    }
+}
+
+impl WalletUTXO {
+    /// Number of confirmations this output has, given the height of the wallet's chain tip
+    pub fn confirmations(&self, tip_height: u64) -> u32 {
+        match self.block_height {
+            Some(h) if h <= tip_height => (tip_height - h + 1) as u32,
+            _ => 0,
+        }
+    }
+}
+
+/// Tracks the UTXOs of a set of P2PKH addresses by scanning the blocks on the SPV client's
+/// best chain, so a miner does not need bitcoind's wallet (`importaddress` and `listunspent`)
+/// to find out what it can spend.  Transactions the miner broadcasts are recorded as pending, so
+/// their inputs are not selected twice and their change can be spent before it confirms.
+pub struct BitcoinWallet {
+    pub path: String,
+    pub network_id: BitcoinNetworkType,
+    /// Height of the first block to scan.  Outputs mined before this height are not found.
+    /// Saved in the wallet when it is created, so it does not move on restart.
+    pub birth_height: u64,
+    readwrite: bool,
+    conn: DBConn,
+}
+
+impl BitcoinWallet {
+    /// Open the wallet at `path`, creating it if need be.  `default_birth_height` is only used
+    /// if the wallet has no saved birth height, i.e. it is new.
+    pub fn new(
+        path: &str,
+        network_id: BitcoinNetworkType,
+        default_birth_height: u64,
+        readwrite: bool,
+    ) -> Result<BitcoinWallet, btc_error> {
+        let conn = BitcoinWallet::db_open(path, readwrite)?;
+        let mut wallet = BitcoinWallet {
+            path: path.to_owned(),
+            network_id,
+            birth_height: default_birth_height,
+            readwrite,
+            conn,
+        };
+        match wallet.get_metadata(WALLET_BIRTH_HEIGHT_KEY)? {
+            Some(saved) => {
+                wallet.birth_height = saved.parse().map_err(|_e| db_error::ParseError)?;
+            }
+            None => {
+                if readwrite {
+                    wallet
+                        .put_metadata(WALLET_BIRTH_HEIGHT_KEY, &default_birth_height.to_string())?;
+                }
+            }
+        }
+        Ok(wallet)
+    }
+
+    /// Change the height of the first block to scan.  If it is lower than before, all scanned
+    /// blocks are forgotten so the next sync rescans them from the new birth height.
+    pub fn set_birth_height(&mut self, birth_height: u64) -> Result<(), btc_error> {
+        let old_birth_height = self.birth_height;
+        let mut tx = self.tx_begin()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+            &[
+                &WALLET_BIRTH_HEIGHT_KEY as &dyn ToSql,
+                &birth_height.to_string(),
+            ],
+        )
+        .map_err(db_error::SqliteError)?;
+        if birth_height < old_birth_height {
+            BitcoinWallet::inner_forget_blocks_from(&mut tx, birth_height)?;
+        }
+        tx.commit().map_err(db_error::SqliteError)?;
+
+        debug!(
+            "Wallet {} birth height is now {} (was {})",
+            &self.path, birth_height, old_birth_height
+        );
+        self.birth_height = birth_height;
+        Ok(())
+    }
+
+    pub fn conn(&self) -> &DBConn {
+        &self.conn
+    }
+
+    pub fn tx_begin<'a>(&'a mut self) -> Result<DBTx<'a>, btc_error> {
+        if !self.readwrite {
+            return Err(db_error::ReadOnly.into());
+        }
+
+        let tx = tx_begin_immediate(&mut self.conn)?;
+        Ok(tx)
+    }
+
+    fn db_instantiate(conn: &mut DBConn) -> Result<(), btc_error> {
+        let tx = tx_begin_immediate(conn)?;
+
+        for row_text in WALLET_INITIAL_SCHEMA {
+            tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
+        }
+
+        tx.execute(
+            "INSERT INTO db_config (version) VALUES (?1)",
+            &[&WALLET_DB_VERSION],
+        )
+        .map_err(db_error::SqliteError)?;
+
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    fn db_open(path: &str, readwrite: bool) -> Result<DBConn, btc_error> {
+        let mut create_flag = false;
+        let open_flags = if fs::metadata(path).is_err() {
+            // need to create
+            if readwrite {
+                create_flag = true;
+                OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE
+            } else {
+                return Err(btc_error::DBError(db_error::NoDBError));
+            }
+        } else {
+            // can just open
+            if readwrite {
+                OpenFlags::SQLITE_OPEN_READ_WRITE
+            } else {
+                OpenFlags::SQLITE_OPEN_READ_ONLY
+            }
+        };
+
+        let mut conn = sqlite_open(path, open_flags, false)
+            .map_err(|e| btc_error::DBError(db_error::SqliteError(e)))?;
+
+        if create_flag {
+            BitcoinWallet::db_instantiate(&mut conn)?;
+        }
+
+        Ok(conn)
+    }
+
+    /// Start tracking the P2PKH address with the given public key hash.
+    /// If the address is new to the wallet, all blocks are forgotten so the next sync rescans
+    /// them from the birth height.  Returns true if the address was new.
+    pub fn track_address(&mut self, address_hash: &Hash160) -> Result<bool, btc_error> {
+        if self.get_tracked_addresses()?.contains(address_hash) {
+            return Ok(false);
+        }
+
+        let birth_height = self.birth_height;
+        let mut tx = self.tx_begin()?;
+        tx.execute(
+            "INSERT INTO tracked_addresses (address_hash) VALUES (?1)",
+            &[&address_hash.to_hex()],
+        )
+        .map_err(db_error::SqliteError)?;
+        BitcoinWallet::inner_forget_blocks_from(&mut tx, birth_height)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+
+        debug!(
+            "Wallet {} now tracks {}; will rescan from {}",
+            &self.path, address_hash, birth_height
+        );
+        Ok(true)
+    }
+
+    pub fn get_tracked_addresses(&self) -> Result<Vec<Hash160>, btc_error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT address_hash FROM tracked_addresses")
+            .map_err(db_error::SqliteError)?;
+        let rows = stmt
+            .query_map(NO_PARAMS, |row| row.get::<_, String>(0))
+            .map_err(db_error::SqliteError)?;
+
+        let mut ret = vec![];
+        for row in rows {
+            let hex = row.map_err(db_error::SqliteError)?;
+            ret.push(Hash160::from_hex(&hex).map_err(|_e| db_error::ParseError)?);
+        }
+        Ok(ret)
+    }
+
+    fn get_tracked_scripts(&self) -> Result<HashSet<Script>, btc_error> {
+        Ok(self
+            .get_tracked_addresses()?
+            .iter()
+            .map(|h| BitcoinAddress::to_p2pkh_tx_out(h, 0).script_pubkey)
+            .collect())
+    }
+
+    /// Get the height of the highest block this wallet has scanned
+    pub fn get_scanned_height(&self) -> Result<Option<u64>, btc_error> {
+        let height = query_row::<u64, _>(
+            &self.conn,
+            "SELECT height FROM scanned_blocks ORDER BY height DESC LIMIT 1",
+            NO_PARAMS,
+        )?;
+        Ok(height)
+    }
+
+    /// Get the hash of the block this wallet scanned at the given height
+    pub fn get_scanned_block_hash(&self, height: u64) -> Result<Option<Sha256dHash>, btc_error> {
+        let hash = self
+            .conn
+            .query_row(
+                "SELECT block_hash FROM scanned_blocks WHERE height = ?1",
+                &[&u64_to_sql(height)?],
+                |row| row.get::<_, Sha256dHash>(0),
+            )
+            .optional()
+            .map_err(db_error::SqliteError)?;
+        Ok(hash)
+    }
+
+    /// Forget all blocks at or above the given height, e.g. because they were reorged out.
+    /// Outputs they created are dropped, and spends they confirmed are undone.
+    pub fn forget_blocks_from(&mut self, height: u64) -> Result<(), btc_error> {
+        let mut tx = self.tx_begin()?;
+        BitcoinWallet::inner_forget_blocks_from(&mut tx, height)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    fn inner_forget_blocks_from<'a>(tx: &mut DBTx<'a>, height: u64) -> Result<(), btc_error> {
+        let args: &[&dyn ToSql] = &[&u64_to_sql(height)?];
+        tx.execute("DELETE FROM scanned_blocks WHERE height >= ?1", args)
+            .map_err(db_error::SqliteError)?;
+        tx.execute(
+            "UPDATE utxos SET spent_by = NULL, spent_height = NULL WHERE spent_height >= ?1",
+            args,
+        )
+        .map_err(db_error::SqliteError)?;
+        tx.execute("DELETE FROM utxos WHERE block_height >= ?1", args)
+            .map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Drop a pending transaction that will never be mined (it conflicts with a mined
+    /// transaction, or it was replaced by fee), along with any pending transactions that spend
+    /// its outputs.  Its inputs become spendable again.
+    fn inner_drop_pending_tx<'a>(tx: &mut DBTx<'a>, txid: &Sha256dHash) -> Result<(), btc_error> {
+        let children = {
+            let mut stmt = tx
+                .prepare("SELECT DISTINCT spent_by FROM utxos WHERE txid = ?1 AND block_height IS NULL AND spent_by IS NOT NULL")
+                .map_err(db_error::SqliteError)?;
+            let rows = stmt
+                .query_map(&[txid], |row| row.get::<_, Sha256dHash>(0))
+                .map_err(db_error::SqliteError)?;
+
+            let mut children = vec![];
+            for row in rows {
+                children.push(row.map_err(db_error::SqliteError)?);
+            }
+            children
+        };
+        for child in children.iter() {
+            BitcoinWallet::inner_drop_pending_tx(tx, child)?;
+        }
+
+        tx.execute(
+            "DELETE FROM utxos WHERE txid = ?1 AND block_height IS NULL",
+            &[txid],
+        )
+        .map_err(db_error::SqliteError)?;
+        tx.execute(
+            "UPDATE utxos SET spent_by = NULL WHERE spent_by = ?1 AND spent_height IS NULL",
+            &[txid],
+        )
+        .map_err(db_error::SqliteError)?;
+        tx.execute("DELETE FROM pending_txs WHERE txid = ?1", &[txid])
+            .map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Add a transaction's spends and outputs to the wallet.
+    /// If block_height is None, the transaction is one we sent that is not yet mined.
+    fn inner_apply_tx<'a>(
+        tx: &mut DBTx<'a>,
+        scripts: &HashSet<Script>,
+        btc_tx: &Transaction,
+        block_height: Option<u64>,
+    ) -> Result<bool, btc_error> {
+        let txid = btc_tx.txid();
+        let height_sql = match block_height {
+            Some(h) => Some(u64_to_sql(h)?),
+            None => None,
+        };
+        let mut relevant = false;
+
+        for input in btc_tx.input.iter() {
+            let prev_txid = &input.previous_output.txid;
+            let prev_vout = input.previous_output.vout;
+
+            let spent_by: Option<Option<Sha256dHash>> = tx
+                .query_row(
+                    "SELECT spent_by FROM utxos WHERE txid = ?1 AND vout = ?2",
+                    &[prev_txid as &dyn ToSql, &prev_vout],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(db_error::SqliteError)?;
+
+            let spent_by = match spent_by {
+                Some(spent_by) => spent_by,
+                None => {
+                    // not ours
+                    continue;
+                }
+            };
+            relevant = true;
+
+            if let Some(other_txid) = spent_by {
+                if other_txid != txid && block_height.is_some() {
+                    // a different transaction of ours spent this output, and it lost
+                    debug!(
+                        "Pending transaction {} conflicts with mined transaction {}",
+                        other_txid.be_hex_string(),
+                        txid.be_hex_string()
+                    );
+                    BitcoinWallet::inner_drop_pending_tx(tx, &other_txid)?;
+                }
+            }
+
+            tx.execute(
+                "UPDATE utxos SET spent_by = ?1, spent_height = ?2 WHERE txid = ?3 AND vout = ?4",
+                &[&txid as &dyn ToSql, &height_sql, prev_txid, &prev_vout],
+            )
+            .map_err(db_error::SqliteError)?;
+        }
+
+        for (vout, output) in btc_tx.output.iter().enumerate() {
+            if !scripts.contains(&output.script_pubkey) {
+                continue;
+            }
+            relevant = true;
+
+            let vout = vout as u32;
+            let updated = tx
+                .execute(
+                    "UPDATE utxos SET block_height = ?1 WHERE txid = ?2 AND vout = ?3",
+                    &[&height_sql as &dyn ToSql, &txid, &vout],
+                )
+                .map_err(db_error::SqliteError)?;
+
+            if updated == 0 {
+                tx.execute(
+                    "INSERT INTO utxos (txid, vout, amount, script_pubkey, block_height) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    &[
+                        &txid as &dyn ToSql,
+                        &vout,
+                        &u64_to_sql(output.value)?,
+                        &to_hex(output.script_pubkey.as_bytes()),
+                        &height_sql,
+                    ],
+                )
+                .map_err(db_error::SqliteError)?;
+            }
+        }
+
+        if block_height.is_some() {
+            tx.execute("DELETE FROM pending_txs WHERE txid = ?1", &[&txid])
+                .map_err(db_error::SqliteError)?;
+        }
+
+        Ok(relevant)
+    }
+
+    /// Scan a block on the best chain for transactions that pay to or spend from the wallet.
+    /// Blocks must be processed in height order.
+    pub fn process_block(&mut self, block: &Block, block_height: u64) -> Result<(), btc_error> {
+        if let Some(scanned_height) = self.get_scanned_height()? {
+            if scanned_height + 1 != block_height {
+                warn!(
+                    "Wallet {} scanned up to {}, but was given block {}",
+                    &self.path, scanned_height, block_height
+                );
+                return Err(btc_error::NoncontiguousHeader);
+            }
+        }
+
+        let scripts = self.get_tracked_scripts()?;
+        let block_hash = block.bitcoin_hash();
+
+        let mut tx = self.tx_begin()?;
+        let mut num_relevant = 0;
+        for btc_tx in block.txdata.iter() {
+            if BitcoinWallet::inner_apply_tx(&mut tx, &scripts, btc_tx, Some(block_height))? {
+                num_relevant += 1;
+            }
+        }
+
+        tx.execute(
+            "INSERT INTO scanned_blocks (height, block_hash) VALUES (?1, ?2)",
+            &[&u64_to_sql(block_height)? as &dyn ToSql, &block_hash],
+        )
+        .map_err(db_error::SqliteError)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+
+        if num_relevant > 0 {
+            debug!(
+                "Wallet {}: {} relevant transaction(s) in block {} ({})",
+                &self.path,
+                num_relevant,
+                block_height,
+                block_hash.be_hex_string()
+            );
+        }
+        Ok(())
+    }
+
+    /// Bring the wallet up to date with the SPV client's best chain:  forget any scanned blocks
+    /// that are no longer on it, and download and scan the blocks after the last one that is --
+    /// at most `max_blocks` of them, if given, so callers can scan a little at a time.
+    /// Returns the height of the highest scanned block and the height of the best chain's tip.
+    pub fn sync(
+        &mut self,
+        indexer: &BitcoinIndexer,
+        max_blocks: Option<u64>,
+    ) -> Result<(u64, u64), btc_error> {
+        let spv_client = SpvClient::new(
+            &indexer.config.spv_headers_path,
+            0,
+            None,
+            self.network_id,
+            false,
+            false,
+        )?;
+        let tip_height = spv_client.get_highest_header_height()?;
+
+        // find the highest scanned block that is still canonical
+        let mut next_height = self.birth_height;
+        if let Some(scanned_height) = self.get_scanned_height()? {
+            let mut height = cmp::min(scanned_height, tip_height);
+            loop {
+                let canonical_hash = spv_client
+                    .read_block_header(height)?
+                    .map(|hdr| hdr.header.bitcoin_hash());
+                let scanned_hash = self.get_scanned_block_hash(height)?;
+                if scanned_hash.is_some() && scanned_hash == canonical_hash {
+                    next_height = height + 1;
+                    break;
+                }
+                if height <= self.birth_height {
+                    break;
+                }
+                height -= 1;
+            }
+
+            if next_height <= scanned_height {
+                info!(
+                    "Wallet {}: blocks from height {} are no longer canonical",
+                    &self.path, next_height
+                );
+                self.forget_blocks_from(next_height)?;
+            }
+        }
+
+        let last_height = match max_blocks {
+            Some(max_blocks) => cmp::min(tip_height, next_height + max_blocks.max(1) - 1),
+            None => tip_height,
+        };
+
+        let mut downloader = BitcoinBlockDownloader::new(indexer.dup());
+        for height in next_height..(last_height + 1) {
+            let block_header = spv_client
+                .read_block_header(height)?
+                .ok_or(btc_error::MissingHeader)?;
+            let ipc_block = downloader.run(&BitcoinHeaderIPC {
+                block_header,
+                block_height: height,
+            })?;
+            match ipc_block.block_message {
+                btc_message::NetworkMessage::Block(ref block) => {
+                    self.process_block(block, height)?;
+                }
+                _ => {
+                    return Err(btc_error::InvalidReply);
+                }
+            }
+        }
+
+        let scanned_height = self
+            .get_scanned_height()?
+            .unwrap_or(self.birth_height.saturating_sub(1));
+        Ok((scanned_height, tip_height))
+    }
+
+    /// Record a transaction we are about to broadcast.  Its inputs are marked as spent, and its
+    /// outputs to the wallet (i.e. its change) become spendable right away.  If it replaces a
+    /// pending transaction by fee, the replaced transaction (and anything spending its change)
+    /// is dropped.
+    pub fn record_pending_tx(
+        &mut self,
+        btc_tx: &Transaction,
+        replaces: Option<&Sha256dHash>,
+    ) -> Result<(), btc_error> {
+        let scripts = self.get_tracked_scripts()?;
+        let txid = btc_tx.txid();
+        let tx_hex = {
+            let bytes = serialize(btc_tx).map_err(btc_error::SerializationError)?;
+            to_hex(&bytes)
+        };
+
+        let mut tx = self.tx_begin()?;
+        if let Some(replaced_txid) = replaces {
+            BitcoinWallet::inner_drop_pending_tx(&mut tx, replaced_txid)?;
+        }
+
+        BitcoinWallet::inner_apply_tx(&mut tx, &scripts, btc_tx, None)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO pending_txs (txid, tx_hex, replaces, sent_at) VALUES (?1, ?2, ?3, ?4)",
+            &[
+                &txid as &dyn ToSql,
+                &tx_hex,
+                &replaces,
+                &u64_to_sql(get_epoch_time_secs())?,
+            ],
+        )
+        .map_err(db_error::SqliteError)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Get the raw transactions we sent that are not yet mined, oldest first
+    pub fn get_pending_txs(&self) -> Result<Vec<(Sha256dHash, String)>, btc_error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT txid, tx_hex FROM pending_txs ORDER BY sent_at")
+            .map_err(db_error::SqliteError)?;
+        let rows = stmt
+            .query_map(NO_PARAMS, |row| {
+                let txid: Sha256dHash = row.get(0)?;
+                let tx_hex: String = row.get(1)?;
+                Ok((txid, tx_hex))
+            })
+            .map_err(db_error::SqliteError)?;
+
+        let mut ret = vec![];
+        for row in rows {
+            ret.push(row.map_err(db_error::SqliteError)?);
+        }
+        Ok(ret)
+    }
+
+    /// Get all unspent outputs, including the change of our pending transactions
+    pub fn get_utxos(&self) -> Result<Vec<WalletUTXO>, btc_error> {
+        let utxos = query_rows(
+            &self.conn,
+            "SELECT * FROM utxos WHERE spent_by IS NULL ORDER BY amount",
+            NO_PARAMS,
+        )?;
+        Ok(utxos)
+    }
+
+    /// Get the wallet's outputs that the given transaction spends
+    pub fn get_utxos_spent_by(&self, txid: &Sha256dHash) -> Result<Vec<WalletUTXO>, btc_error> {
+        let utxos = query_rows(
+            &self.conn,
+            "SELECT * FROM utxos WHERE spent_by = ?1 ORDER BY amount",
+            &[txid],
+        )?;
+        Ok(utxos)
+    }
+
+    /// Total value of the unspent outputs
+    pub fn get_balance(&self) -> Result<u64, btc_error> {
+        Ok(self.get_utxos()?.iter().map(|u| u.amount).sum())
+    }
+
+    /// Choose unspent outputs worth at least `total_required`, skipping any in `exclude`.
+    /// The smallest single output that covers the amount is preferred; otherwise, outputs are
+    /// added from largest to smallest.  If the wallet holds many outputs, some of the smallest
+    /// ones that are worth more than the fee to spend them at `fee_rate` are added as well, so
+    /// they get consolidated into the change.
+    /// Returns None if the wallet cannot cover the amount.
+    pub fn select_utxos(
+        &self,
+        total_required: u64,
+        fee_rate: u64,
+        exclude: &[(Sha256dHash, u32)],
+    ) -> Result<Option<Vec<WalletUTXO>>, btc_error> {
+        // sorted by ascending amount
+        let candidates: Vec<WalletUTXO> = self
+            .get_utxos()?
+            .into_iter()
+            .filter(|u| !exclude.contains(&(u.txid.clone(), u.vout)))
+            .collect();
+
+        let mut selected = vec![];
+        let mut remaining = vec![];
+        if let Some(idx) = candidates.iter().position(|u| u.amount >= total_required) {
+            for (i, utxo) in candidates.into_iter().enumerate() {
+                if i == idx {
+                    selected.push(utxo);
+                } else {
+                    remaining.push(utxo);
+                }
+            }
+        } else {
+            let mut total = 0;
+            for utxo in candidates.into_iter().rev() {
+                if total < total_required {
+                    total += utxo.amount;
+                    selected.push(utxo);
+                } else {
+                    remaining.push(utxo);
+                }
+            }
+            if total < total_required {
+                return Ok(None);
+            }
+            remaining.reverse();
+        }
+
+        if selected.len() + remaining.len() > CONSOLIDATION_THRESHOLD {
+            let min_amount = 2 * P2PKH_INPUT_SIZE * fee_rate;
+            let mut num_consolidated = 0;
+            for utxo in remaining.into_iter() {
+                if num_consolidated >= MAX_CONSOLIDATION_INPUTS {
+                    break;
+                }
+                if utxo.block_height.is_some() && utxo.amount > min_amount {
+                    selected.push(utxo);
+                    num_consolidated += 1;
+                }
+            }
+        }
+
+        Ok(Some(selected))
+    }
+
+    pub fn get_metadata(&self, key: &str) -> Result<Option<String>, btc_error> {
+        let value = self
+            .conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = ?1",
+                &[&key],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(db_error::SqliteError)?;
+        Ok(value)
+    }
+
+    pub fn put_metadata(&mut self, key: &str, value: &str) -> Result<(), btc_error> {
+        let tx = self.tx_begin()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+            &[&key, &value],
+        )
+        .map_err(db_error::SqliteError)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    pub fn delete_metadata(&mut self, key: &str) -> Result<(), btc_error> {
+        let tx = self.tx_begin()?;
+        tx.execute("DELETE FROM metadata WHERE key = ?1", &[&key])
+            .map_err(db_error::SqliteError)?;
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use deps::bitcoin::blockdata::block::BlockHeader;
+    use deps::bitcoin::blockdata::transaction::{OutPoint, TxIn, TxOut};
+
+    fn setup_wallet(path: &str) -> BitcoinWallet {
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+        BitcoinWallet::new(path, BitcoinNetworkType::Regtest, 100, true).unwrap()
+    }
+
+    fn make_block(height: u64, txs: Vec<Transaction>) -> Block {
+        Block {
+            header: BlockHeader {
+                version: 1,
+                prev_blockhash: Sha256dHash([0u8; 32]),
+                merkle_root: Sha256dHash([0u8; 32]),
+                time: height as u32,
+                bits: 0,
+                nonce: 0,
+            },
+            txdata: txs,
+        }
+    }
+
+    fn make_tx(
+        inputs: Vec<(Sha256dHash, u32)>,
+        outputs: Vec<TxOut>,
+        lock_time: u32,
+    ) -> Transaction {
+        Transaction {
+            version: 1,
+            lock_time,
+            input: inputs
+                .into_iter()
+                .map(|(txid, vout)| TxIn {
+                    previous_output: OutPoint { txid, vout },
+                    script_sig: Script::new(),
+                    sequence: 0xFFFFFFFD,
+                    witness: vec![],
+                })
+                .collect(),
+            output: outputs,
+        }
+    }
+
+    #[test]
+    fn test_wallet_scan_blocks() {
+        let mut wallet = setup_wallet("/tmp/test_wallet_scan_blocks.sqlite");
+        let ours = Hash160([0x11; 20]);
+        let theirs = Hash160([0x22; 20]);
+
+        assert!(wallet.track_address(&ours).unwrap());
+        assert!(!wallet.track_address(&ours).unwrap());
+
+        // a block paying us, and someone else
+        let funding_tx = make_tx(
+            vec![(Sha256dHash([0xff; 32]), 0)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 100_000),
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 200_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 300_000),
+            ],
+            0,
+        );
+        wallet
+            .process_block(&make_block(100, vec![funding_tx.clone()]), 100)
+            .unwrap();
+
+        assert_eq!(wallet.get_scanned_height().unwrap(), Some(100));
+        assert_eq!(wallet.get_balance().unwrap(), 400_000);
+
+        let utxos = wallet.get_utxos().unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert_eq!(utxos[0].vout, 0);
+        assert_eq!(utxos[0].amount, 100_000);
+        assert_eq!(utxos[0].block_height, Some(100));
+        assert_eq!(utxos[0].confirmations(102), 3);
+        assert_eq!(utxos[1].vout, 2);
+
+        // blocks must be contiguous
+        assert!(wallet.process_block(&make_block(102, vec![]), 102).is_err());
+
+        // a block spending one of our outputs, with change back to us
+        let spend_tx = make_tx(
+            vec![(funding_tx.txid(), 2)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 250_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 40_000),
+            ],
+            0,
+        );
+        wallet
+            .process_block(&make_block(101, vec![spend_tx.clone()]), 101)
+            .unwrap();
+
+        assert_eq!(wallet.get_balance().unwrap(), 140_000);
+        assert_eq!(
+            wallet.get_utxos_spent_by(&spend_tx.txid()).unwrap().len(),
+            1
+        );
+
+        // reorg block 101 out
+        wallet.forget_blocks_from(101).unwrap();
+        assert_eq!(wallet.get_scanned_height().unwrap(), Some(100));
+        assert_eq!(wallet.get_balance().unwrap(), 400_000);
+        assert_eq!(
+            wallet.get_utxos_spent_by(&spend_tx.txid()).unwrap().len(),
+            0
+        );
+
+        // tracking a new address means rescanning everything
+        assert!(wallet.track_address(&theirs).unwrap());
+        assert_eq!(wallet.get_scanned_height().unwrap(), None);
+        assert_eq!(wallet.get_balance().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_wallet_pending_txs() {
+        let mut wallet = setup_wallet("/tmp/test_wallet_pending_txs.sqlite");
+        let ours = Hash160([0x11; 20]);
+        let theirs = Hash160([0x22; 20]);
+        wallet.track_address(&ours).unwrap();
+
+        let funding_tx = make_tx(
+            vec![(Sha256dHash([0xff; 32]), 0)],
+            vec![BitcoinAddress::to_p2pkh_tx_out(&ours, 100_000)],
+            0,
+        );
+        wallet
+            .process_block(&make_block(100, vec![funding_tx.clone()]), 100)
+            .unwrap();
+
+        // send a transaction, and chain another one off of its change
+        let commit_1 = make_tx(
+            vec![(funding_tx.txid(), 0)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 10_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 89_000),
+            ],
+            0,
+        );
+        wallet.record_pending_tx(&commit_1, None).unwrap();
+
+        let utxos = wallet.get_utxos().unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txid, commit_1.txid());
+        assert_eq!(utxos[0].block_height, None);
+        assert_eq!(utxos[0].confirmations(100), 0);
+
+        let commit_2 = make_tx(
+            vec![(commit_1.txid(), 1)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 10_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 78_000),
+            ],
+            0,
+        );
+        wallet.record_pending_tx(&commit_2, None).unwrap();
+        assert_eq!(wallet.get_balance().unwrap(), 78_000);
+        assert_eq!(wallet.get_pending_txs().unwrap().len(), 2);
+
+        // replace the first one by fee.  The second one spent its change, so it's dropped too.
+        let commit_1_rbf = make_tx(
+            vec![(funding_tx.txid(), 0)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 10_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 88_000),
+            ],
+            0,
+        );
+        wallet
+            .record_pending_tx(&commit_1_rbf, Some(&commit_1.txid()))
+            .unwrap();
+
+        let pending = wallet.get_pending_txs().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, commit_1_rbf.txid());
+        assert_eq!(wallet.get_balance().unwrap(), 88_000);
+        assert_eq!(
+            wallet.get_utxos_spent_by(&commit_1_rbf.txid()).unwrap()[0].txid,
+            funding_tx.txid()
+        );
+
+        // a conflicting transaction gets mined instead (e.g. an earlier replacement we forgot)
+        let commit_1_other = make_tx(
+            vec![(funding_tx.txid(), 0)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 10_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 87_000),
+            ],
+            1,
+        );
+        wallet
+            .process_block(&make_block(101, vec![commit_1_other.clone()]), 101)
+            .unwrap();
+
+        assert_eq!(wallet.get_pending_txs().unwrap().len(), 0);
+        let utxos = wallet.get_utxos().unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txid, commit_1_other.txid());
+        assert_eq!(utxos[0].block_height, Some(101));
+
+        // a pending transaction that gets mined keeps its change
+        let commit_3 = make_tx(
+            vec![(commit_1_other.txid(), 1)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&theirs, 10_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 76_000),
+            ],
+            0,
+        );
+        wallet.record_pending_tx(&commit_3, None).unwrap();
+        wallet
+            .process_block(&make_block(102, vec![commit_3.clone()]), 102)
+            .unwrap();
+
+        assert_eq!(wallet.get_pending_txs().unwrap().len(), 0);
+        let utxos = wallet.get_utxos().unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].txid, commit_3.txid());
+        assert_eq!(utxos[0].block_height, Some(102));
+    }
+
+    #[test]
+    fn test_wallet_select_utxos() {
+        let mut wallet = setup_wallet("/tmp/test_wallet_select_utxos.sqlite");
+        let ours = Hash160([0x11; 20]);
+        wallet.track_address(&ours).unwrap();
+
+        let funding_tx = make_tx(
+            vec![(Sha256dHash([0xff; 32]), 0)],
+            vec![
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 10_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 50_000),
+                BitcoinAddress::to_p2pkh_tx_out(&ours, 20_000),
+            ],
+            0,
+        );
+        wallet
+            .process_block(&make_block(100, vec![funding_tx.clone()]), 100)
+            .unwrap();
+
+        // smallest single output that covers the amount
+        let selected = wallet.select_utxos(15_000, 1, &[]).unwrap().unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].amount, 20_000);
+
+        // largest first
+        let selected = wallet.select_utxos(65_000, 1, &[]).unwrap().unwrap();
+        assert_eq!(
+            selected.iter().map(|u| u.amount).collect::<Vec<_>>(),
+            vec![50_000, 20_000]
+        );
+
+        // exclusions
+        let selected = wallet
+            .select_utxos(15_000, 1, &[(funding_tx.txid(), 2)])
+            .unwrap()
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].amount, 50_000);
+
+        // not enough
+        assert!(wallet.select_utxos(80_001, 1, &[]).unwrap().is_none());
+
+        // lots of small outputs get consolidated, if they're worth spending
+        let dust_tx = make_tx(
+            vec![(Sha256dHash([0xfe; 32]), 0)],
+            (0..CONSOLIDATION_THRESHOLD)
+                .map(|i| BitcoinAddress::to_p2pkh_tx_out(&ours, 1_000 + i as u64))
+                .collect(),
+            0,
+        );
+        wallet
+            .process_block(&make_block(101, vec![dust_tx]), 101)
+            .unwrap();
+
+        let selected = wallet.select_utxos(15_000, 1, &[]).unwrap().unwrap();
+        assert_eq!(selected.len(), 1 + MAX_CONSOLIDATION_INPUTS);
+        assert_eq!(selected[0].amount, 20_000);
+        assert_eq!(
+            selected[1..].iter().map(|u| u.amount).collect::<Vec<_>>(),
+            vec![1_000, 1_001, 1_002, 1_003, 1_004]
+        );
+
+        // ...but not if the fee to spend them is too high
+        let selected = wallet.select_utxos(15_000, 10, &[]).unwrap().unwrap();
+        assert_eq!(
+            selected.iter().map(|u| u.amount).collect::<Vec<_>>(),
+            vec![20_000, 10_000, 50_000]
+        );
+    }
+
+    #[test]
+    fn test_wallet_metadata() {
+        let mut wallet = setup_wallet("/tmp/test_wallet_metadata.sqlite");
+        assert_eq!(wallet.get_metadata("foo").unwrap(), None);
+        wallet.put_metadata("foo", "bar").unwrap();
+        assert_eq!(wallet.get_metadata("foo").unwrap(), Some("bar".to_string()));
+        wallet.put_metadata("foo", "baz").unwrap();
+        assert_eq!(wallet.get_metadata("foo").unwrap(), Some("baz".to_string()));
+        wallet.delete_metadata("foo").unwrap();
+        assert_eq!(wallet.get_metadata("foo").unwrap(), None);
+    }
+
+    #[test]
+    fn test_wallet_birth_height() {
+        let path = "/tmp/test_wallet_birth_height.sqlite";
+        let mut wallet = setup_wallet(path);
+        assert_eq!(wallet.birth_height, 100);
+        let ours = Hash160([0x11; 20]);
+        wallet.track_address(&ours).unwrap();
+        wallet.process_block(&make_block(100, vec![]), 100).unwrap();
+
+        // the birth height is saved when the wallet is created
+        let mut wallet = BitcoinWallet::new(path, BitcoinNetworkType::Regtest, 200, true).unwrap();
+        assert_eq!(wallet.birth_height, 100);
+        assert_eq!(wallet.get_scanned_height().unwrap(), Some(100));
+
+        // raising it keeps what was scanned
+        wallet.set_birth_height(150).unwrap();
+        assert_eq!(wallet.get_scanned_height().unwrap(), Some(100));
+
+        // lowering it forces a rescan
+        wallet.set_birth_height(50).unwrap();
+        assert_eq!(wallet.get_scanned_height().unwrap(), None);
+        let wallet = BitcoinWallet::new(path, BitcoinNetworkType::Regtest, 200, false).unwrap();
+        assert_eq!(wallet.birth_height, 50);
+    }
+}
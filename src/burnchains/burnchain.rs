@@ -365,21 +365,25 @@ impl BurnchainBlock {
     pub fn block_height(&self) -> u64 {
         match *self {
             BurnchainBlock::Bitcoin(ref data) => data.block_height,
+            BurnchainBlock::Replay(ref data) => data.block_height,
         }
     }
 
     pub fn block_hash(&self) -> BurnchainHeaderHash {
         match *self {
             BurnchainBlock::Bitcoin(ref data) => data.block_hash.clone(),
+            BurnchainBlock::Replay(ref data) => data.block_hash.clone(),
         }
     }
 
     pub fn parent_block_hash(&self) -> BurnchainHeaderHash {
         match *self {
             BurnchainBlock::Bitcoin(ref data) => data.parent_block_hash.clone(),
+            BurnchainBlock::Replay(ref data) => data.parent_block_hash.clone(),
         }
     }
 
+    /// Raw burnchain transactions.  Replayed blocks carry already-parsed operations instead.
     pub fn txs(&self) -> Vec<BurnchainTransaction> {
         match *self {
             BurnchainBlock::Bitcoin(ref data) => data
@@ -387,12 +391,14 @@ impl BurnchainBlock {
                 .iter()
                 .map(|ref tx| BurnchainTransaction::Bitcoin((*tx).clone()))
                 .collect(),
+            BurnchainBlock::Replay(_) => vec![],
         }
     }
 
     pub fn timestamp(&self) -> u64 {
         match *self {
             BurnchainBlock::Bitcoin(ref data) => data.timestamp,
+            BurnchainBlock::Replay(ref data) => data.timestamp,
         }
     }
 
@@ -405,6 +411,7 @@ impl BurnchainBlock {
                 num_txs: data.txs.len() as u64,
                 timestamp: data.timestamp,
             },
+            BurnchainBlock::Replay(ref data) => data.header(),
         }
     }
 }
@@ -338,7 +338,13 @@ impl BurnchainDB {
         let header = block.header();
         debug!("Storing new burnchain block";
               "burn_header_hash" => %header.block_hash.to_string());
-        let mut blockstack_ops = self.get_blockstack_transactions(burnchain, block, &header);
+        let mut blockstack_ops = match *block {
+            BurnchainBlock::Bitcoin(_) => {
+                self.get_blockstack_transactions(burnchain, block, &header)
+            }
+            // replayed blocks carry their operations already parsed
+            BurnchainBlock::Replay(ref data) => data.ops.clone(),
+        };
         apply_blockstack_txs_safety_checks(header.block_height, &mut blockstack_ops);
 
         let db_tx = self.tx_begin()?;
@@ -52,12 +52,14 @@ use self::bitcoin::Error as btc_error;
 use self::bitcoin::{
     BitcoinBlock, BitcoinInputType, BitcoinTransaction, BitcoinTxInput, BitcoinTxOutput,
 };
+use self::replay::ReplayBlock;
 
 /// This module contains drivers and types for all burn chains we support.
 pub mod bitcoin;
 pub mod burnchain;
 pub mod db;
 pub mod indexer;
+pub mod replay;
 
 #[derive(Serialize, Deserialize)]
 pub struct Txid(pub [u8; 32]);
@@ -270,6 +272,7 @@ impl BurnchainTransaction {
 #[derive(Debug, PartialEq, Clone)]
 pub enum BurnchainBlock {
     Bitcoin(BitcoinBlock),
+    Replay(ReplayBlock),
     // TODO: fill in some more types as we support them
 }
 
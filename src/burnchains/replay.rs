@@ -0,0 +1,572 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json;
+
+use burnchains::db::BurnchainDB;
+use burnchains::indexer::{
+    BurnBlockIPC, BurnHeaderIPC, BurnchainBlockDownloader, BurnchainBlockParser, BurnchainIndexer,
+};
+use burnchains::BurnchainBlock;
+use burnchains::BurnchainBlockHeader;
+use burnchains::Error as burnchain_error;
+use chainstate::burn::operations::BlockstackOperationType;
+use core::StacksEpoch;
+
+use crate::types::chainstate::BurnchainHeaderHash;
+use crate::types::proof::ClarityMarfTrieId;
+
+/// A burnchain block loaded from a replay fixture, along with the Blockstack operations that
+/// were mined in it.  Unlike a Bitcoin block, the operations are already parsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayBlock {
+    pub block_height: u64,
+    pub block_hash: BurnchainHeaderHash,
+    pub parent_block_hash: BurnchainHeaderHash,
+    pub timestamp: u64,
+    pub ops: Vec<BlockstackOperationType>,
+}
+
+impl ReplayBlock {
+    pub fn header(&self) -> BurnchainBlockHeader {
+        BurnchainBlockHeader {
+            block_height: self.block_height,
+            block_hash: self.block_hash.clone(),
+            parent_block_hash: self.parent_block_hash.clone(),
+            num_txs: self.ops.len() as u64,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// A linear burnchain history to replay.  The first block is the burnchain's first block, and
+/// each subsequent block builds on the one before it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFixture {
+    pub blocks: Vec<ReplayBlock>,
+}
+
+impl ReplayFixture {
+    /// Load a fixture.  Files ending in `.json` are read as JSON fixtures; anything else is
+    /// treated as the `burnchain.sqlite` database of an existing node.
+    pub fn load(path: &str) -> Result<ReplayFixture, burnchain_error> {
+        let is_json = Path::new(path)
+            .extension()
+            .map(|ext| ext == "json")
+            .unwrap_or(false);
+
+        if is_json {
+            ReplayFixture::from_json_file(path)
+        } else {
+            ReplayFixture::from_burnchain_db(path)
+        }
+    }
+
+    pub fn from_json_file(path: &str) -> Result<ReplayFixture, burnchain_error> {
+        let contents = fs::read_to_string(path).map_err(burnchain_error::FSError)?;
+        let fixture: ReplayFixture = serde_json::from_str(&contents).map_err(|e| {
+            warn!("Failed to parse replay fixture {}: {:?}", path, &e);
+            burnchain_error::ParseError
+        })?;
+        fixture.validate()?;
+        Ok(fixture)
+    }
+
+    /// Load the canonical burnchain history stored in a node's burnchain DB.
+    pub fn from_burnchain_db(path: &str) -> Result<ReplayFixture, burnchain_error> {
+        let burnchain_db = BurnchainDB::open(path, false)?;
+        let mut cursor = burnchain_db.get_canonical_chain_tip()?.block_hash;
+        let mut blocks = vec![];
+        loop {
+            let block_data = burnchain_db.get_burnchain_block(&cursor)?;
+            let header = block_data.header;
+            blocks.push(ReplayBlock {
+                block_height: header.block_height,
+                block_hash: header.block_hash,
+                parent_block_hash: header.parent_block_hash.clone(),
+                timestamp: header.timestamp,
+                ops: block_data.ops,
+            });
+            if header.parent_block_hash == BurnchainHeaderHash::sentinel() {
+                break;
+            }
+            cursor = header.parent_block_hash;
+        }
+        blocks.reverse();
+
+        let fixture = ReplayFixture { blocks };
+        fixture.validate()?;
+        Ok(fixture)
+    }
+
+    pub fn to_json_file(&self, path: &str) -> Result<(), burnchain_error> {
+        let contents =
+            serde_json::to_string_pretty(self).expect("BUG: failed to serialize replay fixture");
+        fs::write(path, contents).map_err(burnchain_error::FSError)
+    }
+
+    /// Make sure the fixture describes a single unbroken chain, and that every operation
+    /// belongs to the block it is listed in.
+    fn validate(&self) -> Result<(), burnchain_error> {
+        if self.blocks.len() == 0 {
+            warn!("Replay fixture has no blocks");
+            return Err(burnchain_error::MissingHeaders);
+        }
+
+        for i in 1..self.blocks.len() {
+            let parent = &self.blocks[i - 1];
+            let block = &self.blocks[i];
+            if block.block_height != parent.block_height + 1 {
+                warn!(
+                    "Replay fixture block {} follows block {}",
+                    block.block_height, parent.block_height
+                );
+                return Err(burnchain_error::MissingHeaders);
+            }
+            if block.parent_block_hash != parent.block_hash {
+                warn!(
+                    "Replay fixture block {} at height {} does not build on {}",
+                    &block.block_hash, block.block_height, &parent.block_hash
+                );
+                return Err(burnchain_error::MissingParentBlock);
+            }
+        }
+
+        for block in self.blocks.iter() {
+            for op in block.ops.iter() {
+                if op.block_height() != block.block_height
+                    || op.burn_header_hash() != block.block_hash
+                {
+                    warn!(
+                        "Replay fixture op {} is listed in block {} at height {}, but belongs to {} at height {}",
+                        &op.txid(),
+                        &block.block_hash,
+                        block.block_height,
+                        &op.burn_header_hash(),
+                        op.block_height()
+                    );
+                    return Err(burnchain_error::ParseError);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayHeaderIPC {
+    pub block_header: BurnchainBlockHeader,
+}
+
+impl BurnHeaderIPC for ReplayHeaderIPC {
+    type H = BurnchainBlockHeader;
+
+    fn header(&self) -> BurnchainBlockHeader {
+        self.block_header.clone()
+    }
+
+    fn height(&self) -> u64 {
+        self.block_header.block_height
+    }
+
+    /// Bitcoin-style (little-endian) header hash, so that
+    /// `BurnchainHeaderHash::from_bitcoin_hash()` recovers the fixture's block hash.
+    fn header_hash(&self) -> [u8; 32] {
+        let mut bytes = self.block_header.block_hash.0.clone();
+        bytes.reverse();
+        bytes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayBlockIPC {
+    pub header_data: ReplayHeaderIPC,
+    pub block: ReplayBlock,
+}
+
+impl BurnBlockIPC for ReplayBlockIPC {
+    type H = ReplayHeaderIPC;
+    type B = ReplayBlock;
+
+    fn header(&self) -> ReplayHeaderIPC {
+        self.header_data.clone()
+    }
+
+    fn height(&self) -> u64 {
+        self.header_data.height()
+    }
+
+    fn block(&self) -> ReplayBlock {
+        self.block.clone()
+    }
+}
+
+pub struct ReplayBlockDownloader {
+    blocks: Arc<Vec<ReplayBlock>>,
+}
+
+impl BurnchainBlockDownloader for ReplayBlockDownloader {
+    type H = ReplayHeaderIPC;
+    type B = ReplayBlockIPC;
+
+    fn download(&mut self, header: &ReplayHeaderIPC) -> Result<ReplayBlockIPC, burnchain_error> {
+        let first_block_height = self.blocks[0].block_height;
+        let block = header
+            .height()
+            .checked_sub(first_block_height)
+            .and_then(|idx| self.blocks.get(idx as usize))
+            .filter(|block| block.block_hash == header.block_header.block_hash)
+            .ok_or_else(|| burnchain_error::UnknownBlock(header.block_header.block_hash.clone()))?;
+
+        Ok(ReplayBlockIPC {
+            header_data: header.clone(),
+            block: block.clone(),
+        })
+    }
+}
+
+pub struct ReplayBlockParser {}
+
+impl BurnchainBlockParser for ReplayBlockParser {
+    type D = ReplayBlockDownloader;
+
+    fn parse(&mut self, ipc_block: &ReplayBlockIPC) -> Result<BurnchainBlock, burnchain_error> {
+        Ok(BurnchainBlock::Replay(ipc_block.block.clone()))
+    }
+}
+
+/// Burnchain indexer that serves blocks out of a replay fixture instead of a Bitcoin node.
+/// The fixture's chain never reorgs and never grows, so syncing against it is deterministic.
+#[derive(Debug, Clone)]
+pub struct ReplayIndexer {
+    fixture_path: String,
+    epochs: Vec<StacksEpoch>,
+    blocks: Arc<Vec<ReplayBlock>>,
+}
+
+impl ReplayIndexer {
+    pub fn new(
+        fixture_path: &str,
+        epochs: Vec<StacksEpoch>,
+    ) -> Result<ReplayIndexer, burnchain_error> {
+        let fixture = ReplayFixture::load(fixture_path)?;
+        Ok(ReplayIndexer::from_fixture(fixture_path, fixture, epochs))
+    }
+
+    pub fn from_fixture(
+        fixture_path: &str,
+        fixture: ReplayFixture,
+        epochs: Vec<StacksEpoch>,
+    ) -> ReplayIndexer {
+        ReplayIndexer {
+            fixture_path: fixture_path.to_string(),
+            epochs,
+            blocks: Arc::new(fixture.blocks),
+        }
+    }
+
+    /// The first block of the fixture, which is the burnchain's first block
+    pub fn first_block(&self) -> &ReplayBlock {
+        &self.blocks[0]
+    }
+
+    /// The last block of the fixture.  Syncing stops here.
+    pub fn last_block(&self) -> &ReplayBlock {
+        &self.blocks[self.blocks.len() - 1]
+    }
+}
+
+impl BurnchainIndexer for ReplayIndexer {
+    type P = ReplayBlockParser;
+
+    /// Nothing to connect to
+    fn connect(&mut self) -> Result<(), burnchain_error> {
+        Ok(())
+    }
+
+    fn get_first_block_height(&self) -> u64 {
+        self.first_block().block_height
+    }
+
+    fn get_first_block_header_hash(&self) -> Result<BurnchainHeaderHash, burnchain_error> {
+        Ok(self.first_block().block_hash.clone())
+    }
+
+    fn get_first_block_header_timestamp(&self) -> Result<u64, burnchain_error> {
+        Ok(self.first_block().timestamp)
+    }
+
+    fn get_stacks_epochs(&self) -> Vec<StacksEpoch> {
+        self.epochs.clone()
+    }
+
+    /// The fixture stands in for the headers file
+    fn get_headers_path(&self) -> String {
+        self.fixture_path.clone()
+    }
+
+    /// Number of headers, counting from height 0
+    fn get_headers_height(&self) -> Result<u64, burnchain_error> {
+        Ok(self.last_block().block_height + 1)
+    }
+
+    fn get_highest_header_height(&self) -> Result<u64, burnchain_error> {
+        Ok(self.last_block().block_height)
+    }
+
+    /// A fixture never reorgs
+    fn find_chain_reorg(&mut self) -> Result<u64, burnchain_error> {
+        self.get_highest_header_height()
+    }
+
+    /// All headers are known up front, so this only reports how far they go
+    fn sync_headers(
+        &mut self,
+        _start_height: u64,
+        end_height: Option<u64>,
+    ) -> Result<u64, burnchain_error> {
+        let highest = self.get_highest_header_height()?;
+        Ok(end_height.map(|h| h.min(highest)).unwrap_or(highest))
+    }
+
+    fn drop_headers(&mut self, _new_height: u64) -> Result<(), burnchain_error> {
+        Ok(())
+    }
+
+    /// Read fixture headers within a range
+    fn read_headers(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<ReplayHeaderIPC>, burnchain_error> {
+        Ok(self
+            .blocks
+            .iter()
+            .filter(|block| start_block <= block.block_height && block.block_height < end_block)
+            .map(|block| ReplayHeaderIPC {
+                block_header: block.header(),
+            })
+            .collect())
+    }
+
+    fn downloader(&self) -> ReplayBlockDownloader {
+        ReplayBlockDownloader {
+            blocks: self.blocks.clone(),
+        }
+    }
+
+    fn parser(&self) -> ReplayBlockParser {
+        ReplayBlockParser {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use burnchains::Burnchain;
+    use burnchains::Txid;
+    use chainstate::burn::operations::LeaderKeyRegisterOp;
+    use chainstate::burn::ConsensusHash;
+    use chainstate::coordinator::comm::CoordinatorCommunication;
+    use core::STACKS_EPOCHS_REGTEST;
+    use util::hash::{hex_bytes, Hash160};
+    use util::vrf::VRFPublicKey;
+
+    use crate::types::chainstate::StacksAddress;
+
+    use super::*;
+
+    fn make_leader_key(
+        block_height: u64,
+        block_hash: &BurnchainHeaderHash,
+        vtxindex: u32,
+    ) -> BlockstackOperationType {
+        BlockstackOperationType::LeaderKeyRegister(LeaderKeyRegisterOp {
+            consensus_hash: ConsensusHash([0x22; 20]),
+            public_key: VRFPublicKey::from_bytes(
+                &hex_bytes("a366b51292bef4edd64063d9145c617fec373bceb0758e98cd72becd84d54c7a")
+                    .unwrap(),
+            )
+            .unwrap(),
+            memo: vec![01, 02, 03, 04, 05],
+            address: StacksAddress {
+                version: 26,
+                bytes: Hash160([vtxindex as u8; 20]),
+            },
+            txid: Txid([(block_height as u8).wrapping_add(vtxindex as u8); 32]),
+            vtxindex,
+            block_height,
+            burn_header_hash: block_hash.clone(),
+        })
+    }
+
+    fn make_fixture(first_block_height: u64, num_blocks: u64) -> ReplayFixture {
+        let mut blocks: Vec<ReplayBlock> = vec![];
+        for i in 0..num_blocks {
+            let block_height = first_block_height + i;
+            let block_hash = BurnchainHeaderHash([(i + 1) as u8; 32]);
+            let parent_block_hash = match blocks.last() {
+                Some(parent) => parent.block_hash.clone(),
+                None => BurnchainHeaderHash::sentinel(),
+            };
+            let ops = if i > 0 && i % 2 == 0 {
+                vec![
+                    make_leader_key(block_height, &block_hash, 1),
+                    make_leader_key(block_height, &block_hash, 2),
+                ]
+            } else {
+                vec![]
+            };
+            blocks.push(ReplayBlock {
+                block_height,
+                block_hash,
+                parent_block_hash,
+                timestamp: 1000 + i,
+                ops,
+            });
+        }
+        ReplayFixture { blocks }
+    }
+
+    #[test]
+    fn test_replay_fixture_json() {
+        let path = "/tmp/test-replay-fixture-json.json";
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+
+        let fixture = make_fixture(100, 6);
+        fixture.to_json_file(path).unwrap();
+        assert_eq!(ReplayFixture::load(path).unwrap(), fixture);
+
+        // gap in the chain
+        let mut bad_fixture = fixture.clone();
+        bad_fixture.blocks.remove(3);
+        bad_fixture.to_json_file(path).unwrap();
+        assert!(ReplayFixture::load(path).is_err());
+
+        // op in the wrong block
+        let mut bad_fixture = fixture.clone();
+        let misplaced_op = bad_fixture.blocks[2].ops.pop().unwrap();
+        bad_fixture.blocks[3].ops.push(misplaced_op);
+        bad_fixture.to_json_file(path).unwrap();
+        assert!(ReplayFixture::load(path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_indexer_headers() {
+        let fixture = make_fixture(100, 6);
+        let mut indexer = ReplayIndexer::from_fixture(
+            "/tmp/test-replay-indexer-headers.json",
+            fixture.clone(),
+            STACKS_EPOCHS_REGTEST.to_vec(),
+        );
+
+        assert_eq!(indexer.get_first_block_height(), 100);
+        assert_eq!(
+            indexer.get_first_block_header_hash().unwrap(),
+            fixture.blocks[0].block_hash
+        );
+        assert_eq!(indexer.get_first_block_header_timestamp().unwrap(), 1000);
+        assert_eq!(indexer.get_highest_header_height().unwrap(), 105);
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 105);
+        assert_eq!(indexer.sync_headers(100, None).unwrap(), 105);
+        assert_eq!(indexer.sync_headers(100, Some(103)).unwrap(), 103);
+        assert_eq!(indexer.sync_headers(100, Some(200)).unwrap(), 105);
+
+        let headers = indexer.read_headers(102, 110).unwrap();
+        assert_eq!(headers.len(), 4);
+        for (i, hdr) in headers.iter().enumerate() {
+            let block = &fixture.blocks[i + 2];
+            assert_eq!(hdr.block_header, block.header());
+            assert_eq!(
+                BurnchainHeaderHash::from_bytes_be(&hdr.header_hash()).unwrap(),
+                block.block_hash
+            );
+
+            let ipc_block = indexer.downloader().download(hdr).unwrap();
+            assert_eq!(
+                indexer.parser().parse(&ipc_block).unwrap(),
+                BurnchainBlock::Replay(block.clone())
+            );
+        }
+
+        // a header that isn't in the fixture can't be downloaded
+        let mut bad_header = headers[0].clone();
+        bad_header.block_header.block_hash = BurnchainHeaderHash([0xff; 32]);
+        assert!(indexer.downloader().download(&bad_header).is_err());
+    }
+
+    #[test]
+    fn test_replay_sync_with_indexer() {
+        let working_dir = "/tmp/test-replay-sync-with-indexer";
+        if fs::metadata(working_dir).is_ok() {
+            fs::remove_dir_all(working_dir).unwrap();
+        }
+        fs::create_dir_all(working_dir).unwrap();
+
+        let fixture = make_fixture(100, 10);
+        let fixture_path = format!("{}/fixture.json", working_dir);
+        fixture.to_json_file(&fixture_path).unwrap();
+
+        let mut indexer =
+            ReplayIndexer::new(&fixture_path, STACKS_EPOCHS_REGTEST.to_vec()).unwrap();
+
+        let mut burnchain = Burnchain::regtest(working_dir);
+        burnchain.first_block_height = indexer.get_first_block_height();
+        burnchain.initial_reward_start_block = indexer.get_first_block_height();
+        burnchain.first_block_hash = indexer.get_first_block_header_hash().unwrap();
+        burnchain.first_block_timestamp =
+            indexer.get_first_block_header_timestamp().unwrap() as u32;
+
+        let (_coord_recv, coord_comm) = CoordinatorCommunication::instantiate();
+
+        // replay in two batches
+        let tip = burnchain
+            .sync_with_indexer(&mut indexer, coord_comm.clone(), None, Some(4), None)
+            .unwrap();
+        assert_eq!(tip.block_height, 104);
+
+        let tip = burnchain
+            .sync_with_indexer(&mut indexer, coord_comm.clone(), None, None, None)
+            .unwrap();
+        assert_eq!(tip, fixture.blocks[9].header());
+
+        // nothing left to replay
+        let tip = burnchain
+            .sync_with_indexer(&mut indexer, coord_comm.clone(), None, None, None)
+            .unwrap();
+        assert_eq!(tip, fixture.blocks[9].header());
+
+        let burnchain_db = BurnchainDB::open(&burnchain.get_burnchaindb_path(), false).unwrap();
+        for block in fixture.blocks.iter().skip(1) {
+            let block_data = burnchain_db.get_burnchain_block(&block.block_hash).unwrap();
+            assert_eq!(block_data.header, block.header());
+            assert_eq!(block_data.ops, block.ops);
+        }
+
+        // the node's burnchain DB can itself be replayed
+        let replayed = ReplayFixture::load(&burnchain.get_burnchaindb_path()).unwrap();
+        assert_eq!(replayed, fixture);
+    }
+}
@@ -700,6 +700,13 @@ impl<
         let mut processed_blocks = self.chain_state_db.process_blocks(sortdb_handle, 1)?;
         let stacks_tip = SortitionDB::get_canonical_burn_chain_tip(self.sortition_db.conn())?;
         update_stacks_tip_height(stacks_tip.canonical_stacks_tip_height as i64);
+        self.chain_state_db.prune_blocks(
+            &StacksBlockHeader::make_index_block_hash(
+                &stacks_tip.canonical_stacks_tip_consensus_hash,
+                &stacks_tip.canonical_stacks_tip_hash,
+            ),
+            stacks_tip.canonical_stacks_tip_height,
+        )?;
 
         while let Some(block_result) = processed_blocks.pop() {
             if let (Some(block_receipt), _) = block_result {
@@ -0,0 +1,872 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use sha2::{Digest, Sha512Trunc256};
+
+use chainstate::burn::db::sortdb::SortitionDB;
+use chainstate::burn::ConsensusHash;
+use chainstate::stacks::db::StacksChainState;
+use chainstate::stacks::index::Error as MARFError;
+use chainstate::stacks::Error as ChainstateError;
+use util::db::Error as DBError;
+use util::hash::Sha512Trunc256Sum;
+
+use crate::types::chainstate::{
+    BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksBlockHeader, StacksBlockId,
+};
+use crate::types::proof::TrieHash;
+
+/// Version of the snapshot manifest format
+pub const SNAPSHOT_MANIFEST_VERSION: u32 = 1;
+
+/// Name of the manifest file in a snapshot directory
+pub const SNAPSHOT_MANIFEST_FILENAME: &str = "manifest.json";
+
+#[derive(Debug)]
+pub enum Error {
+    IOError(io::Error),
+    DBError(DBError),
+    ChainstateError(ChainstateError),
+    MARFError(MARFError),
+    /// The manifest could not be decoded or encoded
+    BadManifest(String),
+    /// The snapshot was made for a different network
+    WrongNetwork(String),
+    /// A snapshotted file is missing or does not match the manifest
+    BadFile(String),
+    /// The node's state does not match the manifest
+    StateMismatch(String),
+    /// Importing would overwrite existing node state
+    AlreadyExists(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::IOError(ref e) => fmt::Display::fmt(e, f),
+            Error::DBError(ref e) => fmt::Display::fmt(e, f),
+            Error::ChainstateError(ref e) => fmt::Display::fmt(e, f),
+            Error::MARFError(ref e) => fmt::Display::fmt(e, f),
+            Error::BadManifest(ref s) => write!(f, "Bad snapshot manifest: {}", s),
+            Error::WrongNetwork(ref s) => write!(f, "Snapshot is for a different network: {}", s),
+            Error::BadFile(ref s) => write!(f, "Bad snapshot file: {}", s),
+            Error::StateMismatch(ref s) => write!(f, "Node state does not match snapshot: {}", s),
+            Error::AlreadyExists(ref s) => write!(f, "Node state already exists: {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            Error::IOError(ref e) => Some(e),
+            Error::DBError(ref e) => Some(e),
+            Error::ChainstateError(ref e) => Some(e),
+            Error::MARFError(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(o: io::Error) -> Error {
+        Error::IOError(o)
+    }
+}
+
+impl From<DBError> for Error {
+    fn from(o: DBError) -> Error {
+        Error::DBError(o)
+    }
+}
+
+impl From<ChainstateError> for Error {
+    fn from(o: ChainstateError) -> Error {
+        Error::ChainstateError(o)
+    }
+}
+
+impl From<MARFError> for Error {
+    fn from(o: MARFError) -> Error {
+        Error::MARFError(o)
+    }
+}
+
+/// A file in a snapshot.  `path` is relative to the node's state directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub path: String,
+    pub size: u64,
+    pub sha512_256: Sha512Trunc256Sum,
+}
+
+/// Describes a snapshot of a node's state:  the Stacks block on the canonical fork it was taken
+/// at, the burnchain block whose sortition elected that Stacks block, the root hashes of the
+/// sortition and Stacks MARFs at those blocks, and the hash of every file in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub mainnet: bool,
+    pub chain_id: u32,
+    /// burnchain block whose sortition elected the Stacks block
+    pub burn_block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub consensus_hash: ConsensusHash,
+    pub sortition_id: SortitionId,
+    pub sortition_root_hash: TrieHash,
+    /// Stacks block on the canonical fork
+    pub stacks_block_height: u64,
+    pub stacks_consensus_hash: ConsensusHash,
+    pub stacks_block_hash: BlockHeaderHash,
+    pub index_block_hash: StacksBlockId,
+    pub marf_root_hash: TrieHash,
+    pub files: Vec<SnapshotFile>,
+}
+
+impl SnapshotManifest {
+    /// Load a snapshot's manifest from its directory
+    pub fn load(snapshot_dir: &Path) -> Result<SnapshotManifest, Error> {
+        let manifest_path = snapshot_dir.join(SNAPSHOT_MANIFEST_FILENAME);
+        let fd = fs::File::open(&manifest_path)?;
+        let manifest: SnapshotManifest = serde_json::from_reader(fd)
+            .map_err(|e| Error::BadManifest(format!("{:?}: {}", &manifest_path, e)))?;
+        if manifest.version != SNAPSHOT_MANIFEST_VERSION {
+            return Err(Error::BadManifest(format!(
+                "unsupported version {}",
+                manifest.version
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Store this manifest to a snapshot directory
+    pub fn store(&self, snapshot_dir: &Path) -> Result<(), Error> {
+        let manifest_path = snapshot_dir.join(SNAPSHOT_MANIFEST_FILENAME);
+        let mut fd = fs::File::create(&manifest_path)?;
+        serde_json::to_writer_pretty(&mut fd, self)
+            .map_err(|e| Error::BadManifest(format!("{:?}: {}", &manifest_path, e)))?;
+        fd.sync_all()?;
+        Ok(())
+    }
+
+    /// Verify that every file in the manifest is present in the snapshot directory, with the
+    /// expected size and hash.
+    pub fn verify_files(&self, snapshot_dir: &Path) -> Result<(), Error> {
+        for file in self.files.iter() {
+            let path = snapshot_dir.join(safe_relative_path(&file.path)?);
+            let size = fs::metadata(&path)
+                .map_err(|e| Error::BadFile(format!("{}: {}", &file.path, e)))?
+                .len();
+            if size != file.size {
+                return Err(Error::BadFile(format!(
+                    "{}: expected {} bytes, got {}",
+                    &file.path, file.size, size
+                )));
+            }
+            let hash = copy_and_hash(&path, None)?;
+            if hash != file.sha512_256 {
+                return Err(Error::BadFile(format!(
+                    "{}: expected hash {}, got {}",
+                    &file.path, &file.sha512_256, &hash
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify that the sortition DB has the burnchain block this snapshot was taken at on its
+    /// canonical burnchain fork, and that this block's sortition elected the snapshot's Stacks
+    /// block.  The sortition DB does not need to stop at this block.
+    pub fn verify_sortdb(&self, sortdb: &SortitionDB) -> Result<(), Error> {
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        if burn_tip.block_height < self.burn_block_height {
+            return Err(Error::StateMismatch(format!(
+                "sortition DB is only synced to burn height {}, but the snapshot is at burn height {}",
+                burn_tip.block_height, self.burn_block_height
+            )));
+        }
+        let snapshot =
+            SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &self.consensus_hash)?
+                .ok_or_else(|| {
+                    Error::StateMismatch(format!(
+                        "no sortition with consensus hash {}",
+                        &self.consensus_hash
+                    ))
+                })?;
+        if snapshot.block_height != self.burn_block_height
+            || snapshot.burn_header_hash != self.burn_header_hash
+            || snapshot.sortition_id != self.sortition_id
+        {
+            return Err(Error::StateMismatch(format!(
+                "consensus hash {} is at burnchain block {} at height {} (sortition {}), expected {} at height {} (sortition {})",
+                &self.consensus_hash,
+                &snapshot.burn_header_hash,
+                snapshot.block_height,
+                &snapshot.sortition_id,
+                &self.burn_header_hash,
+                self.burn_block_height,
+                &self.sortition_id
+            )));
+        }
+        if snapshot.index_root != self.sortition_root_hash {
+            return Err(Error::StateMismatch(format!(
+                "sortition MARF root hash is {}, expected {}",
+                &snapshot.index_root, &self.sortition_root_hash
+            )));
+        }
+        if !snapshot.sortition
+            || snapshot.winning_stacks_block_hash != self.stacks_block_hash
+            || snapshot.consensus_hash != self.stacks_consensus_hash
+        {
+            return Err(Error::StateMismatch(format!(
+                "sortition {} did not elect Stacks block {}/{}",
+                &snapshot.sortition_id, &self.stacks_consensus_hash, &self.stacks_block_hash
+            )));
+        }
+        let ancestor = SortitionDB::get_ancestor_snapshot(
+            &sortdb.index_conn(),
+            self.burn_block_height,
+            &burn_tip.sortition_id,
+        )?;
+        if ancestor.map(|sn| sn.sortition_id) != Some(self.sortition_id.clone()) {
+            return Err(Error::StateMismatch(format!(
+                "sortition {} is not on the canonical burnchain fork",
+                &self.sortition_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Verify that the chain state has the Stacks block this snapshot was taken at, that its MARF
+    /// root hash is the one in the manifest, and that its Clarity state is the one its header
+    /// commits to.
+    pub fn verify_chainstate(&self, chainstate: &mut StacksChainState) -> Result<(), Error> {
+        let header_info = StacksChainState::get_anchored_block_header_info(
+            chainstate.db(),
+            &self.stacks_consensus_hash,
+            &self.stacks_block_hash,
+        )?
+        .ok_or_else(|| {
+            Error::StateMismatch(format!(
+                "no header for Stacks block {}",
+                &self.index_block_hash
+            ))
+        })?;
+        if header_info.block_height != self.stacks_block_height {
+            return Err(Error::StateMismatch(format!(
+                "Stacks block {} is at height {}, expected {}",
+                &self.index_block_hash, header_info.block_height, self.stacks_block_height
+            )));
+        }
+        let marf_root_hash = chainstate
+            .state_index
+            .get_root_hash_at(&self.index_block_hash)?;
+        if header_info.index_root != self.marf_root_hash || marf_root_hash != self.marf_root_hash {
+            return Err(Error::StateMismatch(format!(
+                "MARF root hash of {} is {} (header says {}), expected {}",
+                &self.index_block_hash,
+                &marf_root_hash,
+                &header_info.index_root,
+                &self.marf_root_hash
+            )));
+        }
+
+        // the boot block's state is fixed by the genesis data, and its header is synthesized
+        if header_info.block_height == 0 {
+            return Ok(());
+        }
+        let block_hash = header_info.anchored_header.block_hash();
+        if block_hash != self.stacks_block_hash {
+            return Err(Error::StateMismatch(format!(
+                "header of Stacks block {} hashes to {}",
+                &self.stacks_block_hash, &block_hash
+            )));
+        }
+        let state_root_hash = chainstate
+            .clarity_state
+            .with_marf(|marf| marf.get_root_hash_at(&self.index_block_hash))?;
+        if state_root_hash != header_info.anchored_header.state_index_root {
+            return Err(Error::StateMismatch(format!(
+                "Clarity state root hash of {} is {}, but its header commits to {}",
+                &self.index_block_hash,
+                &state_root_hash,
+                &header_info.anchored_header.state_index_root
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Make sure that a path from a manifest stays inside of the directory it is relative to.
+fn safe_relative_path(path: &str) -> Result<PathBuf, Error> {
+    let path = PathBuf::from(path);
+    if path.components().count() == 0
+        || !path.components().all(|c| match c {
+            Component::Normal(_) => true,
+            _ => false,
+        })
+    {
+        return Err(Error::BadManifest(format!("invalid path {:?}", &path)));
+    }
+    Ok(path)
+}
+
+/// Hash a file, copying it to `dest` as we go if given.
+fn copy_and_hash(src: &Path, dest: Option<&Path>) -> Result<Sha512Trunc256Sum, Error> {
+    let mut src_fd = fs::File::open(src)?;
+    let mut dest_fd = match dest {
+        Some(dest) => {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Some(fs::File::create(dest)?)
+        }
+        None => None,
+    };
+
+    let mut hasher = Sha512Trunc256::new();
+    let mut buf = vec![0u8; 65536];
+    loop {
+        let nr = src_fd.read(&mut buf)?;
+        if nr == 0 {
+            break;
+        }
+        hasher.input(&buf[0..nr]);
+        if let Some(ref mut dest_fd) = dest_fd {
+            dest_fd.write_all(&buf[0..nr])?;
+        }
+    }
+    if let Some(dest_fd) = dest_fd {
+        dest_fd.sync_all()?;
+    }
+    Ok(Sha512Trunc256Sum::from_hasher(hasher))
+}
+
+/// Find all files under `path` (a file or a directory), relative to `root`.
+fn list_files(root: &Path, path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let full_path = root.join(path);
+    if fs::metadata(&full_path)?.is_dir() {
+        for entry in fs::read_dir(&full_path)? {
+            list_files(root, &path.join(entry?.file_name()), files)?;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Export a snapshot of a node's state into `snapshot_dir`, which must not already exist.
+/// `entries` are the files and directories (relative to `state_dir`) to include, and must include
+/// the sortition DB at `sortdb_path` and the chain state at `chainstate_path`.  The manifest
+/// describes the Stacks block at `stacks_block_height` on the canonical fork, or the canonical
+/// Stacks chain tip if not given, so that importers can pick a block their own sortition DB has
+/// already seen.  The node must not be running.
+pub fn export_snapshot(
+    state_dir: &Path,
+    entries: &[&str],
+    sortdb_path: &str,
+    chainstate_path: &str,
+    mainnet: bool,
+    chain_id: u32,
+    stacks_block_height: Option<u64>,
+    snapshot_dir: &Path,
+) -> Result<SnapshotManifest, Error> {
+    if fs::metadata(snapshot_dir).is_ok() {
+        return Err(Error::AlreadyExists(format!("{:?}", snapshot_dir)));
+    }
+    if fs::metadata(chainstate_path).is_err() {
+        return Err(Error::StateMismatch(format!(
+            "no chain state at {}",
+            chainstate_path
+        )));
+    }
+
+    let sortdb = SortitionDB::open(sortdb_path, false)?;
+    let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+    let stacks_tip = StacksBlockHeader::make_index_block_hash(
+        &burn_tip.canonical_stacks_tip_consensus_hash,
+        &burn_tip.canonical_stacks_tip_hash,
+    );
+
+    let (header_info, marf_root_hash) = {
+        let (mut chainstate, _) = StacksChainState::open(mainnet, chain_id, chainstate_path)?;
+        let index_block_hash = match stacks_block_height {
+            Some(height) => chainstate
+                .index_conn()?
+                .get_ancestor_block_hash(height, &stacks_tip)?
+                .ok_or_else(|| {
+                    Error::StateMismatch(format!(
+                        "no Stacks block at height {} on the canonical fork (tip is at height {})",
+                        height, burn_tip.canonical_stacks_tip_height
+                    ))
+                })?,
+            None => stacks_tip,
+        };
+        let header_info = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            chainstate.db(),
+            &index_block_hash,
+        )?
+        .ok_or_else(|| {
+            Error::StateMismatch(format!("no header for Stacks block {}", &index_block_hash))
+        })?;
+        let marf_root_hash = chainstate.state_index.get_root_hash_at(&index_block_hash)?;
+        (header_info, marf_root_hash)
+    };
+
+    let snapshot =
+        SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &header_info.consensus_hash)?
+            .ok_or_else(|| {
+                Error::StateMismatch(format!(
+                    "no sortition with consensus hash {}",
+                    &header_info.consensus_hash
+                ))
+            })?;
+
+    let mut paths = vec![];
+    for entry in entries.iter() {
+        list_files(state_dir, &safe_relative_path(entry)?, &mut paths)?;
+    }
+    paths.sort();
+
+    fs::create_dir_all(snapshot_dir)?;
+    let mut files = vec![];
+    for path in paths.into_iter() {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| Error::BadFile(format!("{:?} is not valid UTF-8", &path)))?
+            .to_string();
+        let sha512_256 = copy_and_hash(&state_dir.join(&path), Some(&snapshot_dir.join(&path)))?;
+        let size = fs::metadata(&snapshot_dir.join(&path))?.len();
+        debug!(
+            "Snapshot {}: {} bytes, hash {}",
+            &path_str, size, &sha512_256
+        );
+        files.push(SnapshotFile {
+            path: path_str,
+            size,
+            sha512_256,
+        });
+    }
+
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_MANIFEST_VERSION,
+        mainnet,
+        chain_id,
+        burn_block_height: snapshot.block_height,
+        burn_header_hash: snapshot.burn_header_hash,
+        consensus_hash: snapshot.consensus_hash,
+        sortition_id: snapshot.sortition_id,
+        sortition_root_hash: snapshot.index_root,
+        stacks_block_height: header_info.block_height,
+        stacks_consensus_hash: header_info.consensus_hash.clone(),
+        stacks_block_hash: header_info.anchored_header.block_hash(),
+        index_block_hash: StacksBlockHeader::make_index_block_hash(
+            &header_info.consensus_hash,
+            &header_info.anchored_header.block_hash(),
+        ),
+        marf_root_hash,
+        files,
+    };
+    manifest.store(snapshot_dir)?;
+    Ok(manifest)
+}
+
+/// Import the snapshot in `snapshot_dir` into `state_dir`, which must not already contain any of
+/// the snapshot's files.  The snapshot's files are verified against its manifest, and the
+/// manifest is verified against `trusted_sortdb_path` -- a sortition DB that this node synced
+/// from its own bitcoind, and which must already have the snapshot's burnchain block -- before
+/// anything is copied.  Then the copied sortition DB and chain state are verified against the
+/// manifest.  If verification fails, the imported files are removed.
+pub fn import_snapshot(
+    snapshot_dir: &Path,
+    state_dir: &Path,
+    sortdb_path: &str,
+    chainstate_path: &str,
+    trusted_sortdb_path: &str,
+    mainnet: bool,
+    chain_id: u32,
+) -> Result<SnapshotManifest, Error> {
+    let manifest = SnapshotManifest::load(snapshot_dir)?;
+    if manifest.mainnet != mainnet || manifest.chain_id != chain_id {
+        return Err(Error::WrongNetwork(format!(
+            "snapshot is for mainnet={} chain ID {:08x}, expected mainnet={} chain ID {:08x}",
+            manifest.mainnet, manifest.chain_id, mainnet, chain_id
+        )));
+    }
+    manifest.verify_files(snapshot_dir)?;
+
+    // the snapshot's own sortition DB can't vouch for the snapshot
+    {
+        let trusted_sortdb = SortitionDB::open(trusted_sortdb_path, false)?;
+        manifest.verify_sortdb(&trusted_sortdb)?;
+    }
+
+    // top-level files and directories that the import creates
+    let mut entries = vec![];
+    for file in manifest.files.iter() {
+        let path = safe_relative_path(&file.path)?;
+        let entry = PathBuf::from(
+            path.components()
+                .next()
+                .expect("BUG: empty path")
+                .as_os_str(),
+        );
+        if !entries.contains(&entry) {
+            if fs::metadata(state_dir.join(&entry)).is_ok() {
+                return Err(Error::AlreadyExists(format!(
+                    "{:?}",
+                    state_dir.join(&entry)
+                )));
+            }
+            entries.push(entry);
+        }
+    }
+
+    let res = import_and_verify(
+        &manifest,
+        snapshot_dir,
+        state_dir,
+        sortdb_path,
+        chainstate_path,
+    );
+    if let Err(e) = res {
+        warn!(
+            "Failed to import snapshot from {:?}: {:?}",
+            snapshot_dir, &e
+        );
+        for entry in entries.iter() {
+            let path = state_dir.join(entry);
+            let cleanup = if fs::metadata(&path).map(|md| md.is_dir()).unwrap_or(false) {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if let Err(cleanup_e) = cleanup {
+                if cleanup_e.kind() != io::ErrorKind::NotFound {
+                    error!("Failed to remove imported {:?}: {:?}", &path, &cleanup_e);
+                }
+            }
+        }
+        return Err(e);
+    }
+    Ok(manifest)
+}
+
+fn import_and_verify(
+    manifest: &SnapshotManifest,
+    snapshot_dir: &Path,
+    state_dir: &Path,
+    sortdb_path: &str,
+    chainstate_path: &str,
+) -> Result<(), Error> {
+    for file in manifest.files.iter() {
+        let path = safe_relative_path(&file.path)?;
+        let sha512_256 = copy_and_hash(&snapshot_dir.join(&path), Some(&state_dir.join(&path)))?;
+        if sha512_256 != file.sha512_256 {
+            return Err(Error::BadFile(format!(
+                "{}: expected hash {}, got {}",
+                &file.path, &file.sha512_256, &sha512_256
+            )));
+        }
+    }
+
+    let stacks_tip = {
+        let sortdb = SortitionDB::open(sortdb_path, false)?;
+        manifest.verify_sortdb(&sortdb)?;
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        StacksBlockHeader::make_index_block_hash(
+            &burn_tip.canonical_stacks_tip_consensus_hash,
+            &burn_tip.canonical_stacks_tip_hash,
+        )
+    };
+
+    let (mut chainstate, _) =
+        StacksChainState::open(manifest.mainnet, manifest.chain_id, chainstate_path)?;
+    manifest.verify_chainstate(&mut chainstate)?;
+    if chainstate
+        .index_conn()?
+        .get_ancestor_block_height(&manifest.index_block_hash, &stacks_tip)?
+        != Some(manifest.stacks_block_height)
+    {
+        return Err(Error::StateMismatch(format!(
+            "canonical Stacks chain tip {} does not descend from {}",
+            &stacks_tip, &manifest.index_block_hash
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use burnchains::PoxConstants;
+    use chainstate::stacks::db::ChainStateBootData;
+    use core::StacksEpoch;
+    use util::get_epoch_time_secs;
+
+    fn state_dir(test_name: &str) -> PathBuf {
+        let path = PathBuf::from(format!("/tmp/stacks-snapshot-test-{}", test_name));
+        if fs::metadata(&path).is_ok() {
+            fs::remove_dir_all(&path).unwrap();
+        }
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn path_str(dir: &Path, entry: &str) -> String {
+        dir.join(entry).to_str().unwrap().to_string()
+    }
+
+    /// Make a node state directory with a booted chain state and a sortition DB
+    fn make_node_state(test_name: &str) -> PathBuf {
+        let dir = state_dir(test_name);
+        SortitionDB::connect(
+            &path_str(&dir, "burnchain/sortition"),
+            0,
+            &BurnchainHeaderHash([0u8; 32]),
+            get_epoch_time_secs(),
+            &StacksEpoch::unit_test_pre_2_05(0),
+            true,
+        )
+        .unwrap();
+
+        let mut boot_data = ChainStateBootData {
+            initial_balances: vec![],
+            post_flight_callback: None,
+            first_burnchain_block_hash: BurnchainHeaderHash([0u8; 32]),
+            first_burnchain_block_height: 0,
+            first_burnchain_block_timestamp: 0,
+            pox_constants: PoxConstants::testnet_default(),
+            get_bulk_initial_lockups: None,
+            get_bulk_initial_balances: None,
+            get_bulk_initial_names: None,
+            get_bulk_initial_namespaces: None,
+        };
+        StacksChainState::open_and_exec(
+            false,
+            0x80000000,
+            &path_str(&dir, "chainstate"),
+            Some(&mut boot_data),
+        )
+        .unwrap();
+        dir
+    }
+
+    fn export_at(
+        node_dir: &Path,
+        snapshot_dir: &Path,
+        stacks_block_height: Option<u64>,
+    ) -> Result<SnapshotManifest, Error> {
+        export_snapshot(
+            node_dir,
+            &["burnchain", "chainstate"],
+            &path_str(node_dir, "burnchain/sortition"),
+            &path_str(node_dir, "chainstate"),
+            false,
+            0x80000000,
+            stacks_block_height,
+            snapshot_dir,
+        )
+    }
+
+    fn export(node_dir: &Path, snapshot_dir: &Path) -> Result<SnapshotManifest, Error> {
+        export_at(node_dir, snapshot_dir, None)
+    }
+
+    /// Import a snapshot, trusting the sortition DB in `trusted_dir`
+    fn import(
+        snapshot_dir: &Path,
+        node_dir: &Path,
+        trusted_dir: &Path,
+        chain_id: u32,
+    ) -> Result<SnapshotManifest, Error> {
+        import_snapshot(
+            snapshot_dir,
+            node_dir,
+            &path_str(node_dir, "burnchain/sortition"),
+            &path_str(node_dir, "chainstate"),
+            &path_str(trusted_dir, "burnchain/sortition"),
+            false,
+            chain_id,
+        )
+    }
+
+    #[test]
+    fn test_export_import_snapshot() {
+        let node_dir = make_node_state("export-import");
+        let snapshot_dir = state_dir("export-import-snapshot");
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+
+        let manifest = export(&node_dir, &snapshot_dir).unwrap();
+        assert_eq!(manifest.stacks_block_height, 0);
+        assert!(manifest.files.len() > 0);
+        assert!(manifest
+            .files
+            .iter()
+            .all(|f| f.path.starts_with("burnchain/") || f.path.starts_with("chainstate/")));
+        assert_eq!(SnapshotManifest::load(&snapshot_dir).unwrap(), manifest);
+
+        // can't export over an existing snapshot
+        match export(&node_dir, &snapshot_dir) {
+            Err(Error::AlreadyExists(_)) => {}
+            x => panic!("expected AlreadyExists, got {:?}", &x),
+        }
+
+        let new_node_dir = state_dir("export-import-new-node");
+        let imported = import(&snapshot_dir, &new_node_dir, &node_dir, 0x80000000).unwrap();
+        assert_eq!(imported, manifest);
+        for file in manifest.files.iter() {
+            assert!(fs::metadata(new_node_dir.join(&file.path)).is_ok());
+        }
+
+        // the imported node state can itself be snapshotted, and it's the same state
+        let new_snapshot_dir = state_dir("export-import-new-snapshot");
+        fs::remove_dir_all(&new_snapshot_dir).unwrap();
+        let new_manifest = export(&new_node_dir, &new_snapshot_dir).unwrap();
+        assert_eq!(new_manifest.index_block_hash, manifest.index_block_hash);
+        assert_eq!(new_manifest.marf_root_hash, manifest.marf_root_hash);
+        assert_eq!(
+            new_manifest.sortition_root_hash,
+            manifest.sortition_root_hash
+        );
+
+        // can't import over existing state
+        match import(&snapshot_dir, &new_node_dir, &node_dir, 0x80000000) {
+            Err(Error::AlreadyExists(_)) => {}
+            x => panic!("expected AlreadyExists, got {:?}", &x),
+        }
+    }
+
+    #[test]
+    fn test_import_bad_snapshot() {
+        let node_dir = make_node_state("import-bad");
+        let snapshot_dir = state_dir("import-bad-snapshot");
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+        let manifest = export(&node_dir, &snapshot_dir).unwrap();
+
+        // wrong network
+        let new_node_dir = state_dir("import-bad-new-node");
+        match import(&snapshot_dir, &new_node_dir, &node_dir, 0x00000001) {
+            Err(Error::WrongNetwork(_)) => {}
+            x => panic!("expected WrongNetwork, got {:?}", &x),
+        }
+
+        // corrupt file
+        let corrupt_path = snapshot_dir.join(&manifest.files[0].path);
+        let mut bytes = fs::read(&corrupt_path).unwrap();
+        bytes[0] ^= 0xff;
+        fs::write(&corrupt_path, &bytes).unwrap();
+        match import(&snapshot_dir, &new_node_dir, &node_dir, 0x80000000) {
+            Err(Error::BadFile(_)) => {}
+            x => panic!("expected BadFile, got {:?}", &x),
+        }
+        assert_eq!(fs::read_dir(&new_node_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_import_mismatched_manifest() {
+        let node_dir = make_node_state("import-mismatch");
+        let snapshot_dir = state_dir("import-mismatch-snapshot");
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+        let mut manifest = export(&node_dir, &snapshot_dir).unwrap();
+
+        // manifest doesn't match the trusted sortition DB, so nothing is copied
+        manifest.burn_block_height += 1;
+        manifest.store(&snapshot_dir).unwrap();
+        let new_node_dir = state_dir("import-mismatch-new-node");
+        match import(&snapshot_dir, &new_node_dir, &node_dir, 0x80000000) {
+            Err(Error::StateMismatch(_)) => {}
+            x => panic!("expected StateMismatch, got {:?}", &x),
+        }
+        assert_eq!(fs::read_dir(&new_node_dir).unwrap().count(), 0);
+
+        // manifest doesn't match the chain state
+        manifest.burn_block_height -= 1;
+        manifest.marf_root_hash = TrieHash([0x11; 32]);
+        manifest.store(&snapshot_dir).unwrap();
+        match import(&snapshot_dir, &new_node_dir, &node_dir, 0x80000000) {
+            Err(Error::StateMismatch(_)) => {}
+            x => panic!("expected StateMismatch, got {:?}", &x),
+        }
+        assert_eq!(fs::read_dir(&new_node_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_import_untrusted_snapshot() {
+        let node_dir = make_node_state("import-untrusted");
+        let snapshot_dir = state_dir("import-untrusted-snapshot");
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+        export(&node_dir, &snapshot_dir).unwrap();
+
+        // the importing node synced a different burnchain, so nothing gets copied
+        let trusted_dir = state_dir("import-untrusted-trusted");
+        SortitionDB::connect(
+            &path_str(&trusted_dir, "burnchain/sortition"),
+            0,
+            &BurnchainHeaderHash([0x01; 32]),
+            get_epoch_time_secs(),
+            &StacksEpoch::unit_test_pre_2_05(0),
+            true,
+        )
+        .unwrap();
+        let new_node_dir = state_dir("import-untrusted-new-node");
+        match import(&snapshot_dir, &new_node_dir, &trusted_dir, 0x80000000) {
+            Err(Error::StateMismatch(_)) => {}
+            x => panic!("expected StateMismatch, got {:?}", &x),
+        }
+        assert_eq!(fs::read_dir(&new_node_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_export_snapshot_at_height() {
+        let node_dir = make_node_state("export-at-height");
+        let tip_snapshot_dir = state_dir("export-at-height-tip");
+        fs::remove_dir_all(&tip_snapshot_dir).unwrap();
+        let tip_manifest = export(&node_dir, &tip_snapshot_dir).unwrap();
+
+        let snapshot_dir = state_dir("export-at-height-snapshot");
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+        let manifest = export_at(&node_dir, &snapshot_dir, Some(0)).unwrap();
+        assert_eq!(manifest.index_block_hash, tip_manifest.index_block_hash);
+        assert_eq!(manifest.consensus_hash, manifest.stacks_consensus_hash);
+
+        // no block at this height on the canonical fork
+        fs::remove_dir_all(&snapshot_dir).unwrap();
+        match export_at(&node_dir, &snapshot_dir, Some(1)) {
+            Err(Error::StateMismatch(_)) => {}
+            x => panic!("expected StateMismatch, got {:?}", &x),
+        }
+        assert!(fs::metadata(&snapshot_dir).is_err());
+    }
+
+    #[test]
+    fn test_safe_relative_path() {
+        assert!(safe_relative_path("chainstate/vm/index.sqlite").is_ok());
+        assert!(safe_relative_path("headers.sqlite").is_ok());
+        assert!(safe_relative_path("").is_err());
+        assert!(safe_relative_path("/etc/passwd").is_err());
+        assert!(safe_relative_path("../chainstate").is_err());
+        assert!(safe_relative_path("chainstate/../../x").is_err());
+    }
+}
@@ -18,6 +18,7 @@ use std::boxed::Box;
 use std::cmp;
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::fmt;
 
 use address::AddressHashMode;
 use burnchains::bitcoin::address::BitcoinAddress;
@@ -29,7 +30,7 @@ use chainstate::stacks::index::marf::MarfConnection;
 use chainstate::stacks::Error;
 use clarity_vm::clarity::ClarityConnection;
 use core::{POX_MAXIMAL_SCALING, POX_THRESHOLD_STEPS_USTX};
-use util::hash::Hash160;
+use util::hash::{to_hex, Hash160};
 use vm::contexts::ContractContext;
 use vm::costs::{
     cost_functions::ClarityCostFunction, ClarityCostFunctionReference, CostStateSummary,
@@ -121,6 +122,108 @@ impl StacksAddress {
     }
 }
 
+/// A principal's current stacking state in the PoX contract, as reported by `get-stacker-info`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackerInfo {
+    pub amount_ustx: u128,
+    pub pox_addr: StacksAddress,
+    pub first_reward_cycle: u64,
+    pub lock_period: u64,
+}
+
+/// A principal's entry in the PoX contract's `delegation-state` map
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelegationInfo {
+    pub amount_ustx: u128,
+    pub delegated_to: PrincipalData,
+    pub until_burn_height: Option<u64>,
+    pub pox_addr: Option<StacksAddress>,
+}
+
+/// Who registered a reward set entry with the PoX contract
+#[derive(Debug, Clone, PartialEq)]
+pub enum RewardSetEntrySource {
+    /// a stacker's own `stack-stx` call
+    Solo(PrincipalData),
+    /// a delegate's `stack-aggregation-commit` call
+    Delegated(PrincipalData),
+}
+
+/// A BNS name's resolution, as reported by the BNS contract's `name-resolve`.  Heights are Stacks
+/// block heights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BnsNameInfo {
+    pub owner: PrincipalData,
+    pub zonefile_hash: Vec<u8>,
+    pub lease_started_at: u64,
+    pub lease_ending_at: Option<u64>,
+}
+
+/// Why the BNS contract's `name-resolve` refused to resolve a name
+#[derive(Debug, Clone, PartialEq)]
+pub enum BnsNameError {
+    /// the name or its namespace does not exist
+    NotFound,
+    /// the name's lease has expired
+    Expired,
+    /// the name's lease has expired, but its owner can still renew it
+    GracePeriod,
+    /// the name was revoked
+    Revoked,
+    /// some other BNS error code
+    Other(i128),
+}
+
+impl BnsNameError {
+    fn from_code(code: i128) -> BnsNameError {
+        match code {
+            // ERR_NAME_NOT_FOUND, ERR_NAMESPACE_NOT_FOUND
+            2013 | 1005 => BnsNameError::NotFound,
+            // ERR_NAME_EXPIRED
+            2008 => BnsNameError::Expired,
+            // ERR_NAME_GRACE_PERIOD
+            2009 => BnsNameError::GracePeriod,
+            // ERR_NAME_REVOKED
+            2014 => BnsNameError::Revoked,
+            code => BnsNameError::Other(code),
+        }
+    }
+}
+
+impl fmt::Display for BnsNameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BnsNameError::NotFound => write!(f, "name not found"),
+            BnsNameError::Expired => write!(f, "name expired"),
+            BnsNameError::GracePeriod => write!(f, "name in grace period"),
+            BnsNameError::Revoked => write!(f, "name revoked"),
+            BnsNameError::Other(code) => write!(f, "BNS error {}", code),
+        }
+    }
+}
+
+/// A BNS namespace's price function, from its entry in the BNS contract's `namespaces` map
+#[derive(Debug, Clone, PartialEq)]
+pub struct BnsPriceFunction {
+    pub buckets: Vec<u128>,
+    pub base: u128,
+    pub coeff: u128,
+    pub nonalpha_discount: u128,
+    pub no_vowel_discount: u128,
+}
+
+/// A BNS namespace's entry in the BNS contract's `namespaces` map.  Heights are Stacks block
+/// heights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BnsNamespaceInfo {
+    pub namespace_import: PrincipalData,
+    pub revealed_at: u64,
+    pub launched_at: Option<u64>,
+    pub lifetime: u64,
+    pub can_update_price_function: bool,
+    pub price_function: BnsPriceFunction,
+}
+
 /// Extract a PoX address from its tuple representation
 fn tuple_to_pox_addr(tuple_data: TupleData) -> (AddressHashMode, Hash160) {
     let version_value = tuple_data
@@ -146,6 +249,49 @@ fn tuple_to_pox_addr(tuple_data: TupleData) -> (AddressHashMode, Hash160) {
     (version, hashbytes)
 }
 
+/// Extract a principal's stacking state from its tuple representation in the PoX contract
+fn tuple_to_stacker_info(data: TupleData, mainnet: bool) -> StackerInfo {
+    let amount_ustx = data
+        .get("amount-ustx")
+        .expect("FATAL: no 'amount-ustx' in stacking state")
+        .to_owned()
+        .expect_u128();
+    let pox_addr = pox_addr_to_stacks_address(
+        data.get("pox-addr")
+            .expect("FATAL: no 'pox-addr' in stacking state")
+            .to_owned()
+            .expect_tuple(),
+        mainnet,
+    );
+    let lock_period = data
+        .get("lock-period")
+        .expect("FATAL: no 'lock-period' in stacking state")
+        .to_owned()
+        .expect_u128() as u64;
+    let first_reward_cycle = data
+        .get("first-reward-cycle")
+        .expect("FATAL: no 'first-reward-cycle' in stacking state")
+        .to_owned()
+        .expect_u128() as u64;
+
+    StackerInfo {
+        amount_ustx,
+        pox_addr,
+        first_reward_cycle,
+        lock_period,
+    }
+}
+
+/// Convert a PoX address tuple into the Stacks address with the same hash mode and bytes
+pub fn pox_addr_to_stacks_address(tuple_data: TupleData, mainnet: bool) -> StacksAddress {
+    let (hash_mode, hash) = tuple_to_pox_addr(tuple_data);
+    let version = match mainnet {
+        true => hash_mode.to_version_mainnet(),
+        false => hash_mode.to_version_testnet(),
+    };
+    StacksAddress::new(version, hash)
+}
+
 impl StacksChainState {
     fn eval_boot_code_read_only(
         &mut self,
@@ -366,6 +512,18 @@ impl StacksChainState {
             return Ok(vec![]);
         }
 
+        self.get_reward_set_entries(sortdb, block_id, reward_cycle)
+    }
+
+    /// Get the PoX addresses registered for a given reward cycle, and how many uSTX are stacked
+    /// for each, in the order in which the PoX contract recorded them.  Unlike
+    /// `get_reward_addresses()`, this does not check whether or not PoX is active in that cycle.
+    pub fn get_reward_set_entries(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        reward_cycle: u64,
+    ) -> Result<Vec<(StacksAddress, u128)>, Error> {
         // how many in this cycle?
         let num_addrs = self
             .eval_boot_code_read_only(
@@ -405,7 +563,7 @@ impl StacksChainState {
                 .to_owned()
                 .expect_tuple();
 
-            let (hash_mode, hash) = tuple_to_pox_addr(pox_addr_tuple);
+            let address = pox_addr_to_stacks_address(pox_addr_tuple, self.mainnet);
 
             let total_ustx = tuple_data
                 .get("total-ustx")
@@ -413,21 +571,335 @@ impl StacksChainState {
                 .to_owned()
                 .expect_u128();
 
-            let version = match self.mainnet {
-                true => hash_mode.to_version_mainnet(),
-                false => hash_mode.to_version_testnet(),
-            };
-
             test_debug!(
                 "PoX reward address (for {} ustx): {:?}",
                 total_ustx,
-                &StacksAddress::new(version, hash)
+                &address
             );
-            ret.push((StacksAddress::new(version, hash), total_ustx));
+            ret.push((address, total_ustx));
         }
 
         Ok(ret)
     }
+
+    /// Get a principal's current stacking state from the PoX contract.  Returns None if the
+    /// principal is not stacking, or if its lock has expired.
+    pub fn get_stacker_info(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        stacker: &PrincipalData,
+    ) -> Result<Option<StackerInfo>, Error> {
+        let data = match self
+            .eval_boot_code_read_only(
+                sortdb,
+                block_id,
+                "pox",
+                &format!("(get-stacker-info '{})", stacker),
+            )?
+            .expect_optional()
+        {
+            Some(data) => data.expect_tuple(),
+            None => {
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(tuple_to_stacker_info(data, self.mainnet)))
+    }
+
+    /// Get a principal's entry in the PoX contract's `stacking-state` map, if any.  Unlike
+    /// `get_stacker_info()`, this also returns entries whose lock has expired.
+    pub fn get_stacking_state(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        stacker: &PrincipalData,
+    ) -> Result<Option<StackerInfo>, Error> {
+        let data = match self
+            .eval_boot_code_read_only(
+                sortdb,
+                block_id,
+                "pox",
+                &format!("(map-get? stacking-state {{ stacker: '{} }})", stacker),
+            )?
+            .expect_optional()
+        {
+            Some(data) => data.expect_tuple(),
+            None => {
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(tuple_to_stacker_info(data, self.mainnet)))
+    }
+
+    /// Get the delegation a principal has registered with the PoX contract, if any.  Expired
+    /// delegations are still returned; the caller can check `until_burn_height`.
+    pub fn get_delegation_info(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        stacker: &PrincipalData,
+    ) -> Result<Option<DelegationInfo>, Error> {
+        let data = match self
+            .eval_boot_code_read_only(
+                sortdb,
+                block_id,
+                "pox",
+                &format!("(map-get? delegation-state {{ stacker: '{} }})", stacker),
+            )?
+            .expect_optional()
+        {
+            Some(data) => data.expect_tuple(),
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let amount_ustx = data
+            .get("amount-ustx")
+            .expect("FATAL: no 'amount-ustx' in delegation-state")
+            .to_owned()
+            .expect_u128();
+        let delegated_to = data
+            .get("delegated-to")
+            .expect("FATAL: no 'delegated-to' in delegation-state")
+            .to_owned()
+            .expect_principal();
+        let until_burn_height = data
+            .get("until-burn-ht")
+            .expect("FATAL: no 'until-burn-ht' in delegation-state")
+            .to_owned()
+            .expect_optional()
+            .map(|ht| ht.expect_u128() as u64);
+        let pox_addr = data
+            .get("pox-addr")
+            .expect("FATAL: no 'pox-addr' in delegation-state")
+            .to_owned()
+            .expect_optional()
+            .map(|addr| pox_addr_to_stacks_address(addr.expect_tuple(), self.mainnet));
+
+        Ok(Some(DelegationInfo {
+            amount_ustx,
+            delegated_to,
+            until_burn_height,
+            pox_addr,
+        }))
+    }
+
+    /// Work out who registered a reward cycle's reward set entry for a PoX address.  The PoX
+    /// contract's reward set entries do not record this, so this reads the `stacking-state` and
+    /// `delegation-state` of the principal whose address has the PoX address's hash mode and
+    /// bytes -- i.e. the account whose key also controls the reward address.  If that principal's
+    /// STX are locked to the PoX address for the reward cycle, then the entry is its own if it
+    /// did not delegate them, and its delegate's if it did.  Returns None otherwise.
+    pub fn get_reward_set_entry_source(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        pox_addr: &StacksAddress,
+        reward_cycle: u64,
+    ) -> Result<Option<RewardSetEntrySource>, Error> {
+        let principal = PrincipalData::from(pox_addr.clone());
+        let stacking = match self.get_stacking_state(sortdb, block_id, &principal)? {
+            Some(stacking) => stacking,
+            None => {
+                return Ok(None);
+            }
+        };
+        if stacking.pox_addr != *pox_addr
+            || reward_cycle < stacking.first_reward_cycle
+            || reward_cycle >= stacking.first_reward_cycle + stacking.lock_period
+        {
+            return Ok(None);
+        }
+
+        let source = match self.get_delegation_info(sortdb, block_id, &principal)? {
+            Some(delegation) => RewardSetEntrySource::Delegated(delegation.delegated_to),
+            None => RewardSetEntrySource::Solo(principal),
+        };
+        Ok(Some(source))
+    }
+
+    /// Resolve a BNS name with the BNS contract's `name-resolve`.  The outer error is for failing
+    /// to query the contract; the inner one is for names that do not resolve.
+    pub fn get_bns_name(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        namespace: &[u8],
+        name: &[u8],
+    ) -> Result<Result<BnsNameInfo, BnsNameError>, Error> {
+        let data = match self
+            .eval_boot_code_read_only(
+                sortdb,
+                block_id,
+                "bns",
+                &format!("(name-resolve 0x{} 0x{})", to_hex(namespace), to_hex(name)),
+            )?
+            .expect_result()
+        {
+            Ok(data) => data.expect_tuple(),
+            Err(code) => {
+                return Ok(Err(BnsNameError::from_code(code.expect_i128())));
+            }
+        };
+
+        let owner = data
+            .get("owner")
+            .expect("FATAL: no 'owner' in return value from (name-resolve)")
+            .to_owned()
+            .expect_principal();
+        let zonefile_hash = data
+            .get("zonefile-hash")
+            .expect("FATAL: no 'zonefile-hash' in return value from (name-resolve)")
+            .to_owned()
+            .expect_buff(20);
+        let lease_started_at = data
+            .get("lease-started-at")
+            .expect("FATAL: no 'lease-started-at' in return value from (name-resolve)")
+            .to_owned()
+            .expect_u128() as u64;
+        let lease_ending_at = data
+            .get("lease-ending-at")
+            .expect("FATAL: no 'lease-ending-at' in return value from (name-resolve)")
+            .to_owned()
+            .expect_optional()
+            .map(|ht| ht.expect_u128() as u64);
+
+        Ok(Ok(BnsNameInfo {
+            owner,
+            zonefile_hash,
+            lease_started_at,
+            lease_ending_at,
+        }))
+    }
+
+    /// Get a BNS namespace's properties from the BNS contract's `namespaces` map.  Returns None
+    /// if the namespace has not been revealed.
+    pub fn get_bns_namespace(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        namespace: &[u8],
+    ) -> Result<Option<BnsNamespaceInfo>, Error> {
+        let data = match self
+            .eval_boot_code_read_only(
+                sortdb,
+                block_id,
+                "bns",
+                &format!("(map-get? namespaces 0x{})", to_hex(namespace)),
+            )?
+            .expect_optional()
+        {
+            Some(data) => data.expect_tuple(),
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let namespace_import = data
+            .get("namespace-import")
+            .expect("FATAL: no 'namespace-import' in namespaces")
+            .to_owned()
+            .expect_principal();
+        let revealed_at = data
+            .get("revealed-at")
+            .expect("FATAL: no 'revealed-at' in namespaces")
+            .to_owned()
+            .expect_u128() as u64;
+        let launched_at = data
+            .get("launched-at")
+            .expect("FATAL: no 'launched-at' in namespaces")
+            .to_owned()
+            .expect_optional()
+            .map(|ht| ht.expect_u128() as u64);
+        let lifetime = data
+            .get("lifetime")
+            .expect("FATAL: no 'lifetime' in namespaces")
+            .to_owned()
+            .expect_u128() as u64;
+        let can_update_price_function = data
+            .get("can-update-price-function")
+            .expect("FATAL: no 'can-update-price-function' in namespaces")
+            .to_owned()
+            .expect_bool();
+
+        let price_function = data
+            .get("price-function")
+            .expect("FATAL: no 'price-function' in namespaces")
+            .to_owned()
+            .expect_tuple();
+        let get_price_param = |param: &str| {
+            price_function
+                .get(param)
+                .expect("FATAL: missing parameter in namespace price function")
+                .to_owned()
+                .expect_u128()
+        };
+        let price_function = BnsPriceFunction {
+            buckets: price_function
+                .get("buckets")
+                .expect("FATAL: no 'buckets' in namespace price function")
+                .to_owned()
+                .expect_list()
+                .into_iter()
+                .map(|bucket| bucket.expect_u128())
+                .collect(),
+            base: get_price_param("base"),
+            coeff: get_price_param("coeff"),
+            nonalpha_discount: get_price_param("nonalpha-discount"),
+            no_vowel_discount: get_price_param("no-vowel-discount"),
+        };
+
+        Ok(Some(BnsNamespaceInfo {
+            namespace_import,
+            revealed_at,
+            launched_at,
+            lifetime,
+            can_update_price_function,
+            price_function,
+        }))
+    }
+
+    /// Get the name owned by a principal, as `(namespace, name)`, with the BNS contract's
+    /// `resolve-principal`.  BNS associates at most one name with each principal.  Returns None
+    /// if the principal owns no name, or if its name does not resolve.
+    pub fn get_bns_owner_name(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+        owner: &PrincipalData,
+    ) -> Result<Option<(Vec<u8>, Vec<u8>)>, Error> {
+        let data = match self
+            .eval_boot_code_read_only(
+                sortdb,
+                block_id,
+                "bns",
+                &format!("(resolve-principal '{})", owner),
+            )?
+            .expect_result()
+        {
+            Ok(data) => data.expect_tuple(),
+            Err(_) => {
+                return Ok(None);
+            }
+        };
+
+        let namespace = data
+            .get("namespace")
+            .expect("FATAL: no 'namespace' in return value from (resolve-principal)")
+            .to_owned()
+            .expect_buff(20);
+        let name = data
+            .get("name")
+            .expect("FATAL: no 'name' in return value from (resolve-principal)")
+            .to_owned()
+            .expect_buff(48);
+
+        Ok(Some((namespace, name)))
+    }
 }
 
 #[cfg(test)]
@@ -1672,6 +2144,23 @@ pub mod test {
                     assert_eq!((reward_addrs[0].0).bytes, key_to_stacks_addr(&alice).bytes);
                     assert_eq!(reward_addrs[0].1, 1024 * POX_THRESHOLD_STEPS_USTX);
 
+                    // Alice stacked to her own address, so the entry is hers
+                    let source = with_sortdb(&mut peer, |ref mut chainstate, ref sortdb| {
+                        chainstate.get_reward_set_entry_source(
+                            sortdb,
+                            &tip_index_block,
+                            &reward_addrs[0].0,
+                            cur_reward_cycle as u64,
+                        )
+                    })
+                    .unwrap();
+                    assert_eq!(
+                        source,
+                        Some(RewardSetEntrySource::Solo(
+                            key_to_stacks_addr(&alice).into()
+                        ))
+                    );
+
                     // Lock-up is consistent with stacker state
                     let alice_account = get_account(&mut peer, &key_to_stacks_addr(&alice).into());
                     assert_eq!(alice_account.stx_balance.amount_unlocked, 0);
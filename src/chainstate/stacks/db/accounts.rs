@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::collections::HashMap;
 
 use rusqlite::types::ToSql;
@@ -32,6 +33,7 @@ use vm::database::*;
 use vm::types::*;
 
 use crate::types::chainstate::{StacksAddress, StacksBlockHeader, StacksBlockId};
+use crate::types::proof::TrieMerkleProof;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct MinerReward {
@@ -43,6 +45,21 @@ pub struct MinerReward {
     pub vtxindex: u32, // will be 0 for the reward to the miner, and >0 for user burn supports
 }
 
+/// A principal's STX balance and nonce as of a given Stacks block
+#[derive(Debug)]
+pub struct AccountHistoryEntry {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub burn_block_height: u64,
+    pub unlocked: u128,
+    pub locked: u128,
+    pub unlock_height: u64,
+    pub nonce: u64,
+    /// MARF proofs of the balance and nonce, if requested and if the account has them
+    pub balance_proof: Option<TrieMerkleProof<StacksBlockId>>,
+    pub nonce_proof: Option<TrieMerkleProof<StacksBlockId>>,
+}
+
 impl FromRow<MinerPaymentSchedule> for MinerPaymentSchedule {
     fn from_row<'a>(row: &'a Row) -> Result<MinerPaymentSchedule, db_error> {
         let address = StacksAddress::from_column(row, "address")?;
@@ -153,6 +170,97 @@ impl StacksChainState {
         })
     }
 
+    /// Get a principal's STX balance and nonce as of each block at heights `start_height`
+    /// through `end_height` (inclusive) in the fork that ends at `tip`, ordered by height.
+    /// Heights above `tip` are skipped.  If `with_proof` is set, each entry also carries MARF
+    /// proofs of the balance and nonce.
+    pub fn get_account_history(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        start_height: u64,
+        end_height: u64,
+        with_proof: bool,
+    ) -> Result<Vec<AccountHistoryEntry>, Error> {
+        let tip_height = match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            self.db(),
+            tip,
+        )? {
+            Some(header_info) => header_info.block_height,
+            None => {
+                return Err(Error::NoSuchBlockError);
+            }
+        };
+
+        let mut ancestors = vec![];
+        {
+            let index_conn = self.index_conn()?;
+            for height in start_height..(cmp::min(end_height, tip_height) + 1) {
+                match index_conn
+                    .get_ancestor_block_hash(height, tip)
+                    .map_err(Error::DBError)?
+                {
+                    Some(ancestor) => ancestors.push((height, ancestor)),
+                    None => {
+                        warn!("No ancestor of {} at height {}", tip, height);
+                    }
+                }
+            }
+        }
+
+        let mut history = vec![];
+        for (height, ancestor) in ancestors.into_iter() {
+            let entry_opt = self.with_read_only_clarity_tx(burn_dbconn, &ancestor, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
+                    let (balance, balance_proof) = if with_proof {
+                        let key = ClarityDatabase::make_key_for_account_balance(principal);
+                        clarity_db
+                            .get_with_proof::<STXBalance>(&key)
+                            .map(|(balance, proof)| (balance, Some(proof)))
+                            .unwrap_or_else(|| (STXBalance::zero(), None))
+                    } else {
+                        (clarity_db.get_account_stx_balance(principal), None)
+                    };
+                    let (nonce, nonce_proof) = if with_proof {
+                        let key = ClarityDatabase::make_key_for_account_nonce(principal);
+                        clarity_db
+                            .get_with_proof(&key)
+                            .map(|(nonce, proof)| (nonce, Some(proof)))
+                            .unwrap_or_else(|| (0, None))
+                    } else {
+                        (clarity_db.get_account_nonce(principal), None)
+                    };
+
+                    let unlocked = balance.get_available_balance_at_burn_block(burn_block_height);
+                    let (locked, unlock_height) =
+                        balance.get_locked_balance_at_burn_block(burn_block_height);
+
+                    AccountHistoryEntry {
+                        index_block_hash: ancestor.clone(),
+                        block_height: height,
+                        burn_block_height,
+                        unlocked,
+                        locked,
+                        unlock_height,
+                        nonce,
+                        balance_proof,
+                        nonce_proof,
+                    }
+                })
+            });
+            match entry_opt {
+                Some(entry) => history.push(entry),
+                None => {
+                    // no state for this block (e.g. it has not been processed)
+                    warn!("No chain state for {} at height {}", &ancestor, height);
+                }
+            }
+        }
+        Ok(history)
+    }
+
     pub fn get_account_ft<'a>(
         clarity_tx: &mut ClarityTx<'a>,
         contract_id: &QualifiedContractIdentifier,
@@ -38,6 +38,8 @@ use chainstate::burn::BlockSnapshot;
 use chainstate::stacks::db::accounts::MinerReward;
 use chainstate::stacks::db::transactions::TransactionNonceMismatch;
 use chainstate::stacks::db::*;
+use chainstate::stacks::index::marf::MarfConnection;
+use chainstate::stacks::index::trie_sql;
 use chainstate::stacks::index::MarfTrieId;
 use chainstate::stacks::Error;
 use chainstate::stacks::*;
@@ -68,7 +70,7 @@ use vm::ast::build_ast;
 use vm::contexts::AssetMap;
 use vm::contracts::Contract;
 use vm::costs::LimitedCostTracker;
-use vm::database::{BurnStateDB, ClarityDatabase, NULL_BURN_STATE_DB};
+use vm::database::{BurnStateDB, ClarityDatabase, SqliteConnection, NULL_BURN_STATE_DB};
 use vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, SequenceData,
     StandardPrincipalData, TupleData, TypeSignature, Value,
@@ -138,6 +140,7 @@ pub enum MemPoolRejection {
     NoCoinbaseViaMempool,
     NoSuchChainTip(ConsensusHash, BlockHeaderHash),
     ConflictingNonceInMempool,
+    MemPoolFull,
     TooMuchChaining {
         max_nonce: u64,
         actual_nonce: u64,
@@ -222,6 +225,7 @@ impl MemPoolRejection {
                 Some(json!({"message": e.to_string()})),
             ),
             ConflictingNonceInMempool => ("ConflictingNonceInMempool", None),
+            MemPoolFull => ("MemPoolFull", None),
             ContractAlreadyExists(id) => (
                 "ContractAlreadyExists",
                 Some(json!({ "contract_identifier": id.to_string() })),
@@ -267,6 +271,10 @@ impl From<db_error> for MemPoolRejection {
 pub const MINIMUM_TX_FEE: u64 = 1;
 pub const MINIMUM_TX_FEE_RATE_PER_BYTE: u64 = 1;
 
+// Smallest prune depth a node may be configured with.  This is one mainnet reward cycle, so a
+// PoX anchor block reorg never needs a block body that has already been pruned.
+pub const MINIMUM_PRUNE_DEPTH: u64 = 2100;
+
 impl StagingBlock {
     pub fn is_first_mined(&self) -> bool {
         self.parent_anchored_block_hash == FIRST_STACKS_BLOCK_HASH
@@ -794,6 +802,227 @@ impl StacksChainState {
         StacksChainState::free_block(blocks_path, consensus_hash, &block_header.block_hash())
     }
 
+    /// Get the height below which this node has pruned its block and microblock bodies.
+    /// Returns 0 if nothing has been pruned.
+    pub fn get_pruned_height(blocks_conn: &DBConn) -> Result<u64, Error> {
+        if !StacksChainState::has_pruned_blocks(blocks_conn)? {
+            return Ok(0);
+        }
+        let sql = "SELECT pruned_height FROM pruned_blocks LIMIT 1";
+        let height = query_row::<u64, _>(blocks_conn, sql, NO_PARAMS)?.unwrap_or(0);
+        Ok(height)
+    }
+
+    /// Get the height of a processed anchored block, if we have it.
+    fn get_processed_block_height(
+        blocks_conn: &DBConn,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<Option<u64>, Error> {
+        let sql = "SELECT height FROM staging_blocks WHERE index_block_hash = ?1 AND processed = 1";
+        let args: &[&dyn ToSql] = &[index_block_hash];
+        query_row::<u64, _>(blocks_conn, sql, args).map_err(Error::DBError)
+    }
+
+    /// Has the body of this processed anchored block been pruned?
+    pub fn is_block_pruned(
+        blocks_conn: &DBConn,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<bool, Error> {
+        match StacksChainState::get_processed_block_height(blocks_conn, index_block_hash)? {
+            Some(height) => Ok(height < StacksChainState::get_pruned_height(blocks_conn)?),
+            None => Ok(false),
+        }
+    }
+
+    /// Has the data of this processed microblock been pruned?  Microblocks are pruned along with
+    /// the anchored block that confirms them, which is one block above their parent anchored block.
+    pub fn is_microblock_pruned(
+        blocks_conn: &DBConn,
+        index_microblock_hash: &StacksBlockId,
+    ) -> Result<bool, Error> {
+        let sql = "SELECT staging_blocks.height FROM staging_microblocks \
+                   JOIN staging_blocks ON staging_microblocks.index_block_hash = staging_blocks.index_block_hash \
+                   WHERE staging_microblocks.index_microblock_hash = ?1 AND staging_microblocks.processed = 1 \
+                   LIMIT 1";
+        let args: &[&dyn ToSql] = &[index_microblock_hash];
+        match query_row::<u64, _>(blocks_conn, sql, args)? {
+            Some(height) => Ok(height + 1 < StacksChainState::get_pruned_height(blocks_conn)?),
+            None => Ok(false),
+        }
+    }
+
+    /// Delete the bodies of all processed anchored blocks below the given height, as well as the
+    /// data of the microblocks they confirm.  Block headers and staging metadata are left alone,
+    /// since the headers are needed to validate new blocks.
+    ///
+    /// The MARF tries of the blocks on `canonical_tip`'s fork are kept, since every trie can
+    /// back-pointer into any of its ancestors (and `at-block` can read any historical state).
+    /// The tries of blocks on forks that died out below `prune_height` are deleted, along with
+    /// their Clarity contract metadata.  This means a pruned node cannot process a block that
+    /// builds on a fork that forked off more than its prune depth ago.
+    ///
+    /// A pruned block's file is truncated to zero bytes, just like an invalid block's.  This way,
+    /// the block is still considered stored (so it won't be downloaded again), but
+    /// `get_blocks_inventory()` reports it as absent.
+    ///
+    /// Returns the number of anchored blocks pruned.  Only call this on a chainstate with a prune
+    /// depth (see `set_prune_depth()`).
+    pub fn prune_blocks_below(
+        &mut self,
+        canonical_tip: &StacksBlockId,
+        prune_height: u64,
+    ) -> Result<u64, Error> {
+        let pruned_height = StacksChainState::get_pruned_height(self.db())?;
+        if prune_height <= pruned_height {
+            return Ok(0);
+        }
+
+        let sql =
+            "SELECT * FROM staging_blocks WHERE processed = 1 AND height >= ?1 AND height < ?2";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(pruned_height)?, &u64_to_sql(prune_height)?];
+        let blocks = query_rows::<StagingBlock, _>(self.db(), sql, args)?;
+
+        let mut num_pruned = 0;
+        for block in blocks.iter() {
+            let block_path = StacksChainState::get_block_path(
+                &self.blocks_path,
+                &block.consensus_hash,
+                &block.anchored_block_hash,
+            )?;
+            let sz = match StacksChainState::get_file_size(&block_path) {
+                Ok(sz) => sz,
+                Err(Error::DBError(db_error::NotFoundError)) => {
+                    continue;
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            };
+            if sz == 0 {
+                // already freed
+                continue;
+            }
+
+            fs::OpenOptions::new()
+                .read(false)
+                .write(true)
+                .truncate(true)
+                .open(&block_path)
+                .map_err(|e| Error::DBError(db_error::IOError(e)))?;
+
+            num_pruned += 1;
+        }
+
+        let orphaned_blocks = self.find_orphaned_blocks(canonical_tip, &blocks, prune_height)?;
+
+        let tx = self.db_tx_begin()?;
+
+        // the newly-pruned blocks confirm the microblocks whose parent anchored blocks are in
+        // [pruned_height - 1, prune_height - 1).  Microblock data is keyed by hash, so don't
+        // delete any that is also part of a stream that isn't pruned yet.
+        let sql = "DELETE FROM staging_microblocks_data WHERE block_hash IN \
+                   (SELECT staging_microblocks.microblock_hash FROM staging_blocks \
+                    JOIN staging_microblocks ON staging_microblocks.index_block_hash = staging_blocks.index_block_hash \
+                    WHERE staging_blocks.processed = 1 AND staging_blocks.height >= ?1 AND staging_blocks.height < ?2 \
+                    AND staging_microblocks.processed = 1 \
+                    AND NOT EXISTS \
+                    (SELECT 1 FROM staging_microblocks AS unpruned_microblocks \
+                     JOIN staging_blocks AS unpruned_blocks ON unpruned_microblocks.index_block_hash = unpruned_blocks.index_block_hash \
+                     WHERE unpruned_microblocks.microblock_hash = staging_microblocks.microblock_hash \
+                     AND unpruned_blocks.height >= ?2))";
+        let args: &[&dyn ToSql] = &[
+            &u64_to_sql(pruned_height.saturating_sub(1))?,
+            &u64_to_sql(prune_height - 1)?,
+        ];
+        tx.execute(sql, args)?;
+
+        for index_block_hash in orphaned_blocks.iter() {
+            trie_sql::drop_trie(&tx, index_block_hash)?;
+        }
+
+        let args: &[&dyn ToSql] = &[&u64_to_sql(prune_height)?];
+        tx.execute("UPDATE pruned_blocks SET pruned_height = ?1", args)?;
+        tx.commit()?;
+
+        self.clarity_state.with_marf(|marf| -> Result<(), Error> {
+            let tx = marf.storage_tx()?;
+            for index_block_hash in orphaned_blocks.iter() {
+                trie_sql::drop_trie(&tx, index_block_hash)?;
+                SqliteConnection::drop_metadata(&tx, index_block_hash);
+            }
+            tx.commit()?;
+            Ok(())
+        })?;
+
+        debug!(
+            "Pruned {} block(s) and their microblocks below height {}, and the tries of {} orphaned block(s)",
+            num_pruned,
+            prune_height,
+            orphaned_blocks.len()
+        );
+        Ok(num_pruned)
+    }
+
+    /// Find the blocks in `blocks` (all below `prune_height`) that are on forks that died out below
+    /// `prune_height`:  blocks that are neither ancestors of `canonical_tip` nor of any processed
+    /// block at `prune_height`.  A block whose ancestry cannot be determined is kept.
+    fn find_orphaned_blocks(
+        &mut self,
+        canonical_tip: &StacksBlockId,
+        blocks: &[StagingBlock],
+        prune_height: u64,
+    ) -> Result<Vec<StacksBlockId>, Error> {
+        let sql = "SELECT index_block_hash FROM staging_blocks WHERE processed = 1 AND height = ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(prune_height)?];
+        let mut live_tips =
+            query_row_columns::<StacksBlockId, _>(self.db(), sql, args, "index_block_hash")?;
+        live_tips.push(canonical_tip.clone());
+
+        let mut orphaned = vec![];
+        for block in blocks.iter() {
+            let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                &block.consensus_hash,
+                &block.anchored_block_hash,
+            );
+            let mut is_orphaned = true;
+            for tip in live_tips.iter() {
+                match self
+                    .state_index
+                    .get_block_at_height(block.height as u32, tip)
+                {
+                    Ok(Some(ancestor)) if ancestor != index_block_hash => {}
+                    _ => {
+                        is_orphaned = false;
+                        break;
+                    }
+                }
+            }
+            if is_orphaned {
+                orphaned.push(index_block_hash);
+            }
+        }
+        Ok(orphaned)
+    }
+
+    /// Prune block and microblock bodies that are more than `self.prune_depth` blocks behind the
+    /// given canonical Stacks tip.  Does nothing if this chainstate is not pruned.
+    pub fn prune_blocks(
+        &mut self,
+        canonical_tip: &StacksBlockId,
+        canonical_tip_height: u64,
+    ) -> Result<u64, Error> {
+        let prune_depth = match self.prune_depth {
+            Some(depth) => depth,
+            None => {
+                return Ok(0);
+            }
+        };
+        if canonical_tip_height <= prune_depth {
+            return Ok(0);
+        }
+        self.prune_blocks_below(canonical_tip, canonical_tip_height - prune_depth)
+    }
+
     /// Get a list of all anchored blocks' hashes, and their burnchain headers
     pub fn list_blocks(
         blocks_conn: &DBConn,
@@ -1927,6 +2156,8 @@ impl StacksChainState {
         let mut block_bench_total = 0;
         let mut mblock_bench_total = 0;
 
+        let pruned_height = StacksChainState::get_pruned_height(self.db())?;
+
         for (consensus_hash, stacks_header_hash_opt) in header_hashes.iter() {
             match stacks_header_hash_opt {
                 None => {
@@ -1944,6 +2175,21 @@ impl StacksChainState {
                         stacks_header_hash,
                     );
 
+                    if pruned_height > 0 {
+                        // a pruned block is still stored (so it won't be downloaded again),
+                        // but its body and the microblocks it confirms are gone.
+                        let height = StacksChainState::get_processed_block_height(
+                            self.db(),
+                            &index_block_hash,
+                        )?;
+                        if height.map(|h| h < pruned_height).unwrap_or(false) {
+                            test_debug!("Pruned anchored block {}", &index_block_hash);
+                            block_bits.push(false);
+                            microblock_bits.push(false);
+                            continue;
+                        }
+                    }
+
                     let block_bench_start = get_epoch_time_ms();
                     let mut parent_microblock_hash = None;
 
@@ -4782,6 +5028,7 @@ impl StacksChainState {
         .expect("FATAL: failed to advance chain tip");
 
         chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &tx_receipts);
+        chainstate_tx.log_transaction_events(&new_tip, &tx_receipts);
 
         let epoch_receipt = StacksEpochReceipt {
             header: new_tip,
@@ -9253,6 +9500,190 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn stacks_db_prune_blocks() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "stacks_db_prune_blocks");
+
+        let mut blocks: Vec<StacksBlock> = vec![];
+        let mut privks = vec![];
+        let mut microblocks = vec![];
+        let mut consensus_hashes = vec![];
+        let mut parent_consensus_hashes = vec![];
+
+        for i in 0..8 {
+            let privk = StacksPrivateKey::new();
+            let mut block = make_empty_coinbase_block(&privk);
+            block.header.total_work.work = (i + 1) as u64;
+
+            blocks.push(block);
+            privks.push(privk);
+
+            consensus_hashes.push(ConsensusHash([((i + 1) as u8); 20]));
+            parent_consensus_hashes.push(ConsensusHash([(i as u8); 20]));
+        }
+
+        for i in 0..blocks.len() {
+            let mut mblocks = make_sample_microblock_stream(&privks[i], &blocks[i].block_hash());
+            mblocks.truncate(3);
+
+            if i + 1 < blocks.len() {
+                blocks[i + 1].header.parent_block = blocks[i].block_hash();
+                blocks[i + 1].header.parent_microblock = mblocks[2].block_hash();
+                blocks[i + 1].header.parent_microblock_sequence = mblocks[2].header.sequence;
+            }
+
+            microblocks.push(mblocks);
+        }
+
+        let block_hashes: Vec<BlockHeaderHash> =
+            blocks.iter().map(|ref b| b.block_hash()).collect();
+        let header_hashes_all: Vec<(ConsensusHash, Option<BlockHeaderHash>)> = consensus_hashes
+            .iter()
+            .zip(block_hashes.iter())
+            .map(|(ref burn, ref block)| ((*burn).clone(), Some((*block).clone())))
+            .collect();
+
+        for i in 0..blocks.len() {
+            for mblock in microblocks[i].iter() {
+                store_staging_microblock(
+                    &mut chainstate,
+                    &consensus_hashes[i],
+                    &block_hashes[i],
+                    mblock,
+                );
+            }
+            store_staging_block(
+                &mut chainstate,
+                &consensus_hashes[i],
+                &blocks[i],
+                &parent_consensus_hashes[i],
+                1,
+                2,
+            );
+        }
+
+        for i in 0..blocks.len() {
+            set_block_processed(
+                &mut chainstate,
+                &consensus_hashes[i],
+                &block_hashes[i],
+                true,
+            );
+            if i + 1 < blocks.len() {
+                for mblock in microblocks[i].iter() {
+                    set_microblocks_processed(
+                        &mut chainstate,
+                        &consensus_hashes[i + 1],
+                        &block_hashes[i + 1],
+                        &mblock.block_hash(),
+                    );
+                }
+            }
+        }
+
+        assert_eq!(
+            StacksChainState::get_pruned_height(chainstate.db()).unwrap(),
+            0
+        );
+
+        let tip = StacksBlockHeader::make_index_block_hash(
+            &consensus_hashes[blocks.len() - 1],
+            &block_hashes[blocks.len() - 1],
+        );
+
+        // not a pruned node
+        assert_eq!(chainstate.prune_blocks(&tip, 8).unwrap(), 0);
+        assert_eq!(
+            StacksChainState::get_pruned_height(chainstate.db()).unwrap(),
+            0
+        );
+        assert!(!table_exists(chainstate.db(), "pruned_blocks").unwrap());
+
+        // tip is too low to prune anything
+        chainstate.set_prune_depth(Some(3)).unwrap();
+        assert!(table_exists(chainstate.db(), "pruned_blocks").unwrap());
+        assert_eq!(chainstate.prune_blocks(&tip, 3).unwrap(), 0);
+
+        // prune blocks at heights 1 through 4
+        assert_eq!(chainstate.prune_blocks(&tip, 8).unwrap(), 4);
+        assert_eq!(
+            StacksChainState::get_pruned_height(chainstate.db()).unwrap(),
+            5
+        );
+
+        // idempotent
+        assert_eq!(chainstate.prune_blocks(&tip, 8).unwrap(), 0);
+        chainstate.set_prune_depth(Some(3)).unwrap();
+        assert_eq!(
+            StacksChainState::get_pruned_height(chainstate.db()).unwrap(),
+            5
+        );
+
+        let block_inv_all = chainstate.get_blocks_inventory(&header_hashes_all).unwrap();
+        for i in 0..blocks.len() {
+            let index_block_hash =
+                StacksBlockHeader::make_index_block_hash(&consensus_hashes[i], &block_hashes[i]);
+            let pruned = i < 4;
+
+            // pruned blocks are still considered stored, so they won't be downloaded again
+            assert!(StacksChainState::has_stored_block(
+                chainstate.db(),
+                &chainstate.blocks_path,
+                &consensus_hashes[i],
+                &block_hashes[i]
+            )
+            .unwrap());
+
+            assert_eq!(
+                StacksChainState::is_block_pruned(chainstate.db(), &index_block_hash).unwrap(),
+                pruned
+            );
+            assert_eq!(
+                StacksChainState::load_block(
+                    &chainstate.blocks_path,
+                    &consensus_hashes[i],
+                    &block_hashes[i]
+                )
+                .unwrap()
+                .is_none(),
+                pruned
+            );
+            assert_eq!(block_inv_all.has_ith_block(i as u16), !pruned);
+            assert_eq!(
+                block_inv_all.has_ith_microblock_stream(i as u16),
+                !pruned && i > 0
+            );
+
+            // microblock streams are pruned along with the blocks that confirm them
+            if i + 1 < blocks.len() {
+                let mblocks_pruned = i + 1 < 4;
+                for mblock in microblocks[i].iter() {
+                    let index_microblock_hash = StacksBlockHeader::make_index_block_hash(
+                        &consensus_hashes[i],
+                        &mblock.block_hash(),
+                    );
+                    assert_eq!(
+                        StacksChainState::is_microblock_pruned(
+                            chainstate.db(),
+                            &index_microblock_hash
+                        )
+                        .unwrap(),
+                        mblocks_pruned
+                    );
+                    assert_eq!(
+                        StacksChainState::load_staging_microblock_bytes(
+                            chainstate.db(),
+                            &mblock.block_hash()
+                        )
+                        .unwrap()
+                        .is_none(),
+                        mblocks_pruned
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_get_parent_block_header() {
         let peer_config = TestPeerConfig::new("test_get_parent_block_header", 21313, 21314);
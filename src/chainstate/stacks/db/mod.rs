@@ -60,8 +60,8 @@ use net::atlas::BNS_CHARS_REGEX;
 use net::Error as net_error;
 use util::db::Error as db_error;
 use util::db::{
-    query_count, query_row, tx_begin_immediate, tx_busy_handler, DBConn, DBTx, FromColumn, FromRow,
-    IndexDBConn, IndexDBTx,
+    query_count, query_row, query_rows, table_exists, tx_begin_immediate, tx_busy_handler,
+    u64_to_sql, DBConn, DBTx, FromColumn, FromRow, IndexDBConn, IndexDBTx,
 };
 use util::hash::to_hex;
 use vm::analysis::analysis_db::AnalysisDatabase;
@@ -70,11 +70,12 @@ use vm::ast::build_ast;
 use vm::contexts::OwnedEnvironment;
 use vm::costs::{ExecutionCost, LimitedCostTracker};
 use vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
+    BurnStateDB, ClarityDatabase, ClaritySerializable, HeadersDB, STXBalance, SqliteConnection,
+    NULL_BURN_STATE_DB,
 };
 use vm::representations::ClarityName;
 use vm::representations::ContractName;
-use vm::types::TupleData;
+use vm::types::{PrincipalData, QualifiedContractIdentifier, TupleData};
 use {monitoring, util};
 
 use crate::clarity_vm::database::marf::MarfedKV;
@@ -95,6 +96,7 @@ pub mod unconfirmed;
 lazy_static! {
     pub static ref TRANSACTION_LOG: bool =
         std::env::var("STACKS_TRANSACTION_LOG") == Ok("1".into());
+    pub static ref EVENT_LOG: bool = std::env::var("STACKS_EVENT_LOG") == Ok("1".into());
 }
 
 pub struct StacksChainState {
@@ -107,6 +109,9 @@ pub struct StacksChainState {
     pub clarity_state_index_root: String, // path to dir containing clarity MARF and side-store
     pub root_path: String,
     pub unconfirmed_state: Option<UnconfirmedState>,
+    /// If set, block and microblock bodies more than this many blocks behind the canonical
+    /// Stacks tip are deleted as new blocks get processed.  Set with `set_prune_depth()`.
+    prune_depth: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -171,6 +176,44 @@ pub struct StacksEpochReceipt {
     pub evaluated_epoch: StacksEpochId,
 }
 
+/// A transaction in the transaction log, as processed in one block.
+/// Only recorded if the transaction log is enabled (see `TRANSACTION_LOG`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionLogEntry {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub tx_hex: String,
+    pub result: String,
+}
+
+/// A transaction's receipt in the event log, as processed in one block.
+/// Only recorded if the event log is enabled (see `EVENT_LOG`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionReceiptEntry {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// position of the transaction in the block's receipts
+    pub tx_index: u32,
+    /// the transaction's result, as a hex-encoded Clarity value
+    pub result: String,
+    pub post_condition_aborted: bool,
+    pub execution_cost: ExecutionCost,
+}
+
+/// An event in the event log, as emitted by a transaction processed in one block.
+/// Only recorded if the event log is enabled (see `EVENT_LOG`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionEventEntry {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+    /// the event, encoded the same way as it is for event observers
+    pub event: serde_json::Value,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DBConfig {
     pub version: String,
@@ -249,6 +292,68 @@ impl FromRow<DBConfig> for DBConfig {
     }
 }
 
+impl FromRow<TransactionLogEntry> for TransactionLogEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<TransactionLogEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let tx_hex: String = row.get_unwrap("tx_hex");
+        let result: String = row.get_unwrap("result");
+
+        Ok(TransactionLogEntry {
+            txid,
+            index_block_hash,
+            tx_hex,
+            result,
+        })
+    }
+}
+
+impl FromRow<TransactionReceiptEntry> for TransactionReceiptEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<TransactionReceiptEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let result: String = row.get_unwrap("result");
+        let post_condition_aborted: bool = row.get_unwrap("post_condition_aborted");
+        let execution_cost_json: String = row.get_unwrap("execution_cost");
+        let execution_cost = serde_json::from_str(&execution_cost_json)
+            .map_err(|e| db_error::SerializationError(e))?;
+
+        Ok(TransactionReceiptEntry {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            result,
+            post_condition_aborted,
+            execution_cost,
+        })
+    }
+}
+
+impl FromRow<TransactionEventEntry> for TransactionEventEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<TransactionEventEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let event_index: u32 = row.get_unwrap("event_index");
+        let event_json: String = row.get_unwrap("event");
+        let event =
+            serde_json::from_str(&event_json).map_err(|e| db_error::SerializationError(e))?;
+
+        Ok(TransactionEventEntry {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            event_index,
+            event,
+        })
+    }
+}
+
 impl FromRow<StacksHeaderInfo> for StacksHeaderInfo {
     fn from_row<'a>(row: &'a Row) -> Result<StacksHeaderInfo, db_error> {
         let block_height: u64 = u64::from_column(row, "block_height")?;
@@ -458,6 +563,78 @@ impl<'a> ChainstateTx<'a> {
             }
         }
     }
+
+    /// Record each transaction's receipt and events in the event log, if it is enabled.
+    pub fn log_transaction_events(
+        &self,
+        header: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) {
+        if !*EVENT_LOG {
+            return;
+        }
+        if let Err(e) = self.inner_log_transaction_events(header, receipts) {
+            warn!("Failed to log TX events: {:?}", e;
+                  "index_block_hash" => %header.index_block_hash());
+        }
+    }
+
+    fn inner_log_transaction_events(
+        &self,
+        header: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        let block_id = header.index_block_hash();
+        let block_height = u64_to_sql(header.block_height)?;
+        let insert_receipt = "INSERT INTO transaction_receipts
+            (txid, index_block_hash, block_height, tx_index, result, post_condition_aborted, execution_cost)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)";
+        let insert_event = "INSERT INTO transaction_events
+            (txid, index_block_hash, block_height, tx_index, event_index, contract_identifier, sender, recipient, event)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
+
+        for (tx_index, receipt) in receipts.iter().enumerate() {
+            let txid = receipt.transaction.txid();
+            let tx_index = tx_index as u32;
+            let result = receipt.result.serialize();
+            let execution_cost = serde_json::to_string(&receipt.execution_cost)
+                .map_err(|e| Error::DBError(db_error::SerializationError(e)))?;
+            let args: &[&dyn ToSql] = &[
+                &txid,
+                &block_id,
+                &block_height,
+                &tx_index,
+                &result,
+                &receipt.post_condition_aborted,
+                &execution_cost,
+            ];
+            self.tx.tx().execute(insert_receipt, args)?;
+
+            let committed = !receipt.post_condition_aborted;
+            for (event_index, event) in receipt.events.iter().enumerate() {
+                let contract_identifier = event.contract_identifier().map(|id| id.to_string());
+                let sender = event.sender().map(|p| p.to_string());
+                let recipient = event.recipient().map(|p| p.to_string());
+                let event_json = event
+                    .json_serialize(event_index, &txid, committed)
+                    .to_string();
+                let event_index = event_index as u32;
+                let args: &[&dyn ToSql] = &[
+                    &txid,
+                    &block_id,
+                    &block_height,
+                    &tx_index,
+                    &event_index,
+                    &contract_identifier,
+                    &sender,
+                    &recipient,
+                    &event_json,
+                ];
+                self.tx.tx().execute(insert_event, args)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'a> Deref for ChainstateTx<'a> {
@@ -655,6 +832,54 @@ const CHAINSTATE_SCHEMA_2: &'static [&'static str] = &[
     "#,
 ];
 
+/// The event log: receipts and events of each transaction, as processed in each block.
+/// These tables are not part of the chainstate schema; they are only created once the node
+/// runs with the event log enabled (see `EVENT_LOG`).
+const EVENT_LOG_SCHEMA: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS transaction_receipts(
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,
+        result TEXT NOT NULL,                   -- hex-encoded Clarity value
+        post_condition_aborted INTEGER NOT NULL,
+        execution_cost TEXT NOT NULL,           -- JSON-encoded ExecutionCost
+        PRIMARY KEY(txid,index_block_hash)
+    );"#,
+    "CREATE INDEX IF NOT EXISTS index_block_hash_receipts ON transaction_receipts(index_block_hash);",
+    r#"
+    CREATE TABLE IF NOT EXISTS transaction_events(
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,
+        event_index INTEGER NOT NULL,
+        contract_identifier TEXT,               -- emitting contract, or contract that defines the asset
+        sender TEXT,                            -- principal that sent, burnt, or locked an asset
+        recipient TEXT,                         -- principal that received an asset
+        event TEXT NOT NULL,                    -- JSON-encoded event, as sent to event observers
+        PRIMARY KEY(txid,index_block_hash,event_index)
+    );"#,
+    "CREATE INDEX IF NOT EXISTS contract_events ON transaction_events(contract_identifier,block_height);",
+    "CREATE INDEX IF NOT EXISTS sender_events ON transaction_events(sender,block_height);",
+    "CREATE INDEX IF NOT EXISTS recipient_events ON transaction_events(recipient,block_height);",
+    "CREATE INDEX IF NOT EXISTS block_height_events ON transaction_events(block_height);",
+];
+
+/// Pruning bookkeeping: the height below which block and microblock bodies have been pruned,
+/// and the indexes the pruning queries need.  These are not part of the chainstate schema; they
+/// are only created once the node runs with a prune depth (see `set_prune_depth()`).
+const PRUNING_SCHEMA: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS pruned_blocks(
+        pruned_height INTEGER NOT NULL
+    );"#,
+    "INSERT INTO pruned_blocks (pruned_height) SELECT 0 WHERE NOT EXISTS (SELECT 1 FROM pruned_blocks);",
+    "CREATE INDEX IF NOT EXISTS processed_stacks_block_heights ON staging_blocks(processed,height);",
+    "CREATE INDEX IF NOT EXISTS staging_microblocks_hash ON staging_microblocks(microblock_hash);",
+];
+
 #[cfg(test)]
 pub const MINER_REWARD_MATURITY: u64 = 2; // small for testing purposes
 
@@ -851,9 +1076,46 @@ impl StacksChainState {
                     StacksChainState::load_db_config(tx).expect("CORRUPTION: no db_config found");
             }
         }
+
+        if *EVENT_LOG {
+            StacksChainState::instantiate_event_log(tx)?;
+        }
+        Ok(())
+    }
+
+    /// Create the event log's tables, if they don't exist yet.
+    pub fn instantiate_event_log(conn: &DBConn) -> Result<(), Error> {
+        for cmd in EVENT_LOG_SCHEMA.iter() {
+            conn.execute_batch(cmd)?;
+        }
         Ok(())
     }
 
+    /// Set this chainstate's prune depth.  The first time a prune depth is set, this creates the
+    /// tables and indexes that pruning needs; chainstates that are never pruned don't have them.
+    pub fn set_prune_depth(&mut self, prune_depth: Option<u64>) -> Result<(), Error> {
+        if prune_depth.is_some() {
+            let tx = self.db_tx_begin()?;
+            for cmd in PRUNING_SCHEMA.iter() {
+                tx.execute_batch(cmd)?;
+            }
+            tx.commit()?;
+        }
+        self.prune_depth = prune_depth;
+        Ok(())
+    }
+
+    /// Has this chainstate ever been pruned?
+    fn has_pruned_blocks(conn: &DBConn) -> Result<bool, Error> {
+        table_exists(conn, "pruned_blocks").map_err(|e| Error::DBError(db_error::SqliteError(e)))
+    }
+
+    /// Has this chainstate ever run with the event log enabled?
+    fn has_event_log(conn: &DBConn) -> Result<bool, Error> {
+        table_exists(conn, "transaction_events")
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))
+    }
+
     fn open_db(
         mainnet: bool,
         chain_id: u32,
@@ -1526,6 +1788,7 @@ impl StacksChainState {
             clarity_state_index_root: clarity_state_index_root,
             root_path: path_str.to_string(),
             unconfirmed_state: None,
+            prune_depth: None,
         };
 
         let mut receipts = vec![];
@@ -1627,6 +1890,94 @@ impl StacksChainState {
         self.state_index.sqlite_conn()
     }
 
+    /// Get the transaction log's entries for a transaction -- one for each block it was
+    /// processed in, across all forks. Empty if the transaction log is disabled.
+    pub fn get_logged_transaction(
+        conn: &DBConn,
+        txid: &Txid,
+    ) -> Result<Vec<TransactionLogEntry>, Error> {
+        let sql = "SELECT * FROM transactions WHERE txid = ?1 ORDER BY id";
+        query_rows(conn, sql, &[txid]).map_err(Error::DBError)
+    }
+
+    /// Get the event log's receipt for a transaction, as processed in the given block.
+    pub fn get_logged_receipt(
+        conn: &DBConn,
+        txid: &Txid,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<Option<TransactionReceiptEntry>, Error> {
+        if !StacksChainState::has_event_log(conn)? {
+            return Ok(None);
+        }
+        let sql = "SELECT * FROM transaction_receipts WHERE txid = ?1 AND index_block_hash = ?2";
+        let args: &[&dyn ToSql] = &[txid, index_block_hash];
+        query_row(conn, sql, args).map_err(Error::DBError)
+    }
+
+    /// Get a page of the event log's entries for events emitted by the given contract, or
+    /// concerning an asset it defines.  Only blocks below `before_height` are considered, and
+    /// entries are ordered from the most recent block to the oldest (across all forks).
+    pub fn get_logged_contract_events(
+        conn: &DBConn,
+        contract_identifier: &QualifiedContractIdentifier,
+        before_height: u64,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<TransactionEventEntry>, Error> {
+        if !StacksChainState::has_event_log(conn)? {
+            return Ok(vec![]);
+        }
+        let sql = "SELECT * FROM transaction_events WHERE contract_identifier = ?1 AND block_height < ?2
+                   ORDER BY block_height DESC, index_block_hash, tx_index, event_index LIMIT ?3 OFFSET ?4";
+        let args: &[&dyn ToSql] = &[
+            &contract_identifier.to_string(),
+            &u64_to_sql(before_height)?,
+            &limit,
+            &offset,
+        ];
+        query_rows(conn, sql, args).map_err(Error::DBError)
+    }
+
+    /// Get a page of the event log's entries for events in which the given principal sent,
+    /// received, burnt, or locked an asset.  Only blocks below `before_height` are considered,
+    /// and entries are ordered from the most recent block to the oldest (across all forks).
+    pub fn get_logged_principal_events(
+        conn: &DBConn,
+        principal: &PrincipalData,
+        before_height: u64,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<TransactionEventEntry>, Error> {
+        if !StacksChainState::has_event_log(conn)? {
+            return Ok(vec![]);
+        }
+        let sql = "SELECT * FROM transaction_events WHERE (sender = ?1 OR recipient = ?1) AND block_height < ?2
+                   ORDER BY block_height DESC, index_block_hash, tx_index, event_index LIMIT ?3 OFFSET ?4";
+        let args: &[&dyn ToSql] = &[
+            &principal.to_string(),
+            &u64_to_sql(before_height)?,
+            &limit,
+            &offset,
+        ];
+        query_rows(conn, sql, args).map_err(Error::DBError)
+    }
+
+    /// Get all of the event log's entries for blocks between `start_height` and `end_height`
+    /// (inclusive), ordered from the oldest block to the most recent (across all forks).
+    pub fn get_logged_block_range_events(
+        conn: &DBConn,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<TransactionEventEntry>, Error> {
+        if !StacksChainState::has_event_log(conn)? {
+            return Ok(vec![]);
+        }
+        let sql = "SELECT * FROM transaction_events WHERE block_height >= ?1 AND block_height <= ?2
+                   ORDER BY block_height, index_block_hash, tx_index, event_index";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(start_height)?, &u64_to_sql(end_height)?];
+        query_rows(conn, sql, args).map_err(Error::DBError)
+    }
+
     /// Begin processing an epoch's transactions within the context of a chainstate transaction
     pub fn chainstate_block_begin<'a>(
         chainstate_tx: &'a ChainstateTx<'a>,
@@ -2086,6 +2437,7 @@ pub mod test {
     use chainstate::stacks::*;
     use stx_genesis::GenesisData;
     use vm::tests::TEST_BURN_STATE_DB;
+    use vm::types::AssetIdentifier;
 
     use crate::util::boot::boot_code_test_addr;
 
@@ -2341,4 +2693,164 @@ pub mod test {
             MAINNET_2_0_GENESIS_ROOT_HASH
         );
     }
+
+    #[test]
+    fn test_event_log() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "event-log");
+
+        let contract_id = QualifiedContractIdentifier::new(
+            boot_code_test_addr().into(),
+            ContractName::try_from("token".to_string()).unwrap(),
+        );
+        let alice = PrincipalData::from(StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0x01; 20]),
+        });
+        let bob = PrincipalData::from(StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0x02; 20]),
+        });
+
+        let print_event = StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+            key: (contract_id.clone(), "print".to_string()),
+            value: Value::Int(1),
+        });
+        let ft_event =
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(FTTransferEventData {
+                asset_identifier: AssetIdentifier {
+                    contract_identifier: contract_id.clone(),
+                    asset_name: "token".into(),
+                },
+                sender: alice.clone(),
+                recipient: bob.clone(),
+                amount: 10,
+            }));
+        let stx_event =
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(STXMintEventData {
+                recipient: alice.clone(),
+                amount: 100,
+            }));
+
+        let make_receipt =
+            |txid: Txid, events: Vec<StacksTransactionEvent>| StacksTransactionReceipt {
+                transaction: TransactionOrigin::Burn(txid),
+                events,
+                post_condition_aborted: false,
+                result: Value::okay_true(),
+                stx_burned: 0,
+                contract_analysis: None,
+                execution_cost: ExecutionCost {
+                    write_length: 1,
+                    write_count: 2,
+                    read_length: 3,
+                    read_count: 4,
+                    runtime: 5,
+                },
+                microblock_header: None,
+            };
+
+        let mut header = StacksHeaderInfo::regtest_genesis();
+        header.block_height = 1;
+        let first_block = header.index_block_hash();
+        let first_receipts = vec![
+            make_receipt(Txid([0x11; 32]), vec![stx_event.clone()]),
+            make_receipt(
+                Txid([0x12; 32]),
+                vec![print_event.clone(), ft_event.clone()],
+            ),
+        ];
+
+        let mut second_header = StacksHeaderInfo::regtest_genesis();
+        second_header.block_height = 2;
+        second_header.consensus_hash = ConsensusHash([0x22; 20]);
+        let second_block = second_header.index_block_hash();
+        let second_receipts = vec![make_receipt(Txid([0x21; 32]), vec![print_event.clone()])];
+
+        // a node that never enabled the event log has no events
+        assert!(
+            StacksChainState::get_logged_block_range_events(chainstate.db(), 0, 10)
+                .unwrap()
+                .is_empty()
+        );
+
+        StacksChainState::instantiate_event_log(chainstate.db()).unwrap();
+        {
+            let (chainstate_tx, _) = chainstate.chainstate_tx_begin().unwrap();
+            chainstate_tx
+                .inner_log_transaction_events(&header, &first_receipts)
+                .unwrap();
+            chainstate_tx
+                .inner_log_transaction_events(&second_header, &second_receipts)
+                .unwrap();
+            chainstate_tx.commit().unwrap();
+        }
+
+        // contract events are found by the emitting contract, and by the asset's contract
+        let events = StacksChainState::get_logged_contract_events(
+            chainstate.db(),
+            &contract_id,
+            u32::MAX as u64,
+            10,
+            0,
+        )
+        .unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].index_block_hash, second_block);
+        assert_eq!(events[0].txid, Txid([0x21; 32]));
+        assert_eq!(events[1].txid, Txid([0x12; 32]));
+        assert_eq!(events[1].event_index, 0);
+        assert_eq!(events[1].event["type"], "contract_event");
+        assert_eq!(events[2].event_index, 1);
+        assert_eq!(events[2].event["type"], "ft_transfer_event");
+
+        // paging
+        let events =
+            StacksChainState::get_logged_contract_events(chainstate.db(), &contract_id, 2, 1, 1)
+                .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_index, 1);
+
+        // principal events are found by sender and by recipient
+        let events =
+            StacksChainState::get_logged_principal_events(chainstate.db(), &alice, 10, 10, 0)
+                .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].txid, Txid([0x11; 32]));
+        assert_eq!(events[0].event["type"], "stx_mint_event");
+        assert_eq!(events[1].event["type"], "ft_transfer_event");
+
+        let events =
+            StacksChainState::get_logged_principal_events(chainstate.db(), &bob, 10, 10, 0)
+                .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event["ft_transfer_event"]["amount"], "10");
+
+        // block ranges are inclusive
+        let events =
+            StacksChainState::get_logged_block_range_events(chainstate.db(), 1, 1).unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.index_block_hash == first_block));
+        let events =
+            StacksChainState::get_logged_block_range_events(chainstate.db(), 1, 2).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[3].index_block_hash, second_block);
+
+        // receipts carry the execution cost
+        let receipt =
+            StacksChainState::get_logged_receipt(chainstate.db(), &Txid([0x12; 32]), &first_block)
+                .unwrap()
+                .unwrap();
+        assert_eq!(receipt.tx_index, 1);
+        assert_eq!(receipt.block_height, 1);
+        assert_eq!(receipt.result, Value::okay_true().serialize());
+        assert!(!receipt.post_condition_aborted);
+        assert_eq!(receipt.execution_cost.runtime, 5);
+        assert!(StacksChainState::get_logged_receipt(
+            chainstate.db(),
+            &Txid([0x12; 32]),
+            &second_block
+        )
+        .unwrap()
+        .is_none());
+    }
 }
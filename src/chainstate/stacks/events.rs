@@ -158,6 +158,89 @@ impl StacksTransactionEvent {
             }),
         }
     }
+
+    /// The contract that emitted this event, or that defines the asset it concerns.
+    /// STX events have none.
+    pub fn contract_identifier(&self) -> Option<&QualifiedContractIdentifier> {
+        match self {
+            StacksTransactionEvent::SmartContractEvent(event_data) => Some(&event_data.key.0),
+            StacksTransactionEvent::STXEvent(_) => None,
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+                Some(&event_data.asset_identifier.contract_identifier)
+            }
+        }
+    }
+
+    /// The principal that sent, burnt, or locked an asset in this event, if any.
+    pub fn sender(&self) -> Option<&PrincipalData> {
+        match self {
+            StacksTransactionEvent::SmartContractEvent(_) => None,
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(event_data)) => {
+                Some(&event_data.sender)
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(_)) => None,
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(event_data)) => {
+                Some(&event_data.sender)
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(event_data)) => {
+                Some(&event_data.locked_address)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+                Some(&event_data.sender)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(_)) => None,
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+                Some(&event_data.sender)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+                Some(&event_data.sender)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(_)) => None,
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+                Some(&event_data.sender)
+            }
+        }
+    }
+
+    /// The principal that received an asset in this event, if any.
+    pub fn recipient(&self) -> Option<&PrincipalData> {
+        match self {
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(event_data)) => {
+                Some(&event_data.recipient)
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(event_data)) => {
+                Some(&event_data.recipient)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+                Some(&event_data.recipient)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => {
+                Some(&event_data.recipient)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+                Some(&event_data.recipient)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => {
+                Some(&event_data.recipient)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -425,6 +425,17 @@ pub fn drop_unconfirmed_trie<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Resul
     Ok(())
 }
 
+/// Drop a confirmed trie.  Only safe if no other trie back-pointers into it.
+pub fn drop_trie<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Result<(), Error> {
+    debug!("Drop trie sqlite blob {}", bhh);
+    conn.execute(
+        "DELETE FROM marf_data WHERE block_hash = ? AND unconfirmed = 0",
+        &[bhh],
+    )?;
+    debug!("Dropped trie sqlite blob {}", bhh);
+    Ok(())
+}
+
 pub fn clear_lock_data(conn: &Connection) -> Result<(), Error> {
     conn.execute("DELETE FROM block_extension_locks", NO_PARAMS)?;
     Ok(())
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cell::RefCell;
 use std::convert::TryInto;
 use std::env;
 use std::fs;
@@ -22,6 +23,7 @@ use std::io::{Read, Write};
 use std::iter::Iterator;
 use std::path::PathBuf;
 use std::process;
+use std::rc::Rc;
 
 use rand::Rng;
 use rusqlite::types::ToSql;
@@ -40,16 +42,24 @@ use vm::ContractName;
 
 use vm::analysis;
 use vm::analysis::contract_interface_builder::build_contract_interface;
+use vm::analysis::lint_checker::LintChecker;
 use vm::analysis::{errors::CheckError, errors::CheckResult, AnalysisDatabase, ContractAnalysis};
 use vm::ast::build_ast;
+#[cfg(feature = "developer-mode")]
+use vm::ast::formatter;
 use vm::contexts::{AssetMap, OwnedEnvironment};
+use vm::costs::profiler::{CostProfiler, COST_DIMENSIONS};
 use vm::costs::ExecutionCost;
 use vm::costs::LimitedCostTracker;
+use vm::coverage::CoverageReporter;
 use vm::database::{
     BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
 };
+use vm::debug::Debugger;
 use vm::errors::{Error, InterpreterResult, RuntimeErrorType};
-use vm::types::{PrincipalData, QualifiedContractIdentifier};
+use vm::types::{
+    FixedFunction, FunctionType, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+};
 use vm::{SymbolicExpression, SymbolicExpressionType, Value};
 
 use burnchains::PoxConstants;
@@ -111,8 +121,11 @@ where command is one of:
   eval_at_block      like `eval_at_chaintip`, but accepts a index-block-hash to evaluate at,
                      must be passed eval string via stdin.
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
-  repl               to typecheck and evaluate expressions in a stdin/stdout loop.
+  repl               to typecheck and evaluate expressions in a stdin/stdout loop,
+                     with a step debugger (type `::help` at the prompt).
   execute            to execute a public function of a defined contract.
+  test               to run the test functions of a project of contracts.
+  fmt                to format the source code of contracts.
   generate_address   to generate a random Stacks public address for testing purposes.
 ",
         invoked_by
@@ -120,6 +133,17 @@ where command is one of:
     panic_test!()
 }
 
+const REPL_HELP: &str = "REPL commands:
+  ::deploy CONTRACT-NAME FILE   deploy a contract so that it can be called from the REPL
+  ::break [CONTRACT:]LINE       set a breakpoint
+  ::delete ID                   delete a breakpoint
+  ::breakpoints                 list the breakpoints
+  ::debug EXPR                  evaluate an expression in the step debugger
+  ::help                        print this message
+
+Execution pauses at breakpoints and at runtime errors. Type `help` at the
+(debug) prompt for the debugger's commands.";
+
 #[cfg_attr(tarpaulin, skip)]
 fn friendly_expect<A, B: std::fmt::Display>(input: Result<A, B>, msg: &str) -> A {
     input.unwrap_or_else(|e| {
@@ -630,7 +654,7 @@ impl HeadersDB for CLIHeadersDB {
 fn get_eval_input(invoked_by: &str, args: &[String]) -> EvalInput {
     if args.len() < 3 || args.len() > 4 {
         eprintln!(
-            "Usage: {} {} [--costs] [contract-identifier] (program.clar) [vm-state.db]",
+            "Usage: {} {} [--costs] [--profile FOLDED-FILE [--profile-dimension DIMENSION]] [contract-identifier] (program.clar) [vm-state.db]",
             invoked_by, args[0]
         );
         panic_test!();
@@ -784,12 +808,280 @@ fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C)
         .unwrap();
 }
 
+/// Address that deploys the contracts, and calls the tests, of a `test` project
+const CLI_TEST_DEPLOYER: &str = "S1G2081040G2081040G2081040G208105NK8PE5";
+
+/// A contract loaded from a `test` project
+struct ProjectContract {
+    contract_identifier: QualifiedContractIdentifier,
+    path: String,
+    content: String,
+    is_test: bool,
+}
+
+/// A contract that has been deployed by `test`
+struct DeployedContract {
+    contract: ProjectContract,
+    ast: Vec<SymbolicExpression>,
+    analysis: ContractAnalysis,
+}
+
+/// Load all the `.clar` files in a project subdirectory, in file name order.
+/// Each one is deployed as the contract named by its file stem.
+fn load_project_contracts(
+    dir: &PathBuf,
+    deployer: &StandardPrincipalData,
+    is_test: bool,
+) -> Result<Vec<ProjectContract>, String> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "clar").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    let mut contracts = vec![];
+    for path in paths.into_iter() {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| format!("Invalid contract file name: {}", path.display()))?;
+        let contract_name = ContractName::try_from(name.to_string())
+            .map_err(|e| format!("Invalid contract name '{}': {}", name, e))?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Error reading file {}: {}", path.display(), e))?;
+
+        contracts.push(ProjectContract {
+            contract_identifier: QualifiedContractIdentifier::new(deployer.clone(), contract_name),
+            path: path.display().to_string(),
+            content,
+            is_test,
+        });
+    }
+    Ok(contracts)
+}
+
+/// Deploy a project's contracts.  They may depend on one another, so keep making passes over
+/// the ones that failed analysis until no more of them can be deployed.
+/// On failure, returns the path of a contract that could not be deployed, and why.
+fn deploy_project_contracts<C: ClarityStorage>(
+    header_db: &CLIHeadersDB,
+    marf: &mut C,
+    mut pending: Vec<ProjectContract>,
+) -> Result<Vec<DeployedContract>, (String, String)> {
+    let mainnet = header_db.is_mainnet();
+    let mut deployed = vec![];
+
+    while pending.len() > 0 {
+        let num_pending = pending.len();
+        let mut still_pending = vec![];
+        let mut last_error = None;
+
+        for contract in pending.into_iter() {
+            let mut ast = parse(&contract.contract_identifier, &contract.content)
+                .map_err(|e| (contract.path.clone(), format!("{}", e)))?;
+            let parsed_ast = ast.clone();
+
+            let analysis = match run_analysis(
+                &contract.contract_identifier,
+                &mut ast,
+                header_db,
+                marf,
+                true,
+            ) {
+                Ok(analysis) => analysis,
+                Err((e, _)) => {
+                    // may depend on a contract we haven't deployed yet
+                    last_error = Some((contract.path.clone(), format!("{}", e)));
+                    still_pending.push(contract);
+                    continue;
+                }
+            };
+
+            let db = marf.get_clarity_db(header_db, &NULL_BURN_STATE_DB);
+            let mut vm_env = OwnedEnvironment::new_free(mainnet, db, DEFAULT_CLI_EPOCH);
+            vm_env
+                .initialize_contract(contract.contract_identifier.clone(), &contract.content)
+                .map_err(|e| (contract.path.clone(), format!("{}", e)))?;
+
+            debug!("Deployed {}", &contract.contract_identifier);
+            deployed.push(DeployedContract {
+                contract,
+                ast: parsed_ast,
+                analysis,
+            });
+        }
+
+        if still_pending.len() == num_pending {
+            // no progress
+            return Err(last_error.expect("BUG: no error for an undeployable contract"));
+        }
+        pending = still_pending;
+    }
+    Ok(deployed)
+}
+
+/// Run one of a project's test functions, and then roll back everything it did, so that each
+/// test starts from the freshly-deployed project state.
+/// Returns the test's result, and what it cost.
+fn run_project_test<C: ClarityStorage>(
+    header_db: &CLIHeadersDB,
+    marf: &mut C,
+    contract_identifier: &QualifiedContractIdentifier,
+    test_name: &str,
+    coverage: &mut Option<CoverageReporter>,
+) -> (Result<Value, Error>, ExecutionCost) {
+    let mainnet = header_db.is_mainnet();
+    let mut db = marf.get_clarity_db(header_db, &NULL_BURN_STATE_DB);
+    let cost_track = LimitedCostTracker::new(
+        mainnet,
+        if mainnet {
+            BLOCK_LIMIT_MAINNET_20.clone()
+        } else {
+            HELIUM_BLOCK_LIMIT_20.clone()
+        },
+        &mut db,
+        DEFAULT_CLI_EPOCH,
+    )
+    .unwrap();
+
+    // everything the test does happens within this (never-committed) transaction
+    db.begin();
+    let mut vm_env = OwnedEnvironment::new_cost_limited(mainnet, db, cost_track, DEFAULT_CLI_EPOCH);
+    if let Some(reporter) = coverage.take() {
+        vm_env.set_coverage_reporter(reporter);
+    }
+
+    let sender = PrincipalData::from(contract_identifier.issuer.clone());
+    let result = vm_env
+        .execute_transaction(sender, contract_identifier.clone(), test_name, &[])
+        .map(|(value, _, _)| value);
+    let cost = vm_env.get_cost_total();
+    *coverage = vm_env.take_coverage_reporter();
+
+    let (mut db, _) = vm_env
+        .destruct()
+        .expect("BUG: test did not return to the top-level context");
+    db.roll_back();
+
+    (result, cost)
+}
+
+/// Find a test contract's test functions: its public functions named `test-*`.
+fn find_project_tests(analysis: &ContractAnalysis) -> Vec<(String, bool)> {
+    analysis
+        .public_function_types
+        .iter()
+        .filter(|(name, _)| name.as_str().starts_with("test-"))
+        .map(|(name, function_type)| {
+            // tests can't take arguments
+            let takes_no_args = match function_type {
+                FunctionType::Fixed(FixedFunction { args, .. }) => args.len() == 0,
+                _ => false,
+            };
+            (name.to_string(), takes_no_args)
+        })
+        .collect()
+}
+
+/// Write the coverage of a project's (non-test) contracts to a directory, along with an lcov
+/// report of it.
+fn write_project_coverage(
+    coverage_dir: &str,
+    deployed: &[DeployedContract],
+    coverage: &CoverageReporter,
+) -> io::Result<()> {
+    fs::create_dir_all(coverage_dir)?;
+    let dir = PathBuf::from(coverage_dir);
+
+    let mut register_files = vec![];
+    for deployed_contract in deployed.iter().filter(|c| !c.contract.is_test) {
+        let contract_identifier = &deployed_contract.contract.contract_identifier;
+        let register_file = dir.join(format!("{}.clarcovref", &contract_identifier.name));
+        CoverageReporter::register_src_file(
+            contract_identifier,
+            &deployed_contract.contract.path,
+            &deployed_contract.ast,
+            &register_file,
+        )?;
+        register_files.push(register_file);
+    }
+
+    let coverage_file = dir.join("tests.clarcov");
+    coverage.to_file(&coverage_file)?;
+
+    let lcov_file = dir.join("lcov.info");
+    CoverageReporter::produce_lcov(
+        &lcov_file.to_string_lossy(),
+        &register_files,
+        &[coverage_file],
+    )
+}
+
 pub fn add_costs(result: &mut serde_json::Value, costs: bool, runtime: ExecutionCost) {
     if costs {
         result["costs"] = serde_json::to_value(runtime).unwrap();
     }
 }
 
+/// Where to write a cost profile's folded stacks, and which cost dimension to weight them by
+pub struct ProfileArgs {
+    folded_path: String,
+    dimension: String,
+}
+
+/// Consume `--profile FOLDED-FILE` and `--profile-dimension DIMENSION` from `argv`.
+fn consume_profile_args(argv: &mut Vec<String>) -> Option<ProfileArgs> {
+    let folded_path = match consume_arg(argv, &["--profile"], true) {
+        Ok(folded_path) => folded_path,
+        Err(_) => {
+            eprintln!("Expected argument for --profile");
+            panic_test!();
+        }
+    };
+    let dimension = match consume_arg(argv, &["--profile-dimension"], true) {
+        Ok(dimension) => dimension.unwrap_or("runtime".to_string()),
+        Err(_) => {
+            eprintln!("Expected argument for --profile-dimension");
+            panic_test!();
+        }
+    };
+    if !COST_DIMENSIONS.contains(&dimension.as_str()) {
+        eprintln!(
+            "Unknown cost dimension '{}'. Expected one of: {}",
+            dimension,
+            COST_DIMENSIONS.join(", ")
+        );
+        panic_test!();
+    }
+    folded_path.map(|folded_path| ProfileArgs {
+        folded_path,
+        dimension,
+    })
+}
+
+/// Write out the profile's folded stacks, and add its report to the result.
+pub fn add_profile(
+    result: &mut serde_json::Value,
+    profile_args: &Option<ProfileArgs>,
+    profiler: Option<CostProfiler>,
+) {
+    if let (Some(profile_args), Some(profiler)) = (profile_args, profiler) {
+        let folded = profiler
+            .to_folded(&profile_args.dimension)
+            .expect("BUG: unchecked cost dimension");
+        friendly_expect(
+            fs::write(&profile_args.folded_path, folded),
+            &format!("Failed to write {}", &profile_args.folded_path),
+        );
+        result["profile"] = serde_json::to_value(profiler.report()).unwrap();
+    }
+}
+
 pub fn add_assets(result: &mut serde_json::Value, assets: bool, asset_map: AssetMap) {
     if assets {
         result["assets"] = asset_map.to_json();
@@ -930,7 +1222,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
         "check" => {
             if args.len() < 2 {
                 eprintln!(
-                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--testnet] (vm-state.db)",
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--lint] [--testnet] (vm-state.db)",
                     invoked_by, args[0]
                 );
                 panic_test!();
@@ -965,6 +1257,12 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 false
             };
 
+            let lint = if let Ok(Some(_)) = consume_arg(&mut argv, &["--lint"], false) {
+                true
+            } else {
+                false
+            };
+
             // NOTE: ignored if we're using a DB
             let mut testnet_given = false;
             let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
@@ -1054,6 +1352,11 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 result["analysis"] =
                     serde_json::to_value(&build_contract_interface(&contract_analysis)).unwrap();
             }
+            if lint {
+                // lint warnings never fail the check
+                result["warnings"] =
+                    serde_json::to_value(&LintChecker::run(&contract_analysis)).unwrap();
+            }
             (0, Some(result))
         }
         "repl" => {
@@ -1066,15 +1369,26 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             let mut marf = MemoryBackingStore::new();
             let mut vm_env =
                 OwnedEnvironment::new_free(mainnet, marf.as_clarity_db(), DEFAULT_CLI_EPOCH);
-            let mut exec_env = vm_env.get_exec_environment(None);
             let mut analysis_marf = MemoryBackingStore::new();
 
             let contract_id = QualifiedContractIdentifier::transient();
 
+            let debugger = Rc::new(RefCell::new(Debugger::new(
+                Box::new(|| {
+                    let mut buffer = String::new();
+                    match io::stdin().read_line(&mut buffer) {
+                        Ok(0) | Err(_) => None,
+                        Ok(_) => Some(buffer),
+                    }
+                }),
+                Box::new(io::stdout()),
+            )));
+            vm_env.set_eval_hook(Box::new(debugger.clone()));
+
             let mut stdout = io::stdout();
 
             loop {
-                let content: String = {
+                let mut content: String = {
                     let mut buffer = String::new();
                     stdout.write(b"> ").unwrap_or_else(|e| {
                         panic!("Failed to write stdout prompt string:\n{}", e);
@@ -1083,6 +1397,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                         panic!("Failed to flush stdout prompt string:\n{}", e);
                     });
                     match io::stdin().read_line(&mut buffer) {
+                        Ok(0) => break,
                         Ok(_) => buffer,
                         Err(error) => {
                             eprintln!("Error reading from stdin:\n{}", error);
@@ -1091,6 +1406,95 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 };
 
+                if content.trim_start().starts_with("::") {
+                    let command_line = content.trim().to_string();
+                    let mut parts = command_line.splitn(2, char::is_whitespace);
+                    let command = parts.next().unwrap_or("");
+                    let rest = parts.next().unwrap_or("").trim();
+                    match command {
+                        "::deploy" => {
+                            let mut args = rest.split_whitespace();
+                            let (name, path) = match (args.next(), args.next()) {
+                                (Some(name), Some(path)) => (name, path),
+                                _ => {
+                                    println!("Usage: ::deploy CONTRACT-NAME FILE");
+                                    continue;
+                                }
+                            };
+                            let deploy_id = match QualifiedContractIdentifier::local(name) {
+                                Ok(id) => id,
+                                Err(error) => {
+                                    println!("Invalid contract name:\n{}", error);
+                                    continue;
+                                }
+                            };
+                            let source = match fs::read_to_string(path) {
+                                Ok(source) => source,
+                                Err(error) => {
+                                    println!("Failed to read {}:\n{}", path, error);
+                                    continue;
+                                }
+                            };
+                            let mut ast = match parse(&deploy_id, &source) {
+                                Ok(val) => val,
+                                Err(error) => {
+                                    println!("Parse error:\n{}", error);
+                                    continue;
+                                }
+                            };
+                            if let Err((error, _)) =
+                                run_analysis_free(&deploy_id, &mut ast, &mut analysis_marf, true)
+                            {
+                                println!("Type check error:\n{}", error);
+                                continue;
+                            }
+                            // the analysis store only finds a contract's analysis if the
+                            //  contract exists in it, too.
+                            let mut analysis_env = OwnedEnvironment::new_free(
+                                mainnet,
+                                analysis_marf.as_clarity_db(),
+                                DEFAULT_CLI_EPOCH,
+                            );
+                            if let Err(error) =
+                                analysis_env.initialize_contract(deploy_id.clone(), &source)
+                            {
+                                println!("Execution error:\n{}", error);
+                                continue;
+                            }
+                            match vm_env.initialize_contract(deploy_id.clone(), &source) {
+                                Ok(_) => println!("Deployed {}", deploy_id),
+                                Err(error) => println!("Execution error:\n{}", error),
+                            }
+                        }
+                        "::break" => match debugger.borrow_mut().add_breakpoint_spec(rest) {
+                            Ok(id) => println!("Set breakpoint #{}", id),
+                            Err(error) => println!("{}", error),
+                        },
+                        "::delete" => match rest.parse::<usize>() {
+                            Ok(id) => {
+                                if !debugger.borrow_mut().delete_breakpoint(id) {
+                                    println!("No such breakpoint #{}", id);
+                                }
+                            }
+                            Err(_) => println!("Usage: ::delete ID"),
+                        },
+                        "::breakpoints" => {
+                            for breakpoint in debugger.borrow().breakpoints().iter() {
+                                println!("  {}", breakpoint);
+                            }
+                        }
+                        "::debug" => {
+                            // evaluate the expression, pausing before its first step
+                            debugger.borrow_mut().step_into_next();
+                            content = rest.to_string();
+                        }
+                        _ => println!("{}", REPL_HELP),
+                    }
+                    if command != "::debug" {
+                        continue;
+                    }
+                }
+
                 let mut ast = match parse(&contract_id, &content) {
                     Ok(val) => val,
                     Err(error) => {
@@ -1099,7 +1503,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 };
 
-                match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, true) {
+                match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, false) {
                     Ok(_) => (),
                     Err((error, _)) => {
                         println!("Type check error:\n{}", error);
@@ -1107,8 +1511,8 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 }
 
-                let eval_result = match exec_env.eval_raw(&content) {
-                    Ok(val) => val,
+                let eval_result = match vm_env.eval_raw(&content) {
+                    Ok((val, _, _)) => val,
                     Err(error) => {
                         println!("Execution error:\n{}", error);
                         continue;
@@ -1117,6 +1521,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
                 println!("{}", eval_result);
             }
+            (0, None)
         }
         "eval_raw" => {
             let content: String = {
@@ -1175,6 +1580,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let profile_args = consume_profile_args(&mut argv);
 
             let evalInput = get_eval_input(invoked_by, &argv);
             let vm_filename = if argv.len() == 3 { &argv[2] } else { &argv[3] };
@@ -1188,15 +1594,19 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost = with_env_costs(mainnet, &header_db, &mut marf, |vm_env| {
-                    vm_env
+                    if profile_args.is_some() {
+                        vm_env.set_cost_profiler(CostProfiler::new());
+                    }
+                    let result = vm_env
                         .get_exec_environment(None)
-                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content)
+                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content);
+                    (result, vm_env.take_cost_profiler())
                 });
                 (header_db, marf, result_and_cost)
             });
 
             match result_and_cost {
-                (Ok(result), cost) => {
+                ((Ok(result), profiler), cost) => {
                     let mut result_json = json!({
                         "output": serde_json::to_value(&result).unwrap(),
                         "success": true,
@@ -1204,10 +1614,11 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
                     add_serialized_output(&mut result_json, result);
                     add_costs(&mut result_json, costs, cost);
+                    add_profile(&mut result_json, &profile_args, profiler);
 
                     (0, Some(result_json))
                 }
-                (Err(error), cost) => {
+                ((Err(error), profiler), cost) => {
                     let mut result_json = json!({
                         "error": {
                             "runtime": serde_json::to_value(&format!("{}", error)).unwrap()
@@ -1216,6 +1627,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     });
 
                     add_costs(&mut result_json, costs, cost);
+                    add_profile(&mut result_json, &profile_args, profiler);
 
                     (1, Some(result_json))
                 }
@@ -1229,6 +1641,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let profile_args = consume_profile_args(&mut argv);
 
             let evalInput = get_eval_input(invoked_by, &argv);
             let vm_filename = if argv.len() == 3 { &argv[2] } else { &argv[3] };
@@ -1241,15 +1654,19 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             let mainnet = header_db.is_mainnet();
             let result_and_cost = at_chaintip(vm_filename, marf_kv, |mut marf| {
                 let result_and_cost = with_env_costs(mainnet, &header_db, &mut marf, |vm_env| {
-                    vm_env
+                    if profile_args.is_some() {
+                        vm_env.set_cost_profiler(CostProfiler::new());
+                    }
+                    let result = vm_env
                         .get_exec_environment(None)
-                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content)
+                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content);
+                    (result, vm_env.take_cost_profiler())
                 });
                 (marf, result_and_cost)
             });
 
             match result_and_cost {
-                (Ok(result), cost) => {
+                ((Ok(result), profiler), cost) => {
                     let mut result_json = json!({
                         "output": serde_json::to_value(&result).unwrap(),
                         "success": true,
@@ -1257,10 +1674,11 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
                     add_serialized_output(&mut result_json, result);
                     add_costs(&mut result_json, costs, cost);
+                    add_profile(&mut result_json, &profile_args, profiler);
 
                     (0, Some(result_json))
                 }
-                (Err(error), cost) => {
+                ((Err(error), profiler), cost) => {
                     let mut result_json = json!({
                         "error": {
                             "runtime": serde_json::to_value(&format!("{}", error)).unwrap()
@@ -1269,6 +1687,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     });
 
                     add_costs(&mut result_json, costs, cost);
+                    add_profile(&mut result_json, &profile_args, profiler);
 
                     (1, Some(result_json))
                 }
@@ -1469,9 +1888,11 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let profile_args = consume_profile_args(&mut argv);
 
             if argv.len() < 5 {
-                eprintln!("Usage: {} {} [--costs] [--assets] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
+                eprintln!("Usage: {} {} [--costs] [--assets] [--profile FOLDED-FILE [--profile-dimension DIMENSION]] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
+                eprintln!("   If --profile is given, a cost profile is added to the output, and the cost of each call stack is written to FOLDED-FILE in the folded-stacks format of flamegraph.pl, weighted by DIMENSION (default: runtime).");
                 panic_test!();
             }
 
@@ -1517,13 +1938,22 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost = with_env_costs(mainnet, &header_db, &mut marf, |vm_env| {
-                    vm_env.execute_transaction(sender, contract_identifier, &tx_name, &arguments)
+                    if profile_args.is_some() {
+                        vm_env.set_cost_profiler(CostProfiler::new());
+                    }
+                    let result = vm_env.execute_transaction(
+                        sender,
+                        contract_identifier,
+                        &tx_name,
+                        &arguments,
+                    );
+                    (result, vm_env.take_cost_profiler())
                 });
                 (header_db, marf, result_and_cost)
             });
 
             match result_and_cost {
-                (Ok((x, asset_map, events)), cost) => {
+                ((Ok((x, asset_map, events)), profiler), cost) => {
                     if let Value::Response(data) = x {
                         if data.committed {
                             let mut result = json!({
@@ -1534,6 +1964,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
                             add_serialized_output(&mut result, *data.data);
                             add_costs(&mut result, costs, cost);
+                            add_profile(&mut result, &profile_args, profiler);
                             add_assets(&mut result, assets, asset_map);
 
                             let events_json: Vec<_> = events
@@ -1551,6 +1982,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                             });
 
                             add_costs(&mut result, costs, cost);
+                            add_profile(&mut result, &profile_args, profiler);
                             add_serialized_output(&mut result, *data.data);
                             add_assets(&mut result, assets, asset_map);
 
@@ -1567,7 +1999,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                         (1, Some(result))
                     }
                 }
-                (Err(error), _) => {
+                ((Err(error), _), _) => {
                     let result = json!({
                         "error": {
                             "runtime": "Transaction execution error.",
@@ -1579,6 +2011,234 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 }
             }
         }
+        "test" => {
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
+                false
+            } else {
+                true
+            };
+            let costs = if let Ok(Some(_)) = consume_arg(&mut argv, &["--costs"], false) {
+                true
+            } else {
+                false
+            };
+            let coverage_dir = if let Ok(optarg) = consume_arg(&mut argv, &["--coverage"], true) {
+                optarg
+            } else {
+                eprintln!("Expected argument for --coverage");
+                panic_test!();
+            };
+
+            if argv.len() != 2 {
+                eprintln!(
+                    "Usage: {} {} [--testnet] [--costs] [--coverage COVERAGE-DIR] [project-dir]",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   The contracts in project-dir/contracts/*.clar are deployed, followed by the test contracts in project-dir/tests/*.clar.");
+                eprintln!("   Every public function of a test contract named `test-*` is a test. It takes no arguments, and passes if it returns (ok ...).");
+                eprintln!("   If --coverage is given, the coverage data and an lcov report (lcov.info) are written to COVERAGE-DIR.");
+                panic_test!();
+            }
+
+            let deployer = friendly_expect(
+                PrincipalData::parse_standard_principal(CLI_TEST_DEPLOYER),
+                "BUG: failed to parse test deployer address",
+            );
+            let project_dir = PathBuf::from(&argv[1]);
+            let mut contracts = friendly_expect(
+                load_project_contracts(&project_dir.join("contracts"), &deployer, false),
+                "Failed to load contracts",
+            );
+            contracts.extend(friendly_expect(
+                load_project_contracts(&project_dir.join("tests"), &deployer, true),
+                "Failed to load test contracts",
+            ));
+
+            let header_db = CLIHeadersDB::new_memory(mainnet);
+            let mut marf = MemoryBackingStore::new();
+            install_boot_code(&header_db, &mut marf);
+
+            let deployed = match deploy_project_contracts(&header_db, &mut marf, contracts) {
+                Ok(deployed) => deployed,
+                Err((path, error)) => {
+                    let result = json!({
+                        "message": "Failed to deploy project.",
+                        "error": {
+                            "contract": path,
+                            "initialization": error,
+                        }
+                    });
+                    return (1, Some(result));
+                }
+            };
+
+            let mut coverage = Some(CoverageReporter::new());
+            let mut tests_json = vec![];
+            let mut num_passed = 0;
+            let mut num_failed = 0;
+
+            for deployed_contract in deployed.iter().filter(|c| c.contract.is_test) {
+                let contract_identifier = &deployed_contract.contract.contract_identifier;
+                for (test_name, takes_no_args) in find_project_tests(&deployed_contract.analysis) {
+                    let mut test_result = json!({
+                        "contract": contract_identifier.to_string(),
+                        "test": test_name,
+                    });
+
+                    let success = if !takes_no_args {
+                        test_result["error"] = json!({
+                            "runtime": "Test functions cannot take arguments."
+                        });
+                        false
+                    } else {
+                        let (result, cost) = run_project_test(
+                            &header_db,
+                            &mut marf,
+                            contract_identifier,
+                            &test_name,
+                            &mut coverage,
+                        );
+                        add_costs(&mut test_result, costs, cost);
+
+                        match result {
+                            Ok(Value::Response(data)) => {
+                                test_result["output"] = serde_json::to_value(&data.data).unwrap();
+                                data.committed
+                            }
+                            Ok(x) => {
+                                test_result["error"] = json!({
+                                    "runtime": "Expected a ResponseType result from test.",
+                                    "output": serde_json::to_value(&x).unwrap()
+                                });
+                                false
+                            }
+                            Err(error) => {
+                                test_result["error"] = json!({
+                                    "runtime": serde_json::to_value(&format!("{}", error)).unwrap()
+                                });
+                                false
+                            }
+                        }
+                    };
+
+                    if success {
+                        num_passed += 1;
+                    } else {
+                        num_failed += 1;
+                    }
+                    test_result["success"] = json!(success);
+                    tests_json.push(test_result);
+                }
+            }
+
+            let coverage = coverage.unwrap_or_else(CoverageReporter::new);
+            let mut coverage_json = json!({});
+            for deployed_contract in deployed.iter().filter(|c| !c.contract.is_test) {
+                let contract_identifier = &deployed_contract.contract.contract_identifier;
+                let (executed_lines, executable_lines) =
+                    coverage.line_coverage(contract_identifier, &deployed_contract.ast);
+                coverage_json[contract_identifier.to_string()] = json!({
+                    "executed_lines": executed_lines,
+                    "executable_lines": executable_lines,
+                });
+            }
+
+            if let Some(coverage_dir) = coverage_dir {
+                friendly_expect(
+                    write_project_coverage(&coverage_dir, &deployed, &coverage),
+                    "Failed to write coverage report",
+                );
+            }
+
+            let result = json!({
+                "message": format!("{} passed, {} failed.", num_passed, num_failed),
+                "passed": num_passed,
+                "failed": num_failed,
+                "tests": tests_json,
+                "coverage": coverage_json,
+            });
+            (if num_failed == 0 { 0 } else { 1 }, Some(result))
+        }
+        #[cfg(feature = "developer-mode")]
+        "fmt" => {
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let check = if let Ok(Some(_)) = consume_arg(&mut argv, &["--check"], false) {
+                true
+            } else {
+                false
+            };
+
+            if argv.len() < 2 || (argv.len() > 2 && argv[1..].iter().any(|path| path == "-")) {
+                eprintln!(
+                    "Usage: {} {} [--check] [program-file.clar...]",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Each file is rewritten with its formatted source. If the file is `-`, the program is read from stdin and its formatted source is written to stdout.");
+                eprintln!("   If --check is given, nothing is written, and the command fails if any file is not formatted.");
+                panic_test!();
+            }
+
+            let mut unformatted = vec![];
+            for path in argv[1..].iter() {
+                let content = if path == "-" {
+                    let mut buffer = String::new();
+                    friendly_expect(
+                        io::stdin().read_to_string(&mut buffer),
+                        "Error reading from stdin.",
+                    );
+                    buffer
+                } else {
+                    friendly_expect(
+                        fs::read_to_string(path),
+                        &format!("Error reading file: {}", path),
+                    )
+                };
+
+                let formatted = match formatter::format_source(&content) {
+                    Ok(formatted) => formatted,
+                    Err(error) => {
+                        let result = json!({
+                            "message": "Failed to parse program.",
+                            "error": {
+                                "file": path,
+                                "parse": error.to_string(),
+                            }
+                        });
+                        return (1, Some(result));
+                    }
+                };
+
+                if path == "-" && !check {
+                    print!("{}", formatted);
+                    return (0, None);
+                }
+                if formatted == content {
+                    continue;
+                }
+                if !check {
+                    friendly_expect(
+                        fs::write(path, &formatted),
+                        &format!("Error writing file: {}", path),
+                    );
+                }
+                unformatted.push(path.clone());
+            }
+
+            if check {
+                let result = json!({
+                    "message": format!("{} file(s) not formatted.", unformatted.len()),
+                    "unformatted": unformatted,
+                });
+                (if unformatted.len() == 0 { 0 } else { 1 }, Some(result))
+            } else {
+                let result = json!({
+                    "message": format!("{} file(s) formatted.", unformatted.len()),
+                    "formatted": unformatted,
+                });
+                (0, Some(result))
+            }
+        }
         _ => {
             print_usage(invoked_by);
             (1, None)
@@ -1800,6 +2460,24 @@ mod test {
         assert!(result["costs"] != json!(null));
         assert!(result["assets"] == json!(null));
 
+        eprintln!("check names with lint");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--lint".to_string(),
+                "sample-contracts/names.clar".to_string(),
+                db_name.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert!(result["message"].as_str().unwrap().len() > 0);
+        assert!(result["warnings"].is_array());
+
         eprintln!("launch names with costs and assets");
         let invoked = invoke_command(
             "test",
@@ -1898,6 +2576,32 @@ mod test {
         );
         assert!(result["costs"] != json!(null));
 
+        eprintln!("eval tokens with profile");
+        let folded_path = format!("{}.folded", &db_name);
+        let invoked = invoke_command(
+            "test",
+            &[
+                "eval".to_string(),
+                "--costs".to_string(),
+                "--profile".to_string(),
+                folded_path.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens-mint.clar".to_string(),
+                db_name.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert_eq!(result["profile"]["total"], result["costs"]);
+        assert!(result["profile"]["functions"].as_array().unwrap().len() > 0);
+        let folded = fs::read_to_string(&folded_path).unwrap();
+        assert!(folded
+            .lines()
+            .any(|line| line.starts_with("S1G2081040G2081040G2081040G208105NK8PE5.tokens:")));
+
         eprintln!("eval_at_chaintip tokens");
         let invoked = invoke_command(
             "test",
@@ -2047,4 +2751,156 @@ mod test {
                 })
         );
     }
+
+    #[test]
+    fn test_project_tests() {
+        let project_dir = format!("/tmp/clarity-project-{}", rand::thread_rng().gen::<i32>());
+        let coverage_dir = format!("{}/coverage", &project_dir);
+        fs::create_dir_all(format!("{}/contracts", &project_dir)).unwrap();
+        fs::create_dir_all(format!("{}/tests", &project_dir)).unwrap();
+
+        fs::write(
+            format!("{}/contracts/counter.clar", &project_dir),
+            "(define-data-var counter uint u0)
+             (define-public (increment)
+                (begin
+                    (var-set counter (+ (var-get counter) u1))
+                    (ok (var-get counter))))
+             (define-read-only (get-counter) (var-get counter))",
+        )
+        .unwrap();
+
+        // deployed before counter.clar, but depends on it
+        fs::write(
+            format!("{}/contracts/adder.clar", &project_dir),
+            "(define-public (add-two)
+                (begin
+                    (unwrap-panic (contract-call? .counter increment))
+                    (contract-call? .counter increment)))",
+        )
+        .unwrap();
+
+        fs::write(
+            format!("{}/tests/counter-test.clar", &project_dir),
+            "(define-public (test-increment)
+                (begin
+                    (unwrap-panic (contract-call? .counter increment))
+                    (asserts! (is-eq (contract-call? .counter get-counter) u1) (err u1))
+                    (ok true)))
+             ;; only passes if test-increment's changes were rolled back
+             (define-public (test-increment-again)
+                (begin
+                    (unwrap-panic (contract-call? .counter increment))
+                    (asserts! (is-eq (contract-call? .counter get-counter) u1) (err u1))
+                    (ok true)))
+             (define-public (test-add-two)
+                (begin
+                    (asserts! (is-eq (unwrap-panic (contract-call? .adder add-two)) u2) (err u2))
+                    (ok true)))
+             (define-public (test-fails)
+                (if (is-eq u1 u1) (err u3) (ok true)))
+             (define-public (test-with-args (x uint))
+                (ok x))
+             (define-public (not-a-test)
+                (ok true))",
+        )
+        .unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "test".to_string(),
+                "--coverage".to_string(),
+                coverage_dir.clone(),
+                project_dir.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        eprintln!("{}", &result);
+
+        assert_eq!(exit, 1);
+        assert_eq!(result["passed"].as_u64().unwrap(), 3);
+        assert_eq!(result["failed"].as_u64().unwrap(), 2);
+
+        let tests = result["tests"].as_array().unwrap();
+        assert_eq!(tests.len(), 5);
+        for test in tests.iter() {
+            let test_name = test["test"].as_str().unwrap();
+            let expect_success = match test_name {
+                "test-increment" | "test-increment-again" | "test-add-two" => true,
+                "test-fails" | "test-with-args" => false,
+                _ => panic!("Unexpected test {}", test_name),
+            };
+            assert_eq!(test["success"].as_bool().unwrap(), expect_success);
+        }
+
+        let counter_coverage =
+            &result["coverage"]["S1G2081040G2081040G2081040G208105NK8PE5.counter"];
+        assert!(counter_coverage["executed_lines"].as_u64().unwrap() > 0);
+        assert!(counter_coverage["executable_lines"].as_u64().unwrap() > 0);
+
+        assert!(fs::metadata(format!("{}/lcov.info", &coverage_dir)).is_ok());
+    }
+
+    #[test]
+    fn test_project_tests_undeployable() {
+        let project_dir = format!("/tmp/clarity-project-{}", rand::thread_rng().gen::<i32>());
+        fs::create_dir_all(format!("{}/contracts", &project_dir)).unwrap();
+
+        fs::write(
+            format!("{}/contracts/caller.clar", &project_dir),
+            "(define-public (call) (contract-call? .missing foo))",
+        )
+        .unwrap();
+
+        let invoked = invoke_command("test", &["test".to_string(), project_dir.clone()]);
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        assert!(result["error"]["contract"]
+            .as_str()
+            .unwrap()
+            .ends_with("caller.clar"));
+    }
+
+    #[test]
+    fn test_fmt() {
+        let clar_name = format!("/tmp/test-fmt_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &clar_name,
+            "(define-read-only (get-one)   ;; one\n    (ok   1))",
+        )
+        .unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &["fmt".to_string(), "--check".to_string(), clar_name.clone()],
+        );
+        assert_eq!(invoked.0, 1);
+        assert_eq!(invoked.1.unwrap()["unformatted"], json!([clar_name]));
+
+        let invoked = invoke_command("test", &["fmt".to_string(), clar_name.clone()]);
+        assert_eq!(invoked.0, 0);
+        assert_eq!(invoked.1.unwrap()["formatted"], json!([clar_name]));
+        assert_eq!(
+            fs::read_to_string(&clar_name).unwrap(),
+            "(define-read-only (get-one) ;; one\n  (ok 1))\n"
+        );
+
+        let invoked = invoke_command(
+            "test",
+            &["fmt".to_string(), "--check".to_string(), clar_name.clone()],
+        );
+        assert_eq!(invoked.0, 0);
+        assert_eq!(invoked.1.unwrap()["unformatted"], json!([]));
+
+        fs::write(&clar_name, "(define-read-only (get-one)").unwrap();
+        let invoked = invoke_command("test", &["fmt".to_string(), clar_name.clone()]);
+        assert_eq!(invoked.0, 1);
+        assert!(invoked.1.unwrap()["error"]["parse"].is_string());
+    }
 }
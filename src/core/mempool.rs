@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
+use std::collections::HashSet;
 use std::fs;
 use std::io::Read;
 use std::ops::Deref;
@@ -78,6 +79,12 @@ use crate::util::db::table_exists;
 pub const MEMPOOL_MAX_TRANSACTION_AGE: u64 = 256;
 pub const MAXIMUM_MEMPOOL_TX_CHAINING: u64 = 25;
 
+// default bounds on the mempool's size
+pub const MEMPOOL_DEFAULT_MAX_TX_COUNT: u64 = 100_000;
+pub const MEMPOOL_DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+// default minimum fee-rate increase, in percent, for a replace-by-fee
+pub const MEMPOOL_DEFAULT_REPLACE_BY_FEE_RATE_BUMP: u64 = 10;
+
 pub struct MemPoolAdmitter {
     cur_block: BlockHeaderHash,
     cur_consensus_hash: ConsensusHash,
@@ -116,6 +123,8 @@ pub enum MemPoolDropReason {
     REPLACE_BY_FEE,
     STALE_COLLECT,
     TOO_EXPENSIVE,
+    LOW_FEE_RATE_EVICT,
+    ADMIN_DROP,
 }
 
 pub struct ConsiderTransaction {
@@ -140,6 +149,8 @@ impl std::fmt::Display for MemPoolDropReason {
             MemPoolDropReason::TOO_EXPENSIVE => write!(f, "TooExpensive"),
             MemPoolDropReason::REPLACE_ACROSS_FORK => write!(f, "ReplaceAcrossFork"),
             MemPoolDropReason::REPLACE_BY_FEE => write!(f, "ReplaceByFee"),
+            MemPoolDropReason::LOW_FEE_RATE_EVICT => write!(f, "LowFeeRateEviction"),
+            MemPoolDropReason::ADMIN_DROP => write!(f, "AdminDrop"),
         }
     }
 }
@@ -209,6 +220,63 @@ impl MemPoolWalkSettings {
     }
 }
 
+/// Bounds on how big the mempool can get, and on what it takes to replace a transaction in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolLimits {
+    /// Maximum number of transactions held.  0 means unbounded.
+    pub max_tx_count: u64,
+    /// Maximum total size of the transactions held, in bytes.  0 means unbounded.
+    pub max_bytes: u64,
+    /// A transaction can only replace another one with the same nonce if its fee rate is higher
+    /// by at least this percentage.
+    pub replace_by_fee_rate_bump: u64,
+}
+
+impl MemPoolLimits {
+    pub fn default() -> MemPoolLimits {
+        MemPoolLimits {
+            max_tx_count: MEMPOOL_DEFAULT_MAX_TX_COUNT,
+            max_bytes: MEMPOOL_DEFAULT_MAX_BYTES,
+            replace_by_fee_rate_bump: MEMPOOL_DEFAULT_REPLACE_BY_FEE_RATE_BUMP,
+        }
+    }
+
+    /// Would a mempool with this many transactions and bytes be over the limits?
+    fn is_exceeded_by(&self, tx_count: u64, bytes: u64) -> bool {
+        (self.max_tx_count > 0 && tx_count > self.max_tx_count)
+            || (self.max_bytes > 0 && bytes > self.max_bytes)
+    }
+}
+
+/// How many eviction candidates to load from the mempool at a time
+const EVICTION_CANDIDATES_PAGE_SIZE: u64 = 128;
+
+/// A transaction that can be evicted from the mempool to make room for others
+struct EvictionCandidate {
+    txid: Txid,
+    origin_address: StacksAddress,
+    origin_nonce: u64,
+    len: u64,
+    fee_rate: f64,
+}
+
+impl FromRow<EvictionCandidate> for EvictionCandidate {
+    fn from_row<'a>(row: &'a Row) -> Result<EvictionCandidate, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let origin_address = StacksAddress::from_column(row, "origin_address")?;
+        let origin_nonce = u64::from_column(row, "origin_nonce")?;
+        let len = u64::from_column(row, "length")?;
+        let fee_rate: f64 = row.get("effective_fee_rate")?;
+        Ok(EvictionCandidate {
+            txid,
+            origin_address,
+            origin_nonce,
+            len,
+            fee_rate,
+        })
+    }
+}
+
 impl FromRow<Txid> for Txid {
     fn from_row<'a>(row: &'a Row) -> Result<Txid, db_error> {
         row.get(0).map_err(db_error::SqliteError)
@@ -329,17 +397,32 @@ const MEMPOOL_SCHEMA_2: &'static [&'static str] = &[
     "#,
 ];
 
+const MEMPOOL_SCHEMA_3: &'static [&'static str] = &[
+    r#"
+    -- the cost estimator's fee rate if there is one, or else the cost metric's fee rate for
+    -- the transaction's length, so the mempool can be ranked by fee rate
+    ALTER TABLE mempool ADD COLUMN effective_fee_rate REAL NOT NULL DEFAULT 0;
+    "#,
+    "CREATE INDEX by_effective_fee_rate ON mempool(effective_fee_rate);",
+    r#"
+    INSERT INTO schema_version (version) VALUES (3)
+    "#,
+];
+
 pub struct MemPoolDB {
     db: DBConn,
     path: String,
     admitter: MemPoolAdmitter,
     cost_estimator: Box<dyn CostEstimator>,
     metric: Box<dyn CostMetric>,
+    limits: MemPoolLimits,
 }
 
 pub struct MemPoolTx<'a> {
     tx: DBTx<'a>,
     admitter: &'a mut MemPoolAdmitter,
+    metric: &'a dyn CostMetric,
+    limits: &'a MemPoolLimits,
 }
 
 impl<'a> Deref for MemPoolTx<'a> {
@@ -356,8 +439,18 @@ impl<'a> DerefMut for MemPoolTx<'a> {
 }
 
 impl<'a> MemPoolTx<'a> {
-    pub fn new(tx: DBTx<'a>, admitter: &'a mut MemPoolAdmitter) -> MemPoolTx<'a> {
-        MemPoolTx { tx, admitter }
+    pub fn new(
+        tx: DBTx<'a>,
+        admitter: &'a mut MemPoolAdmitter,
+        metric: &'a dyn CostMetric,
+        limits: &'a MemPoolLimits,
+    ) -> MemPoolTx<'a> {
+        MemPoolTx {
+            tx,
+            admitter,
+            metric,
+            limits,
+        }
     }
 
     pub fn commit(self) -> Result<(), db_error> {
@@ -486,6 +579,9 @@ impl MemPoolDB {
         if version < 2 {
             MemPoolDB::apply_schema_2(&tx)?;
         }
+        if version < 3 {
+            MemPoolDB::apply_schema_3(&tx, metric.as_ref())?;
+        }
 
         tx.commit()?;
 
@@ -495,9 +591,20 @@ impl MemPoolDB {
             admitter,
             cost_estimator,
             metric,
+            limits: MemPoolLimits::default(),
         })
     }
 
+    /// Set the bounds on this mempool's size and on replace-by-fee.  They are enforced as new
+    /// transactions are submitted.
+    pub fn set_limits(&mut self, limits: MemPoolLimits) {
+        self.limits = limits;
+    }
+
+    pub fn get_limits(&self) -> &MemPoolLimits {
+        &self.limits
+    }
+
     fn get_schema_version(conn: &DBConn) -> Result<Option<i64>, db_error> {
         let is_versioned = table_exists(conn, "schema_version")?;
         if !is_versioned {
@@ -523,6 +630,29 @@ impl MemPoolDB {
         Ok(())
     }
 
+    fn apply_schema_3(tx: &Transaction, metric: &dyn CostMetric) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_3 {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        // fill in the fee rates of the transactions already in the mempool
+        let txs = query_rows::<MemPoolTxMetadata, _>(tx, "SELECT * FROM mempool", NO_PARAMS)?;
+        for md in txs.into_iter() {
+            let fee_rate = MemPoolDB::effective_fee_rate(
+                metric,
+                MemPoolDB::get_fee_rate_estimate(tx, &md.txid)?,
+                md.tx_fee,
+                md.len,
+            );
+            tx.execute(
+                "UPDATE mempool SET effective_fee_rate = ?1 WHERE txid = ?2",
+                rusqlite::params![fee_rate, &md.txid],
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub fn reset_last_known_nonces(&mut self) -> Result<(), db_error> {
         let sql =
             "UPDATE mempool SET last_known_origin_nonce = NULL, last_known_sponsor_nonce = NULL";
@@ -698,6 +828,10 @@ impl MemPoolDB {
                 "INSERT OR REPLACE INTO fee_estimates(txid, fee_rate) VALUES (?, ?)",
                 rusqlite::params![&txid, fee_rate_f64],
             )?;
+            sql_tx.execute(
+                "UPDATE mempool SET effective_fee_rate = ? WHERE txid = ?",
+                rusqlite::params![fee_rate_f64, &txid],
+            )?;
             updated += 1;
         }
 
@@ -811,7 +945,12 @@ impl MemPoolDB {
 
     pub fn tx_begin<'a>(&'a mut self) -> Result<MemPoolTx<'a>, db_error> {
         let tx = tx_begin_immediate(&mut self.db)?;
-        Ok(MemPoolTx::new(tx, &mut self.admitter))
+        Ok(MemPoolTx::new(
+            tx,
+            &mut self.admitter,
+            self.metric.as_ref(),
+            &self.limits,
+        ))
     }
 
     fn db_has_tx(conn: &DBConn, txid: &Txid) -> Result<bool, db_error> {
@@ -892,6 +1031,46 @@ impl MemPoolDB {
         Ok(rows)
     }
 
+    /// Get up to `max_txids` txids in the mempool, across all tips, highest fee rate first.
+    pub fn get_txids_by_fee_rate(conn: &DBConn, max_txids: u64) -> Result<Vec<Txid>, db_error> {
+        let sql = "SELECT txid FROM mempool ORDER BY effective_fee_rate DESC LIMIT ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(max_txids)?];
+        query_rows(conn, sql, args)
+    }
+
+    /// Find the transactions a peer is missing, highest fee rate first.  `is_known` tells
+    /// whether or not the peer already has the transaction with the given txid.  Only the
+    /// `max_scan` highest fee-rate transactions are considered.  At most `max_txs` transactions
+    /// are returned, and we stop once their total length reaches `max_bytes`.
+    pub fn find_missing_txs<F>(
+        conn: &DBConn,
+        is_known: F,
+        max_scan: u64,
+        max_txs: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<StacksTransaction>, db_error>
+    where
+        F: Fn(&Txid) -> bool,
+    {
+        let txids = MemPoolDB::get_txids_by_fee_rate(conn, max_scan)?;
+
+        let mut txs = vec![];
+        let mut num_bytes = 0;
+        for txid in txids.iter() {
+            if (txs.len() as u64) >= max_txs || num_bytes >= max_bytes {
+                break;
+            }
+            if is_known(txid) {
+                continue;
+            }
+            if let Some(tx_info) = MemPoolDB::get_tx(conn, txid)? {
+                num_bytes += tx_info.metadata.len;
+                txs.push(tx_info.tx);
+            }
+        }
+        Ok(txs)
+    }
+
     /// Get a transaction's metadata, given address and nonce, and whether the address is used as a sponsor or an origin.
     /// Faster than getting the MemPoolTxInfo, since no deserialization will be needed.
     /// Used to see if there exists a transaction with this info, so as to implement replace-by-fee
@@ -923,6 +1102,143 @@ impl MemPoolDB {
         query_row(conn, &sql, args)
     }
 
+    /// The fee rate used to rank a transaction for replacement and eviction.  This is the cost
+    /// estimator's fee rate if there is one; otherwise, it's the fee rate the cost metric gives to
+    /// the transaction's length alone.  The mempool stores it in its indexed
+    /// `effective_fee_rate` column.
+    fn effective_fee_rate(
+        metric: &dyn CostMetric,
+        fee_rate_estimate: Option<f64>,
+        tx_fee: u64,
+        len: u64,
+    ) -> f64 {
+        match fee_rate_estimate {
+            Some(fee_rate) => fee_rate,
+            None => tx_fee as f64 / cmp::max(metric.from_len(len), 1) as f64,
+        }
+    }
+
+    /// Get the fee rate the cost estimator assigned to a transaction, if it has one
+    fn get_fee_rate_estimate(conn: &DBConn, txid: &Txid) -> Result<Option<f64>, db_error> {
+        let fee_rate: Option<Option<f64>> = conn
+            .query_row(
+                "SELECT fee_rate FROM fee_estimates WHERE txid = ?1",
+                &[txid as &dyn ToSql],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(fee_rate.unwrap_or(None))
+    }
+
+    /// Get the fee rate a transaction in the mempool is ranked by
+    fn get_effective_fee_rate(conn: &DBConn, txid: &Txid) -> Result<Option<f64>, db_error> {
+        let fee_rate = conn
+            .query_row(
+                "SELECT effective_fee_rate FROM mempool WHERE txid = ?1",
+                &[txid as &dyn ToSql],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(fee_rate)
+    }
+
+    /// Find the lowest fee-rate transactions that must be evicted for a new transaction with the
+    /// given origin, length and fee rate to fit within the mempool's limits.  Evicting a
+    /// transaction also evicts its origin's higher-nonce transactions, since they could no longer
+    /// be mined.  `replaced_tx` is the transaction the new one replaces, if any.
+    /// Fails with MemPoolFull if the new transaction would have to evict a transaction that pays
+    /// at least its fee rate, or one of its own origin's lower-nonce transactions.
+    fn find_evictions(
+        tx: &MemPoolTx,
+        origin_address: &StacksAddress,
+        origin_nonce: u64,
+        len: u64,
+        fee_rate: f64,
+        replaced_tx: Option<&MemPoolTxMetadata>,
+    ) -> Result<Vec<Txid>, MemPoolRejection> {
+        let (mut tx_count, mut bytes) = query_row::<(u64, u64), _>(
+            tx,
+            "SELECT COUNT(*), IFNULL(SUM(length), 0) FROM mempool",
+            NO_PARAMS,
+        )?
+        .unwrap_or((0, 0));
+
+        let replaced_txid = replaced_tx.map(|replaced_tx| &replaced_tx.txid);
+        if let Some(replaced_tx) = replaced_tx {
+            tx_count = tx_count.saturating_sub(1);
+            bytes = bytes.saturating_sub(replaced_tx.len);
+        }
+        tx_count += 1;
+        bytes += len;
+        if !tx.limits.is_exceeded_by(tx_count, bytes) {
+            return Ok(vec![]);
+        }
+
+        let page_sql = "SELECT txid, origin_address, origin_nonce, length, effective_fee_rate \
+                        FROM mempool ORDER BY effective_fee_rate ASC LIMIT ?1 OFFSET ?2";
+        let descendants_sql = "SELECT txid, origin_address, origin_nonce, length, \
+                               effective_fee_rate FROM mempool \
+                               WHERE origin_address = ?1 AND origin_nonce > ?2";
+
+        let mut evicted = vec![];
+        let mut evicted_set = HashSet::new();
+        let mut offset = 0;
+        while tx.limits.is_exceeded_by(tx_count, bytes) {
+            let args: &[&dyn ToSql] = &[
+                &u64_to_sql(EVICTION_CANDIDATES_PAGE_SIZE)?,
+                &u64_to_sql(offset)?,
+            ];
+            let candidates = query_rows::<EvictionCandidate, _>(tx, page_sql, args)?;
+            if candidates.len() == 0 {
+                break;
+            }
+            offset += candidates.len() as u64;
+
+            for candidate in candidates.into_iter() {
+                if !tx.limits.is_exceeded_by(tx_count, bytes) {
+                    break;
+                }
+                if Some(&candidate.txid) == replaced_txid || evicted_set.contains(&candidate.txid) {
+                    continue;
+                }
+                if candidate.fee_rate >= fee_rate {
+                    debug!(
+                        "Mempool is full of transactions that pay at least {}",
+                        fee_rate
+                    );
+                    return Err(MemPoolRejection::MemPoolFull);
+                }
+                if candidate.origin_address == *origin_address
+                    && candidate.origin_nonce < origin_nonce
+                {
+                    // evicting this would leave the new transaction unmineable
+                    return Err(MemPoolRejection::MemPoolFull);
+                }
+
+                let args: &[&dyn ToSql] = &[
+                    &candidate.origin_address.to_string(),
+                    &u64_to_sql(candidate.origin_nonce)?,
+                ];
+                let descendants = query_rows::<EvictionCandidate, _>(tx, descendants_sql, args)?;
+                for evictee in Some(candidate).into_iter().chain(descendants.into_iter()) {
+                    if Some(&evictee.txid) == replaced_txid || evicted_set.contains(&evictee.txid) {
+                        continue;
+                    }
+                    tx_count -= 1;
+                    bytes -= evictee.len;
+                    evicted_set.insert(evictee.txid.clone());
+                    evicted.push(evictee.txid);
+                }
+            }
+        }
+
+        if tx.limits.is_exceeded_by(tx_count, bytes) {
+            // the new transaction alone is too big
+            return Err(MemPoolRejection::MemPoolFull);
+        }
+        Ok(evicted)
+    }
+
     fn are_blocks_in_same_fork(
         chainstate: &mut StacksChainState,
         first_consensus_hash: &ConsensusHash,
@@ -957,7 +1273,8 @@ impl MemPoolDB {
     }
 
     /// Add a transaction to the mempool.  If it already exists, then replace it if the given fee
-    /// is higher than the one that's already there.
+    /// is higher than the one that's already there, and its fee rate is sufficiently higher as
+    /// well.  If the mempool is full, evict the lowest fee-rate transactions to make room.
     /// Carry out the mempool admission test before adding.
     /// Don't call directly; use submit()
     fn try_add_tx(
@@ -968,6 +1285,7 @@ impl MemPoolDB {
         txid: Txid,
         tx_bytes: Vec<u8>,
         tx_fee: u64,
+        fee_rate_estimate: Option<f64>,
         height: u64,
         origin_address: &StacksAddress,
         origin_nonce: u64,
@@ -976,6 +1294,7 @@ impl MemPoolDB {
         event_observer: Option<&dyn MemPoolEventDispatcher>,
     ) -> Result<(), MemPoolRejection> {
         let length = tx_bytes.len() as u64;
+        let fee_rate = MemPoolDB::effective_fee_rate(tx.metric, fee_rate_estimate, tx_fee, length);
 
         // do we already have txs with either the same origin nonce or sponsor nonce ?
         let prior_tx = {
@@ -994,11 +1313,25 @@ impl MemPoolDB {
 
         // if so, is this a replace-by-fee? or a replace-in-chain-tip?
         let add_tx = if let Some(ref prior_tx) = prior_tx {
-            if tx_fee > prior_tx.tx_fee {
+            let prior_fee_rate = MemPoolDB::get_effective_fee_rate(tx, &prior_tx.txid)?
+                .unwrap_or_else(|| {
+                    MemPoolDB::effective_fee_rate(tx.metric, None, prior_tx.tx_fee, prior_tx.len)
+                });
+            let min_fee_rate =
+                prior_fee_rate * (100 + tx.limits.replace_by_fee_rate_bump) as f64 / 100.0;
+
+            if tx_fee > prior_tx.tx_fee && fee_rate >= min_fee_rate {
                 // is this a replace-by-fee ?
                 debug!(
-                    "Can replace {} with {} for {},{} by fee ({} < {})",
-                    &prior_tx.txid, &txid, origin_address, origin_nonce, &prior_tx.tx_fee, &tx_fee
+                    "Can replace {} with {} for {},{} by fee ({} < {}, fee rate {} < {})",
+                    &prior_tx.txid,
+                    &txid,
+                    origin_address,
+                    origin_nonce,
+                    &prior_tx.tx_fee,
+                    &tx_fee,
+                    prior_fee_rate,
+                    fee_rate
                 );
                 replace_reason = MemPoolDropReason::REPLACE_BY_FEE;
                 true
@@ -1017,7 +1350,7 @@ impl MemPoolDB {
                 replace_reason = MemPoolDropReason::REPLACE_ACROSS_FORK;
                 true
             } else {
-                // there's a >= fee tx in this fork, cannot add
+                // there's a >= fee (or not much lower fee rate) tx in this fork, cannot add
                 info!("TX conflicts with sponsor/origin nonce in same fork with >= fee";
                       "new_txid" => %txid, 
                       "old_txid" => %prior_tx.txid,
@@ -1026,7 +1359,9 @@ impl MemPoolDB {
                       "sponsor_addr" => %sponsor_address,
                       "sponsor_nonce" => sponsor_nonce,
                       "new_fee" => tx_fee,
-                      "old_fee" => prior_tx.tx_fee);
+                      "old_fee" => prior_tx.tx_fee,
+                      "new_fee_rate" => fee_rate,
+                      "min_fee_rate" => min_fee_rate);
                 false
             }
         } else {
@@ -1038,6 +1373,27 @@ impl MemPoolDB {
             return Err(MemPoolRejection::ConflictingNonceInMempool);
         }
 
+        // make room, if need be
+        let evicted_txids = MemPoolDB::find_evictions(
+            tx,
+            origin_address,
+            origin_nonce,
+            length,
+            fee_rate,
+            prior_tx.as_ref(),
+        )?;
+        for evicted_txid in evicted_txids.iter() {
+            debug!(
+                "Evict {} from the mempool to make room for {}",
+                evicted_txid, &txid
+            );
+            tx.execute(
+                "DELETE FROM mempool WHERE txid = ?1",
+                &[evicted_txid as &dyn ToSql],
+            )
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+        }
+
         let sql = "INSERT OR REPLACE INTO mempool (
             txid,
             origin_address,
@@ -1050,8 +1406,9 @@ impl MemPoolDB {
             block_header_hash,
             height,
             accept_time,
-            tx)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)";
+            tx,
+            effective_fee_rate)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)";
 
         let args: &[&dyn ToSql] = &[
             &txid,
@@ -1066,6 +1423,7 @@ impl MemPoolDB {
             &u64_to_sql(height)?,
             &u64_to_sql(get_epoch_time_secs())?,
             &tx_bytes,
+            &fee_rate,
         ];
 
         tx.execute(sql, args)
@@ -1076,6 +1434,14 @@ impl MemPoolDB {
             event_observer.mempool_txs_dropped(vec![prior_tx.txid], replace_reason);
         };
 
+        // ...and if any were evicted to make room
+        if let Some(event_observer) = event_observer {
+            if evicted_txids.len() > 0 {
+                event_observer
+                    .mempool_txs_dropped(evicted_txids, MemPoolDropReason::LOW_FEE_RATE_EVICT);
+            }
+        }
+
         Ok(())
     }
 
@@ -1206,6 +1572,7 @@ impl MemPoolDB {
             txid.clone(),
             tx_data,
             tx_fee,
+            fee_rate_estimate,
             height,
             &origin_address,
             origin_nonce,
@@ -1366,6 +1733,7 @@ impl MemPoolDB {
 mod tests {
     use address::AddressHashMode;
     use burnchains::Address;
+    use burnchains::Txid;
     use chainstate::burn::ConsensusHash;
     use chainstate::stacks::db::test::chainstate_path;
     use chainstate::stacks::db::test::instantiate_chainstate;
@@ -1410,7 +1778,7 @@ mod tests {
         chainstate::stacks::db::StacksHeaderInfo, util::vrf::VRFProof, vm::costs::ExecutionCost,
     };
 
-    use super::MemPoolDB;
+    use super::{MemPoolDB, MemPoolLimits, MemPoolTx};
 
     const FOO_CONTRACT: &'static str = "(define-public (foo) (ok 1))
                                         (define-public (bar (x uint)) (ok x))";
@@ -1579,6 +1947,7 @@ mod tests {
                 txid,
                 tx_bytes,
                 tx_fee,
+                None,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -1768,6 +2137,7 @@ mod tests {
             txid,
             tx_bytes,
             tx_fee,
+            None,
             height,
             &origin_address,
             origin_nonce,
@@ -1821,6 +2191,7 @@ mod tests {
             txid,
             tx_bytes,
             tx_fee,
+            None,
             height,
             &origin_address,
             origin_nonce,
@@ -1918,6 +2289,7 @@ mod tests {
             txid,
             tx_bytes,
             tx_fee,
+            None,
             height,
             &origin_address,
             origin_nonce,
@@ -1946,6 +2318,7 @@ mod tests {
             txid,
             tx_bytes,
             tx_fee,
+            None,
             height,
             &origin_address,
             origin_nonce,
@@ -2020,6 +2393,7 @@ mod tests {
                 txid,
                 tx_bytes,
                 tx_fee,
+                None,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -2052,7 +2426,7 @@ mod tests {
             // test replace-by-fee with a higher fee
             let old_txid = txid;
 
-            tx.set_tx_fee(124);
+            tx.set_tx_fee(136);
             assert!(txid != tx.txid());
 
             let txid = tx.txid();
@@ -2081,6 +2455,7 @@ mod tests {
                 txid,
                 tx_bytes,
                 tx_fee,
+                None,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -2113,7 +2488,7 @@ mod tests {
 
             assert_eq!(tx_info.tx, expected_tx);
             assert_eq!(tx_info.metadata.len, len);
-            assert_eq!(tx_info.metadata.tx_fee, 124);
+            assert_eq!(tx_info.metadata.tx_fee, 136);
             assert_eq!(tx_info.metadata.origin_address, origin_address);
             assert_eq!(tx_info.metadata.origin_nonce, origin_nonce);
             assert_eq!(tx_info.metadata.sponsor_address, sponsor_address);
@@ -2145,6 +2520,7 @@ mod tests {
                 txid,
                 tx_bytes,
                 tx_fee,
+                None,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -2285,6 +2661,7 @@ mod tests {
             txid,
             tx_bytes,
             tx_fee,
+            None,
             height,
             &origin_address,
             origin_nonce,
@@ -2303,7 +2680,7 @@ mod tests {
         let old_txid = txid;
         let old_tx_fee = tx_fee;
 
-        tx.set_tx_fee(124);
+        tx.set_tx_fee(136);
         tx.payload = TransactionPayload::TokenTransfer(
             stx_address.into(),
             123,
@@ -2317,7 +2694,8 @@ mod tests {
         let tx_fee = tx.get_tx_fee();
         let second_len = tx_bytes.len() as u64;
 
-        // these asserts are to ensure we are using the fee directly, not the fee rate
+        // the unit cost metric's fee rate is the fee itself, so only the fee needs a big enough
+        // bump here
         assert!(second_len < first_len);
         assert!(second_len * tx_fee < first_len * old_tx_fee);
         assert!(tx_fee > old_tx_fee);
@@ -2337,6 +2715,7 @@ mod tests {
             txid,
             tx_bytes,
             tx_fee,
+            None,
             height,
             &origin_address,
             origin_nonce,
@@ -2361,6 +2740,389 @@ mod tests {
         let tx_info = tx_info_opt.unwrap();
         assert_eq!(tx_info.metadata, tx_info_after);
         assert_eq!(tx_info.metadata.len, second_len);
-        assert_eq!(tx_info.metadata.tx_fee, 124);
+        assert_eq!(tx_info.metadata.tx_fee, 136);
+    }
+
+    #[test]
+    fn mempool_db_find_missing_txs() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_find_missing_txs");
+        let chainstate_path = chainstate_path("mempool_db_find_missing_txs");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+        let mut txs = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        );
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        let mut all_txids = vec![];
+        let mut total_len = 0;
+        for (i, mut tx) in txs.drain(..).enumerate() {
+            let origin_address = StacksAddress {
+                version: 22,
+                bytes: Hash160::from_data(&i.to_be_bytes()),
+            };
+            let sponsor_address = StacksAddress {
+                version: 22,
+                bytes: Hash160::from_data(&(i + 1).to_be_bytes()),
+            };
+
+            // later transactions pay higher fee rates
+            tx.set_tx_fee((i as u64 + 1) * 100);
+
+            let txid = tx.txid();
+            let mut tx_bytes = vec![];
+            tx.consensus_serialize(&mut tx_bytes).unwrap();
+            total_len += tx_bytes.len() as u64;
+
+            let tx_fee = tx.get_tx_fee();
+            let origin_nonce = tx.get_origin_nonce();
+            let sponsor_nonce = match tx.get_sponsor_nonce() {
+                Some(n) => n,
+                None => origin_nonce,
+            };
+
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                txid.clone(),
+                tx_bytes,
+                tx_fee,
+                None,
+                100,
+                &origin_address,
+                origin_nonce,
+                &sponsor_address,
+                sponsor_nonce,
+                None,
+            )
+            .unwrap();
+
+            all_txids.push(txid);
+        }
+        mempool_tx.commit().unwrap();
+
+        // highest fee rate first
+        all_txids.reverse();
+        let num_txs = all_txids.len() as u64;
+
+        let txids = MemPoolDB::get_txids_by_fee_rate(mempool.conn(), num_txs + 1).unwrap();
+        assert_eq!(txids, all_txids);
+
+        let txids = MemPoolDB::get_txids_by_fee_rate(mempool.conn(), 2).unwrap();
+        assert_eq!(txids, all_txids[0..2].to_vec());
+
+        // peer has nothing
+        let missing =
+            MemPoolDB::find_missing_txs(mempool.conn(), |_| false, num_txs, num_txs, total_len)
+                .unwrap();
+        assert_eq!(
+            missing.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>(),
+            all_txids
+        );
+
+        // peer has everything
+        let missing =
+            MemPoolDB::find_missing_txs(mempool.conn(), |_| true, num_txs, num_txs, total_len)
+                .unwrap();
+        assert_eq!(missing.len(), 0);
+
+        // peer has every other transaction
+        let known: Vec<Txid> = all_txids.iter().step_by(2).cloned().collect();
+        let missing = MemPoolDB::find_missing_txs(
+            mempool.conn(),
+            |txid| known.contains(txid),
+            num_txs,
+            num_txs,
+            total_len,
+        )
+        .unwrap();
+        assert_eq!(
+            missing.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>(),
+            all_txids
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .cloned()
+                .collect::<Vec<Txid>>()
+        );
+
+        // scan limit -- only the highest fee-rate transactions are considered
+        let missing = MemPoolDB::find_missing_txs(
+            mempool.conn(),
+            |txid| known.contains(txid),
+            1,
+            num_txs,
+            total_len,
+        )
+        .unwrap();
+        assert_eq!(missing.len(), 0);
+
+        // count limit
+        let missing =
+            MemPoolDB::find_missing_txs(mempool.conn(), |_| false, num_txs, 3, total_len).unwrap();
+        assert_eq!(missing.len(), 3);
+
+        // byte limit -- stop once the limit is reached
+        let missing =
+            MemPoolDB::find_missing_txs(mempool.conn(), |_| false, num_txs, num_txs, 1).unwrap();
+        assert_eq!(missing.len(), 1);
+    }
+
+    #[test]
+    fn mempool_db_evict_lowest_fee_rate() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_evict_lowest_fee_rate");
+        let chainstate_path = chainstate_path("mempool_db_evict_lowest_fee_rate");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+        mempool.set_limits(MemPoolLimits {
+            max_tx_count: 3,
+            max_bytes: 0,
+            replace_by_fee_rate_bump: 10,
+        });
+
+        // add a tx whose origin and sponsor are derived from `i`
+        fn add_tx(
+            mempool_tx: &mut MemPoolTx,
+            chainstate: &mut StacksChainState,
+            tx: &StacksTransaction,
+            i: usize,
+        ) -> Result<(), MemPoolRejection> {
+            let origin_address = StacksAddress {
+                version: 22,
+                bytes: Hash160::from_data(&i.to_be_bytes()),
+            };
+            let sponsor_address = StacksAddress {
+                version: 22,
+                bytes: Hash160::from_data(&(i + 1000).to_be_bytes()),
+            };
+            let origin_nonce = tx.get_origin_nonce();
+            MemPoolDB::try_add_tx(
+                mempool_tx,
+                chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                tx.txid(),
+                tx.serialize_to_vec(),
+                tx.get_tx_fee(),
+                None,
+                100,
+                &origin_address,
+                origin_nonce,
+                &sponsor_address,
+                origin_nonce,
+                None,
+            )
+        }
+
+        let mut txs = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        );
+        let mut txs: Vec<StacksTransaction> = txs.drain(..).take(5).collect();
+        for (tx, fee) in txs.iter_mut().zip([200, 100, 300, 150, 50].iter()) {
+            tx.set_tx_fee(*fee);
+        }
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for i in 0..3 {
+            add_tx(&mut mempool_tx, &mut chainstate, &txs[i], i).unwrap();
+        }
+
+        // full, so the lowest fee-rate transaction makes way
+        add_tx(&mut mempool_tx, &mut chainstate, &txs[3], 3).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &txs[1].txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &txs[3].txid()).unwrap());
+
+        // ...but not for a transaction that pays less than everything in it
+        match add_tx(&mut mempool_tx, &mut chainstate, &txs[4], 4).unwrap_err() {
+            MemPoolRejection::MemPoolFull => {}
+            e => panic!("unexpected rejection: {:?}", &e),
+        };
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &txs[4].txid()).unwrap());
+        for i in [0, 2, 3].iter() {
+            assert!(MemPoolDB::db_has_tx(&mempool_tx, &txs[*i].txid()).unwrap());
+        }
+
+        // a replacement needs a big enough fee-rate bump...
+        let mut replacement = txs[0].clone();
+        replacement.set_tx_fee(210);
+        match add_tx(&mut mempool_tx, &mut chainstate, &replacement, 0).unwrap_err() {
+            MemPoolRejection::ConflictingNonceInMempool => {}
+            e => panic!("unexpected rejection: {:?}", &e),
+        };
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &txs[0].txid()).unwrap());
+
+        // ...and replaces the old transaction without evicting anything else
+        replacement.set_tx_fee(220);
+        add_tx(&mut mempool_tx, &mut chainstate, &replacement, 0).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &txs[0].txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &replacement.txid()).unwrap());
+        for i in [2, 3].iter() {
+            assert!(MemPoolDB::db_has_tx(&mempool_tx, &txs[*i].txid()).unwrap());
+        }
+        mempool_tx.commit().unwrap();
+
+        mempool.set_limits(MemPoolLimits {
+            max_tx_count: 4,
+            max_bytes: 0,
+            replace_by_fee_rate_bump: 10,
+        });
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        let mut child = txs[3].clone();
+        child.set_origin_nonce(txs[3].get_origin_nonce() + 1);
+        child.set_tx_fee(400);
+        add_tx(&mut mempool_tx, &mut chainstate, &child, 3).unwrap();
+
+        // a transaction can't evict its own origin's lower-nonce transactions
+        let mut grandchild = txs[3].clone();
+        grandchild.set_origin_nonce(txs[3].get_origin_nonce() + 2);
+        grandchild.set_tx_fee(400);
+        match add_tx(&mut mempool_tx, &mut chainstate, &grandchild, 3).unwrap_err() {
+            MemPoolRejection::MemPoolFull => {}
+            e => panic!("unexpected rejection: {:?}", &e),
+        };
+
+        // evicting a transaction evicts its origin's higher-nonce transactions too
+        txs[4].set_tx_fee(500);
+        add_tx(&mut mempool_tx, &mut chainstate, &txs[4], 4).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &txs[3].txid()).unwrap());
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &child.txid()).unwrap());
+        for txid in [replacement.txid(), txs[2].txid(), txs[4].txid()].iter() {
+            assert!(MemPoolDB::db_has_tx(&mempool_tx, txid).unwrap());
+        }
+    }
+
+    #[test]
+    fn mempool_db_rank_by_cost_metric_fee_rate() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_db_rank_by_cost_metric_fee_rate");
+        let chainstate_path = chainstate_path("mempool_db_rank_by_cost_metric_fee_rate");
+        let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+        mempool.set_limits(MemPoolLimits {
+            max_tx_count: 2,
+            max_bytes: 0,
+            replace_by_fee_rate_bump: 10,
+        });
+
+        // add a tx whose origin and sponsor are derived from `i`
+        fn add_tx(
+            mempool_tx: &mut MemPoolTx,
+            chainstate: &mut StacksChainState,
+            tx: &StacksTransaction,
+            i: usize,
+            fee_rate_estimate: Option<f64>,
+        ) -> Result<(), MemPoolRejection> {
+            let origin_address = StacksAddress {
+                version: 22,
+                bytes: Hash160::from_data(&i.to_be_bytes()),
+            };
+            let sponsor_address = StacksAddress {
+                version: 22,
+                bytes: Hash160::from_data(&(i + 1000).to_be_bytes()),
+            };
+            let origin_nonce = tx.get_origin_nonce();
+            MemPoolDB::try_add_tx(
+                mempool_tx,
+                chainstate,
+                &ConsensusHash([0x1; 20]),
+                &BlockHeaderHash([0x2; 32]),
+                tx.txid(),
+                tx.serialize_to_vec(),
+                tx.get_tx_fee(),
+                fee_rate_estimate,
+                100,
+                &origin_address,
+                origin_nonce,
+                &sponsor_address,
+                origin_nonce,
+                None,
+            )
+        }
+
+        fn fee_per_byte(tx: &StacksTransaction) -> f64 {
+            tx.get_tx_fee() as f64 / tx.serialize_to_vec().len() as f64
+        }
+
+        let mut txs = codec_all_transactions(
+            &TransactionVersion::Testnet,
+            0x80000000,
+            &TransactionAnchorMode::Any,
+            &TransactionPostConditionMode::Allow,
+        );
+        txs.sort_by_key(|tx| tx.serialize_to_vec().len());
+
+        // the unit cost metric's fee rate is the fee itself, so small transactions pay a higher
+        // fee per byte than big ones with a higher fee rate
+        let mut small = txs.first().unwrap().clone();
+        let mut big = txs.last().unwrap().clone();
+        small.set_tx_fee(100);
+        big.set_tx_fee(200);
+        let mut big_2 = big.clone();
+        big_2.set_origin_nonce(big.get_origin_nonce() + 1);
+        big_2.set_tx_fee(150);
+        assert!(fee_per_byte(&small) > fee_per_byte(&big_2));
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx(&mut mempool_tx, &mut chainstate, &small, 0, None).unwrap();
+        add_tx(&mut mempool_tx, &mut chainstate, &big, 1, None).unwrap();
+
+        // eviction follows the cost metric's fee rate
+        add_tx(&mut mempool_tx, &mut chainstate, &big_2, 2, None).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &small.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &big.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &big_2.txid()).unwrap());
+
+        // ...and so does replace-by-fee, even if the fee per byte goes way up
+        let mut replacement = small.clone();
+        replacement.set_origin_nonce(big_2.get_origin_nonce());
+        replacement.set_tx_fee(160);
+        assert!(fee_per_byte(&replacement) > 1.1 * fee_per_byte(&big_2));
+        match add_tx(&mut mempool_tx, &mut chainstate, &replacement, 2, None).unwrap_err() {
+            MemPoolRejection::ConflictingNonceInMempool => {}
+            e => panic!("unexpected rejection: {:?}", &e),
+        };
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &big_2.txid()).unwrap());
+
+        replacement.set_tx_fee(170);
+        add_tx(&mut mempool_tx, &mut chainstate, &replacement, 2, None).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &big_2.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &replacement.txid()).unwrap());
+
+        // the cost estimator's fee rate takes precedence over the fee
+        let mut replacement_2 = replacement.clone();
+        replacement_2.set_tx_fee(1000);
+        match add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &replacement_2,
+            2,
+            Some(1.0),
+        )
+        .unwrap_err()
+        {
+            MemPoolRejection::ConflictingNonceInMempool => {}
+            e => panic!("unexpected rejection: {:?}", &e),
+        };
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &replacement.txid()).unwrap());
+
+        let mut newcomer = small.clone();
+        newcomer.set_tx_fee(1000);
+        match add_tx(&mut mempool_tx, &mut chainstate, &newcomer, 3, Some(1.0)).unwrap_err() {
+            MemPoolRejection::MemPoolFull => {}
+            e => panic!("unexpected rejection: {:?}", &e),
+        };
+        add_tx(&mut mempool_tx, &mut chainstate, &newcomer, 3, Some(180.0)).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &replacement.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &big.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &newcomer.txid()).unwrap());
     }
 }
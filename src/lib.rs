@@ -97,6 +97,8 @@ pub mod core;
 pub mod deps;
 
 pub mod clarity;
+#[cfg(feature = "developer-mode")]
+pub mod lsp;
 
 pub mod monitoring;
 pub mod types;
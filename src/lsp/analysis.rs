@@ -0,0 +1,383 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity_vm::database::MemoryBackingStore;
+use vm::analysis;
+use vm::analysis::ContractAnalysis;
+use vm::ast;
+use vm::costs::LimitedCostTracker;
+use vm::diagnostic::Diagnostic;
+use vm::docs;
+use vm::representations::Span;
+use vm::types::{FunctionType, PrincipalData, QualifiedContractIdentifier, TypeSignature, Value};
+use vm::{ClarityName, DefineFunctions, NativeFunctions, NativeVariables, SymbolicExpression};
+
+/// The result of running a contract through the parser and the analysis passes.
+pub struct CheckedContract {
+    pub contract_identifier: QualifiedContractIdentifier,
+    /// The parsed expressions, or empty if the contract failed to parse
+    pub expressions: Vec<SymbolicExpression>,
+    /// The analysis, if the contract passed every analysis pass
+    pub analysis: Option<ContractAnalysis>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Where a name used in a contract is defined
+#[derive(Debug, PartialEq)]
+pub enum Definition {
+    /// A span in the same contract
+    Local(Span),
+    /// Another contract, and optionally the name of something defined in it
+    External(QualifiedContractIdentifier, Option<ClarityName>),
+}
+
+/// Parse and analyze `source` as `contract_identifier`. `dependencies` are the other contracts
+///   in the workspace, which are analyzed first so that the contract may call into them.
+pub fn check_contract(
+    contract_identifier: &QualifiedContractIdentifier,
+    source: &str,
+    dependencies: &[(QualifiedContractIdentifier, String)],
+) -> CheckedContract {
+    let mut contract_ast = match ast::build_ast(contract_identifier, source, &mut ()) {
+        Ok(contract_ast) => contract_ast,
+        Err(e) => {
+            return CheckedContract {
+                contract_identifier: contract_identifier.clone(),
+                expressions: vec![],
+                analysis: None,
+                diagnostics: vec![e.diagnostic],
+            }
+        }
+    };
+
+    let mut store = MemoryBackingStore::new();
+    deploy_dependencies(&mut store, dependencies);
+
+    let result = analysis::run_analysis(
+        contract_identifier,
+        &mut contract_ast.expressions,
+        &mut store.as_analysis_db(),
+        false,
+        LimitedCostTracker::new_free(),
+    );
+    let (analysis, diagnostics) = match result {
+        Ok(analysis) => (Some(analysis), vec![]),
+        Err((e, _)) => (None, vec![e.diagnostic]),
+    };
+
+    CheckedContract {
+        contract_identifier: contract_identifier.clone(),
+        expressions: contract_ast.expressions,
+        analysis,
+        diagnostics,
+    }
+}
+
+/// Analyze and store each of `contracts`. Since they may call one another, the ones that fail
+///   analysis are retried until a pass makes no more progress.
+fn deploy_dependencies(
+    store: &mut MemoryBackingStore,
+    contracts: &[(QualifiedContractIdentifier, String)],
+) {
+    let mut pending = vec![];
+    for (contract_identifier, source) in contracts.iter() {
+        match ast::build_ast(contract_identifier, source, &mut ()) {
+            Ok(contract_ast) => pending.push((contract_identifier, source, contract_ast)),
+            Err(e) => debug!("Skipping unparseable {}: {}", contract_identifier, e),
+        }
+    }
+
+    while pending.len() > 0 {
+        let attempted = pending.len();
+        let mut remaining = vec![];
+        for (contract_identifier, source, mut contract_ast) in pending.drain(..) {
+            let result = analysis::run_analysis(
+                contract_identifier,
+                &mut contract_ast.expressions,
+                &mut store.as_analysis_db(),
+                false,
+                LimitedCostTracker::new_free(),
+            );
+            let contract_analysis = match result {
+                Ok(contract_analysis) => contract_analysis,
+                Err(_) => {
+                    remaining.push((contract_identifier, source, contract_ast));
+                    continue;
+                }
+            };
+
+            // the analysis database only finds metadata for contracts with a committed hash
+            let mut clarity_db = store.as_clarity_db();
+            clarity_db.begin();
+            clarity_db
+                .insert_contract_hash(contract_identifier, source)
+                .expect("Failed to store contract hash");
+            clarity_db.commit();
+
+            let mut analysis_db = store.as_analysis_db();
+            analysis_db.begin();
+            analysis_db
+                .insert_contract(contract_identifier, &contract_analysis)
+                .expect("Failed to store contract analysis");
+            analysis_db.commit();
+        }
+
+        if remaining.len() == attempted {
+            for (contract_identifier, ..) in remaining.iter() {
+                debug!("Skipping {}, which fails analysis", contract_identifier);
+            }
+            break;
+        }
+        pending = remaining;
+    }
+}
+
+/// Does `span` contain the 1-indexed `line` and `column`?
+fn span_contains(span: &Span, line: u32, column: u32) -> bool {
+    (span.start_line, span.start_column) <= (line, column)
+        && (line, column) <= (span.end_line, span.end_column)
+}
+
+/// Find the expressions containing the 1-indexed `line` and `column`, outermost first.
+pub fn expressions_at(
+    expressions: &[SymbolicExpression],
+    line: u32,
+    column: u32,
+) -> Vec<&SymbolicExpression> {
+    let mut path = vec![];
+    let mut level = expressions;
+    while let Some(expr) = level
+        .iter()
+        .find(|expr| span_contains(&expr.span, line, column))
+    {
+        path.push(expr);
+        match expr.match_list() {
+            Some(children) => level = children,
+            None => break,
+        }
+    }
+    path
+}
+
+/// If `expr` is the function being applied by the list `parent`, get its name.
+fn applied_function_name<'a>(
+    expr: &'a SymbolicExpression,
+    parent: Option<&SymbolicExpression>,
+) -> Option<&'a str> {
+    let list = parent?.match_list()?;
+    if list.first()?.id != expr.id {
+        return None;
+    }
+    expr.match_atom().map(|name| name.as_str())
+}
+
+fn function_api_documentation(api: &docs::FunctionAPI) -> String {
+    format!(
+        "```clarity\n{}\n```\n`{}` -> `{}`\n\n{}",
+        api.signature, api.input_type, api.output_type, api.description
+    )
+}
+
+fn keyword_api_documentation(api: &docs::KeywordAPI) -> String {
+    format!(
+        "```clarity\n{}\n```\n`{}`\n\n{}",
+        api.name, api.output_type, api.description
+    )
+}
+
+fn user_function_documentation(kind: &str, name: &str, function_type: &FunctionType) -> String {
+    format!(
+        "```clarity\n({} {})\n```\n`{}` -> `{}`",
+        kind,
+        name,
+        docs::get_input_type_string(function_type),
+        docs::get_output_type_string(function_type)
+    )
+}
+
+/// Get the documentation for the native, define or user-defined function `name`.
+fn function_documentation(analysis: Option<&ContractAnalysis>, name: &str) -> Option<String> {
+    if let Some(function) = NativeFunctions::lookup_by_name(name) {
+        return Some(function_api_documentation(&docs::make_api_reference(
+            &function,
+        )));
+    }
+    if let Some(define_type) = DefineFunctions::lookup_by_name(name) {
+        return Some(function_api_documentation(&docs::make_define_reference(
+            &define_type,
+        )));
+    }
+
+    let analysis = analysis?;
+    let kinds = [
+        ("define-public", &analysis.public_function_types),
+        ("define-read-only", &analysis.read_only_function_types),
+        ("define-private", &analysis.private_function_types),
+    ];
+    kinds.iter().find_map(|(kind, function_types)| {
+        function_types
+            .iter()
+            .find(|(function_name, _)| function_name.as_str() == name)
+            .map(|(_, function_type)| user_function_documentation(kind, name, function_type))
+    })
+}
+
+/// Get the markdown to show when hovering over the 1-indexed `line` and `column`, and the
+///   span of the expression it describes: the documentation of a function or keyword, or the
+///   type inferred for the expression.
+pub fn hover(checked: &CheckedContract, line: u32, column: u32) -> Option<(String, Span)> {
+    let path = expressions_at(&checked.expressions, line, column);
+    let expr = *path.last()?;
+    let parent = if path.len() >= 2 {
+        Some(path[path.len() - 2])
+    } else {
+        None
+    };
+
+    if let Some(name) = applied_function_name(expr, parent) {
+        if let Some(documentation) = function_documentation(checked.analysis.as_ref(), name) {
+            return Some((documentation, expr.span.clone()));
+        }
+    } else if let Some(name) = expr.match_atom() {
+        if let Some(api) =
+            NativeVariables::lookup_by_name(name).and_then(|v| docs::make_keyword_reference(&v))
+        {
+            return Some((keyword_api_documentation(&api), expr.span.clone()));
+        }
+    }
+
+    let type_map = checked.analysis.as_ref()?.type_map.as_ref()?;
+    match type_map.get_type(expr)? {
+        TypeSignature::NoType => None,
+        type_signature => Some((
+            format!("```clarity\n{}\n```", type_signature),
+            expr.span.clone(),
+        )),
+    }
+}
+
+/// If `expr` is a `define-*` form, get its defining keyword and the expression naming what
+///   it defines.
+fn match_define(expr: &SymbolicExpression) -> Option<(DefineFunctions, &SymbolicExpression)> {
+    let list = expr.match_list()?;
+    let define_type = DefineFunctions::lookup_by_name(list.first()?.match_atom()?)?;
+    let name = list.get(1)?;
+    // functions are named by the first element of their signature
+    let name = match name.match_list() {
+        Some(signature) => signature.first()?,
+        None => name,
+    };
+    name.match_atom()?;
+    Some((define_type, name))
+}
+
+/// Get the names defined at the top level of a contract.
+pub fn defined_names(expressions: &[SymbolicExpression]) -> Vec<(DefineFunctions, ClarityName)> {
+    expressions
+        .iter()
+        .filter_map(match_define)
+        .filter_map(|(define_type, name)| Some((define_type, name.match_atom()?.clone())))
+        .collect()
+}
+
+/// Find the span of the top-level definition of `name`.
+pub fn find_top_level_definition(expressions: &[SymbolicExpression], name: &str) -> Option<Span> {
+    expressions
+        .iter()
+        .filter_map(match_define)
+        .find(|(_, name_expr)| name_expr.match_atom().map(|n| n.as_str()) == Some(name))
+        .map(|(_, name_expr)| name_expr.span.clone())
+}
+
+/// Find the binding of `name` by a `let` or by the arguments of a function enclosing the
+///   innermost of `path`.
+fn find_local_binding(path: &[&SymbolicExpression], name: &str) -> Option<Span> {
+    let binds = |binding: &SymbolicExpression| {
+        binding
+            .match_list()
+            .and_then(|pair| pair.first())
+            .and_then(|bound| {
+                if bound.match_atom().map(|n| n.as_str()) == Some(name) {
+                    Some(bound.span.clone())
+                } else {
+                    None
+                }
+            })
+    };
+
+    for expr in path.iter().rev() {
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => continue,
+        };
+        let bindings = match list.first().and_then(|head| head.match_atom()) {
+            Some(head) if head.as_str() == "let" => list.get(1).and_then(|b| b.match_list()),
+            Some(head) => match DefineFunctions::lookup_by_name(head) {
+                Some(DefineFunctions::PrivateFunction)
+                | Some(DefineFunctions::PublicFunction)
+                | Some(DefineFunctions::ReadOnlyFunction) => list
+                    .get(1)
+                    .and_then(|signature| signature.match_list())
+                    .and_then(|signature| signature.get(1..)),
+                _ => None,
+            },
+            None => None,
+        };
+        if let Some(span) = bindings.and_then(|bindings| bindings.iter().find_map(binds)) {
+            return Some(span);
+        }
+    }
+    None
+}
+
+/// Find where the name or contract at the 1-indexed `line` and `column` is defined.
+pub fn find_definition(
+    expressions: &[SymbolicExpression],
+    line: u32,
+    column: u32,
+) -> Option<Definition> {
+    let path = expressions_at(expressions, line, column);
+    let expr = *path.last()?;
+
+    if let Some(Value::Principal(PrincipalData::Contract(contract_identifier))) =
+        expr.match_literal_value()
+    {
+        return Some(Definition::External(contract_identifier.clone(), None));
+    }
+
+    let name = expr.match_atom()?;
+    if path.len() >= 2 {
+        // the function named by a `contract-call?` is defined in the called contract
+        let list = path[path.len() - 2].match_list()?;
+        let is_contract_call = list
+            .first()
+            .and_then(|head| head.match_atom())
+            .map(|n| n.as_str())
+            == Some(NativeFunctions::ContractCall.get_name_str());
+        if is_contract_call && list.len() >= 3 && list[2].id == expr.id {
+            return match list[1].match_literal_value() {
+                Some(Value::Principal(PrincipalData::Contract(contract_identifier))) => Some(
+                    Definition::External(contract_identifier.clone(), Some(name.clone())),
+                ),
+                _ => None,
+            };
+        }
+    }
+
+    find_local_binding(&path[..path.len() - 1], name)
+        .or_else(|| find_top_level_definition(expressions, name))
+        .map(Definition::Local)
+}
@@ -0,0 +1,592 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+pub mod analysis;
+pub mod protocol;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use self::analysis::{CheckedContract, Definition};
+use vm::ast;
+use vm::diagnostic::{Diagnostic, Level};
+use vm::docs;
+use vm::representations::Span;
+use vm::types::QualifiedContractIdentifier;
+use vm::DefineFunctions;
+
+pub const CLARITY_FILE_EXTENSION: &str = "clar";
+
+/// LSP `TextDocumentSyncKind.Full`
+const SYNC_FULL: u64 = 1;
+
+/// LSP `CompletionItemKind`s
+const COMPLETION_FUNCTION: u64 = 3;
+const COMPLETION_VARIABLE: u64 = 6;
+const COMPLETION_INTERFACE: u64 = 8;
+const COMPLETION_KEYWORD: u64 = 14;
+const COMPLETION_CONSTANT: u64 = 21;
+
+/// LSP `DiagnosticSeverity`s
+const SEVERITY_ERROR: u64 = 1;
+const SEVERITY_WARNING: u64 = 2;
+
+struct Document {
+    text: String,
+    /// The result of checking `text`, cleared whenever the workspace changes
+    checked: Option<CheckedContract>,
+}
+
+/// A language server for Clarity. It publishes the diagnostics of the parser and analysis passes
+///   when a contract is opened or saved, and answers hover, go-to-definition and completion
+///   requests. Every `.clar` file under the workspace root is a contract named by its file stem,
+///   deployed by the same transient principal, so `.contract-name` principals resolve between them.
+pub struct LanguageServer {
+    root: Option<PathBuf>,
+    /// Open documents, by URI
+    documents: HashMap<String, Document>,
+    shutdown_requested: bool,
+    exit_requested: bool,
+}
+
+/// Get the name of the contract stored at `uri`
+pub fn contract_identifier_for_uri(uri: &str) -> QualifiedContractIdentifier {
+    Url::parse(uri)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .and_then(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|stem| stem.to_string())
+        })
+        .and_then(|name| QualifiedContractIdentifier::local(&name).ok())
+        .unwrap_or_else(QualifiedContractIdentifier::transient)
+}
+
+/// Convert a Clarity span (1-indexed, inclusive) to an LSP range (0-indexed, end-exclusive)
+fn span_to_range(span: &Span) -> JsonValue {
+    json!({
+        "start": {
+            "line": span.start_line.saturating_sub(1),
+            "character": span.start_column.saturating_sub(1),
+        },
+        "end": {
+            "line": span.end_line.saturating_sub(1),
+            "character": span.end_column,
+        },
+    })
+}
+
+/// Get the 1-indexed line and column of an LSP `TextDocumentPositionParams`
+fn position_from_params(params: &JsonValue) -> Option<(u32, u32)> {
+    let position = params.get("position")?;
+    let line = position.get("line")?.as_u64()?;
+    let character = position.get("character")?.as_u64()?;
+    Some((line as u32 + 1, character as u32 + 1))
+}
+
+fn document_uri_from_params(params: &JsonValue) -> Option<&str> {
+    params.get("textDocument")?.get("uri")?.as_str()
+}
+
+fn diagnostic_to_json(diagnostic: &Diagnostic) -> JsonValue {
+    let range = span_to_range(diagnostic.spans.first().unwrap_or(&Span::zero()));
+    let mut message = diagnostic.message.clone();
+    if let Some(ref suggestion) = diagnostic.suggestion {
+        message.push_str(&format!("\n{}", suggestion));
+    }
+    let severity = match diagnostic.level {
+        Level::Error => SEVERITY_ERROR,
+        Level::Warning => SEVERITY_WARNING,
+    };
+    json!({
+        "range": range,
+        "severity": severity,
+        "source": "clarity",
+        "message": message,
+    })
+}
+
+fn markdown(value: String) -> JsonValue {
+    json!({
+        "kind": "markdown",
+        "value": value,
+    })
+}
+
+/// Find every Clarity contract under `dir`, skipping hidden directories.
+fn find_contract_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read directory {}: {}", dir.display(), e);
+            return;
+        }
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if path.is_dir() {
+            if !hidden {
+                find_contract_files(&path, found);
+            }
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some(CLARITY_FILE_EXTENSION) {
+            found.push(path);
+        }
+    }
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        LanguageServer {
+            root: None,
+            documents: HashMap::new(),
+            shutdown_requested: false,
+            exit_requested: false,
+        }
+    }
+
+    /// Once the client has sent `exit`, the code the server process should exit with.
+    pub fn exit_code(&self) -> Option<i32> {
+        if !self.exit_requested {
+            None
+        } else if self.shutdown_requested {
+            Some(0)
+        } else {
+            Some(1)
+        }
+    }
+
+    /// Handle one message from the client, returning the messages to send back.
+    pub fn handle_message(&mut self, message: &JsonValue) -> Vec<JsonValue> {
+        let method = match message.get("method").and_then(|m| m.as_str()) {
+            Some(method) => method,
+            // a response to a request we never make
+            None => return vec![],
+        };
+        let params = message.get("params").unwrap_or(&JsonValue::Null);
+        let id = message.get("id");
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(JsonValue::Null)
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            "exit" => {
+                self.exit_requested = true;
+                return vec![];
+            }
+            "textDocument/didOpen" => return self.did_open(params),
+            "textDocument/didChange" => return self.did_change(params),
+            "textDocument/didSave" => return self.did_save(params),
+            "textDocument/didClose" => return self.did_close(params),
+            _ => match id {
+                Some(id) => {
+                    return vec![protocol::error_response(
+                        id,
+                        protocol::METHOD_NOT_FOUND,
+                        &format!("Unsupported method: {}", method),
+                    )]
+                }
+                // notifications we don't support can be ignored
+                None => return vec![],
+            },
+        };
+
+        let id = match id {
+            Some(id) => id,
+            None => {
+                warn!("Received request {} without an id", method);
+                return vec![];
+            }
+        };
+        match result {
+            Ok(result) => vec![protocol::response(id, result)],
+            Err(message) => vec![protocol::error_response(
+                id,
+                protocol::INVALID_PARAMS,
+                &message,
+            )],
+        }
+    }
+
+    fn initialize(&mut self, params: &JsonValue) -> JsonValue {
+        self.root = params
+            .get("rootUri")
+            .and_then(|uri| uri.as_str())
+            .and_then(|uri| Url::parse(uri).ok())
+            .and_then(|url| url.to_file_path().ok())
+            .or_else(|| {
+                params
+                    .get("rootPath")
+                    .and_then(|path| path.as_str())
+                    .map(PathBuf::from)
+            });
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": SYNC_FULL,
+                    "save": { "includeText": true },
+                },
+                "hoverProvider": true,
+                "definitionProvider": true,
+                "completionProvider": {
+                    "triggerCharacters": ["("],
+                },
+            },
+            "serverInfo": {
+                "name": "clarity-lsp",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    /// Get the URI, contract name and source of every contract in the workspace: the open
+    ///   documents, and the files under the workspace root.
+    fn workspace_contracts(&self) -> Vec<(String, QualifiedContractIdentifier, String)> {
+        let mut contracts: Vec<_> = self
+            .documents
+            .iter()
+            .map(|(uri, document)| {
+                (
+                    uri.clone(),
+                    contract_identifier_for_uri(uri),
+                    document.text.clone(),
+                )
+            })
+            .collect();
+
+        if let Some(ref root) = self.root {
+            let mut paths = vec![];
+            find_contract_files(root, &mut paths);
+            paths.sort();
+            for path in paths.into_iter() {
+                let uri = match Url::from_file_path(&path) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => continue,
+                };
+                if self.documents.contains_key(&uri) {
+                    continue;
+                }
+                match fs::read_to_string(&path) {
+                    Ok(source) => {
+                        let contract_identifier = contract_identifier_for_uri(&uri);
+                        contracts.push((uri, contract_identifier, source));
+                    }
+                    Err(e) => warn!("Failed to read {}: {}", path.display(), e),
+                }
+            }
+        }
+        contracts
+    }
+
+    /// Check an open document against the rest of the workspace, if it hasn't been already.
+    fn check_document(&mut self, uri: &str) -> Option<&CheckedContract> {
+        if self.documents.get(uri)?.checked.is_none() {
+            let contract_identifier = contract_identifier_for_uri(uri);
+            let dependencies: Vec<_> = self
+                .workspace_contracts()
+                .into_iter()
+                .filter(|(other_uri, other_identifier, _)| {
+                    other_uri != uri && other_identifier != &contract_identifier
+                })
+                .map(|(_, other_identifier, source)| (other_identifier, source))
+                .collect();
+
+            let document = self.documents.get_mut(uri)?;
+            document.checked = Some(analysis::check_contract(
+                &contract_identifier,
+                &document.text,
+                &dependencies,
+            ));
+        }
+        self.documents.get(uri)?.checked.as_ref()
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str) -> Option<JsonValue> {
+        let diagnostics: Vec<_> = self
+            .check_document(uri)?
+            .diagnostics
+            .iter()
+            .map(diagnostic_to_json)
+            .collect();
+        Some(protocol::notification(
+            "textDocument/publishDiagnostics",
+            json!({
+                "uri": uri,
+                "diagnostics": diagnostics,
+            }),
+        ))
+    }
+
+    /// A change to any contract can change whether the others check
+    fn invalidate_documents(&mut self) {
+        for document in self.documents.values_mut() {
+            document.checked = None;
+        }
+    }
+
+    fn did_open(&mut self, params: &JsonValue) -> Vec<JsonValue> {
+        let text_document = match params.get("textDocument") {
+            Some(text_document) => text_document,
+            None => return vec![],
+        };
+        let (uri, text) = match (
+            text_document.get("uri").and_then(|uri| uri.as_str()),
+            text_document.get("text").and_then(|text| text.as_str()),
+        ) {
+            (Some(uri), Some(text)) => (uri, text),
+            _ => return vec![],
+        };
+
+        self.invalidate_documents();
+        self.documents.insert(
+            uri.to_string(),
+            Document {
+                text: text.to_string(),
+                checked: None,
+            },
+        );
+        self.publish_diagnostics(uri).into_iter().collect()
+    }
+
+    fn did_change(&mut self, params: &JsonValue) -> Vec<JsonValue> {
+        // with full document sync, the last change holds the whole document
+        let text = params
+            .get("contentChanges")
+            .and_then(|changes| changes.as_array())
+            .and_then(|changes| changes.last())
+            .and_then(|change| change.get("text"))
+            .and_then(|text| text.as_str());
+        if let (Some(uri), Some(text)) = (document_uri_from_params(params), text) {
+            if let Some(document) = self.documents.get_mut(uri) {
+                document.text = text.to_string();
+                self.invalidate_documents();
+            }
+        }
+        vec![]
+    }
+
+    fn did_save(&mut self, params: &JsonValue) -> Vec<JsonValue> {
+        let uri = match document_uri_from_params(params) {
+            Some(uri) => uri.to_string(),
+            None => return vec![],
+        };
+        if let Some(text) = params.get("text").and_then(|text| text.as_str()) {
+            if let Some(document) = self.documents.get_mut(&uri) {
+                document.text = text.to_string();
+            }
+        }
+        self.invalidate_documents();
+
+        // saving one contract may fix or break the others that are open
+        let mut uris: Vec<_> = self
+            .documents
+            .keys()
+            .filter(|other| *other != &uri)
+            .cloned()
+            .collect();
+        uris.sort();
+        uris.insert(0, uri);
+        uris.iter()
+            .filter_map(|uri| self.publish_diagnostics(uri))
+            .collect()
+    }
+
+    fn did_close(&mut self, params: &JsonValue) -> Vec<JsonValue> {
+        let uri = match document_uri_from_params(params) {
+            Some(uri) => uri,
+            None => return vec![],
+        };
+        self.documents.remove(uri);
+        self.invalidate_documents();
+        vec![protocol::notification(
+            "textDocument/publishDiagnostics",
+            json!({
+                "uri": uri,
+                "diagnostics": [],
+            }),
+        )]
+    }
+
+    fn hover(&mut self, params: &JsonValue) -> Result<JsonValue, String> {
+        let uri = document_uri_from_params(params).ok_or("Missing textDocument")?;
+        let (line, column) = position_from_params(params).ok_or("Missing position")?;
+        let checked = match self.check_document(uri) {
+            Some(checked) => checked,
+            None => return Ok(JsonValue::Null),
+        };
+        Ok(match analysis::hover(checked, line, column) {
+            Some((contents, span)) => json!({
+                "contents": markdown(contents),
+                "range": span_to_range(&span),
+            }),
+            None => JsonValue::Null,
+        })
+    }
+
+    fn definition(&mut self, params: &JsonValue) -> Result<JsonValue, String> {
+        let uri = document_uri_from_params(params).ok_or("Missing textDocument")?;
+        let (line, column) = position_from_params(params).ok_or("Missing position")?;
+        let text = match self.documents.get(uri) {
+            Some(document) => document.text.clone(),
+            None => return Ok(JsonValue::Null),
+        };
+
+        // definitions only need the contract to parse, not to pass analysis
+        let contract_identifier = contract_identifier_for_uri(uri);
+        let expressions = match ast::build_ast(&contract_identifier, &text, &mut ()) {
+            Ok(contract_ast) => contract_ast.expressions,
+            Err(_) => return Ok(JsonValue::Null),
+        };
+
+        let location = match analysis::find_definition(&expressions, line, column) {
+            Some(Definition::Local(span)) => Some((uri.to_string(), span)),
+            Some(Definition::External(target, name)) => self
+                .workspace_contracts()
+                .into_iter()
+                .find(|(_, other_identifier, _)| other_identifier == &target)
+                .map(|(target_uri, _, source)| {
+                    let span = name
+                        .and_then(|name| {
+                            let target_ast = ast::build_ast(&target, &source, &mut ()).ok()?;
+                            analysis::find_top_level_definition(&target_ast.expressions, &name)
+                        })
+                        .unwrap_or_else(Span::zero);
+                    (target_uri, span)
+                }),
+            None => None,
+        };
+
+        Ok(match location {
+            Some((uri, span)) => json!({
+                "uri": uri,
+                "range": span_to_range(&span),
+            }),
+            None => JsonValue::Null,
+        })
+    }
+
+    fn completion(&mut self, params: &JsonValue) -> Result<JsonValue, String> {
+        let uri = document_uri_from_params(params).ok_or("Missing textDocument")?;
+
+        let reference = docs::make_all_api_reference();
+        let mut items: Vec<_> = reference
+            .functions
+            .iter()
+            .map(|api| {
+                json!({
+                    "label": api.name,
+                    "kind": COMPLETION_FUNCTION,
+                    "detail": api.signature,
+                    "documentation": markdown(api.description.clone()),
+                })
+            })
+            .collect();
+        items.extend(reference.keywords.iter().map(|api| {
+            json!({
+                "label": api.name,
+                "kind": COMPLETION_KEYWORD,
+                "detail": api.output_type,
+                "documentation": markdown(api.description.to_string()),
+            })
+        }));
+
+        // names defined by the document itself, as of its last successful parse
+        let contract_identifier = contract_identifier_for_uri(uri);
+        let defined_names = self
+            .documents
+            .get(uri)
+            .and_then(|document| ast::build_ast(&contract_identifier, &document.text, &mut ()).ok())
+            .map(|contract_ast| analysis::defined_names(&contract_ast.expressions))
+            .unwrap_or_default();
+        items.extend(defined_names.into_iter().map(|(define_type, name)| {
+            let kind = match define_type {
+                DefineFunctions::PrivateFunction
+                | DefineFunctions::PublicFunction
+                | DefineFunctions::ReadOnlyFunction => COMPLETION_FUNCTION,
+                DefineFunctions::Constant => COMPLETION_CONSTANT,
+                DefineFunctions::Trait | DefineFunctions::UseTrait => COMPLETION_INTERFACE,
+                _ => COMPLETION_VARIABLE,
+            };
+            json!({
+                "label": name.as_str(),
+                "kind": kind,
+                "detail": define_type.get_name_str(),
+            })
+        }));
+
+        Ok(JsonValue::Array(items))
+    }
+}
+
+/// Serve one client over `input` and `output` until it sends `exit` or closes its input.
+///   Returns the code the process should exit with.
+pub fn run<R: BufRead, W: Write>(mut input: R, mut output: W) -> i32 {
+    let mut server = LanguageServer::new();
+    loop {
+        let message = match protocol::read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => {
+                info!("Client closed its input without exiting");
+                return 1;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidData => {
+                warn!("Failed to parse message: {}", e);
+                let reply = protocol::error_response(
+                    &JsonValue::Null,
+                    protocol::PARSE_ERROR,
+                    &e.to_string(),
+                );
+                if let Err(e) = protocol::write_message(&mut output, &reply) {
+                    error!("Failed to write message: {}", e);
+                    return 1;
+                }
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to read message: {}", e);
+                return 1;
+            }
+        };
+
+        for reply in server.handle_message(&message).iter() {
+            if let Err(e) = protocol::write_message(&mut output, reply) {
+                error!("Failed to write message: {}", e);
+                return 1;
+            }
+        }
+
+        if let Some(exit_code) = server.exit_code() {
+            return exit_code;
+        }
+    }
+}
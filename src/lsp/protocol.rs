@@ -0,0 +1,125 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::io::{BufRead, Read, Write};
+
+use serde_json::Value as JsonValue;
+
+/// JSON-RPC error codes used by the Language Server Protocol
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Largest message body `read_message` will buffer
+pub const MAX_CONTENT_LENGTH: usize = 4 * 1024 * 1024;
+
+/// Read one message from `input`, which must be framed by a `Content-Length` header
+///   as in the base protocol of the LSP specification.
+/// Returns Ok(None) if the input was closed before a message began.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<JsonValue>> {
+    let mut content_length = None;
+    let mut read_any = false;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            if read_any {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "input closed while reading message headers",
+                ));
+            }
+            return Ok(None);
+        }
+        read_any = true;
+
+        let line = line.trim_end();
+        if line.len() == 0 {
+            break;
+        }
+
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("Content-Length") {
+            let length = value.parse::<usize>().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid Content-Length: {}", value),
+                )
+            })?;
+            content_length = Some(length);
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+
+    if content_length > MAX_CONTENT_LENGTH {
+        // skip the body so the next message can still be read
+        io::copy(&mut input.take(content_length as u64), &mut io::sink())?;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Content-Length {} exceeds the maximum of {} bytes",
+                content_length, MAX_CONTENT_LENGTH
+            ),
+        ));
+    }
+
+    let mut body = vec![0u8; content_length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write one message to `output`, framed by a `Content-Length` header.
+pub fn write_message<W: Write>(output: &mut W, message: &JsonValue) -> io::Result<()> {
+    let body = serde_json::to_string(message)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+pub fn response(id: &JsonValue, result: JsonValue) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    })
+}
+
+pub fn error_response(id: &JsonValue, code: i64, message: &str) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+pub fn notification(method: &str, params: JsonValue) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+    })
+}
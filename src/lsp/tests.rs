@@ -0,0 +1,340 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use super::protocol::{read_message, write_message, MAX_CONTENT_LENGTH};
+use super::*;
+
+const TOKEN_CONTRACT: &str = "(define-fungible-token stackaroo)
+(define-public (transfer (amount uint) (recipient principal))
+  (ft-transfer? stackaroo amount tx-sender recipient))
+";
+
+const MARKET_CONTRACT: &str = "(define-constant fee u10)
+(define-private (total (price uint))
+  (let ((with-fee (+ price fee)))
+    with-fee))
+(define-public (buy (price uint) (seller principal))
+  (contract-call? .token transfer (total price) seller))
+";
+
+fn make_workspace(name: &str, contracts: &[(&str, &str)]) -> PathBuf {
+    let root = PathBuf::from(format!("/tmp/clarity-lsp-tests-{}", name));
+    if fs::metadata(&root).is_ok() {
+        fs::remove_dir_all(&root).unwrap();
+    }
+    fs::create_dir_all(&root).unwrap();
+    for (contract_name, source) in contracts.iter() {
+        fs::write(root.join(format!("{}.clar", contract_name)), source).unwrap();
+    }
+    root
+}
+
+fn uri_for(root: &PathBuf, contract_name: &str) -> String {
+    Url::from_file_path(root.join(format!("{}.clar", contract_name)))
+        .unwrap()
+        .to_string()
+}
+
+fn start_server(root: &PathBuf) -> LanguageServer {
+    let mut server = LanguageServer::new();
+    let replies = server.handle_message(&json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "rootUri": Url::from_file_path(root).unwrap().to_string(),
+            "capabilities": {},
+        },
+    }));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+    server
+}
+
+fn open(server: &mut LanguageServer, uri: &str, text: &str) -> Vec<JsonValue> {
+    server.handle_message(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": {
+                "uri": uri,
+                "languageId": "clarity",
+                "version": 1,
+                "text": text,
+            },
+        },
+    }))
+}
+
+fn request(
+    server: &mut LanguageServer,
+    method: &str,
+    uri: &str,
+    line: u32,
+    character: u32,
+) -> JsonValue {
+    let mut replies = server.handle_message(&json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": method,
+        "params": {
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        },
+    }));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["id"], 2);
+    replies.remove(0)["result"].take()
+}
+
+#[test]
+fn test_message_framing() {
+    let message = json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" });
+    let mut buffer = vec![];
+    write_message(&mut buffer, &message).unwrap();
+    write_message(&mut buffer, &message).unwrap();
+
+    let mut input = Cursor::new(buffer);
+    assert_eq!(read_message(&mut input).unwrap(), Some(message.clone()));
+    assert_eq!(read_message(&mut input).unwrap(), Some(message));
+    assert_eq!(read_message(&mut input).unwrap(), None);
+
+    let mut input = Cursor::new(b"Content-Type: application/json\r\n\r\n{}".to_vec());
+    assert!(read_message(&mut input).is_err());
+}
+
+#[test]
+fn test_message_too_large() {
+    let message = json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" });
+    let oversized = MAX_CONTENT_LENGTH + 1;
+    let mut buffer = format!("Content-Length: {}\r\n\r\n", oversized).into_bytes();
+    buffer.extend(vec![b' '; oversized]);
+    write_message(&mut buffer, &message).unwrap();
+
+    // the oversized body is rejected, and the message after it is still read
+    let mut input = Cursor::new(buffer);
+    let err = read_message(&mut input).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(read_message(&mut input).unwrap(), Some(message));
+
+    // a Content-Length far past the cap is rejected without allocating the body
+    let mut input = Cursor::new(format!("Content-Length: {}\r\n\r\n", usize::MAX).into_bytes());
+    let err = read_message(&mut input).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_run_session() {
+    let mut input = vec![];
+    for message in [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]
+    .iter()
+    {
+        write_message(&mut input, message).unwrap();
+    }
+
+    let mut output = vec![];
+    assert_eq!(run(Cursor::new(input), &mut output), 0);
+
+    let mut output = Cursor::new(output);
+    let initialized = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(initialized["result"]["serverInfo"]["name"], "clarity-lsp");
+    let unsupported = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(unsupported["error"]["code"], protocol::METHOD_NOT_FOUND);
+    let shutdown = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(shutdown["id"], 3);
+    assert_eq!(shutdown["result"], JsonValue::Null);
+    assert_eq!(read_message(&mut output).unwrap(), None);
+}
+
+#[test]
+fn test_diagnostics() {
+    let root = make_workspace(
+        "diagnostics",
+        &[("token", TOKEN_CONTRACT), ("market", MARKET_CONTRACT)],
+    );
+    let mut server = start_server(&root);
+
+    // the market checks, since the token contract it calls is in the workspace
+    let market_uri = uri_for(&root, "market");
+    let replies = open(&mut server, &market_uri, MARKET_CONTRACT);
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["method"], "textDocument/publishDiagnostics");
+    assert_eq!(replies[0]["params"]["uri"], market_uri.as_str());
+    assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+
+    // edits aren't checked until they're saved
+    let broken = MARKET_CONTRACT.replace("(+ price fee)", "(+ price 1)");
+    let replies = server.handle_message(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": market_uri, "version": 2 },
+            "contentChanges": [{ "text": broken }],
+        },
+    }));
+    assert_eq!(replies.len(), 0);
+
+    let replies = server.handle_message(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didSave",
+        "params": {
+            "textDocument": { "uri": market_uri },
+        },
+    }));
+    assert_eq!(replies.len(), 1);
+    let diagnostics = replies[0]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["severity"], SEVERITY_ERROR);
+    assert!(diagnostics[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("expecting expression of type 'uint'"));
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 2);
+
+    // parse errors are reported too
+    let bad_uri = uri_for(&root, "bad");
+    let replies = open(&mut server, &bad_uri, "(define-constant x (+ 1 2)");
+    let diagnostics = replies[0]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_hover() {
+    let root = make_workspace(
+        "hover",
+        &[("token", TOKEN_CONTRACT), ("market", MARKET_CONTRACT)],
+    );
+    let mut server = start_server(&root);
+    let market_uri = uri_for(&root, "market");
+    open(&mut server, &market_uri, MARKET_CONTRACT);
+
+    // a native function shows its documentation
+    let hover = request(&mut server, "textDocument/hover", &market_uri, 2, 19);
+    let contents = hover["contents"]["value"].as_str().unwrap();
+    assert!(contents.contains("(+ i1 i2...)"), "{}", contents);
+    assert_eq!(
+        hover["range"],
+        json!({ "start": { "line": 2, "character": 19 }, "end": { "line": 2, "character": 20 } })
+    );
+
+    // a user-defined function shows its type
+    let hover = request(&mut server, "textDocument/hover", &market_uri, 5, 35);
+    let contents = hover["contents"]["value"].as_str().unwrap();
+    assert!(contents.contains("(define-private total)"), "{}", contents);
+    assert!(contents.contains("`uint` -> `uint`"), "{}", contents);
+
+    // any other expression shows its inferred type
+    let hover = request(&mut server, "textDocument/hover", &market_uri, 3, 6);
+    assert_eq!(hover["contents"]["value"], "```clarity\nuint\n```");
+    let hover = request(&mut server, "textDocument/hover", &market_uri, 5, 2);
+    assert_eq!(
+        hover["contents"]["value"],
+        "```clarity\n(response bool uint)\n```"
+    );
+
+    // there's nothing to show past the end of a line
+    let hover = request(&mut server, "textDocument/hover", &market_uri, 0, 40);
+    assert_eq!(hover, JsonValue::Null);
+}
+
+#[test]
+fn test_definition() {
+    let root = make_workspace(
+        "definition",
+        &[("token", TOKEN_CONTRACT), ("market", MARKET_CONTRACT)],
+    );
+    let mut server = start_server(&root);
+    let market_uri = uri_for(&root, "market");
+    open(&mut server, &market_uri, MARKET_CONTRACT);
+
+    // a constant
+    let location = request(&mut server, "textDocument/definition", &market_uri, 2, 28);
+    assert_eq!(location["uri"], market_uri.as_str());
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 0, "character": 17 })
+    );
+
+    // a let binding
+    let location = request(&mut server, "textDocument/definition", &market_uri, 3, 5);
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 2, "character": 9 })
+    );
+
+    // a function argument
+    let location = request(&mut server, "textDocument/definition", &market_uri, 5, 42);
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 4, "character": 21 })
+    );
+
+    // a contract-call? target, in another contract
+    let token_uri = uri_for(&root, "token");
+    let location = request(&mut server, "textDocument/definition", &market_uri, 5, 27);
+    assert_eq!(location["uri"], token_uri.as_str());
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 1, "character": 16 })
+    );
+
+    // the called contract itself
+    let location = request(&mut server, "textDocument/definition", &market_uri, 5, 20);
+    assert_eq!(location["uri"], token_uri.as_str());
+    assert_eq!(
+        location["range"]["start"],
+        json!({ "line": 0, "character": 0 })
+    );
+
+    // natives aren't defined anywhere
+    let location = request(&mut server, "textDocument/definition", &market_uri, 2, 19);
+    assert_eq!(location, JsonValue::Null);
+}
+
+#[test]
+fn test_completion() {
+    let root = make_workspace("completion", &[("market", MARKET_CONTRACT)]);
+    let mut server = start_server(&root);
+    let market_uri = uri_for(&root, "market");
+    open(&mut server, &market_uri, MARKET_CONTRACT);
+
+    let items = request(&mut server, "textDocument/completion", &market_uri, 0, 0);
+    let items = items.as_array().unwrap();
+    let find = |label: &str| {
+        items
+            .iter()
+            .find(|item| item["label"] == label)
+            .expect(&format!("no completion for {}", label))
+    };
+
+    assert_eq!(find("contract-call?")["kind"], COMPLETION_FUNCTION);
+    assert_eq!(find("define-public")["kind"], COMPLETION_FUNCTION);
+    assert_eq!(find("block-height")["kind"], COMPLETION_KEYWORD);
+    assert_eq!(find("fee")["kind"], COMPLETION_CONSTANT);
+    assert_eq!(find("total")["detail"], "define-private");
+}
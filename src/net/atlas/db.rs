@@ -167,7 +167,7 @@ impl AtlasDB {
             );
             return false;
         }
-        if attachment.content.len() as u32 > self.atlas_config.attachments_max_size {
+        if attachment.content.len() as u32 > self.atlas_config.max_attachment_size(contract_id) {
             info!("Atlas: will discard posted attachment - attachment too large");
             return false;
         }
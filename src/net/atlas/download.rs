@@ -164,10 +164,33 @@ impl AttachmentsDownloader {
         match progress {
             AttachmentsBatchStateMachine::Done(ref mut context) => {
                 for attachment in context.attachments.drain() {
-                    let attachments_instances = network
+                    // each contract caps the size of the attachments it commits to
+                    let (attachments_instances, oversized_instances): (Vec<_>, Vec<_>) = network
                         .atlasdb
                         .find_all_attachment_instances(&attachment.hash())
-                        .map_err(|e| net_error::DBError(e))?;
+                        .map_err(|e| net_error::DBError(e))?
+                        .into_iter()
+                        .partition(|attachment_instance| {
+                            attachment.content.len() as u32
+                                <= network
+                                    .atlasdb
+                                    .atlas_config
+                                    .max_attachment_size(&attachment_instance.contract_id)
+                        });
+                    for attachment_instance in oversized_instances.iter() {
+                        info!(
+                            "Atlas: discarding attachment {:?} - too large for {}",
+                            attachment.hash(),
+                            attachment_instance.contract_id
+                        );
+                    }
+                    // no need to try to download it again
+                    context
+                        .attachments_batch
+                        .resolve_attachment(&attachment.hash());
+                    if attachments_instances.is_empty() {
+                        continue;
+                    }
                     network
                         .atlasdb
                         .insert_instantiated_attachment(&attachment)
@@ -175,9 +198,6 @@ impl AttachmentsDownloader {
                     for attachment_instance in attachments_instances.into_iter() {
                         resolved_attachments.push((attachment_instance, attachment.clone()));
                     }
-                    context
-                        .attachments_batch
-                        .resolve_attachment(&attachment.hash())
                 }
 
                 // Carrying events for centralized deregistration
@@ -48,6 +48,8 @@ lazy_static! {
 pub struct AtlasConfig {
     pub contracts: HashSet<QualifiedContractIdentifier>,
     pub attachments_max_size: u32,
+    /// per-contract overrides of `attachments_max_size`
+    pub contracts_max_sizes: HashMap<QualifiedContractIdentifier, u32>,
     pub max_uninstantiated_attachments: u32,
     pub uninstantiated_attachments_expire_after: u32,
     pub unresolved_attachment_instances_expire_after: u32,
@@ -61,12 +63,35 @@ impl AtlasConfig {
         AtlasConfig {
             contracts,
             attachments_max_size: 1_048_576,
+            contracts_max_sizes: HashMap::new(),
             max_uninstantiated_attachments: 10_000,
             uninstantiated_attachments_expire_after: 3_600,
             unresolved_attachment_instances_expire_after: 172_800,
             genesis_attachments: None,
         }
     }
+
+    /// Sync the attachments of another contract.  Its attachments are capped at `max_size` bytes
+    /// if given, and at `attachments_max_size` otherwise.
+    pub fn add_contract(
+        &mut self,
+        contract_id: QualifiedContractIdentifier,
+        max_size: Option<u32>,
+    ) {
+        if let Some(max_size) = max_size {
+            self.contracts_max_sizes
+                .insert(contract_id.clone(), max_size);
+        }
+        self.contracts.insert(contract_id);
+    }
+
+    /// The size, in bytes, of the largest attachment we keep for a contract
+    pub fn max_attachment_size(&self, contract_id: &QualifiedContractIdentifier) -> u32 {
+        self.contracts_max_sizes
+            .get(contract_id)
+            .cloned()
+            .unwrap_or(self.attachments_max_size)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
@@ -102,6 +127,9 @@ pub struct AttachmentInstance {
 impl AttachmentInstance {
     const ATTACHMENTS_INV_PAGE_SIZE: u32 = 64;
 
+    /// Parse an attachment instance from a `print` event of the form
+    /// `{ attachment: { hash: (buff 20), attachment-index: uint, metadata: <any> } }`.  This is
+    /// the schema for any contract synced by Atlas, not just BNS (see `docs/atlas.md`).
     pub fn try_new_from_value(
         value: &Value,
         contract_id: &QualifiedContractIdentifier,
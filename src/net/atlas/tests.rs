@@ -728,6 +728,7 @@ fn test_keep_uninstantiated_attachments() {
     let atlas_config = AtlasConfig {
         contracts,
         attachments_max_size: 16,
+        contracts_max_sizes: HashMap::new(),
         max_uninstantiated_attachments: 10,
         uninstantiated_attachments_expire_after: 10,
         unresolved_attachment_instances_expire_after: 10,
@@ -755,11 +756,41 @@ fn test_keep_uninstantiated_attachments() {
     );
 }
 
+#[test]
+fn test_keep_attachments_per_contract_max_size() {
+    let bns_contract_id = boot_code_id("bns", false);
+    let other_contract_id =
+        QualifiedContractIdentifier::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.registry")
+            .unwrap();
+
+    let mut atlas_config = AtlasConfig::default(false);
+    atlas_config.attachments_max_size = 16;
+    atlas_config.add_contract(other_contract_id.clone(), Some(8));
+    assert_eq!(atlas_config.max_attachment_size(&bns_contract_id), 16);
+    assert_eq!(atlas_config.max_attachment_size(&other_contract_id), 8);
+
+    let atlas_db = AtlasDB::connect_memory(atlas_config).unwrap();
+
+    assert_eq!(
+        atlas_db.should_keep_attachment(&other_contract_id, &new_attachment_from("facade02")),
+        true
+    );
+    assert_eq!(
+        atlas_db.should_keep_attachment(&other_contract_id, &new_attachment_from("facadefacade02")),
+        false
+    );
+    assert_eq!(
+        atlas_db.should_keep_attachment(&bns_contract_id, &new_attachment_from("facadefacade02")),
+        true
+    );
+}
+
 #[test]
 fn test_evict_k_oldest_uninstantiated_attachments() {
     let atlas_config = AtlasConfig {
         contracts: HashSet::new(),
         attachments_max_size: 1024,
+        contracts_max_sizes: HashMap::new(),
         max_uninstantiated_attachments: 10,
         uninstantiated_attachments_expire_after: 0,
         unresolved_attachment_instances_expire_after: 10,
@@ -908,6 +939,7 @@ fn test_evict_expired_uninstantiated_attachments() {
     let atlas_config = AtlasConfig {
         contracts: HashSet::new(),
         attachments_max_size: 1024,
+        contracts_max_sizes: HashMap::new(),
         max_uninstantiated_attachments: 100,
         uninstantiated_attachments_expire_after: 10,
         unresolved_attachment_instances_expire_after: 10,
@@ -977,6 +1009,7 @@ fn test_evict_expired_unresolved_attachment_instances() {
     let atlas_config = AtlasConfig {
         contracts: HashSet::new(),
         attachments_max_size: 1024,
+        contracts_max_sizes: HashMap::new(),
         max_uninstantiated_attachments: 100,
         uninstantiated_attachments_expire_after: 200,
         unresolved_attachment_instances_expire_after: 10,
@@ -1045,6 +1078,7 @@ fn test_get_minmax_heights_atlasdb() {
     let atlas_config = AtlasConfig {
         contracts: HashSet::new(),
         attachments_max_size: 1024,
+        contracts_max_sizes: HashMap::new(),
         max_uninstantiated_attachments: 100,
         uninstantiated_attachments_expire_after: 10,
         unresolved_attachment_instances_expire_after: 10,
@@ -1065,6 +1099,7 @@ fn test_bit_vectors() {
     let atlas_config = AtlasConfig {
         contracts: HashSet::new(),
         attachments_max_size: 1024,
+        contracts_max_sizes: HashMap::new(),
         max_uninstantiated_attachments: 100,
         uninstantiated_attachments_expire_after: 10,
         unresolved_attachment_instances_expire_after: 10,
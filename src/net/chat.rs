@@ -35,6 +35,7 @@ use chainstate::burn::db::sortdb;
 use chainstate::burn::db::sortdb::{BlockHeaderCache, SortitionDB};
 use chainstate::stacks::db::StacksChainState;
 use chainstate::stacks::StacksPublicKey;
+use core::mempool::MemPoolDB;
 use monitoring;
 use net::asn::ASEntry4;
 use net::codec::*;
@@ -592,6 +593,11 @@ impl ConversationP2P {
         self.connection.has_public_key()
     }
 
+    /// Did the peer say in its handshake that it answers mempool sync requests?
+    pub fn supports_mempool_sync(&self) -> bool {
+        self.peer_services & (ServiceFlags::MEMPOOL_SYNC as u16) != 0
+    }
+
     pub fn get_public_key(&self) -> Option<StacksPublicKey> {
         self.connection.get_public_key()
     }
@@ -1590,6 +1596,52 @@ impl ConversationP2P {
         self.sign_and_reply(local_peer, burnchain_view, preamble, response)
     }
 
+    /// Create a response to a mempool sync request: the transactions we have whose tags are not
+    /// in the request, highest fee rate first.
+    pub fn make_mempool_sync_response(
+        mempool: &MemPoolDB,
+        sync_data: &MemPoolSyncData,
+    ) -> Result<StacksMessageType, net_error> {
+        let known_tags: HashSet<TxTag> = sync_data.tags.iter().cloned().collect();
+        // at most one of our transactions is known per tag, so there's no need to look further
+        let max_scan = (known_tags.len() as u64) + (MEMPOOL_SYNC_TXS_MAX as u64);
+        let txs = MemPoolDB::find_missing_txs(
+            mempool.conn(),
+            |txid| known_tags.contains(&TxTag::from_txid(sync_data.seed, txid)),
+            max_scan,
+            MEMPOOL_SYNC_TXS_MAX as u64,
+            MEMPOOL_SYNC_TXS_MAX_BYTES,
+        )
+        .map_err(net_error::DBError)?;
+
+        Ok(StacksMessageType::MemPoolSyncResponse(MemPoolSyncTxsData {
+            txs,
+        }))
+    }
+
+    /// Handle an inbound MemPoolSyncRequest
+    fn handle_mempool_sync_request(
+        &mut self,
+        local_peer: &LocalPeer,
+        mempool: &MemPoolDB,
+        burnchain_view: &BurnchainView,
+        preamble: &Preamble,
+        sync_data: &MemPoolSyncData,
+    ) -> Result<ReplyHandleP2P, net_error> {
+        monitoring::increment_msg_counter("p2p_mempool_sync".to_string());
+
+        let response = ConversationP2P::make_mempool_sync_response(mempool, sync_data)?;
+
+        debug!(
+            "{:?}: handle MemPoolSyncRequest from {:?} ({} tags): {}",
+            &local_peer,
+            &self,
+            sync_data.tags.len(),
+            response.get_message_description()
+        );
+        self.sign_and_reply(local_peer, burnchain_view, preamble, response)
+    }
+
     /// Verify that there are no cycles in our relayers list.
     /// Identify relayers by public key hash
     fn check_relayer_cycles(relayers: &Vec<RelayData>) -> bool {
@@ -1764,6 +1816,7 @@ impl ConversationP2P {
         sortdb: &SortitionDB,
         pox_id: &PoxId,
         chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
         header_cache: &mut BlockHeaderCache,
         chain_view: &BurnchainView,
         msg: StacksMessage,
@@ -1789,6 +1842,14 @@ impl ConversationP2P {
                 &msg.preamble,
                 get_blocks_inv,
             ),
+            StacksMessageType::MemPoolSyncRequest(ref sync_data) => self
+                .handle_mempool_sync_request(
+                    local_peer,
+                    mempool,
+                    chain_view,
+                    &msg.preamble,
+                    sync_data,
+                ),
             StacksMessageType::Blocks(_) => {
                 monitoring::increment_stx_blocks_received_counter();
 
@@ -2175,6 +2236,7 @@ impl ConversationP2P {
         sortdb: &SortitionDB,
         pox_id: &PoxId,
         chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
         header_cache: &mut BlockHeaderCache,
         burnchain_view: &BurnchainView,
     ) -> Result<Vec<StacksMessage>, net_error> {
@@ -2317,6 +2379,7 @@ impl ConversationP2P {
                             sortdb,
                             pox_id,
                             chainstate,
+                            mempool,
                             header_cache,
                             burnchain_view,
                             msg,
@@ -2398,7 +2461,7 @@ mod test {
         data_url: UrlString,
         asn4_entries: &Vec<ASEntry4>,
         initial_neighbors: &Vec<Neighbor>,
-    ) -> (PeerDB, SortitionDB, PoxId, StacksChainState) {
+    ) -> (PeerDB, SortitionDB, PoxId, StacksChainState, MemPoolDB) {
         let test_path = format!("/tmp/blockstack-test-databases-{}", testname);
         match fs::metadata(&test_path) {
             Ok(_) => {
@@ -2457,7 +2520,9 @@ mod test {
             sortdb_reader.get_pox_id().unwrap()
         };
 
-        (peerdb, sortdb, pox_id, chainstate)
+        let mempool = MemPoolDB::open_test(false, network_id, &chainstate_path).unwrap();
+
+        (peerdb, sortdb, pox_id, chainstate, mempool)
     }
 
     fn convo_send_recv(
@@ -2621,24 +2686,26 @@ mod test {
             };
             chain_view.make_test_data();
 
-            let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-                "convo_handshake_accept_1",
-                &burnchain,
-                0x9abcdef0,
-                12350,
-                "http://peer1.com".into(),
-                &vec![],
-                &vec![],
-            );
-            let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-                "convo_handshake_accept_2",
-                &burnchain,
-                0x9abcdef0,
-                12351,
-                "http://peer2.com".into(),
-                &vec![],
-                &vec![],
-            );
+            let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+                make_test_chain_dbs(
+                    "convo_handshake_accept_1",
+                    &burnchain,
+                    0x9abcdef0,
+                    12350,
+                    "http://peer1.com".into(),
+                    &vec![],
+                    &vec![],
+                );
+            let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+                make_test_chain_dbs(
+                    "convo_handshake_accept_2",
+                    &burnchain,
+                    0x9abcdef0,
+                    12351,
+                    "http://peer2.com".into(),
+                    &vec![],
+                    &vec![],
+                );
 
             db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
             db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -2693,6 +2760,7 @@ mod test {
                     &sortdb_2,
                     &pox_id_2,
                     &mut chainstate_2,
+                    &mempool_2,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -2708,6 +2776,7 @@ mod test {
                     &sortdb_1,
                     &pox_id_1,
                     &mut chainstate_1,
+                    &mempool_1,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -2792,24 +2861,26 @@ mod test {
         };
         chain_view.make_test_data();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_handshake_reject_1",
-            &burnchain,
-            0x9abcdef0,
-            12350,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_handshake_reject_2",
-            &burnchain,
-            0x9abcdef0,
-            12351,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_handshake_reject_1",
+                &burnchain,
+                0x9abcdef0,
+                12350,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_handshake_reject_2",
+                &burnchain,
+                0x9abcdef0,
+                12351,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -2864,6 +2935,7 @@ mod test {
                 &sortdb_2,
                 &pox_id_2,
                 &mut chainstate_2,
+                &mempool_2,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -2878,6 +2950,7 @@ mod test {
                 &sortdb_1,
                 &pox_id_1,
                 &mut chainstate_1,
+                &mempool_1,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -2928,24 +3001,26 @@ mod test {
         )
         .unwrap();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_handshake_badsignature_1",
-            &burnchain,
-            0x9abcdef0,
-            12350,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_handshake_badsignature_2",
-            &burnchain,
-            0x9abcdef0,
-            12351,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_handshake_badsignature_1",
+                &burnchain,
+                0x9abcdef0,
+                12350,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_handshake_badsignature_2",
+                &burnchain,
+                0x9abcdef0,
+                12351,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -3004,6 +3079,7 @@ mod test {
             &sortdb_2,
             &pox_id_2,
             &mut chainstate_2,
+            &mempool_2,
             &mut BlockHeaderCache::new(),
             &chain_view,
         );
@@ -3017,6 +3093,7 @@ mod test {
                 &sortdb_1,
                 &pox_id_1,
                 &mut chainstate_1,
+                &mempool_1,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3062,24 +3139,26 @@ mod test {
         )
         .unwrap();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_handshake_self_1",
-            &burnchain,
-            0x9abcdef0,
-            12350,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_handshake_self_2",
-            &burnchain,
-            0x9abcdef0,
-            12351,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_handshake_self_1",
+                &burnchain,
+                0x9abcdef0,
+                12350,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_handshake_self_2",
+                &burnchain,
+                0x9abcdef0,
+                12351,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -3132,6 +3211,7 @@ mod test {
                 &sortdb_1,
                 &pox_id_1,
                 &mut chainstate_1,
+                &mempool_1,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3146,6 +3226,7 @@ mod test {
                 &sortdb_2,
                 &pox_id_2,
                 &mut chainstate_2,
+                &mempool_2,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3197,24 +3278,26 @@ mod test {
         )
         .unwrap();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_ping_1",
-            &burnchain,
-            0x9abcdef0,
-            12350,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_ping_2",
-            &burnchain,
-            0x9abcdef0,
-            12351,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_ping_1",
+                &burnchain,
+                0x9abcdef0,
+                12350,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_ping_2",
+                &burnchain,
+                0x9abcdef0,
+                12351,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -3285,6 +3368,7 @@ mod test {
                 &sortdb_2,
                 &pox_id_2,
                 &mut chainstate_2,
+                &mempool_2,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3305,6 +3389,7 @@ mod test {
                 &sortdb_1,
                 &pox_id_1,
                 &mut chainstate_1,
+                &mempool_1,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3364,24 +3449,26 @@ mod test {
         )
         .unwrap();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_handshake_ping_loop_1",
-            &burnchain,
-            0x9abcdef0,
-            12350,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_handshake_ping_loop_2",
-            &burnchain,
-            0x9abcdef0,
-            12351,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_handshake_ping_loop_1",
+                &burnchain,
+                0x9abcdef0,
+                12350,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_handshake_ping_loop_2",
+                &burnchain,
+                0x9abcdef0,
+                12351,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -3450,6 +3537,7 @@ mod test {
                     &sortdb_2,
                     &pox_id_2,
                     &mut chainstate_2,
+                    &mempool_2,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3468,6 +3556,7 @@ mod test {
                     &sortdb_1,
                     &pox_id_1,
                     &mut chainstate_1,
+                    &mempool_1,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3581,24 +3670,26 @@ mod test {
         )
         .unwrap();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_nack_unsolicited_1",
-            &burnchain,
-            0x9abcdef0,
-            12350,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_nack_unsolicited_2",
-            &burnchain,
-            0x9abcdef0,
-            12351,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_nack_unsolicited_1",
+                &burnchain,
+                0x9abcdef0,
+                12350,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_nack_unsolicited_2",
+                &burnchain,
+                0x9abcdef0,
+                12351,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -3651,6 +3742,7 @@ mod test {
                 &sortdb_2,
                 &pox_id_2,
                 &mut chainstate_2,
+                &mempool_2,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3665,6 +3757,7 @@ mod test {
                 &sortdb_1,
                 &pox_id_1,
                 &mut chainstate_1,
+                &mempool_1,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -3719,24 +3812,26 @@ mod test {
             };
             chain_view.make_test_data();
 
-            let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-                "convo_handshake_getblocksinv_1",
-                &burnchain,
-                0x9abcdef0,
-                12350,
-                "http://peer1.com".into(),
-                &vec![],
-                &vec![],
-            );
-            let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-                "convo_handshake_getblocksinv_2",
-                &burnchain,
-                0x9abcdef0,
-                12351,
-                "http://peer2.com".into(),
-                &vec![],
-                &vec![],
-            );
+            let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+                make_test_chain_dbs(
+                    "convo_handshake_getblocksinv_1",
+                    &burnchain,
+                    0x9abcdef0,
+                    12350,
+                    "http://peer1.com".into(),
+                    &vec![],
+                    &vec![],
+                );
+            let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+                make_test_chain_dbs(
+                    "convo_handshake_getblocksinv_2",
+                    &burnchain,
+                    0x9abcdef0,
+                    12351,
+                    "http://peer2.com".into(),
+                    &vec![],
+                    &vec![],
+                );
 
             db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
             db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -3791,6 +3886,7 @@ mod test {
                     &sortdb_2,
                     &pox_id_2,
                     &mut chainstate_2,
+                    &mempool_2,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3806,6 +3902,7 @@ mod test {
                     &sortdb_1,
                     &pox_id_1,
                     &mut chainstate_1,
+                    &mempool_1,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3889,6 +3986,7 @@ mod test {
                     &sortdb_2,
                     &pox_id_2,
                     &mut chainstate_2,
+                    &mempool_2,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3904,6 +4002,7 @@ mod test {
                     &sortdb_1,
                     &pox_id_1,
                     &mut chainstate_1,
+                    &mempool_1,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3958,6 +4057,7 @@ mod test {
                     &sortdb_2,
                     &pox_id_2,
                     &mut chainstate_2,
+                    &mempool_2,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3973,6 +4073,7 @@ mod test {
                     &sortdb_1,
                     &pox_id_1,
                     &mut chainstate_1,
+                    &mempool_1,
                     &mut BlockHeaderCache::new(),
                     &chain_view,
                 )
@@ -3996,6 +4097,231 @@ mod test {
         })
     }
 
+    #[test]
+    fn convo_handshake_mempool_sync() {
+        with_timeout(100, || {
+            let conn_opts = ConnectionOptions::default();
+
+            let socketaddr_1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+            let socketaddr_2 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 8081);
+
+            let first_burn_hash = BurnchainHeaderHash::from_hex(
+                "0000000000000000000000000000000000000000000000000000000000000000",
+            )
+            .unwrap();
+
+            let burnchain = testing_burnchain_config();
+
+            let mut chain_view = BurnchainView {
+                burn_block_height: 12331, // burnchain.reward_cycle_to_block_height(burnchain.block_height_to_reward_cycle(12348 - 8).unwrap()),
+                burn_block_hash: BurnchainHeaderHash([0x11; 32]),
+                burn_stable_block_height: 12331 - 7, // burnchain.reward_cycle_to_block_height(burnchain.block_height_to_reward_cycle(12341 - 8).unwrap() - 1),
+                burn_stable_block_hash: BurnchainHeaderHash([0x22; 32]),
+                last_burn_block_hashes: HashMap::new(),
+            };
+            chain_view.make_test_data();
+
+            let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+                make_test_chain_dbs(
+                    "convo_handshake_mempool_sync_1",
+                    &burnchain,
+                    0x9abcdef0,
+                    12350,
+                    "http://peer1.com".into(),
+                    &vec![],
+                    &vec![],
+                );
+            let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+                make_test_chain_dbs(
+                    "convo_handshake_mempool_sync_2",
+                    &burnchain,
+                    0x9abcdef0,
+                    12351,
+                    "http://peer2.com".into(),
+                    &vec![],
+                    &vec![],
+                );
+
+            db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
+            db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
+
+            let local_peer_1 = PeerDB::get_local_peer(&peerdb_1.conn()).unwrap();
+            let local_peer_2 = PeerDB::get_local_peer(&peerdb_2.conn()).unwrap();
+
+            let mut convo_1 = ConversationP2P::new(
+                123,
+                456,
+                &burnchain,
+                &socketaddr_2,
+                &conn_opts,
+                true,
+                0,
+                StacksEpoch::unit_test_pre_2_05(0),
+            );
+            let mut convo_2 = ConversationP2P::new(
+                123,
+                456,
+                &burnchain,
+                &socketaddr_1,
+                &conn_opts,
+                true,
+                0,
+                StacksEpoch::unit_test_pre_2_05(0),
+            );
+
+            // no peer public keys known yet
+            assert!(convo_1.connection.get_public_key().is_none());
+            assert!(convo_2.connection.get_public_key().is_none());
+
+            // convo_1 sends a handshake to convo_2
+            let handshake_data_1 = HandshakeData::from_local_peer(&local_peer_1);
+            let handshake_1 = convo_1
+                .sign_message(
+                    &chain_view,
+                    &local_peer_1.private_key,
+                    StacksMessageType::Handshake(handshake_data_1.clone()),
+                )
+                .unwrap();
+            let mut rh_1 = convo_1.send_signed_request(handshake_1, 1000000).unwrap();
+
+            // convo_2 receives it and processes it, and since no one is waiting for it, will forward
+            // it along to the chat caller (us)
+            test_debug!("send handshake");
+            convo_send_recv(&mut convo_1, vec![&mut rh_1], &mut convo_2);
+            let unhandled_2 = convo_2
+                .chat(
+                    &local_peer_2,
+                    &mut peerdb_2,
+                    &sortdb_2,
+                    &pox_id_2,
+                    &mut chainstate_2,
+                    &mempool_2,
+                    &mut BlockHeaderCache::new(),
+                    &chain_view,
+                )
+                .unwrap();
+
+            // convo_1 has a handshakeaccept
+            test_debug!("send handshake-accept");
+            convo_send_recv(&mut convo_2, vec![&mut rh_1], &mut convo_1);
+            let unhandled_1 = convo_1
+                .chat(
+                    &local_peer_1,
+                    &mut peerdb_1,
+                    &sortdb_1,
+                    &pox_id_1,
+                    &mut chainstate_1,
+                    &mempool_1,
+                    &mut BlockHeaderCache::new(),
+                    &chain_view,
+                )
+                .unwrap();
+
+            let reply_1 = rh_1.recv(0).unwrap();
+
+            assert_eq!(unhandled_1.len(), 0);
+            assert_eq!(unhandled_2.len(), 1);
+
+            // both peers advertise mempool sync
+            assert!(convo_1.supports_mempool_sync());
+            assert!(convo_2.supports_mempool_sync());
+
+            // convo 2 returns the handshake from convo 1
+            match unhandled_2[0].payload {
+                StacksMessageType::Handshake(ref data) => {
+                    assert_eq!(handshake_data_1, *data);
+                }
+                _ => {
+                    assert!(false);
+                }
+            };
+
+            // received a valid HandshakeAccept from peer 2
+            match reply_1.payload {
+                StacksMessageType::HandshakeAccept(ref data) => {
+                    assert_eq!(data.handshake.addrbytes, local_peer_2.addrbytes);
+                    assert_eq!(data.handshake.port, local_peer_2.port);
+                    assert_eq!(data.handshake.services, local_peer_2.services);
+                    assert_eq!(
+                        data.handshake.node_public_key,
+                        StacksPublicKeyBuffer::from_public_key(&Secp256k1PublicKey::from_private(
+                            &local_peer_2.private_key
+                        ))
+                    );
+                    assert_eq!(
+                        data.handshake.expire_block_height,
+                        local_peer_2.private_key_expire
+                    );
+                    assert_eq!(data.handshake.data_url, "http://peer2.com".into());
+                    assert_eq!(data.heartbeat_interval, conn_opts.heartbeat);
+                }
+                _ => {
+                    assert!(false);
+                }
+            };
+
+            // convo_1 asks convo_2 for the mempool transactions it doesn't have
+            let sync_data_1 = MemPoolSyncData::from_txids(vec![Txid([0x11; 32])].iter());
+            let sync_msg_1 = convo_1
+                .sign_message(
+                    &chain_view,
+                    &local_peer_1.private_key,
+                    StacksMessageType::MemPoolSyncRequest(sync_data_1),
+                )
+                .unwrap();
+            let mut rh_1 = convo_1.send_signed_request(sync_msg_1, 10000000).unwrap();
+
+            // convo_2 receives it, and handles it
+            test_debug!("send mempool sync request");
+            convo_send_recv(&mut convo_1, vec![&mut rh_1], &mut convo_2);
+            let unhandled_2 = convo_2
+                .chat(
+                    &local_peer_2,
+                    &mut peerdb_2,
+                    &sortdb_2,
+                    &pox_id_2,
+                    &mut chainstate_2,
+                    &mempool_2,
+                    &mut BlockHeaderCache::new(),
+                    &chain_view,
+                )
+                .unwrap();
+
+            // convo_1 gets back a mempool sync response
+            test_debug!("send mempool sync response");
+            convo_send_recv(&mut convo_2, vec![&mut rh_1], &mut convo_1);
+            let unhandled_1 = convo_1
+                .chat(
+                    &local_peer_1,
+                    &mut peerdb_1,
+                    &sortdb_1,
+                    &pox_id_1,
+                    &mut chainstate_1,
+                    &mempool_1,
+                    &mut BlockHeaderCache::new(),
+                    &chain_view,
+                )
+                .unwrap();
+
+            let reply_1 = rh_1.recv(0).unwrap();
+
+            // no unhandled messages forwarded
+            assert_eq!(unhandled_1, vec![]);
+            assert_eq!(unhandled_2, vec![]);
+
+            // convo_2's mempool is empty, so there's nothing to send
+            match reply_1.payload {
+                StacksMessageType::MemPoolSyncResponse(ref data) => {
+                    assert_eq!(data.txs.len(), 0);
+                }
+                x => {
+                    error!("received invalid payload: {:?}", &x);
+                    assert!(false);
+                }
+            }
+        })
+    }
+
     #[test]
     fn convo_natpunch() {
         let conn_opts = ConnectionOptions::default();
@@ -4023,24 +4349,26 @@ mod test {
         )
         .unwrap();
 
-        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
-            "convo_natpunch_1",
-            &burnchain,
-            0x9abcdef0,
-            12352,
-            "http://peer1.com".into(),
-            &vec![],
-            &vec![],
-        );
-        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
-            "convo_natpunch_2",
-            &burnchain,
-            0x9abcdef0,
-            12353,
-            "http://peer2.com".into(),
-            &vec![],
-            &vec![],
-        );
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1, mempool_1) =
+            make_test_chain_dbs(
+                "convo_natpunch_1",
+                &burnchain,
+                0x9abcdef0,
+                12352,
+                "http://peer1.com".into(),
+                &vec![],
+                &vec![],
+            );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2, mempool_2) =
+            make_test_chain_dbs(
+                "convo_natpunch_2",
+                &burnchain,
+                0x9abcdef0,
+                12353,
+                "http://peer2.com".into(),
+                &vec![],
+                &vec![],
+            );
 
         db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
         db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
@@ -4091,6 +4419,7 @@ mod test {
                 &sortdb_2,
                 &pox_id_2,
                 &mut chainstate_2,
+                &mempool_2,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
@@ -4106,6 +4435,7 @@ mod test {
                 &sortdb_1,
                 &pox_id_1,
                 &mut chainstate_1,
+                &mempool_1,
                 &mut BlockHeaderCache::new(),
                 &chain_view,
             )
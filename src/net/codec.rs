@@ -29,6 +29,7 @@ use sha2::Sha512Trunc256;
 use burnchains::BurnchainView;
 use burnchains::PrivateKey;
 use burnchains::PublicKey;
+use burnchains::Txid;
 use chainstate::burn::ConsensusHash;
 use chainstate::stacks::StacksBlock;
 use chainstate::stacks::StacksMicroblock;
@@ -775,6 +776,58 @@ impl StacksMessageCodec for RelayData {
     }
 }
 
+impl TxTag {
+    /// Calculate the tag of a txid, given the seed chosen by the requester
+    pub fn from_txid(seed: u64, txid: &Txid) -> TxTag {
+        let mut sha2 = Sha512Trunc256::new();
+        sha2.input(&seed.to_be_bytes());
+        sha2.input(txid.as_bytes());
+
+        let mut tag_bytes = [0u8; 8];
+        tag_bytes.copy_from_slice(&sha2.result().as_slice()[0..8]);
+        TxTag(tag_bytes)
+    }
+}
+
+impl MemPoolSyncData {
+    /// Summarize the given txids with a fresh random seed
+    pub fn from_txids<'a, I: Iterator<Item = &'a Txid>>(txids: I) -> MemPoolSyncData {
+        let seed: u64 = rand::thread_rng().gen();
+        let tags = txids.map(|txid| TxTag::from_txid(seed, txid)).collect();
+        MemPoolSyncData { seed, tags }
+    }
+}
+
+impl StacksMessageCodec for MemPoolSyncData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.seed)?;
+        write_next(fd, &self.tags)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<MemPoolSyncData, codec_error> {
+        let seed: u64 = read_next(fd)?;
+        let tags: Vec<TxTag> = read_next_at_most::<_, TxTag>(fd, MEMPOOL_SYNC_TAGS_MAX)?;
+        Ok(MemPoolSyncData { seed, tags })
+    }
+}
+
+impl StacksMessageCodec for MemPoolSyncTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<MemPoolSyncTxsData, codec_error> {
+        let txs: Vec<StacksTransaction> = {
+            // loose upper-bound
+            let mut bound_read = BoundReader::from_reader(fd, MAX_MESSAGE_LEN as u64);
+            read_next_at_most::<_, StacksTransaction>(&mut bound_read, MEMPOOL_SYNC_TXS_MAX)
+        }?;
+        Ok(MemPoolSyncTxsData { txs })
+    }
+}
+
 impl StacksMessageType {
     pub fn get_message_id(&self) -> StacksMessageID {
         match *self {
@@ -799,6 +852,8 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => StacksMessageID::Pong,
             StacksMessageType::NatPunchRequest(ref _m) => StacksMessageID::NatPunchRequest,
             StacksMessageType::NatPunchReply(ref _m) => StacksMessageID::NatPunchReply,
+            StacksMessageType::MemPoolSyncRequest(ref _m) => StacksMessageID::MemPoolSyncRequest,
+            StacksMessageType::MemPoolSyncResponse(ref _m) => StacksMessageID::MemPoolSyncResponse,
         }
     }
 
@@ -823,6 +878,8 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => "Pong",
             StacksMessageType::NatPunchRequest(ref _m) => "NatPunchRequest",
             StacksMessageType::NatPunchReply(ref _m) => "NatPunchReply",
+            StacksMessageType::MemPoolSyncRequest(ref _m) => "MemPoolSyncRequest",
+            StacksMessageType::MemPoolSyncResponse(ref _m) => "MemPoolSyncResponse",
         }
     }
 
@@ -881,6 +938,13 @@ impl StacksMessageType {
             StacksMessageType::NatPunchReply(ref m) => {
                 format!("NatPunchReply({},{}:{})", m.nonce, &m.addrbytes, m.port)
             }
+            StacksMessageType::MemPoolSyncRequest(ref m) => {
+                format!("MemPoolSyncRequest({},{})", m.seed, m.tags.len())
+            }
+            StacksMessageType::MemPoolSyncResponse(ref m) => format!(
+                "MemPoolSyncResponse({:?})",
+                m.txs.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>()
+            ),
         }
     }
 }
@@ -914,6 +978,12 @@ impl StacksMessageCodec for StacksMessageID {
             x if x == StacksMessageID::Pong as u8 => StacksMessageID::Pong,
             x if x == StacksMessageID::NatPunchRequest as u8 => StacksMessageID::NatPunchRequest,
             x if x == StacksMessageID::NatPunchReply as u8 => StacksMessageID::NatPunchReply,
+            x if x == StacksMessageID::MemPoolSyncRequest as u8 => {
+                StacksMessageID::MemPoolSyncRequest
+            }
+            x if x == StacksMessageID::MemPoolSyncResponse as u8 => {
+                StacksMessageID::MemPoolSyncResponse
+            }
             _ => {
                 return Err(codec_error::DeserializeError(
                     "Unknown message ID".to_string(),
@@ -947,6 +1017,8 @@ impl StacksMessageCodec for StacksMessageType {
             StacksMessageType::Pong(ref m) => write_next(fd, m)?,
             StacksMessageType::NatPunchRequest(ref nonce) => write_next(fd, nonce)?,
             StacksMessageType::NatPunchReply(ref m) => write_next(fd, m)?,
+            StacksMessageType::MemPoolSyncRequest(ref m) => write_next(fd, m)?,
+            StacksMessageType::MemPoolSyncResponse(ref m) => write_next(fd, m)?,
         }
         Ok(())
     }
@@ -1024,6 +1096,14 @@ impl StacksMessageCodec for StacksMessageType {
                 let m: NatPunchData = read_next(fd)?;
                 StacksMessageType::NatPunchReply(m)
             }
+            StacksMessageID::MemPoolSyncRequest => {
+                let m: MemPoolSyncData = read_next(fd)?;
+                StacksMessageType::MemPoolSyncRequest(m)
+            }
+            StacksMessageID::MemPoolSyncResponse => {
+                let m: MemPoolSyncTxsData = read_next(fd)?;
+                StacksMessageType::MemPoolSyncResponse(m)
+            }
             StacksMessageID::Reserved => {
                 return Err(codec_error::DeserializeError(
                     "Unsupported message ID 'reserved'".to_string(),
@@ -1943,6 +2023,52 @@ pub mod test {
         check_codec_and_corruption::<NatPunchData>(&data, &bytes);
     }
 
+    #[test]
+    fn codec_MemPoolSyncData() {
+        let data = MemPoolSyncData {
+            seed: 0x0102030405060708,
+            tags: vec![TxTag([0x11; 8]), TxTag([0x22; 8])],
+        };
+        let bytes = vec![
+            // seed
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // length
+            0x00, 0x00, 0x00, 0x02, // tags
+            0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+            0x22, 0x22,
+        ];
+
+        check_codec_and_corruption::<MemPoolSyncData>(&data, &bytes);
+
+        // can't send more than MEMPOOL_SYNC_TAGS_MAX tags
+        let too_many = MemPoolSyncData {
+            seed: 0,
+            tags: vec![TxTag([0x11; 8]); (MEMPOOL_SYNC_TAGS_MAX + 1) as usize],
+        };
+        assert!(check_deserialize_failure(&too_many));
+    }
+
+    #[test]
+    fn codec_tx_tags() {
+        let txid_1 = Txid([0x01; 32]);
+        let txid_2 = Txid([0x02; 32]);
+
+        // deterministic for the same seed
+        assert_eq!(TxTag::from_txid(1, &txid_1), TxTag::from_txid(1, &txid_1));
+
+        // differs by txid and by seed
+        assert!(TxTag::from_txid(1, &txid_1) != TxTag::from_txid(1, &txid_2));
+        assert!(TxTag::from_txid(1, &txid_1) != TxTag::from_txid(2, &txid_1));
+
+        let data = MemPoolSyncData::from_txids(vec![txid_1.clone(), txid_2.clone()].iter());
+        assert_eq!(
+            data.tags,
+            vec![
+                TxTag::from_txid(data.seed, &txid_1),
+                TxTag::from_txid(data.seed, &txid_2)
+            ]
+        );
+    }
+
     #[test]
     fn codec_StacksMessage() {
         let payloads: Vec<StacksMessageType> = vec![
@@ -2059,6 +2185,11 @@ pub mod test {
                 port: 12345,
                 nonce: 0x12345678,
             }),
+            StacksMessageType::MemPoolSyncRequest(MemPoolSyncData {
+                seed: 0x0102030405060708,
+                tags: vec![TxTag([0x11; 8]), TxTag([0x22; 8])],
+            }),
+            StacksMessageType::MemPoolSyncResponse(MemPoolSyncTxsData { txs: vec![] }),
         ];
 
         let mut maximal_relayers: Vec<RelayData> = vec![];
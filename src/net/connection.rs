@@ -241,6 +241,16 @@ impl<P: ProtocolFamily> NetworkReplyHandle<P> {
         }
     }
 
+    /// Try to move any buffered data into the inner pipe, but keep the pipe open so more data
+    /// can be written later.
+    /// Return true if there is no more buffered data; false if we need to call this again.
+    pub fn try_flush_buffered(&mut self) -> Result<bool, net_error> {
+        match self.request_pipe_write {
+            Some(ref mut fd) => fd.try_flush().map_err(net_error::WriteError),
+            None => Ok(true),
+        }
+    }
+
     /// Try to flush the inner pipe writer.  If we succeed, drop the inner pipe.
     /// Only call this once you're done sending -- this is just to move the data along.
     /// Return true if we're done sending; false if we need to call this again.
@@ -358,6 +368,10 @@ pub struct ConnectionOptions {
     pub max_attachment_retry_count: u64,
     pub read_only_call_limit: ExecutionCost,
     pub maximum_call_argument_size: u32,
+    /// serve `/v2/transactions/simulate` to RPC clients
+    pub enable_transaction_simulation: bool,
+    /// cost budget of a single simulated transaction
+    pub simulate_transaction_limit: ExecutionCost,
     pub max_block_push_bandwidth: u64,
     pub max_microblocks_push_bandwidth: u64,
     pub max_transaction_push_bandwidth: u64,
@@ -374,6 +388,7 @@ pub struct ConnectionOptions {
     pub max_buffered_microblocks_available: u64,
     pub max_buffered_blocks: u64,
     pub max_buffered_microblocks: u64,
+    pub mempool_sync_interval: u64,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -388,6 +403,7 @@ pub struct ConnectionOptions {
     pub disable_inbound_walks: bool,
     pub disable_natpunch: bool,
     pub disable_inbound_handshakes: bool,
+    pub disable_mempool_sync: bool,
     pub force_disconnect_interval: Option<u64>,
 }
 
@@ -440,6 +456,14 @@ impl std::default::Default for ConnectionOptions {
                 runtime: 1_000_000_000,
             },
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
+            enable_transaction_simulation: false,
+            simulate_transaction_limit: ExecutionCost {
+                write_length: 100000,
+                write_count: 30,
+                read_length: 100000,
+                read_count: 30,
+                runtime: 1_000_000_000,
+            },
             max_block_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_microblocks_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_transaction_push_bandwidth: 0, // infinite upload bandwidth allowed
@@ -456,6 +480,7 @@ impl std::default::Default for ConnectionOptions {
             max_buffered_microblocks_available: 1,
             max_buffered_blocks: 1,
             max_buffered_microblocks: 10,
+            mempool_sync_interval: 30, // how often, in seconds, we ask a peer for mempool transactions we're missing
 
             // no faults on by default
             disable_neighbor_walk: false,
@@ -470,6 +495,7 @@ impl std::default::Default for ConnectionOptions {
             disable_inbound_walks: false,
             disable_natpunch: false,
             disable_inbound_handshakes: false,
+            disable_mempool_sync: false,
             force_disconnect_interval: None,
         }
     }
@@ -161,7 +161,7 @@ impl LocalPeer {
 
         let addr = addrbytes;
         let port = port;
-        let services = ServiceFlags::RELAY;
+        let services = ServiceFlags::RELAY as u16 | ServiceFlags::MEMPOOL_SYNC as u16;
 
         info!(
             "Will be authenticating p2p messages with public key: {}",
@@ -176,7 +176,7 @@ impl LocalPeer {
             private_key_expire: key_expire,
             addrbytes: addr,
             port: port,
-            services: services as u16,
+            services: services,
             data_url: data_url,
             public_ip_address: None,
         }
@@ -448,14 +448,16 @@ impl PeerDB {
         data_url: UrlString,
         p2p_port: u16,
     ) -> Result<(), db_error> {
+        // peer DBs made before mempool sync existed need to start advertising it
         let local_peer_args: &[&dyn ToSql] = &[
             &p2p_port,
             &data_url.as_str(),
+            &(ServiceFlags::MEMPOOL_SYNC as u16),
             &network_id,
             &parent_network_id,
         ];
 
-        match self.conn.execute("UPDATE local_peer SET port = ?, data_url = ? WHERE network_id = ? AND parent_network_id = ?",
+        match self.conn.execute("UPDATE local_peer SET port = ?, data_url = ?, services = services | ? WHERE network_id = ? AND parent_network_id = ?",
                                 local_peer_args) {
             Ok(_) => Ok(()),
             Err(e) => Err(db_error::SqliteError(e))
@@ -1423,7 +1425,10 @@ mod test {
         );
         assert_eq!(local_peer.port, NETWORK_P2P_PORT);
         assert_eq!(local_peer.addrbytes, PeerAddress::from_ipv4(127, 0, 0, 1));
-        assert_eq!(local_peer.services, ServiceFlags::RELAY as u16);
+        assert_eq!(
+            local_peer.services,
+            ServiceFlags::RELAY as u16 | ServiceFlags::MEMPOOL_SYNC as u16
+        );
     }
 
     #[test]
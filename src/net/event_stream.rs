@@ -0,0 +1,412 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// This module contains the fan-out point for the RPC server's server-sent-events endpoint.
+// The node publishes the same payloads it POSTs to its event observers, and each connected
+// client receives the ones that match the event keys it subscribed with.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::fmt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use net::Error as net_error;
+
+use util::get_epoch_time_secs;
+
+use vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
+
+/// Default maximum number of concurrent event stream clients
+pub const DEFAULT_MAX_EVENT_STREAM_SUBSCRIBERS: usize = 32;
+
+/// Default maximum number of undelivered events buffered per client.  A client that falls
+/// further behind than this is disconnected.
+pub const DEFAULT_MAX_EVENT_STREAM_BACKLOG: usize = 1024;
+
+/// How often to send a keep-alive comment on an otherwise-idle event stream
+pub const EVENT_STREAM_HEARTBEAT_SECS: u64 = 10;
+
+/// Event filter a client subscribes with.  These are the same keys accepted by the node's
+/// `events_observer` configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventStreamKey {
+    SmartContractEvent(QualifiedContractIdentifier, String),
+    AssetEvent(AssetIdentifier),
+    STXEvent,
+    MemPoolTransactions,
+    Microblocks,
+    BurnchainBlocks,
+    AnyEvent,
+}
+
+impl EventStreamKey {
+    pub fn from_string(raw_key: &str) -> Option<EventStreamKey> {
+        match raw_key {
+            "*" => return Some(EventStreamKey::AnyEvent),
+            "stx" => return Some(EventStreamKey::STXEvent),
+            "memtx" => return Some(EventStreamKey::MemPoolTransactions),
+            "burn_blocks" => return Some(EventStreamKey::BurnchainBlocks),
+            "microblocks" => return Some(EventStreamKey::Microblocks),
+            _ => {}
+        }
+
+        let comps: Vec<_> = raw_key.split("::").collect();
+        if comps.len() == 1 {
+            let split: Vec<_> = comps[0].split(".").collect();
+            if split.len() != 3 {
+                return None;
+            }
+            let components = (
+                PrincipalData::parse_standard_principal(split[0]),
+                split[1].to_string().try_into(),
+                split[2].to_string().try_into(),
+            );
+            match components {
+                (Ok(address), Ok(name), Ok(asset_name)) => {
+                    let contract_identifier = QualifiedContractIdentifier::new(address, name);
+                    Some(EventStreamKey::AssetEvent(AssetIdentifier {
+                        contract_identifier,
+                        asset_name,
+                    }))
+                }
+                (_, _, _) => None,
+            }
+        } else if comps.len() == 2 {
+            QualifiedContractIdentifier::parse(comps[0])
+                .ok()
+                .map(|contract_id| {
+                    EventStreamKey::SmartContractEvent(contract_id, comps[1].to_string())
+                })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for EventStreamKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventStreamKey::SmartContractEvent(contract_id, topic) => {
+                write!(f, "{}::{}", contract_id, topic)
+            }
+            EventStreamKey::AssetEvent(asset_id) => write!(
+                f,
+                "{}.{}",
+                &asset_id.contract_identifier,
+                asset_id.asset_name.as_str()
+            ),
+            EventStreamKey::STXEvent => write!(f, "stx"),
+            EventStreamKey::MemPoolTransactions => write!(f, "memtx"),
+            EventStreamKey::Microblocks => write!(f, "microblocks"),
+            EventStreamKey::BurnchainBlocks => write!(f, "burn_blocks"),
+            EventStreamKey::AnyEvent => write!(f, "*"),
+        }
+    }
+}
+
+struct EventStreamSubscriber {
+    keys: Vec<EventStreamKey>,
+    /// encoded SSE frames not yet handed to the client's connection
+    frames: VecDeque<Vec<u8>>,
+    /// set if the client fell too far behind; the stream will be closed
+    overflowed: bool,
+}
+
+struct EventStreamState {
+    next_id: u64,
+    subscribers: HashMap<u64, EventStreamSubscriber>,
+    max_subscribers: usize,
+    max_backlog: usize,
+}
+
+/// Shared handle through which events are published to all connected event stream clients.
+/// Cloning the hub yields another handle to the same set of subscribers.
+#[derive(Clone)]
+pub struct EventStreamHub {
+    state: Arc<Mutex<EventStreamState>>,
+}
+
+impl fmt::Debug for EventStreamHub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EventStreamHub({} subscribers)", self.num_subscribers())
+    }
+}
+
+/// Encode a single server-sent event
+fn encode_frame(event_name: &str, payload: &serde_json::Value) -> Vec<u8> {
+    format!("event: {}\ndata: {}\n\n", event_name, payload).into_bytes()
+}
+
+impl EventStreamHub {
+    pub fn new(max_subscribers: usize, max_backlog: usize) -> EventStreamHub {
+        EventStreamHub {
+            state: Arc::new(Mutex::new(EventStreamState {
+                next_id: 0,
+                subscribers: HashMap::new(),
+                max_subscribers: max_subscribers,
+                max_backlog: max_backlog,
+            })),
+        }
+    }
+
+    /// Register a new client.  Returns None if we already have as many clients as we allow.
+    pub fn subscribe(&self, keys: Vec<EventStreamKey>) -> Option<EventStreamSubscription> {
+        let mut state = self
+            .state
+            .lock()
+            .expect("FATAL: event stream hub lock poisoned");
+        if state.subscribers.len() >= state.max_subscribers {
+            return None;
+        }
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.subscribers.insert(
+            id,
+            EventStreamSubscriber {
+                keys: keys,
+                frames: VecDeque::new(),
+                overflowed: false,
+            },
+        );
+
+        Some(EventStreamSubscription {
+            id: id,
+            hub: self.clone(),
+            partial_frame: vec![],
+            last_write: get_epoch_time_secs(),
+        })
+    }
+
+    pub fn num_subscribers(&self) -> usize {
+        self.state
+            .lock()
+            .expect("FATAL: event stream hub lock poisoned")
+            .subscribers
+            .len()
+    }
+
+    /// Publish an event to every client.  `make_payload` is given each client's subscription
+    /// keys, and returns the payload that client should receive (or None if it should not
+    /// receive this event at all).
+    pub fn publish<F>(&self, event_name: &str, mut make_payload: F)
+    where
+        F: FnMut(&[EventStreamKey]) -> Option<serde_json::Value>,
+    {
+        let mut state = self
+            .state
+            .lock()
+            .expect("FATAL: event stream hub lock poisoned");
+        let max_backlog = state.max_backlog;
+        for (id, subscriber) in state.subscribers.iter_mut() {
+            if subscriber.overflowed {
+                continue;
+            }
+            let payload = match make_payload(&subscriber.keys) {
+                Some(payload) => payload,
+                None => {
+                    continue;
+                }
+            };
+            if subscriber.frames.len() >= max_backlog {
+                warn!(
+                    "Event stream client {} fell behind by {} events; disconnecting it",
+                    id,
+                    subscriber.frames.len()
+                );
+                subscriber.overflowed = true;
+                subscriber.frames.clear();
+                continue;
+            }
+            subscriber
+                .frames
+                .push_back(encode_frame(event_name, &payload));
+        }
+    }
+
+    fn pop_frame(&self, id: u64) -> Result<Option<Vec<u8>>, net_error> {
+        let mut state = self
+            .state
+            .lock()
+            .expect("FATAL: event stream hub lock poisoned");
+        match state.subscribers.get_mut(&id) {
+            Some(subscriber) => {
+                if subscriber.overflowed {
+                    Err(net_error::ConnectionBroken)
+                } else {
+                    Ok(subscriber.frames.pop_front())
+                }
+            }
+            None => Err(net_error::ConnectionBroken),
+        }
+    }
+
+    fn unsubscribe(&self, id: u64) {
+        if let Ok(mut state) = self.state.lock() {
+            state.subscribers.remove(&id);
+        }
+    }
+}
+
+/// A single client's view of the event stream.  Dropping it unsubscribes the client.
+pub struct EventStreamSubscription {
+    id: u64,
+    hub: EventStreamHub,
+    /// a frame we have started writing, but which the writer did not fully accept
+    partial_frame: Vec<u8>,
+    last_write: u64,
+}
+
+impl fmt::Debug for EventStreamSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EventStreamSubscription({})", self.id)
+    }
+}
+
+impl EventStreamSubscription {
+    /// Write up to `count` bytes of pending events to `fd`.  If nothing has been written for a
+    /// while, a keep-alive comment is written instead, so that idle connections are not reaped
+    /// by the server or by intermediate proxies.
+    /// Returns the number of bytes written, or an error if the client fell too far behind and
+    /// must be disconnected.
+    pub fn stream_to<W: Write>(&mut self, fd: &mut W, count: u64) -> Result<u64, net_error> {
+        let mut written: u64 = 0;
+        while written < count {
+            if self.partial_frame.len() == 0 {
+                match self.hub.pop_frame(self.id)? {
+                    Some(frame) => {
+                        self.partial_frame = frame;
+                    }
+                    None => {
+                        break;
+                    }
+                }
+            }
+
+            let nw = fd
+                .write(&self.partial_frame)
+                .map_err(net_error::WriteError)?;
+            if nw == 0 {
+                break;
+            }
+            self.partial_frame.drain(0..nw);
+            written += nw as u64;
+        }
+
+        let now = get_epoch_time_secs();
+        if written > 0 {
+            self.last_write = now;
+        } else if self.last_write + EVENT_STREAM_HEARTBEAT_SECS <= now {
+            let heartbeat = b":\n\n";
+            fd.write_all(heartbeat).map_err(net_error::WriteError)?;
+            self.last_write = now;
+            written = heartbeat.len() as u64;
+        }
+        Ok(written)
+    }
+}
+
+impl Drop for EventStreamSubscription {
+    fn drop(&mut self) {
+        self.hub.unsubscribe(self.id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_event_stream_key_roundtrip() {
+        let keys = vec![
+            "*",
+            "stx",
+            "memtx",
+            "burn_blocks",
+            "microblocks",
+            "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world::print",
+            "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world.hello-token",
+        ];
+        for key in keys.into_iter() {
+            let parsed = EventStreamKey::from_string(key).unwrap();
+            assert_eq!(parsed.to_string(), key);
+        }
+
+        assert!(EventStreamKey::from_string("blocks").is_none());
+        assert!(EventStreamKey::from_string("not-a-principal.foo::bar").is_none());
+        assert!(EventStreamKey::from_string("a::b::c").is_none());
+    }
+
+    #[test]
+    fn test_event_stream_hub_publish() {
+        let hub = EventStreamHub::new(2, 16);
+        let mut mempool_sub = hub
+            .subscribe(vec![EventStreamKey::MemPoolTransactions])
+            .unwrap();
+        let mut any_sub = hub.subscribe(vec![EventStreamKey::AnyEvent]).unwrap();
+
+        // at capacity
+        assert!(hub.subscribe(vec![EventStreamKey::AnyEvent]).is_none());
+        assert_eq!(hub.num_subscribers(), 2);
+
+        hub.publish("new_mempool_tx", |_keys| Some(json!(["0x00"])));
+        hub.publish("new_burn_block", |keys| {
+            if keys.contains(&EventStreamKey::AnyEvent) {
+                Some(json!({"burn_block_height": 1}))
+            } else {
+                None
+            }
+        });
+
+        let mut buf = vec![];
+        mempool_sub.stream_to(&mut buf, 4096).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "event: new_mempool_tx\ndata: [\"0x00\"]\n\n"
+        );
+
+        let mut buf = vec![];
+        any_sub.stream_to(&mut buf, 4096).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "event: new_mempool_tx\ndata: [\"0x00\"]\n\nevent: new_burn_block\ndata: {\"burn_block_height\":1}\n\n"
+        );
+
+        // nothing more to send
+        let mut buf = vec![];
+        assert_eq!(any_sub.stream_to(&mut buf, 4096).unwrap(), 0);
+
+        // dropping a subscription frees up a slot
+        drop(mempool_sub);
+        assert_eq!(hub.num_subscribers(), 1);
+        assert!(hub.subscribe(vec![EventStreamKey::AnyEvent]).is_some());
+    }
+
+    #[test]
+    fn test_event_stream_overflow() {
+        let hub = EventStreamHub::new(1, 2);
+        let mut sub = hub.subscribe(vec![EventStreamKey::AnyEvent]).unwrap();
+        for i in 0..3 {
+            hub.publish("new_mempool_tx", |_keys| Some(json!([i])));
+        }
+
+        let mut buf = vec![];
+        assert!(sub.stream_to(&mut buf, 4096).is_err());
+        assert_eq!(buf.len(), 0);
+    }
+}
@@ -41,10 +41,14 @@ use chainstate::burn::ConsensusHash;
 use chainstate::stacks::{StacksBlock, StacksMicroblock, StacksPublicKey, StacksTransaction};
 use deps::httparse;
 use net::atlas::Attachment;
+use net::event_stream::EventStreamKey;
+use net::AccountHistoryResponse;
 use net::CallReadOnlyRequestBody;
 use net::ClientError;
+use net::ConfirmedTransactionResponse;
 use net::Error as net_error;
 use net::Error::ClarityError;
+use net::EventsResponse;
 use net::HttpContentType;
 use net::HttpRequestMetadata;
 use net::HttpRequestPreamble;
@@ -58,6 +62,7 @@ use net::NeighborAddress;
 use net::PeerAddress;
 use net::PeerHost;
 use net::ProtocolFamily;
+use net::SimulateTransactionResponse;
 use net::StacksHttpMessage;
 use net::StacksHttpPreamble;
 use net::UnconfirmedTransactionResponse;
@@ -90,10 +95,33 @@ use crate::codec::{
 use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockId};
 
 use super::FeeRateEstimateRequestBody;
+use super::{AdminBanPeerRequestBody, AdminDropTransactionsRequestBody};
 
 lazy_static! {
     static ref PATH_GETINFO: Regex = Regex::new(r#"^/v2/info$"#).unwrap();
     static ref PATH_GETPOXINFO: Regex = Regex::new(r#"^/v2/pox$"#).unwrap();
+    static ref PATH_GET_REWARD_SET: Regex =
+        Regex::new(r#"^/v2/pox/reward_set/([0-9]{1,20})$"#).unwrap();
+    static ref PATH_GET_STACKER_INFO: Regex = Regex::new(&format!(
+        "^/v2/pox/stackers/(?P<principal>{})$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_BNS_NAME: Regex = Regex::new(
+        r#"^/v2/bns/names/(?P<name>[a-z0-9_-]{1,48})\.(?P<namespace>[a-z0-9_-]{1,20})$"#
+    )
+    .unwrap();
+    static ref PATH_GET_BNS_ZONEFILE: Regex = Regex::new(
+        r#"^/v2/bns/names/(?P<name>[a-z0-9_-]{1,48})\.(?P<namespace>[a-z0-9_-]{1,20})/zonefile$"#
+    )
+    .unwrap();
+    static ref PATH_GET_BNS_NAMESPACE: Regex =
+        Regex::new(r#"^/v2/bns/namespaces/(?P<namespace>[a-z0-9_-]{1,20})$"#).unwrap();
+    static ref PATH_GET_BNS_OWNER_NAMES: Regex = Regex::new(&format!(
+        "^/v2/bns/owners/(?P<principal>{})$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
     static ref PATH_GETNEIGHBORS: Regex = Regex::new(r#"^/v2/neighbors$"#).unwrap();
     static ref PATH_GETBLOCK: Regex = Regex::new(r#"^/v2/blocks/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_GETMICROBLOCKS_INDEXED: Regex =
@@ -104,7 +132,11 @@ lazy_static! {
         Regex::new(r#"^/v2/microblocks/unconfirmed/([0-9a-f]{64})/([0-9]{1,5})$"#).unwrap();
     static ref PATH_GETTRANSACTION_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/transactions/unconfirmed/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETTRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_POSTTRANSACTION: Regex = Regex::new(r#"^/v2/transactions$"#).unwrap();
+    static ref PATH_POST_SIMULATE_TRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/simulate$"#).unwrap();
     static ref PATH_POST_FEE_RATE_ESIMATE: Regex = Regex::new(r#"^/v2/fees/transaction$"#).unwrap();
     static ref PATH_POSTBLOCK: Regex = Regex::new(r#"^/v2/blocks/upload/([0-9a-f]{40})$"#).unwrap();
     static ref PATH_POSTMICROBLOCK: Regex = Regex::new(r#"^/v2/microblocks$"#).unwrap();
@@ -113,6 +145,11 @@ lazy_static! {
         *PRINCIPAL_DATA_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_ACCOUNT_HISTORY: Regex = Regex::new(&format!(
+        "^/v2/accounts/(?P<principal>{})/history/(?P<start>[0-9]{{1,20}})/(?P<end>[0-9]{{1,20}})$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
     static ref PATH_GET_MAP_ENTRY: Regex = Regex::new(&format!(
         "^/v2/map_entry/(?P<address>{})/(?P<contract>{})/(?P<map>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
@@ -142,7 +179,29 @@ lazy_static! {
     static ref PATH_GET_ATTACHMENTS_INV: Regex = Regex::new("^/v2/attachments/inv$").unwrap();
     static ref PATH_GET_ATTACHMENT: Regex =
         Regex::new(r#"^/v2/attachments/([0-9a-f]{40})$"#).unwrap();
+    static ref PATH_GET_EVENT_STREAM: Regex = Regex::new("^/v2/events/stream$").unwrap();
+    static ref PATH_GET_CONTRACT_EVENTS: Regex = Regex::new(&format!(
+        "^/v2/events/contract/(?P<address>{})/(?P<contract>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_PRINCIPAL_EVENTS: Regex = Regex::new(&format!(
+        "^/v2/events/principal/(?P<principal>{})$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_BLOCK_RANGE_EVENTS: Regex =
+        Regex::new(r#"^/v2/events/blocks/([0-9]{1,20})/([0-9]{1,20})$"#).unwrap();
     static ref PATH_OPTIONS_WILDCARD: Regex = Regex::new("^/v2/.{0,4096}$").unwrap();
+    static ref PATH_ADMIN_GET_PEERS: Regex = Regex::new("^/v2/admin/peers$").unwrap();
+    static ref PATH_ADMIN_BAN_PEER: Regex = Regex::new("^/v2/admin/peers/ban$").unwrap();
+    static ref PATH_ADMIN_GET_NEIGHBOR_STATS: Regex =
+        Regex::new("^/v2/admin/peers/stats$").unwrap();
+    static ref PATH_ADMIN_DROP_TRANSACTIONS: Regex =
+        Regex::new("^/v2/admin/mempool/drop$").unwrap();
+    static ref PATH_ADMIN_MEMPOOL_GC: Regex = Regex::new("^/v2/admin/mempool/gc$").unwrap();
+    static ref PATH_ADMIN_SET_MINING: Regex =
+        Regex::new("^/v2/admin/mining/(pause|resume)$").unwrap();
 }
 
 /// HTTP headers that we really care about
@@ -616,6 +675,15 @@ impl<'a, 'state, W: Write> HttpChunkedTransferWriter<'a, 'state, W> {
         to_copy
     }
 
+    /// Send out any buffered chunk data without ending the stream.  Used by long-lived streams,
+    /// whose data should reach the client as soon as it is written.
+    pub fn flush_buffered(&mut self) -> io::Result<usize> {
+        if self.state.corked || self.state.chunk_buf.len() == 0 {
+            return Ok(0);
+        }
+        self.flush_chunk()
+    }
+
     pub fn cork(&mut self) -> () {
         // block future flushes from sending trailing empty chunks -- we're done sending
         self.state.corked = true;
@@ -852,6 +920,17 @@ fn empty_headers<W: Write>(_fd: &mut W) -> Result<(), codec_error> {
     Ok(())
 }
 
+fn admin_auth_headers<W: Write>(
+    fd: &mut W,
+    auth_token: Option<&String>,
+) -> Result<(), codec_error> {
+    if let Some(auth_token) = auth_token {
+        fd.write_all(format!("Authorization: Bearer {}\r\n", auth_token).as_bytes())
+            .map_err(codec_error::WriteError)?;
+    }
+    Ok(())
+}
+
 fn keep_alive_headers<W: Write>(fd: &mut W, md: &HttpResponseMetadata) -> Result<(), codec_error> {
     match md.client_version {
         HttpVersion::Http10 => {
@@ -1453,6 +1532,36 @@ impl HttpRequestType {
         )] = &[
             ("GET", &PATH_GETINFO, &HttpRequestType::parse_getinfo),
             ("GET", &PATH_GETPOXINFO, &HttpRequestType::parse_getpoxinfo),
+            (
+                "GET",
+                &PATH_GET_REWARD_SET,
+                &HttpRequestType::parse_get_reward_set,
+            ),
+            (
+                "GET",
+                &PATH_GET_STACKER_INFO,
+                &HttpRequestType::parse_get_stacker_info,
+            ),
+            (
+                "GET",
+                &PATH_GET_BNS_NAME,
+                &HttpRequestType::parse_get_bns_name,
+            ),
+            (
+                "GET",
+                &PATH_GET_BNS_ZONEFILE,
+                &HttpRequestType::parse_get_bns_zonefile,
+            ),
+            (
+                "GET",
+                &PATH_GET_BNS_NAMESPACE,
+                &HttpRequestType::parse_get_bns_namespace,
+            ),
+            (
+                "GET",
+                &PATH_GET_BNS_OWNER_NAMES,
+                &HttpRequestType::parse_get_bns_owner_names,
+            ),
             (
                 "GET",
                 &PATH_GETNEIGHBORS,
@@ -1479,6 +1588,11 @@ impl HttpRequestType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpRequestType::parse_gettransaction_unconfirmed,
             ),
+            (
+                "GET",
+                &PATH_GETTRANSACTION,
+                &HttpRequestType::parse_gettransaction,
+            ),
             (
                 "POST",
                 &PATH_POST_FEE_RATE_ESIMATE,
@@ -1489,6 +1603,11 @@ impl HttpRequestType {
                 &PATH_POSTTRANSACTION,
                 &HttpRequestType::parse_posttransaction,
             ),
+            (
+                "POST",
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpRequestType::parse_simulate_transaction,
+            ),
             ("POST", &PATH_POSTBLOCK, &HttpRequestType::parse_postblock),
             (
                 "POST",
@@ -1500,6 +1619,11 @@ impl HttpRequestType {
                 &PATH_GET_ACCOUNT,
                 &HttpRequestType::parse_get_account,
             ),
+            (
+                "GET",
+                &PATH_GET_ACCOUNT_HISTORY,
+                &HttpRequestType::parse_get_account_history,
+            ),
             (
                 "POST",
                 &PATH_GET_MAP_ENTRY,
@@ -1545,8 +1669,85 @@ impl HttpRequestType {
                 &PATH_GET_ATTACHMENTS_INV,
                 &HttpRequestType::parse_get_attachments_inv,
             ),
+            (
+                "GET",
+                &PATH_GET_EVENT_STREAM,
+                &HttpRequestType::parse_get_event_stream,
+            ),
+            (
+                "GET",
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpRequestType::parse_get_contract_events,
+            ),
+            (
+                "GET",
+                &PATH_GET_PRINCIPAL_EVENTS,
+                &HttpRequestType::parse_get_principal_events,
+            ),
+            (
+                "GET",
+                &PATH_GET_BLOCK_RANGE_EVENTS,
+                &HttpRequestType::parse_get_block_range_events,
+            ),
+        ];
+
+        let ADMIN_REQUEST_METHODS: &[(
+            &str,
+            &Regex,
+            &dyn Fn(
+                &mut StacksHttp,
+                &HttpRequestPreamble,
+                &Captures,
+                Option<&str>,
+                &mut R,
+            ) -> Result<HttpRequestType, net_error>,
+        )] = &[
+            (
+                "GET",
+                &PATH_ADMIN_GET_PEERS,
+                &HttpRequestType::parse_admin_get_peers,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_BAN_PEER,
+                &HttpRequestType::parse_admin_ban_peer,
+            ),
+            (
+                "GET",
+                &PATH_ADMIN_GET_NEIGHBOR_STATS,
+                &HttpRequestType::parse_admin_get_neighbor_stats,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_DROP_TRANSACTIONS,
+                &HttpRequestType::parse_admin_drop_transactions,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_MEMPOOL_GC,
+                &HttpRequestType::parse_admin_mempool_gc,
+            ),
+            (
+                "POST",
+                &PATH_ADMIN_SET_MINING,
+                &HttpRequestType::parse_admin_set_mining,
+            ),
         ];
 
+        // the admin listener serves only the admin API, and only to clients that present its
+        // bearer token.  The public listener never serves the admin API.
+        let request_methods = match protocol.admin_auth_token {
+            Some(ref auth_token) => {
+                if !HttpRequestType::is_admin_authorized(preamble, auth_token) {
+                    return Err(net_error::ClientError(ClientError::Unauthorized(
+                        "Missing or invalid bearer token".to_string(),
+                    )));
+                }
+                ADMIN_REQUEST_METHODS
+            }
+            None => REQUEST_METHODS,
+        };
+
         // use url::Url to parse path and query string
         //   Url will refuse to parse just a path, so create a dummy URL
         let local_url = format!("http://local{}", &preamble.path);
@@ -1560,7 +1761,7 @@ impl HttpRequestType {
             )
         })?;
 
-        for (verb, regex, parser) in REQUEST_METHODS.iter() {
+        for (verb, regex, parser) in request_methods.iter() {
             match HttpRequestType::try_parse(
                 protocol,
                 verb,
@@ -1631,6 +1832,156 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_reward_set<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetRewardSet".to_string(),
+            ));
+        }
+
+        let reward_cycle = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to reward cycle".to_string(),
+            ))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| net_error::DeserializeError("Failed to parse reward cycle".to_string()))?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetRewardSet(
+            HttpRequestMetadata::from_preamble(preamble),
+            reward_cycle,
+            tip,
+        ))
+    }
+
+    fn parse_get_stacker_info<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetStackerInfo".to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse stacker principal".into())
+        })?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetStackerInfo(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            tip,
+        ))
+    }
+
+    fn parse_get_bns_name<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBnsName".to_string(),
+            ));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetBnsName(
+            HttpRequestMetadata::from_preamble(preamble),
+            captures["name"].to_string(),
+            captures["namespace"].to_string(),
+            tip,
+        ))
+    }
+
+    fn parse_get_bns_zonefile<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBnsZonefile".to_string(),
+            ));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetBnsZonefile(
+            HttpRequestMetadata::from_preamble(preamble),
+            captures["name"].to_string(),
+            captures["namespace"].to_string(),
+            tip,
+        ))
+    }
+
+    fn parse_get_bns_namespace<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBnsNamespace".to_string(),
+            ));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetBnsNamespace(
+            HttpRequestMetadata::from_preamble(preamble),
+            captures["namespace"].to_string(),
+            tip,
+        ))
+    }
+
+    fn parse_get_bns_owner_names<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBnsOwnerNames".to_string(),
+            ));
+        }
+
+        let owner = PrincipalData::parse(&captures["principal"])
+            .map_err(|_e| net_error::DeserializeError("Failed to parse owner principal".into()))?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetBnsOwnerNames(
+            HttpRequestMetadata::from_preamble(preamble),
+            owner,
+            tip,
+        ))
+    }
+
     fn parse_getneighbors<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1733,58 +2084,271 @@ impl HttpRequestType {
         ))
     }
 
-    fn parse_get_map_entry<R: Read>(
+    fn parse_get_account_history<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
         captures: &Captures,
         query: Option<&str>,
-        fd: &mut R,
+        _fd: &mut R,
     ) -> Result<HttpRequestType, net_error> {
-        let content_len = preamble.get_content_length();
-        if !(content_len > 0 && content_len < (BOUND_VALUE_SERIALIZATION_HEX)) {
-            return Err(net_error::DeserializeError(format!(
-                "Invalid Http request: invalid body length for GetMapEntry ({})",
-                content_len
-            )));
-        }
-
-        if preamble.content_type != Some(HttpContentType::JSON) {
+        if preamble.get_content_length() != 0 {
             return Err(net_error::DeserializeError(
-                "Invalid content-type: expected application/json".into(),
+                "Invalid Http request: expected 0-length body for GetAccountHistory".to_string(),
             ));
         }
 
-        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
-            net_error::DeserializeError("Failed to parse contract address".into())
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse account principal".into())
         })?;
-        let contract_name = ContractName::try_from(captures["contract"].to_string())
-            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
-        let map_name = ClarityName::try_from(captures["map"].to_string())
-            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
-
-        let value_hex: String = serde_json::from_reader(fd)
-            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
-
-        let value = Value::try_deserialize_hex_untyped(&value_hex)
-            .map_err(|_e| net_error::DeserializeError("Failed to deserialize key value".into()))?;
+        let start_height = captures["start"]
+            .parse::<u64>()
+            .map_err(|_| net_error::DeserializeError("Failed to parse start height".to_string()))?;
+        let end_height = captures["end"]
+            .parse::<u64>()
+            .map_err(|_| net_error::DeserializeError("Failed to parse end height".to_string()))?;
 
         let with_proof = HttpRequestType::get_proof_query(query);
         let tip = HttpRequestType::get_chain_tip_query(query);
 
-        Ok(HttpRequestType::GetMapEntry(
+        Ok(HttpRequestType::GetAccountHistory(
             HttpRequestMetadata::from_preamble(preamble),
-            contract_addr,
-            contract_name,
-            map_name,
-            value,
+            principal,
+            start_height,
+            end_height,
             tip,
             with_proof,
         ))
     }
 
-    fn parse_call_read_only<R: Read>(
-        protocol: &mut StacksHttp,
-        preamble: &HttpRequestPreamble,
+    /// Does this request carry the admin listener's bearer token?
+    fn is_admin_authorized(preamble: &HttpRequestPreamble, auth_token: &str) -> bool {
+        let presented = match preamble.headers.get("authorization") {
+            Some(value) if value.starts_with("Bearer ") => value[7..].trim().as_bytes(),
+            _ => return false,
+        };
+        let expected = auth_token.as_bytes();
+        if presented.len() != expected.len() {
+            return false;
+        }
+        // compare in constant time, so the token can't be guessed byte-by-byte
+        presented
+            .iter()
+            .zip(expected.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+
+    fn parse_admin_get_peers<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for AdminGetPeers".to_string(),
+            ));
+        }
+        Ok(HttpRequestType::AdminGetPeers(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_admin_ban_peer<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for AdminBanPeer ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: AdminBanPeerRequestBody = serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+        })?;
+
+        Ok(HttpRequestType::AdminBanPeer(
+            HttpRequestMetadata::from_preamble(preamble),
+            body.ip,
+            body.port,
+            body.duration,
+        ))
+    }
+
+    fn parse_admin_get_neighbor_stats<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for AdminGetNeighborStats"
+                    .to_string(),
+            ));
+        }
+        Ok(HttpRequestType::AdminGetNeighborStats(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_admin_drop_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for AdminDropTransactions ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: AdminDropTransactionsRequestBody =
+            serde_json::from_reader(bound_fd).map_err(|e| {
+                net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+            })?;
+
+        let mut txids = vec![];
+        for txid_hex in body.txids.iter() {
+            let txid_hex = if txid_hex.starts_with("0x") {
+                &txid_hex[2..]
+            } else {
+                &txid_hex
+            };
+            let txid = Txid::from_hex(txid_hex)
+                .map_err(|_e| net_error::DeserializeError("Failed to parse txid".to_string()))?;
+            txids.push(txid);
+        }
+
+        Ok(HttpRequestType::AdminDropTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            txids,
+        ))
+    }
+
+    fn parse_admin_mempool_gc<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for AdminMempoolGarbageCollect"
+                    .to_string(),
+            ));
+        }
+        Ok(HttpRequestType::AdminMempoolGarbageCollect(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_admin_set_mining<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for AdminSetMiningPaused".to_string(),
+            ));
+        }
+        let paused = match captures.get(1).map(|m| m.as_str()) {
+            Some("pause") => true,
+            Some("resume") => false,
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Failed to match path to mining action".to_string(),
+                ));
+            }
+        };
+        Ok(HttpRequestType::AdminSetMiningPaused(
+            HttpRequestMetadata::from_preamble(preamble),
+            paused,
+        ))
+    }
+
+    fn parse_get_map_entry<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < (BOUND_VALUE_SERIALIZATION_HEX)) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for GetMapEntry ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".into(),
+            ));
+        }
+
+        let contract_addr = StacksAddress::from_string(&captures["address"]).ok_or_else(|| {
+            net_error::DeserializeError("Failed to parse contract address".into())
+        })?;
+        let contract_name = ContractName::try_from(captures["contract"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+        let map_name = ClarityName::try_from(captures["map"].to_string())
+            .map_err(|_e| net_error::DeserializeError("Failed to parse contract name".into()))?;
+
+        let value_hex: String = serde_json::from_reader(fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        let value = Value::try_deserialize_hex_untyped(&value_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to deserialize key value".into()))?;
+
+        let with_proof = HttpRequestType::get_proof_query(query);
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetMapEntry(
+            HttpRequestMetadata::from_preamble(preamble),
+            contract_addr,
+            contract_name,
+            map_name,
+            value,
+            tip,
+            with_proof,
+        ))
+    }
+
+    fn parse_call_read_only<R: Read>(
+        protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
         captures: &Captures,
         query: Option<&str>,
         fd: &mut R,
@@ -2099,6 +2663,35 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_gettransaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetTransaction".to_string(),
+            ));
+        }
+
+        let txid_hex = regex
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to txid group".to_string(),
+            ))?
+            .as_str();
+
+        let txid = Txid::from_hex(&txid_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to decode txid hex".to_string()))?;
+
+        Ok(HttpRequestType::GetTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            txid,
+        ))
+    }
+
     fn parse_post_fee_rate_estimate<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2196,11 +2789,9 @@ impl HttpRequestType {
         }
     }
 
-    fn parse_posttransaction_octets<R: Read>(
-        preamble: &HttpRequestPreamble,
-        fd: &mut R,
-    ) -> Result<HttpRequestType, net_error> {
-        let tx = StacksTransaction::consensus_deserialize(fd).map_err(|e| {
+    /// Decode a posted transaction, reporting malformed transactions as client errors
+    fn read_posted_transaction<R: Read>(fd: &mut R) -> Result<StacksTransaction, net_error> {
+        StacksTransaction::consensus_deserialize(fd).map_err(|e| {
             if let codec_error::DeserializeError(msg) = e {
                 net_error::ClientError(ClientError::Message(format!(
                     "Failed to deserialize posted transaction: {}",
@@ -2209,7 +2800,14 @@ impl HttpRequestType {
             } else {
                 e.into()
             }
-        })?;
+        })
+    }
+
+    fn parse_posttransaction_octets<R: Read>(
+        preamble: &HttpRequestPreamble,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let tx = HttpRequestType::read_posted_transaction(fd)?;
         Ok(HttpRequestType::PostTransaction(
             HttpRequestMetadata::from_preamble(preamble),
             tx,
@@ -2227,16 +2825,7 @@ impl HttpRequestType {
         let tx = {
             let tx_bytes = hex_bytes(&body.tx)
                 .map_err(|_e| net_error::DeserializeError("Failed to parse tx".into()))?;
-            StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|e| {
-                if let codec_error::DeserializeError(msg) = e {
-                    net_error::ClientError(ClientError::Message(format!(
-                        "Failed to deserialize posted transaction: {}",
-                        msg
-                    )))
-                } else {
-                    e.into()
-                }
-            })
+            HttpRequestType::read_posted_transaction(&mut &tx_bytes[..])
         }?;
 
         let attachment = match body.attachment {
@@ -2256,6 +2845,59 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_simulate_transaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected non-zero-length body for SimulateTransaction"
+                    .to_string(),
+            ));
+        }
+
+        if preamble.get_content_length() > MAX_PAYLOAD_LEN {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: SimulateTransaction body is too big".to_string(),
+            ));
+        }
+
+        let mut bound_fd = BoundReader::from_reader(fd, preamble.get_content_length() as u64);
+
+        // same encodings as PostTransaction, but any attachment is ignored
+        let tx = match preamble.content_type {
+            None => {
+                return Err(net_error::DeserializeError(
+                    "Missing Content-Type for transaction".to_string(),
+                ));
+            }
+            Some(HttpContentType::Bytes) => {
+                HttpRequestType::read_posted_transaction(&mut bound_fd)?
+            }
+            Some(HttpContentType::JSON) => {
+                let body: PostTransactionRequestBody = serde_json::from_reader(&mut bound_fd)
+                    .map_err(|_e| net_error::DeserializeError("Failed to parse body".into()))?;
+                let tx_bytes = hex_bytes(&body.tx)
+                    .map_err(|_e| net_error::DeserializeError("Failed to parse tx".into()))?;
+                HttpRequestType::read_posted_transaction(&mut &tx_bytes[..])?
+            }
+            _ => {
+                return Err(net_error::DeserializeError(
+                    "Wrong Content-Type for transaction; expected application/json".to_string(),
+                ));
+            }
+        };
+
+        Ok(HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            tx,
+            HttpRequestType::get_chain_tip_query(query),
+        ))
+    }
+
     fn parse_postblock<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2459,6 +3101,145 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_event_stream<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let mut keys = vec![];
+        if let Some(query) = query {
+            for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+                if key != "keys" {
+                    continue;
+                }
+                for raw_key in value.split(",") {
+                    match EventStreamKey::from_string(raw_key) {
+                        Some(event_key) => keys.push(event_key),
+                        None => {
+                            return Err(net_error::DeserializeError(format!(
+                                "Invalid Http request: unrecognized event key '{}'",
+                                raw_key
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expecting at least one event key".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::GetEventStream(
+            HttpRequestMetadata::from_preamble(preamble),
+            keys,
+        ))
+    }
+
+    /// get the optional event log paging query arguments (`before_height` and `limit`).
+    /// Take the first value we can parse for each.
+    fn get_event_page_query(query: Option<&str>) -> (Option<u64>, Option<u32>) {
+        let mut before_height = None;
+        let mut limit = None;
+        if let Some(query_string) = query {
+            for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                if key == "before_height" && before_height.is_none() {
+                    before_height = value.parse::<u64>().ok();
+                } else if key == "limit" && limit.is_none() {
+                    limit = value.parse::<u32>().ok();
+                }
+            }
+        }
+        (before_height, limit)
+    }
+
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let (before_height, limit) = HttpRequestType::get_event_page_query(query);
+        HttpRequestType::parse_get_contract_arguments(preamble, captures).map(
+            |(preamble, addr, name)| {
+                HttpRequestType::GetContractEvents(preamble, addr, name, before_height, limit)
+            },
+        )
+    }
+
+    fn parse_get_principal_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetPrincipalEvents".to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"])
+            .map_err(|_e| net_error::DeserializeError("Failed to parse principal".into()))?;
+        let (before_height, limit) = HttpRequestType::get_event_page_query(query);
+
+        Ok(HttpRequestType::GetPrincipalEvents(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            before_height,
+            limit,
+        ))
+    }
+
+    fn parse_get_block_range_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBlockRangeEvents".to_string(),
+            ));
+        }
+
+        let start_height = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to start height".to_string(),
+            ))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| net_error::DeserializeError("Failed to parse start height".to_string()))?;
+        let end_height = captures
+            .get(2)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to end height".to_string(),
+            ))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| net_error::DeserializeError("Failed to parse end height".to_string()))?;
+
+        Ok(HttpRequestType::GetBlockRangeEvents(
+            HttpRequestMetadata::from_preamble(preamble),
+            start_height,
+            end_height,
+        ))
+    }
+
     fn parse_options_preflight<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2476,13 +3257,21 @@ impl HttpRequestType {
         match *self {
             HttpRequestType::GetInfo(ref md) => md,
             HttpRequestType::GetPoxInfo(ref md, _) => md,
+            HttpRequestType::GetRewardSet(ref md, ..) => md,
+            HttpRequestType::GetStackerInfo(ref md, ..) => md,
+            HttpRequestType::GetBnsName(ref md, ..) => md,
+            HttpRequestType::GetBnsZonefile(ref md, ..) => md,
+            HttpRequestType::GetBnsNamespace(ref md, ..) => md,
+            HttpRequestType::GetBnsOwnerNames(ref md, ..) => md,
             HttpRequestType::GetNeighbors(ref md) => md,
             HttpRequestType::GetBlock(ref md, _) => md,
             HttpRequestType::GetMicroblocksIndexed(ref md, _) => md,
             HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
+            HttpRequestType::GetTransaction(ref md, _) => md,
             HttpRequestType::PostTransaction(ref md, _, _) => md,
+            HttpRequestType::SimulateTransaction(ref md, ..) => md,
             HttpRequestType::PostBlock(ref md, ..) => md,
             HttpRequestType::PostMicroblock(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
@@ -2496,6 +3285,17 @@ impl HttpRequestType {
             HttpRequestType::GetAttachmentsInv(ref md, ..) => md,
             HttpRequestType::GetAttachment(ref md, ..) => md,
             HttpRequestType::FeeRateEstimate(ref md, _, _) => md,
+            HttpRequestType::GetEventStream(ref md, _) => md,
+            HttpRequestType::GetContractEvents(ref md, ..) => md,
+            HttpRequestType::GetPrincipalEvents(ref md, ..) => md,
+            HttpRequestType::GetBlockRangeEvents(ref md, ..) => md,
+            HttpRequestType::GetAccountHistory(ref md, ..) => md,
+            HttpRequestType::AdminGetPeers(ref md) => md,
+            HttpRequestType::AdminBanPeer(ref md, ..) => md,
+            HttpRequestType::AdminGetNeighborStats(ref md) => md,
+            HttpRequestType::AdminDropTransactions(ref md, _) => md,
+            HttpRequestType::AdminMempoolGarbageCollect(ref md) => md,
+            HttpRequestType::AdminSetMiningPaused(ref md, _) => md,
             HttpRequestType::ClientError(ref md, ..) => md,
         }
     }
@@ -2504,13 +3304,21 @@ impl HttpRequestType {
         match *self {
             HttpRequestType::GetInfo(ref mut md) => md,
             HttpRequestType::GetPoxInfo(ref mut md, _) => md,
+            HttpRequestType::GetRewardSet(ref mut md, ..) => md,
+            HttpRequestType::GetStackerInfo(ref mut md, ..) => md,
+            HttpRequestType::GetBnsName(ref mut md, ..) => md,
+            HttpRequestType::GetBnsZonefile(ref mut md, ..) => md,
+            HttpRequestType::GetBnsNamespace(ref mut md, ..) => md,
+            HttpRequestType::GetBnsOwnerNames(ref mut md, ..) => md,
             HttpRequestType::GetNeighbors(ref mut md) => md,
             HttpRequestType::GetBlock(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksIndexed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
+            HttpRequestType::GetTransaction(ref mut md, _) => md,
             HttpRequestType::PostTransaction(ref mut md, _, _) => md,
+            HttpRequestType::SimulateTransaction(ref mut md, ..) => md,
             HttpRequestType::PostBlock(ref mut md, ..) => md,
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
@@ -2524,6 +3332,17 @@ impl HttpRequestType {
             HttpRequestType::GetAttachmentsInv(ref mut md, ..) => md,
             HttpRequestType::GetAttachment(ref mut md, ..) => md,
             HttpRequestType::FeeRateEstimate(ref mut md, _, _) => md,
+            HttpRequestType::GetEventStream(ref mut md, _) => md,
+            HttpRequestType::GetContractEvents(ref mut md, ..) => md,
+            HttpRequestType::GetPrincipalEvents(ref mut md, ..) => md,
+            HttpRequestType::GetBlockRangeEvents(ref mut md, ..) => md,
+            HttpRequestType::GetAccountHistory(ref mut md, ..) => md,
+            HttpRequestType::AdminGetPeers(ref mut md) => md,
+            HttpRequestType::AdminBanPeer(ref mut md, ..) => md,
+            HttpRequestType::AdminGetNeighborStats(ref mut md) => md,
+            HttpRequestType::AdminDropTransactions(ref mut md, _) => md,
+            HttpRequestType::AdminMempoolGarbageCollect(ref mut md) => md,
+            HttpRequestType::AdminSetMiningPaused(ref mut md, _) => md,
             HttpRequestType::ClientError(ref mut md, ..) => md,
         }
     }
@@ -2538,6 +3357,21 @@ impl HttpRequestType {
         }
     }
 
+    fn make_event_page_query_string(before_height: Option<u64>, limit: Option<u32>) -> String {
+        let mut args = vec![];
+        if let Some(before_height) = before_height {
+            args.push(format!("before_height={}", before_height));
+        }
+        if let Some(limit) = limit {
+            args.push(format!("limit={}", limit));
+        }
+        if args.len() > 0 {
+            format!("?{}", args.join("&"))
+        } else {
+            "".to_string()
+        }
+    }
+
     pub fn request_path(&self) -> String {
         match self {
             HttpRequestType::GetInfo(_md) => "/v2/info".to_string(),
@@ -2545,6 +3379,38 @@ impl HttpRequestType {
                 "/v2/pox{}",
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::GetRewardSet(_md, reward_cycle, tip_opt) => format!(
+                "/v2/pox/reward_set/{}{}",
+                reward_cycle,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetStackerInfo(_md, principal, tip_opt) => format!(
+                "/v2/pox/stackers/{}{}",
+                principal,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetBnsName(_md, name, namespace, tip_opt) => format!(
+                "/v2/bns/names/{}.{}{}",
+                name,
+                namespace,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetBnsZonefile(_md, name, namespace, tip_opt) => format!(
+                "/v2/bns/names/{}.{}/zonefile{}",
+                name,
+                namespace,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetBnsNamespace(_md, namespace, tip_opt) => format!(
+                "/v2/bns/namespaces/{}{}",
+                namespace,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetBnsOwnerNames(_md, owner, tip_opt) => format!(
+                "/v2/bns/owners/{}{}",
+                owner,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetNeighbors(_md) => "/v2/neighbors".to_string(),
             HttpRequestType::GetBlock(_md, block_hash) => {
                 format!("/v2/blocks/{}", block_hash.to_hex())
@@ -2563,7 +3429,12 @@ impl HttpRequestType {
             HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
                 format!("/v2/transactions/unconfirmed/{}", txid)
             }
+            HttpRequestType::GetTransaction(_md, txid) => format!("/v2/transactions/{}", txid),
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
+            HttpRequestType::SimulateTransaction(_md, _, tip_opt) => format!(
+                "/v2/transactions/simulate{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::PostBlock(_md, ch, ..) => format!("/v2/blocks/upload/{}", &ch),
             HttpRequestType::PostMicroblock(_md, _, tip_opt) => format!(
                 "/v2/microblocks{}",
@@ -2658,6 +3529,56 @@ impl HttpRequestType {
                 format!("/v2/attachments/{}", to_hex(&content_hash.0[..]))
             }
             HttpRequestType::FeeRateEstimate(_, _, _) => self.get_path().to_string(),
+            HttpRequestType::GetEventStream(_md, keys) => format!(
+                "/v2/events/stream?keys={}",
+                keys.iter()
+                    .map(|key| key.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            HttpRequestType::GetContractEvents(
+                _md,
+                contract_addr,
+                contract_name,
+                before_height,
+                limit,
+            ) => format!(
+                "/v2/events/contract/{}/{}{}",
+                contract_addr,
+                contract_name.as_str(),
+                HttpRequestType::make_event_page_query_string(*before_height, *limit)
+            ),
+            HttpRequestType::GetPrincipalEvents(_md, principal, before_height, limit) => format!(
+                "/v2/events/principal/{}{}",
+                principal,
+                HttpRequestType::make_event_page_query_string(*before_height, *limit)
+            ),
+            HttpRequestType::GetBlockRangeEvents(_md, start_height, end_height) => {
+                format!("/v2/events/blocks/{}/{}", start_height, end_height)
+            }
+            HttpRequestType::GetAccountHistory(
+                _md,
+                principal,
+                start_height,
+                end_height,
+                tip_opt,
+                with_proof,
+            ) => format!(
+                "/v2/accounts/{}/history/{}/{}{}",
+                principal,
+                start_height,
+                end_height,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), *with_proof)
+            ),
+            HttpRequestType::AdminGetPeers(_md) => "/v2/admin/peers".to_string(),
+            HttpRequestType::AdminBanPeer(..) => "/v2/admin/peers/ban".to_string(),
+            HttpRequestType::AdminGetNeighborStats(_md) => "/v2/admin/peers/stats".to_string(),
+            HttpRequestType::AdminDropTransactions(..) => "/v2/admin/mempool/drop".to_string(),
+            HttpRequestType::AdminMempoolGarbageCollect(_md) => "/v2/admin/mempool/gc".to_string(),
+            HttpRequestType::AdminSetMiningPaused(_md, paused) => format!(
+                "/v2/admin/mining/{}",
+                if *paused { "pause" } else { "resume" }
+            ),
             HttpRequestType::ClientError(_md, e) => match e {
                 ClientError::NotFound(path) => path.to_string(),
                 _ => "error path unknown".into(),
@@ -2669,6 +3590,12 @@ impl HttpRequestType {
         match self {
             HttpRequestType::GetInfo(..) => "/v2/info",
             HttpRequestType::GetPoxInfo(..) => "/v2/pox",
+            HttpRequestType::GetRewardSet(..) => "/v2/pox/reward_set/:reward_cycle",
+            HttpRequestType::GetStackerInfo(..) => "/v2/pox/stackers/:principal",
+            HttpRequestType::GetBnsName(..) => "/v2/bns/names/:name",
+            HttpRequestType::GetBnsZonefile(..) => "/v2/bns/names/:name/zonefile",
+            HttpRequestType::GetBnsNamespace(..) => "/v2/bns/namespaces/:namespace",
+            HttpRequestType::GetBnsOwnerNames(..) => "/v2/bns/owners/:principal",
             HttpRequestType::GetNeighbors(..) => "/v2/neighbors",
             HttpRequestType::GetBlock(..) => "/v2/blocks/:hash",
             HttpRequestType::GetMicroblocksIndexed(..) => "/v2/microblocks/:hash",
@@ -2677,7 +3604,9 @@ impl HttpRequestType {
                 "/v2/microblocks/unconfirmed/:hash/:seq"
             }
             HttpRequestType::GetTransactionUnconfirmed(..) => "/v2/transactions/unconfirmed/:txid",
+            HttpRequestType::GetTransaction(..) => "/v2/transactions/:txid",
             HttpRequestType::PostTransaction(..) => "/v2/transactions",
+            HttpRequestType::SimulateTransaction(..) => "/v2/transactions/simulate",
             HttpRequestType::PostBlock(..) => "/v2/blocks/upload/:block",
             HttpRequestType::PostMicroblock(..) => "/v2/microblocks",
             HttpRequestType::GetAccount(..) => "/v2/accounts/:principal",
@@ -2694,11 +3623,57 @@ impl HttpRequestType {
             HttpRequestType::GetAttachment(..) => "/v2/attachments/:hash",
             HttpRequestType::GetIsTraitImplemented(..) => "/v2/traits/:principal/:contract_name",
             HttpRequestType::FeeRateEstimate(_, _, _) => "/v2/fees/transaction",
+            HttpRequestType::GetEventStream(..) => "/v2/events/stream",
+            HttpRequestType::GetContractEvents(..) => {
+                "/v2/events/contract/:principal/:contract_name"
+            }
+            HttpRequestType::GetPrincipalEvents(..) => "/v2/events/principal/:principal",
+            HttpRequestType::GetBlockRangeEvents(..) => "/v2/events/blocks/:start/:end",
+            HttpRequestType::GetAccountHistory(..) => "/v2/accounts/:principal/history/:start/:end",
+            HttpRequestType::AdminGetPeers(..) => "/v2/admin/peers",
+            HttpRequestType::AdminBanPeer(..) => "/v2/admin/peers/ban",
+            HttpRequestType::AdminGetNeighborStats(..) => "/v2/admin/peers/stats",
+            HttpRequestType::AdminDropTransactions(..) => "/v2/admin/mempool/drop",
+            HttpRequestType::AdminMempoolGarbageCollect(..) => "/v2/admin/mempool/gc",
+            HttpRequestType::AdminSetMiningPaused(_, true) => "/v2/admin/mining/pause",
+            HttpRequestType::AdminSetMiningPaused(_, false) => "/v2/admin/mining/resume",
             HttpRequestType::OptionsPreflight(..) | HttpRequestType::ClientError(..) => "/",
         }
     }
 
-    pub fn send<W: Write>(&self, _protocol: &mut StacksHttp, fd: &mut W) -> Result<(), net_error> {
+    /// Send an admin API request with a JSON body
+    fn send_admin_json<W: Write, T: serde::ser::Serialize>(
+        protocol: &StacksHttp,
+        fd: &mut W,
+        md: &HttpRequestMetadata,
+        request_path: &str,
+        request_body: &T,
+    ) -> Result<(), net_error> {
+        let mut request_body_bytes = vec![];
+        serde_json::to_writer(&mut request_body_bytes, request_body).map_err(|e| {
+            net_error::SerializeError(format!(
+                "Failed to serialize admin request to JSON: {:?}",
+                &e
+            ))
+        })?;
+
+        HttpRequestPreamble::new_serialized(
+            fd,
+            &md.version,
+            "POST",
+            request_path,
+            &md.peer,
+            md.keep_alive,
+            Some(request_body_bytes.len() as u32),
+            Some(&HttpContentType::JSON),
+            |fd| admin_auth_headers(fd, protocol.admin_auth_token.as_ref()),
+        )?;
+        fd.write_all(&request_body_bytes)
+            .map_err(net_error::WriteError)?;
+        Ok(())
+    }
+
+    pub fn send<W: Write>(&self, protocol: &mut StacksHttp, fd: &mut W) -> Result<(), net_error> {
         match self {
             HttpRequestType::PostTransaction(md, tx, attachment) => {
                 let mut tx_bytes = vec![];
@@ -2744,6 +3719,23 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::SimulateTransaction(md, tx, ..) => {
+                let mut tx_bytes = vec![];
+                write_next(&mut tx_bytes, tx)?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(tx_bytes.len() as u32),
+                    Some(&HttpContentType::Bytes),
+                    empty_headers,
+                )?;
+                fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
+            }
             HttpRequestType::PostBlock(md, _ch, block) => {
                 let mut block_bytes = vec![];
                 write_next(&mut block_bytes, block)?;
@@ -2849,6 +3841,59 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::AdminGetPeers(md) | HttpRequestType::AdminGetNeighborStats(md) => {
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "GET",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    None,
+                    None,
+                    |fd| admin_auth_headers(fd, protocol.admin_auth_token.as_ref()),
+                )?;
+            }
+            HttpRequestType::AdminBanPeer(md, addrbytes, port, duration) => {
+                let request_body = AdminBanPeerRequestBody {
+                    ip: addrbytes.clone(),
+                    port: *port,
+                    duration: duration.clone(),
+                };
+                HttpRequestType::send_admin_json(
+                    protocol,
+                    fd,
+                    md,
+                    &self.request_path(),
+                    &request_body,
+                )?;
+            }
+            HttpRequestType::AdminDropTransactions(md, txids) => {
+                let request_body = AdminDropTransactionsRequestBody {
+                    txids: txids.iter().map(|txid| txid.to_hex()).collect(),
+                };
+                HttpRequestType::send_admin_json(
+                    protocol,
+                    fd,
+                    md,
+                    &self.request_path(),
+                    &request_body,
+                )?;
+            }
+            HttpRequestType::AdminMempoolGarbageCollect(md)
+            | HttpRequestType::AdminSetMiningPaused(md, _) => {
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(0),
+                    None,
+                    |fd| admin_auth_headers(fd, protocol.admin_auth_token.as_ref()),
+                )?;
+            }
             other_type => {
                 let md = other_type.metadata();
                 let request_path = other_type.request_path();
@@ -3093,6 +4138,24 @@ impl HttpResponseType {
         )] = &[
             (&PATH_GETINFO, &HttpResponseType::parse_peerinfo),
             (&PATH_GETPOXINFO, &HttpResponseType::parse_poxinfo),
+            (&PATH_GET_REWARD_SET, &HttpResponseType::parse_reward_set),
+            (
+                &PATH_GET_STACKER_INFO,
+                &HttpResponseType::parse_stacker_info,
+            ),
+            (&PATH_GET_BNS_NAME, &HttpResponseType::parse_bns_name),
+            (
+                &PATH_GET_BNS_ZONEFILE,
+                &HttpResponseType::parse_bns_zonefile,
+            ),
+            (
+                &PATH_GET_BNS_NAMESPACE,
+                &HttpResponseType::parse_bns_namespace,
+            ),
+            (
+                &PATH_GET_BNS_OWNER_NAMES,
+                &HttpResponseType::parse_bns_owner_names,
+            ),
             (&PATH_GETNEIGHBORS, &HttpResponseType::parse_neighbors),
             (&PATH_GETBLOCK, &HttpResponseType::parse_block),
             (&PATH_GET_MAP_ENTRY, &HttpResponseType::parse_get_map_entry),
@@ -3112,7 +4175,21 @@ impl HttpResponseType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpResponseType::parse_transaction_unconfirmed,
             ),
+            (
+                &PATH_GETTRANSACTION,
+                &HttpResponseType::parse_transaction_confirmed,
+            ),
+            (&PATH_GET_CONTRACT_EVENTS, &HttpResponseType::parse_events),
+            (&PATH_GET_PRINCIPAL_EVENTS, &HttpResponseType::parse_events),
+            (
+                &PATH_GET_BLOCK_RANGE_EVENTS,
+                &HttpResponseType::parse_events,
+            ),
             (&PATH_POSTTRANSACTION, &HttpResponseType::parse_txid),
+            (
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpResponseType::parse_transaction_simulation,
+            ),
             (
                 &PATH_POSTBLOCK,
                 &HttpResponseType::parse_stacks_block_accepted,
@@ -3122,6 +4199,10 @@ impl HttpResponseType {
                 &HttpResponseType::parse_microblock_hash,
             ),
             (&PATH_GET_ACCOUNT, &HttpResponseType::parse_get_account),
+            (
+                &PATH_GET_ACCOUNT_HISTORY,
+                &HttpResponseType::parse_get_account_history,
+            ),
             (
                 &PATH_GET_CONTRACT_SRC,
                 &HttpResponseType::parse_get_contract_src,
@@ -3146,6 +4227,27 @@ impl HttpResponseType {
                 &PATH_GET_ATTACHMENTS_INV,
                 &HttpResponseType::parse_get_attachments_inv,
             ),
+            (&PATH_ADMIN_GET_PEERS, &HttpResponseType::parse_admin_peers),
+            (
+                &PATH_ADMIN_BAN_PEER,
+                &HttpResponseType::parse_admin_peer_banned,
+            ),
+            (
+                &PATH_ADMIN_GET_NEIGHBOR_STATS,
+                &HttpResponseType::parse_admin_neighbor_stats,
+            ),
+            (
+                &PATH_ADMIN_DROP_TRANSACTIONS,
+                &HttpResponseType::parse_admin_dropped_transactions,
+            ),
+            (
+                &PATH_ADMIN_MEMPOOL_GC,
+                &HttpResponseType::parse_admin_mempool_gc,
+            ),
+            (
+                &PATH_ADMIN_SET_MINING,
+                &HttpResponseType::parse_admin_mining_status,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -3224,6 +4326,96 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_reward_set<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let reward_set =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::RewardSet(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            reward_set,
+        ))
+    }
+
+    fn parse_stacker_info<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let stacker_info =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::StackerInfo(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            stacker_info,
+        ))
+    }
+
+    fn parse_bns_name<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let name_data =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::BnsName(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            name_data,
+        ))
+    }
+
+    fn parse_bns_zonefile<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let zonefile_data =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::BnsZonefile(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            zonefile_data,
+        ))
+    }
+
+    fn parse_bns_namespace<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let namespace_data =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::BnsNamespace(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            namespace_data,
+        ))
+    }
+
+    fn parse_bns_owner_names<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let owner_names =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::BnsOwnerNames(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            owner_names,
+        ))
+    }
+
     fn parse_neighbors<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3284,6 +4476,105 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_account_history<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let history: AccountHistoryResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetAccountHistory(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            history,
+        ))
+    }
+
+    fn parse_admin_peers<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let peers = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminPeers(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            peers,
+        ))
+    }
+
+    fn parse_admin_peer_banned<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let ban = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminPeerBanned(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            ban,
+        ))
+    }
+
+    fn parse_admin_neighbor_stats<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let stats = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminNeighborStats(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            stats,
+        ))
+    }
+
+    fn parse_admin_dropped_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let dropped = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminDroppedTransactions(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            dropped,
+        ))
+    }
+
+    fn parse_admin_mempool_gc<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let gc = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminMempoolGarbageCollected(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            gc,
+        ))
+    }
+
+    fn parse_admin_mining_status<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let status = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminMiningStatus(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            status,
+        ))
+    }
+
     fn parse_get_map_entry<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3392,33 +4683,89 @@ impl HttpResponseType {
         }
         Ok(HttpResponseType::Microblocks(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            microblocks,
+            microblocks,
+        ))
+    }
+
+    fn parse_transaction_unconfirmed<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let unconfirmed_status: UnconfirmedTransactionResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        // tx payload must decode to a transaction
+        let tx_bytes = hex_bytes(&unconfirmed_status.tx).map_err(|_| {
+            net_error::DeserializeError("Unconfirmed transaction is not hex-encoded".to_string())
+        })?;
+        let _ = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|_| {
+            net_error::DeserializeError(
+                "Unconfirmed transaction is not a well-formed Stacks transaction".to_string(),
+            )
+        })?;
+
+        Ok(HttpResponseType::UnconfirmedTransaction(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            unconfirmed_status,
+        ))
+    }
+
+    fn parse_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let events: EventsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::Events(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            events,
         ))
     }
 
-    fn parse_transaction_unconfirmed<R: Read>(
+    fn parse_transaction_confirmed<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let unconfirmed_status: UnconfirmedTransactionResponse =
+        let confirmed: ConfirmedTransactionResponse =
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
 
         // tx payload must decode to a transaction
-        let tx_bytes = hex_bytes(&unconfirmed_status.tx).map_err(|_| {
-            net_error::DeserializeError("Unconfirmed transaction is not hex-encoded".to_string())
+        let tx_bytes = hex_bytes(&confirmed.tx).map_err(|_| {
+            net_error::DeserializeError("Confirmed transaction is not hex-encoded".to_string())
         })?;
         let _ = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|_| {
             net_error::DeserializeError(
-                "Unconfirmed transaction is not a well-formed Stacks transaction".to_string(),
+                "Confirmed transaction is not a well-formed Stacks transaction".to_string(),
             )
         })?;
 
-        Ok(HttpResponseType::UnconfirmedTransaction(
+        Ok(HttpResponseType::ConfirmedTransaction(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            unconfirmed_status,
+            confirmed,
+        ))
+    }
+
+    fn parse_transaction_simulation<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let simulation: SimulateTransactionResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::TransactionSimulation(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            simulation,
         ))
     }
 
@@ -3522,6 +4869,7 @@ impl HttpResponseType {
             402 => "Payment Required",
             403 => "Forbidden",
             404 => "Not Found",
+            410 => "Gone",
             500 => "Internal Server Error",
             503 => "Service Temporarily Unavailable",
             _ => "Error",
@@ -3553,6 +4901,12 @@ impl HttpResponseType {
         match *self {
             HttpResponseType::PeerInfo(ref md, _) => md,
             HttpResponseType::PoxInfo(ref md, _) => md,
+            HttpResponseType::RewardSet(ref md, _) => md,
+            HttpResponseType::StackerInfo(ref md, _) => md,
+            HttpResponseType::BnsName(ref md, _) => md,
+            HttpResponseType::BnsZonefile(ref md, _) => md,
+            HttpResponseType::BnsNamespace(ref md, _) => md,
+            HttpResponseType::BnsOwnerNames(ref md, _) => md,
             HttpResponseType::Neighbors(ref md, _) => md,
             HttpResponseType::Block(ref md, _) => md,
             HttpResponseType::BlockStream(ref md) => md,
@@ -3569,10 +4923,21 @@ impl HttpResponseType {
             HttpResponseType::GetIsTraitImplemented(ref md, _) => md,
             HttpResponseType::CallReadOnlyFunction(ref md, _) => md,
             HttpResponseType::UnconfirmedTransaction(ref md, _) => md,
+            HttpResponseType::ConfirmedTransaction(ref md, _) => md,
+            HttpResponseType::TransactionSimulation(ref md, _) => md,
             HttpResponseType::GetAttachment(ref md, _) => md,
             HttpResponseType::GetAttachmentsInv(ref md, _) => md,
             HttpResponseType::OptionsPreflight(ref md) => md,
             HttpResponseType::TransactionFeeEstimation(ref md, _) => md,
+            HttpResponseType::EventStream(ref md) => md,
+            HttpResponseType::Events(ref md, _) => md,
+            HttpResponseType::GetAccountHistory(ref md, _) => md,
+            HttpResponseType::AdminPeers(ref md, _) => md,
+            HttpResponseType::AdminPeerBanned(ref md, _) => md,
+            HttpResponseType::AdminNeighborStats(ref md, _) => md,
+            HttpResponseType::AdminDroppedTransactions(ref md, _) => md,
+            HttpResponseType::AdminMempoolGarbageCollected(ref md, _) => md,
+            HttpResponseType::AdminMiningStatus(ref md, _) => md,
             // errors
             HttpResponseType::BadRequestJSON(ref md, _) => md,
             HttpResponseType::BadRequest(ref md, _) => md,
@@ -3688,6 +5053,30 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, pox_info)?;
             }
+            HttpResponseType::RewardSet(ref md, ref reward_set) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, reward_set)?;
+            }
+            HttpResponseType::StackerInfo(ref md, ref stacker_info) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, stacker_info)?;
+            }
+            HttpResponseType::BnsName(ref md, ref name_data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, name_data)?;
+            }
+            HttpResponseType::BnsZonefile(ref md, ref zonefile_data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, zonefile_data)?;
+            }
+            HttpResponseType::BnsNamespace(ref md, ref namespace_data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, namespace_data)?;
+            }
+            HttpResponseType::BnsOwnerNames(ref md, ref owner_names) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, owner_names)?;
+            }
             HttpResponseType::Neighbors(ref md, ref neighbor_data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, neighbor_data)?;
@@ -3725,6 +5114,19 @@ impl HttpResponseType {
                     |ref mut fd| keep_alive_headers(fd, md),
                 )?;
             }
+            HttpResponseType::EventStream(ref md) => {
+                // only send the preamble.  The caller streams events to the client for as long
+                // as the connection stays open.
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    200,
+                    "OK",
+                    None,
+                    &HttpContentType::EventStream,
+                    md.request_id,
+                    |ref mut fd| keep_alive_headers(fd, md),
+                )?;
+            }
             HttpResponseType::Microblocks(ref md, ref microblocks) => {
                 HttpResponsePreamble::new_serialized(
                     fd,
@@ -3796,6 +5198,46 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, unconfirmed_status)?;
             }
+            HttpResponseType::ConfirmedTransaction(ref md, ref confirmed) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, confirmed)?;
+            }
+            HttpResponseType::TransactionSimulation(ref md, ref simulation) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, simulation)?;
+            }
+            HttpResponseType::Events(ref md, ref events) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, events)?;
+            }
+            HttpResponseType::GetAccountHistory(ref md, ref history) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, history)?;
+            }
+            HttpResponseType::AdminPeers(ref md, ref peers) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, peers)?;
+            }
+            HttpResponseType::AdminPeerBanned(ref md, ref ban) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, ban)?;
+            }
+            HttpResponseType::AdminNeighborStats(ref md, ref stats) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, stats)?;
+            }
+            HttpResponseType::AdminDroppedTransactions(ref md, ref dropped) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, dropped)?;
+            }
+            HttpResponseType::AdminMempoolGarbageCollected(ref md, ref gc) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, gc)?;
+            }
+            HttpResponseType::AdminMiningStatus(ref md, ref status) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, status)?;
+            }
             HttpResponseType::OptionsPreflight(ref md) => {
                 HttpResponsePreamble::new_serialized(
                     fd,
@@ -3888,6 +5330,12 @@ impl MessageSequence for StacksHttpMessage {
             StacksHttpMessage::Request(ref req) => match req {
                 HttpRequestType::GetInfo(_) => "HTTP(GetInfo)",
                 HttpRequestType::GetPoxInfo(_, _) => "HTTP(GetPoxInfo)",
+                HttpRequestType::GetRewardSet(..) => "HTTP(GetRewardSet)",
+                HttpRequestType::GetStackerInfo(..) => "HTTP(GetStackerInfo)",
+                HttpRequestType::GetBnsName(..) => "HTTP(GetBnsName)",
+                HttpRequestType::GetBnsZonefile(..) => "HTTP(GetBnsZonefile)",
+                HttpRequestType::GetBnsNamespace(..) => "HTTP(GetBnsNamespace)",
+                HttpRequestType::GetBnsOwnerNames(..) => "HTTP(GetBnsOwnerNames)",
                 HttpRequestType::GetNeighbors(_) => "HTTP(GetNeighbors)",
                 HttpRequestType::GetBlock(_, _) => "HTTP(GetBlock)",
                 HttpRequestType::GetMicroblocksIndexed(_, _) => "HTTP(GetMicroblocksIndexed)",
@@ -3898,7 +5346,9 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetTransactionUnconfirmed(_, _) => {
                     "HTTP(GetTransactionUnconfirmed)"
                 }
+                HttpRequestType::GetTransaction(_, _) => "HTTP(GetTransaction)",
                 HttpRequestType::PostTransaction(_, _, _) => "HTTP(PostTransaction)",
+                HttpRequestType::SimulateTransaction(..) => "HTTP(SimulateTransaction)",
                 HttpRequestType::PostBlock(..) => "HTTP(PostBlock)",
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
@@ -3913,6 +5363,19 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::OptionsPreflight(..) => "HTTP(OptionsPreflight)",
                 HttpRequestType::ClientError(..) => "HTTP(ClientError)",
                 HttpRequestType::FeeRateEstimate(_, _, _) => "HTTP(FeeRateEstimate)",
+                HttpRequestType::GetEventStream(..) => "HTTP(GetEventStream)",
+                HttpRequestType::GetContractEvents(..) => "HTTP(GetContractEvents)",
+                HttpRequestType::GetPrincipalEvents(..) => "HTTP(GetPrincipalEvents)",
+                HttpRequestType::GetBlockRangeEvents(..) => "HTTP(GetBlockRangeEvents)",
+                HttpRequestType::GetAccountHistory(..) => "HTTP(GetAccountHistory)",
+                HttpRequestType::AdminGetPeers(_) => "HTTP(AdminGetPeers)",
+                HttpRequestType::AdminBanPeer(..) => "HTTP(AdminBanPeer)",
+                HttpRequestType::AdminGetNeighborStats(_) => "HTTP(AdminGetNeighborStats)",
+                HttpRequestType::AdminDropTransactions(..) => "HTTP(AdminDropTransactions)",
+                HttpRequestType::AdminMempoolGarbageCollect(_) => {
+                    "HTTP(AdminMempoolGarbageCollect)"
+                }
+                HttpRequestType::AdminSetMiningPaused(..) => "HTTP(AdminSetMiningPaused)",
             },
             StacksHttpMessage::Response(ref res) => match res {
                 HttpResponseType::TokenTransferCost(_, _) => "HTTP(TokenTransferCost)",
@@ -3926,15 +5389,36 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::GetAttachmentsInv(_, _) => "HTTP(GetAttachmentsInv)",
                 HttpResponseType::PeerInfo(_, _) => "HTTP(PeerInfo)",
                 HttpResponseType::PoxInfo(_, _) => "HTTP(PeerInfo)",
+                HttpResponseType::RewardSet(_, _) => "HTTP(RewardSet)",
+                HttpResponseType::StackerInfo(_, _) => "HTTP(StackerInfo)",
+                HttpResponseType::BnsName(_, _) => "HTTP(BnsName)",
+                HttpResponseType::BnsZonefile(_, _) => "HTTP(BnsZonefile)",
+                HttpResponseType::BnsNamespace(_, _) => "HTTP(BnsNamespace)",
+                HttpResponseType::BnsOwnerNames(_, _) => "HTTP(BnsOwnerNames)",
                 HttpResponseType::Neighbors(_, _) => "HTTP(Neighbors)",
                 HttpResponseType::Block(_, _) => "HTTP(Block)",
                 HttpResponseType::BlockStream(_) => "HTTP(BlockStream)",
                 HttpResponseType::Microblocks(_, _) => "HTTP(Microblocks)",
                 HttpResponseType::MicroblockStream(_) => "HTTP(MicroblockStream)",
+                HttpResponseType::EventStream(_) => "HTTP(EventStream)",
+                HttpResponseType::Events(_, _) => "HTTP(Events)",
+                HttpResponseType::GetAccountHistory(_, _) => "HTTP(GetAccountHistory)",
+                HttpResponseType::AdminPeers(_, _) => "HTTP(AdminPeers)",
+                HttpResponseType::AdminPeerBanned(_, _) => "HTTP(AdminPeerBanned)",
+                HttpResponseType::AdminNeighborStats(_, _) => "HTTP(AdminNeighborStats)",
+                HttpResponseType::AdminDroppedTransactions(_, _) => {
+                    "HTTP(AdminDroppedTransactions)"
+                }
+                HttpResponseType::AdminMempoolGarbageCollected(_, _) => {
+                    "HTTP(AdminMempoolGarbageCollected)"
+                }
+                HttpResponseType::AdminMiningStatus(_, _) => "HTTP(AdminMiningStatus)",
                 HttpResponseType::TransactionID(_, _) => "HTTP(Transaction)",
                 HttpResponseType::StacksBlockAccepted(..) => "HTTP(StacksBlockAccepted)",
                 HttpResponseType::MicroblockHash(_, _) => "HTTP(MicroblockHash)",
                 HttpResponseType::UnconfirmedTransaction(_, _) => "HTTP(UnconfirmedTransaction)",
+                HttpResponseType::ConfirmedTransaction(_, _) => "HTTP(ConfirmedTransaction)",
+                HttpResponseType::TransactionSimulation(_, _) => "HTTP(TransactionSimulation)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
                 HttpResponseType::BadRequestJSON(..) | HttpResponseType::BadRequest(..) => {
                     "HTTP(400)"
@@ -4053,6 +5537,9 @@ pub struct StacksHttp {
     chunk_size: usize,
     /// Maximum size of call arguments
     pub maximum_call_argument_size: u32,
+    /// Bearer token for the admin API.  If set, this is an admin connection: only admin requests
+    /// bearing this token are served, and admin requests we send carry it.
+    admin_auth_token: Option<String>,
 }
 
 impl StacksHttp {
@@ -4064,6 +5551,7 @@ impl StacksHttp {
             request_path: None,
             chunk_size: 8192,
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
+            admin_auth_token: None,
         }
     }
 
@@ -4071,6 +5559,14 @@ impl StacksHttp {
         self.chunk_size = size;
     }
 
+    pub fn set_admin_auth_token(&mut self, auth_token: String) -> () {
+        self.admin_auth_token = Some(auth_token);
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin_auth_token.is_some()
+    }
+
     pub fn num_pending(&self) -> usize {
         if self.reply.is_some() {
             1
@@ -4481,6 +5977,7 @@ mod test {
     use util::hash::Hash160;
     use util::hash::MerkleTree;
     use util::hash::Sha512Trunc256Sum;
+    use vm::types::QualifiedContractIdentifier;
 
     use crate::types::chainstate::StacksAddress;
     use crate::types::chainstate::StacksBlockHeader;
@@ -5440,6 +6937,11 @@ mod test {
                 make_test_transaction(),
                 None,
             ),
+            HttpRequestType::SimulateTransaction(
+                http_request_metadata_ip.clone(),
+                make_test_transaction(),
+                Some(StacksBlockId([4u8; 32])),
+            ),
             HttpRequestType::OptionsPreflight(http_request_metadata_ip.clone(), "/".to_string()),
         ];
 
@@ -5459,6 +6961,17 @@ mod test {
         post_transaction_preamble.set_content_type(HttpContentType::Bytes);
         post_transaction_preamble.set_content_length(tx_body.len() as u32);
 
+        let mut simulate_transaction_preamble = HttpRequestPreamble::new(
+            HttpVersion::Http11,
+            "POST".to_string(),
+            format!("/v2/transactions/simulate?tip={}", StacksBlockId([4u8; 32])),
+            http_request_metadata_ip.peer.hostname(),
+            http_request_metadata_ip.peer.port(),
+            http_request_metadata_ip.keep_alive,
+        );
+        simulate_transaction_preamble.set_content_type(HttpContentType::Bytes);
+        simulate_transaction_preamble.set_content_length(tx_body.len() as u32);
+
         // all of these should parse
         let expected_http_preambles = vec![
             HttpRequestPreamble::new(
@@ -5486,6 +6999,7 @@ mod test {
                 http_request_metadata_ip.keep_alive,
             ),
             post_transaction_preamble,
+            simulate_transaction_preamble,
             HttpRequestPreamble::new(
                 HttpVersion::Http11,
                 "OPTIONS".to_string(),
@@ -5496,7 +7010,7 @@ mod test {
             ),
         ];
 
-        let expected_http_bodies = vec![vec![], vec![], vec![], tx_body];
+        let expected_http_bodies = vec![vec![], vec![], vec![], tx_body.clone(), tx_body];
 
         for (test, (expected_http_preamble, expected_http_body)) in tests.iter().zip(
             expected_http_preambles
@@ -6425,6 +7939,451 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_parse_event_stream_request() {
+        let request = "GET /v2/events/stream?keys=memtx,burn_blocks,ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world::print HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+
+        let req = match message {
+            StacksHttpMessage::Request(req) => req,
+            _ => panic!("expected a request"),
+        };
+        match req {
+            HttpRequestType::GetEventStream(_, ref keys) => {
+                assert_eq!(
+                    *keys,
+                    vec![
+                        EventStreamKey::MemPoolTransactions,
+                        EventStreamKey::BurnchainBlocks,
+                        EventStreamKey::SmartContractEvent(
+                            QualifiedContractIdentifier::parse(
+                                "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world"
+                            )
+                            .unwrap(),
+                            "print".to_string()
+                        ),
+                    ]
+                );
+            }
+            _ => panic!("expected GetEventStream, got {:?}", &req),
+        }
+        assert_eq!(
+            req.request_path(),
+            "/v2/events/stream?keys=memtx,burn_blocks,ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world::print"
+        );
+
+        let bad_requests = vec![
+            "GET /v2/events/stream HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+            "GET /v2/events/stream?keys=memtx,nope HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+        ];
+        for bad_request in bad_requests {
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(bad_request.as_bytes()).unwrap();
+            let e = http.read_payload(&preamble, &bad_request.as_bytes()[offset..]);
+            assert!(e.is_err(), "{:?}", &e);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_event_log_requests() {
+        let requests = vec![
+            (
+                "/v2/events/contract/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH/hello-world?before_height=100&limit=10",
+                HttpRequestType::GetContractEvents(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    StacksAddress::from_string("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH").unwrap(),
+                    "hello-world".into(),
+                    Some(100),
+                    Some(10),
+                ),
+            ),
+            (
+                "/v2/events/principal/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH",
+                HttpRequestType::GetPrincipalEvents(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH").unwrap(),
+                    None,
+                    None,
+                ),
+            ),
+            (
+                "/v2/events/principal/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world?limit=5",
+                HttpRequestType::GetPrincipalEvents(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world")
+                        .unwrap(),
+                    None,
+                    Some(5),
+                ),
+            ),
+            (
+                "/v2/events/blocks/10/20",
+                HttpRequestType::GetBlockRangeEvents(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    10,
+                    20,
+                ),
+            ),
+        ];
+
+        for (path, expected) in requests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+
+            let req = match message {
+                StacksHttpMessage::Request(req) => req,
+                _ => panic!("expected a request"),
+            };
+            assert_eq!(req, expected);
+            assert_eq!(req.request_path(), path);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_account_history_requests() {
+        let requests = vec![
+            (
+                "/v2/accounts/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH/history/10/20",
+                HttpRequestType::GetAccountHistory(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH").unwrap(),
+                    10,
+                    20,
+                    None,
+                    true,
+                ),
+            ),
+            (
+                "/v2/accounts/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world/history/0/5?tip=0404040404040404040404040404040404040404040404040404040404040404&proof=0",
+                HttpRequestType::GetAccountHistory(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.hello-world")
+                        .unwrap(),
+                    0,
+                    5,
+                    Some(StacksBlockId([4u8; 32])),
+                    false,
+                ),
+            ),
+        ];
+
+        for (path, expected) in requests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+
+            let req = match message {
+                StacksHttpMessage::Request(req) => req,
+                _ => panic!("expected a request"),
+            };
+            assert_eq!(req, expected);
+            assert_eq!(req.request_path(), path);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_stacking_requests() {
+        let requests = vec![
+            (
+                "/v2/pox/reward_set/12",
+                HttpRequestType::GetRewardSet(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    12,
+                    None,
+                ),
+            ),
+            (
+                "/v2/pox/reward_set/0?tip=0404040404040404040404040404040404040404040404040404040404040404",
+                HttpRequestType::GetRewardSet(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    0,
+                    Some(StacksBlockId([4u8; 32])),
+                ),
+            ),
+            (
+                "/v2/pox/stackers/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH",
+                HttpRequestType::GetStackerInfo(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH").unwrap(),
+                    None,
+                ),
+            ),
+            (
+                "/v2/pox/stackers/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.pool?tip=0404040404040404040404040404040404040404040404040404040404040404",
+                HttpRequestType::GetStackerInfo(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.pool")
+                        .unwrap(),
+                    Some(StacksBlockId([4u8; 32])),
+                ),
+            ),
+        ];
+
+        for (path, expected) in requests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+
+            let req = match message {
+                StacksHttpMessage::Request(req) => req,
+                _ => panic!("expected a request"),
+            };
+            assert_eq!(req, expected);
+            assert_eq!(req.request_path(), path);
+        }
+    }
+
+    #[test]
+    fn test_http_parse_bns_requests() {
+        let requests = vec![
+            (
+                "/v2/bns/names/alice.btc",
+                HttpRequestType::GetBnsName(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    "alice".to_string(),
+                    "btc".to_string(),
+                    None,
+                ),
+            ),
+            (
+                "/v2/bns/names/alice.btc/zonefile?tip=0404040404040404040404040404040404040404040404040404040404040404",
+                HttpRequestType::GetBnsZonefile(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    "alice".to_string(),
+                    "btc".to_string(),
+                    Some(StacksBlockId([4u8; 32])),
+                ),
+            ),
+            (
+                "/v2/bns/namespaces/btc",
+                HttpRequestType::GetBnsNamespace(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    "btc".to_string(),
+                    None,
+                ),
+            ),
+            (
+                "/v2/bns/owners/ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH",
+                HttpRequestType::GetBnsOwnerNames(
+                    HttpRequestMetadata::new("localhost".to_string(), 20443),
+                    PrincipalData::parse("ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH").unwrap(),
+                    None,
+                ),
+            ),
+        ];
+
+        for (path, expected) in requests {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+
+            let req = match message {
+                StacksHttpMessage::Request(req) => req,
+                _ => panic!("expected a request"),
+            };
+            assert_eq!(req, expected);
+            assert_eq!(req.request_path(), path);
+        }
+
+        // names and namespaces use BNS's character set
+        for path in [
+            "/v2/bns/names/Alice.btc",
+            "/v2/bns/names/alice",
+            "/v2/bns/namespaces/b.tc",
+        ]
+        .iter()
+        {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            let (message, _) = http
+                .read_payload(&preamble, &request.as_bytes()[offset..])
+                .unwrap();
+            match message {
+                StacksHttpMessage::Request(HttpRequestType::ClientError(..)) => {}
+                _ => panic!("expected a client error for {}", path),
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_parse_admin_requests() {
+        let ban_body = "{\"ip\":\"127.0.0.1\",\"port\":20444,\"duration\":60}";
+        let drop_body =
+            "{\"txids\":[\"0x0505050505050505050505050505050505050505050505050505050505050505\"]}";
+        let requests = vec![
+            (
+                "GET",
+                "/v2/admin/peers",
+                "",
+                HttpRequestType::AdminGetPeers(HttpRequestMetadata::new(
+                    "localhost".to_string(),
+                    20445,
+                )),
+            ),
+            (
+                "POST",
+                "/v2/admin/peers/ban",
+                ban_body,
+                HttpRequestType::AdminBanPeer(
+                    HttpRequestMetadata::new("localhost".to_string(), 20445),
+                    PeerAddress::from_ipv4(127, 0, 0, 1),
+                    20444,
+                    Some(60),
+                ),
+            ),
+            (
+                "GET",
+                "/v2/admin/peers/stats",
+                "",
+                HttpRequestType::AdminGetNeighborStats(HttpRequestMetadata::new(
+                    "localhost".to_string(),
+                    20445,
+                )),
+            ),
+            (
+                "POST",
+                "/v2/admin/mempool/drop",
+                drop_body,
+                HttpRequestType::AdminDropTransactions(
+                    HttpRequestMetadata::new("localhost".to_string(), 20445),
+                    vec![Txid([5u8; 32])],
+                ),
+            ),
+            (
+                "POST",
+                "/v2/admin/mempool/gc",
+                "",
+                HttpRequestType::AdminMempoolGarbageCollect(HttpRequestMetadata::new(
+                    "localhost".to_string(),
+                    20445,
+                )),
+            ),
+            (
+                "POST",
+                "/v2/admin/mining/pause",
+                "",
+                HttpRequestType::AdminSetMiningPaused(
+                    HttpRequestMetadata::new("localhost".to_string(), 20445),
+                    true,
+                ),
+            ),
+            (
+                "POST",
+                "/v2/admin/mining/resume",
+                "",
+                HttpRequestType::AdminSetMiningPaused(
+                    HttpRequestMetadata::new("localhost".to_string(), 20445),
+                    false,
+                ),
+            ),
+        ];
+
+        for (verb, path, body, expected) in requests {
+            let content_type = if body.len() > 0 {
+                "Content-Type: application/json\r\n"
+            } else {
+                ""
+            };
+            for auth_header in [
+                "",
+                "Authorization: Bearer hunter3\r\n",
+                "Authorization: hunter2\r\n",
+                "Authorization: Bearer hunter2\r\n",
+            ]
+            .iter()
+            {
+                let request = format!(
+                    "{} {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20445\r\n{}{}Content-Length: {}\r\n\r\n{}",
+                    verb, path, auth_header, content_type, body.len(), body
+                );
+
+                // the public listener doesn't serve the admin API
+                let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+                let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+                let (message, _) = http
+                    .read_payload(&preamble, &request.as_bytes()[offset..])
+                    .unwrap();
+                match message {
+                    StacksHttpMessage::Request(HttpRequestType::ClientError(
+                        _,
+                        ClientError::NotFound(_),
+                    )) => {}
+                    _ => panic!("expected a 404 for {}", path),
+                }
+
+                let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+                http.set_admin_auth_token("hunter2".to_string());
+                let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+                let (message, _) = http
+                    .read_payload(&preamble, &request.as_bytes()[offset..])
+                    .unwrap();
+                let req = match message {
+                    StacksHttpMessage::Request(req) => req,
+                    _ => panic!("expected a request"),
+                };
+
+                if *auth_header == "Authorization: Bearer hunter2\r\n" {
+                    assert_eq!(req, expected);
+                    assert_eq!(req.request_path(), path);
+                } else {
+                    match req {
+                        HttpRequestType::ClientError(_, ClientError::Unauthorized(_)) => {}
+                        _ => panic!("expected a 401 for {} with '{}'", path, auth_header),
+                    }
+                }
+            }
+        }
+
+        // the admin listener doesn't serve the public API
+        let request = "GET /v2/info HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20445\r\nAuthorization: Bearer hunter2\r\n\r\n";
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        http.set_admin_auth_token("hunter2".to_string());
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::ClientError(
+                _,
+                ClientError::NotFound(_),
+            )) => {}
+            _ => panic!("expected a 404"),
+        }
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
@@ -16,7 +16,7 @@
 
 use std::borrow::Borrow;
 use std::cmp::PartialEq;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::From;
 use std::convert::TryFrom;
 use std::error;
@@ -60,6 +60,7 @@ use codec::StacksMessageCodec;
 use core::mempool::*;
 use core::POX_REWARD_CYCLE_LENGTH;
 use net::atlas::{Attachment, AttachmentInstance};
+use net::event_stream::EventStreamKey;
 use util::db::DBConn;
 use util::db::Error as db_error;
 use util::get_epoch_time_secs;
@@ -100,6 +101,7 @@ pub mod connection;
 pub mod db;
 pub mod dns;
 pub mod download;
+pub mod event_stream;
 pub mod http;
 pub mod inv;
 pub mod neighbors;
@@ -239,6 +241,8 @@ pub enum ClientError {
     Message(String),
     /// 404
     NotFound(String),
+    /// 401
+    Unauthorized(String),
 }
 
 impl error::Error for ClientError {
@@ -252,6 +256,7 @@ impl fmt::Display for ClientError {
         match self {
             ClientError::Message(s) => write!(f, "{}", s),
             ClientError::NotFound(s) => write!(f, "HTTP path not matched: {}", s),
+            ClientError::Unauthorized(s) => write!(f, "HTTP request not authorized: {}", s),
         }
     }
 }
@@ -597,6 +602,7 @@ pub enum HttpContentType {
     Bytes,
     Text,
     JSON,
+    EventStream,
 }
 
 impl fmt::Display for HttpContentType {
@@ -611,6 +617,7 @@ impl HttpContentType {
             HttpContentType::Bytes => "application/octet-stream",
             HttpContentType::Text => "text/plain",
             HttpContentType::JSON => "application/json",
+            HttpContentType::EventStream => "text/event-stream",
         }
     }
 }
@@ -626,6 +633,8 @@ impl FromStr for HttpContentType {
             Ok(HttpContentType::Text)
         } else if s == "application/json" {
             Ok(HttpContentType::JSON)
+        } else if s == "text/event-stream" {
+            Ok(HttpContentType::EventStream)
         } else {
             Err(codec_error::DeserializeError(
                 "Unsupported HTTP content type".to_string(),
@@ -729,6 +738,26 @@ pub struct BlocksAvailableData {
     pub available: Vec<(ConsensusHash, BurnchainHeaderHash)>,
 }
 
+/// A short, salted digest of a txid.  A set of these summarizes the contents of a mempool.
+pub struct TxTag(pub [u8; 8]);
+impl_array_newtype!(TxTag, u8, 8);
+impl_array_hexstring_fmt!(TxTag);
+impl_byte_array_newtype!(TxTag, u8, 8);
+
+/// Mempool sync request -- the tags of all the transactions the requester already has.
+/// Tags are calculated with the given seed, so a peer can't grind txids that collide with them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolSyncData {
+    pub seed: u64,
+    pub tags: Vec<TxTag>,
+}
+
+/// Mempool sync response -- transactions the requester did not have
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolSyncTxsData {
+    pub txs: Vec<StacksTransaction>,
+}
+
 /// A descriptor of a peer
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct NeighborAddress {
@@ -801,6 +830,7 @@ pub struct HandshakeData {
 pub enum ServiceFlags {
     RELAY = 0x01,
     RPC = 0x02,
+    MEMPOOL_SYNC = 0x04,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -866,6 +896,8 @@ pub enum StacksMessageType {
     Pong(PongData),
     NatPunchRequest(u32),
     NatPunchReply(NatPunchData),
+    MemPoolSyncRequest(MemPoolSyncData),
+    MemPoolSyncResponse(MemPoolSyncTxsData),
 }
 
 /// Peer address variants
@@ -1031,6 +1063,129 @@ pub struct RPCPoxInfoData {
     pub next_reward_cycle_in: u64,
 }
 
+/// How a reward set entry was registered with the PoX contract
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RPCStackingType {
+    /// registered by a stacker's own `stack-stx` call
+    Solo,
+    /// registered by a delegate's `stack-aggregation-commit` call
+    Delegated,
+    /// registered by a principal the PoX contract's state does not identify
+    Unknown,
+}
+
+/// An entry in a reward cycle's reward set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetEntry {
+    pub pox_address: String,
+    pub bitcoin_address: String,
+    pub total_ustx: u64,
+    pub stacking_type: RPCStackingType,
+    /// the solo stacker, or the delegate that committed the entry, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub stacked_by: Option<String>,
+}
+
+/// The data we return on GET /v2/pox/reward_set/{reward cycle}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetData {
+    pub reward_cycle: u64,
+    pub is_pox_active: bool,
+    pub total_ustx_stacked: u64,
+    pub min_threshold_ustx: u64,
+    pub entries: Vec<RPCRewardSetEntry>,
+}
+
+/// A reward cycle in which a stacker's STX are locked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCStackingCycle {
+    pub reward_cycle: u64,
+    pub start_burn_height: u64,
+}
+
+/// A stacker's current stacking state in the PoX contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCStackingInfo {
+    pub amount_ustx: u64,
+    pub pox_address: String,
+    pub bitcoin_address: String,
+    pub first_reward_cycle: u64,
+    pub lock_period: u64,
+    pub unlock_burn_height: u64,
+    pub reward_cycles: Vec<RPCStackingCycle>,
+}
+
+/// A stacker's delegation in the PoX contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCDelegationInfo {
+    pub amount_ustx: u64,
+    pub delegated_to: String,
+    pub until_burn_height: Option<u64>,
+    pub pox_address: Option<String>,
+}
+
+/// The data we return on GET /v2/pox/stackers/{principal}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCStackerInfoData {
+    pub stacker: String,
+    pub locked_ustx: u64,
+    pub unlock_height: u64,
+    pub stacking: Option<RPCStackingInfo>,
+    pub delegation: Option<RPCDelegationInfo>,
+}
+
+/// The data we return on GET /v2/bns/names/{name}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBnsNameData {
+    pub name: String,
+    pub namespace: String,
+    pub owner: String,
+    pub zonefile_hash: String,
+    /// the hex-encoded zonefile, if this node's Atlas store has it
+    pub zonefile: Option<String>,
+    pub lease_started_at: u64,
+    pub lease_ending_at: Option<u64>,
+}
+
+/// The data we return on GET /v2/bns/names/{name}/zonefile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBnsZonefileData {
+    pub name: String,
+    pub zonefile_hash: String,
+    pub zonefile: String,
+}
+
+/// A BNS namespace's price function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBnsPriceFunction {
+    pub buckets: Vec<u64>,
+    pub base: u64,
+    pub coeff: u64,
+    pub nonalpha_discount: u64,
+    pub no_vowel_discount: u64,
+}
+
+/// The data we return on GET /v2/bns/namespaces/{namespace}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBnsNamespaceData {
+    pub namespace: String,
+    pub namespace_import: String,
+    pub revealed_at: u64,
+    pub launched_at: Option<u64>,
+    pub lifetime: u64,
+    pub can_update_price_function: bool,
+    pub price_function: RPCBnsPriceFunction,
+}
+
+/// The data we return on GET /v2/bns/owners/{principal}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBnsOwnerNamesData {
+    pub owner: String,
+    pub names: Vec<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RPCFeeEstimate {
     pub fee_rate: f64,
@@ -1128,6 +1283,29 @@ pub struct AccountEntryResponse {
     pub nonce_proof: Option<String>,
 }
 
+/// A principal's account state as of one Stacks block in an `AccountHistoryResponse`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountHistoryEntryResponse {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub burn_block_height: u64,
+    pub balance: String,
+    pub locked: String,
+    pub unlock_height: u64,
+    pub nonce: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub balance_proof: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub nonce_proof: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountHistoryResponse {
+    pub history: Vec<AccountHistoryEntryResponse>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UnconfirmedTransactionStatus {
     Microblock {
@@ -1143,6 +1321,82 @@ pub struct UnconfirmedTransactionResponse {
     pub status: UnconfirmedTransactionStatus,
 }
 
+/// A block in which a transaction was processed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmedTransactionBlock {
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub consensus_hash: ConsensusHash,
+    pub block_height: u64,
+    /// the transaction's result, as a Clarity value
+    pub result: String,
+    /// whether or not the block is on the canonical Stacks fork
+    pub canonical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmedTransactionResponse {
+    pub tx: String,
+    pub blocks: Vec<ConfirmedTransactionBlock>,
+}
+
+/// An event from the event log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventLogEvent {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    /// the event, encoded the same way as it is for event observers
+    pub event: serde_json::Value,
+}
+
+/// The receipt of a transaction that emitted one or more events in an `EventsResponse`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventLogReceipt {
+    pub txid: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    /// the transaction's result, as a hex-encoded Clarity value
+    pub result: String,
+    pub post_condition_aborted: bool,
+    pub execution_cost: ExecutionCost,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventsResponse {
+    pub events: Vec<EventLogEvent>,
+    pub receipts: Vec<EventLogReceipt>,
+}
+
+/// The outcome of processing a transaction against a chain tip, without committing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulateTransactionResponse {
+    /// whether or not the transaction could be processed at all
+    pub okay: bool,
+    /// the transaction's result, as a hex-serialized Clarity value
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_condition_aborted: Option<bool>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+    /// the transaction's events, encoded the same way as they are for event observers
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<serde_json::Value>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_cost: Option<ExecutionCost>,
+    /// why the transaction could not be processed, if it could not be
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PostTransactionRequestBody {
     pub tx: String,
@@ -1228,6 +1482,20 @@ pub struct FeeRateEstimateRequestBody {
     pub transaction_payload: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct AdminBanPeerRequestBody {
+    pub ip: PeerAddress,
+    pub port: u16,
+    /// how long to ban the peer for, in seconds. 0 lifts the ban.
+    #[serde(default)]
+    pub duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminDropTransactionsRequestBody {
+    pub txids: Vec<String>,
+}
+
 /// Items in the NeighborsInfo -- combines NeighborKey and NeighborAddress
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RPCNeighbor {
@@ -1261,18 +1529,114 @@ pub struct RPCNeighborsInfo {
     pub outbound: Vec<RPCNeighbor>,
 }
 
+/// A peer in the peer DB, as reported by the admin API's `/v2/admin/peers`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminPeer {
+    pub network_id: u32,
+    pub peer_version: u32,
+    #[serde(rename = "ip")]
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    pub public_key_hash: Hash160,
+    pub last_contact_time: u64,
+    /// allow deadline (negative == "forever")
+    pub allowed: i64,
+    /// deny deadline (negative == "forever")
+    pub denied: i64,
+    pub connected: bool,
+}
+
+/// Struct given back from a call to `/v2/admin/peers`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminPeersData {
+    pub peers: Vec<RPCAdminPeer>,
+}
+
+/// Struct given back from a call to `/v2/admin/peers/ban`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminBanPeerData {
+    #[serde(rename = "ip")]
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    /// deny deadline; 0 if the ban was lifted
+    pub denied: u64,
+    /// whether or not the peer was connected.  A banned peer is disconnected shortly after.
+    pub connected: bool,
+}
+
+/// The `NeighborStats` of a connected peer, as reported by `/v2/admin/peers/stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminNeighborStats {
+    pub network_id: u32,
+    pub peer_version: u32,
+    #[serde(rename = "ip")]
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    pub outbound: bool,
+    pub first_contact_time: u64,
+    pub last_contact_time: u64,
+    pub last_send_time: u64,
+    pub last_recv_time: u64,
+    pub last_handshake_time: u64,
+    pub bytes_tx: u64,
+    pub bytes_rx: u64,
+    pub msgs_tx: u64,
+    pub msgs_rx: u64,
+    pub msgs_rx_unsolicited: u64,
+    pub msgs_err: u64,
+    pub health_score: f64,
+    pub msg_rx_counts: BTreeMap<String, u64>,
+}
+
+/// Struct given back from a call to `/v2/admin/peers/stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminNeighborStatsData {
+    pub neighbors: Vec<RPCAdminNeighborStats>,
+}
+
+/// Struct given back from a call to `/v2/admin/mempool/drop`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminDropTransactionsData {
+    pub dropped: Vec<String>,
+}
+
+/// Struct given back from a call to `/v2/admin/mempool/gc`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminMempoolGarbageCollectData {
+    /// transactions received before this Stacks block height were dropped
+    pub min_height: u64,
+}
+
+/// Struct given back from a call to `/v2/admin/mining/pause` or `/v2/admin/mining/resume`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAdminMiningStatusData {
+    pub paused: bool,
+}
+
 /// All HTTP request paths we support, and the arguments they carry in their paths
 #[derive(Debug, Clone, PartialEq)]
 pub enum HttpRequestType {
     GetInfo(HttpRequestMetadata),
     GetPoxInfo(HttpRequestMetadata, Option<StacksBlockId>),
+    GetRewardSet(HttpRequestMetadata, u64, Option<StacksBlockId>),
+    GetStackerInfo(HttpRequestMetadata, PrincipalData, Option<StacksBlockId>),
+    GetBnsName(HttpRequestMetadata, String, String, Option<StacksBlockId>),
+    GetBnsZonefile(HttpRequestMetadata, String, String, Option<StacksBlockId>),
+    GetBnsNamespace(HttpRequestMetadata, String, Option<StacksBlockId>),
+    GetBnsOwnerNames(HttpRequestMetadata, PrincipalData, Option<StacksBlockId>),
     GetNeighbors(HttpRequestMetadata),
     GetBlock(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksIndexed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksConfirmed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksUnconfirmed(HttpRequestMetadata, StacksBlockId, u16),
     GetTransactionUnconfirmed(HttpRequestMetadata, Txid),
+    GetTransaction(HttpRequestMetadata, Txid),
     PostTransaction(HttpRequestMetadata, StacksTransaction, Option<Attachment>),
+    SimulateTransaction(
+        HttpRequestMetadata,
+        StacksTransaction,
+        Option<StacksBlockId>,
+    ),
     PostBlock(HttpRequestMetadata, ConsensusHash, StacksBlock),
     PostMicroblock(HttpRequestMetadata, StacksMicroblock, Option<StacksBlockId>),
     GetAccount(
@@ -1324,6 +1688,31 @@ pub enum HttpRequestType {
         TraitIdentifier,
         Option<StacksBlockId>,
     ),
+    GetEventStream(HttpRequestMetadata, Vec<EventStreamKey>),
+    GetContractEvents(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        Option<u64>,
+        Option<u32>,
+    ),
+    GetPrincipalEvents(HttpRequestMetadata, PrincipalData, Option<u64>, Option<u32>),
+    GetBlockRangeEvents(HttpRequestMetadata, u64, u64),
+    GetAccountHistory(
+        HttpRequestMetadata,
+        PrincipalData,
+        u64,
+        u64,
+        Option<StacksBlockId>,
+        bool,
+    ),
+    /// admin API requests -- only served on the admin listener
+    AdminGetPeers(HttpRequestMetadata),
+    AdminBanPeer(HttpRequestMetadata, PeerAddress, u16, Option<u64>),
+    AdminGetNeighborStats(HttpRequestMetadata),
+    AdminDropTransactions(HttpRequestMetadata, Vec<Txid>),
+    AdminMempoolGarbageCollect(HttpRequestMetadata),
+    AdminSetMiningPaused(HttpRequestMetadata, bool),
     /// catch-all for any errors we should surface from parsing
     ClientError(HttpRequestMetadata, ClientError),
 }
@@ -1400,6 +1789,12 @@ impl From<&HttpRequestType> for HttpResponseMetadata {
 pub enum HttpResponseType {
     PeerInfo(HttpResponseMetadata, RPCPeerInfoData),
     PoxInfo(HttpResponseMetadata, RPCPoxInfoData),
+    RewardSet(HttpResponseMetadata, RPCRewardSetData),
+    StackerInfo(HttpResponseMetadata, RPCStackerInfoData),
+    BnsName(HttpResponseMetadata, RPCBnsNameData),
+    BnsZonefile(HttpResponseMetadata, RPCBnsZonefileData),
+    BnsNamespace(HttpResponseMetadata, RPCBnsNamespaceData),
+    BnsOwnerNames(HttpResponseMetadata, RPCBnsOwnerNamesData),
     Neighbors(HttpResponseMetadata, RPCNeighborsInfo),
     Block(HttpResponseMetadata, StacksBlock),
     BlockStream(HttpResponseMetadata),
@@ -1416,10 +1811,21 @@ pub enum HttpResponseType {
     GetContractSrc(HttpResponseMetadata, ContractSrcResponse),
     GetIsTraitImplemented(HttpResponseMetadata, GetIsTraitImplementedResponse),
     UnconfirmedTransaction(HttpResponseMetadata, UnconfirmedTransactionResponse),
+    ConfirmedTransaction(HttpResponseMetadata, ConfirmedTransactionResponse),
+    TransactionSimulation(HttpResponseMetadata, SimulateTransactionResponse),
     GetAttachment(HttpResponseMetadata, GetAttachmentResponse),
     GetAttachmentsInv(HttpResponseMetadata, GetAttachmentsInvResponse),
     OptionsPreflight(HttpResponseMetadata),
     TransactionFeeEstimation(HttpResponseMetadata, RPCFeeEstimateResponse),
+    EventStream(HttpResponseMetadata),
+    Events(HttpResponseMetadata, EventsResponse),
+    GetAccountHistory(HttpResponseMetadata, AccountHistoryResponse),
+    AdminPeers(HttpResponseMetadata, RPCAdminPeersData),
+    AdminPeerBanned(HttpResponseMetadata, RPCAdminBanPeerData),
+    AdminNeighborStats(HttpResponseMetadata, RPCAdminNeighborStatsData),
+    AdminDroppedTransactions(HttpResponseMetadata, RPCAdminDropTransactionsData),
+    AdminMempoolGarbageCollected(HttpResponseMetadata, RPCAdminMempoolGarbageCollectData),
+    AdminMiningStatus(HttpResponseMetadata, RPCAdminMiningStatusData),
     // peer-given error responses
     BadRequest(HttpResponseMetadata, String),
     BadRequestJSON(HttpResponseMetadata, serde_json::Value),
@@ -1460,6 +1866,8 @@ pub enum StacksMessageID {
     Pong = 16,
     NatPunchRequest = 17,
     NatPunchReply = 18,
+    MemPoolSyncRequest = 19,
+    MemPoolSyncResponse = 20,
     Reserved = 255,
 }
 
@@ -1551,6 +1959,15 @@ pub const ARRAY_MAX_LEN: u32 = u32::MAX;
 // maximum number of neighbors in a NeighborsData
 pub const MAX_NEIGHBORS_DATA_LEN: u32 = 128;
 
+// maximum number of events returned by a contract or principal event log query
+pub const MAX_EVENTS_PER_REQUEST: u32 = 200;
+
+// maximum number of blocks spanned by a block range event log query
+pub const MAX_EVENT_BLOCK_RANGE: u64 = 100;
+
+// maximum number of blocks spanned by an account history query
+pub const MAX_ACCOUNT_HISTORY_RANGE: u64 = 100;
+
 // number of peers to relay to, depending on outbound or inbound
 pub const MAX_BROADCAST_OUTBOUND_RECEIVERS: usize = 8;
 pub const MAX_BROADCAST_INBOUND_RECEIVERS: usize = 16;
@@ -1569,6 +1986,18 @@ pub const GETPOXINV_MAX_BITLEN: u64 = 8;
 // message.
 pub const BLOCKS_PUSHED_MAX: u32 = 32;
 
+// maximum number of txid tags in a mempool sync request (8 bytes each)
+pub const MEMPOOL_SYNC_TAGS_MAX: u32 = 65536;
+
+// maximum number of transactions that can be sent back in reply to a mempool sync request.
+// This bound is needed since it bounds the amount of I/O a peer can be asked to do to validate the
+// message.
+pub const MEMPOOL_SYNC_TXS_MAX: u32 = 128;
+
+// soft limit on how many bytes of transactions will be sent back in reply to a mempool sync
+// request (the last transaction can go over it).
+pub const MEMPOOL_SYNC_TXS_MAX_BYTES: u64 = 2 * 1024 * 1024;
+
 impl_byte_array_message_codec!(ConsensusHash, 20);
 impl_byte_array_message_codec!(Hash160, 20);
 impl_byte_array_message_codec!(BurnchainHeaderHash, 32);
@@ -1577,6 +2006,7 @@ impl_byte_array_message_codec!(StacksBlockId, 32);
 impl_byte_array_message_codec!(MessageSignature, 65);
 impl_byte_array_message_codec!(PeerAddress, 16);
 impl_byte_array_message_codec!(StacksPublicKeyBuffer, 33);
+impl_byte_array_message_codec!(TxTag, 8);
 
 impl_byte_array_serde!(ConsensusHash);
 
@@ -1725,6 +2155,7 @@ pub struct NetworkResult {
     pub pushed_blocks: HashMap<NeighborKey, Vec<BlocksData>>, // all blocks pushed to us
     pub pushed_microblocks: HashMap<NeighborKey, Vec<(Vec<RelayData>, MicroblocksData)>>, // all microblocks pushed to us, and the relay hints from the message
     pub uploaded_transactions: Vec<StacksTransaction>, // transactions sent to us by the http server
+    pub synced_transactions: Vec<StacksTransaction>,   // transactions we fetched from peers' mempools
     pub uploaded_blocks: Vec<BlocksData>,              // blocks sent to us via the http server
     pub uploaded_microblocks: Vec<MicroblocksData>,    // microblocks sent to us by the http server
     pub attachments: Vec<(AttachmentInstance, Attachment)>,
@@ -1748,6 +2179,7 @@ impl NetworkResult {
             pushed_blocks: HashMap::new(),
             pushed_microblocks: HashMap::new(),
             uploaded_transactions: vec![],
+            synced_transactions: vec![],
             uploaded_blocks: vec![],
             uploaded_microblocks: vec![],
             attachments: vec![],
@@ -1768,7 +2200,9 @@ impl NetworkResult {
     }
 
     pub fn has_transactions(&self) -> bool {
-        self.pushed_transactions.len() > 0 || self.uploaded_transactions.len() > 0
+        self.pushed_transactions.len() > 0
+            || self.uploaded_transactions.len() > 0
+            || self.synced_transactions.len() > 0
     }
 
     pub fn has_attachments(&self) -> bool {
@@ -1780,6 +2214,7 @@ impl NetworkResult {
             .values()
             .flat_map(|pushed_txs| pushed_txs.iter().map(|(_, tx)| tx.clone()))
             .chain(self.uploaded_transactions.iter().map(|x| x.clone()))
+            .chain(self.synced_transactions.iter().map(|x| x.clone()))
             .collect()
     }
 
@@ -2577,7 +3012,11 @@ pub mod test {
                     config.server_port,
                 )
                 .unwrap();
-                PeerDB::set_local_services(&mut tx, ServiceFlags::RELAY as u16).unwrap();
+                PeerDB::set_local_services(
+                    &mut tx,
+                    ServiceFlags::RELAY as u16 | ServiceFlags::MEMPOOL_SYNC as u16,
+                )
+                .unwrap();
                 PeerDB::set_local_private_key(
                     &mut tx,
                     &config.private_key,
@@ -228,6 +228,7 @@ pub struct PeerNetwork {
     pub network: Option<NetworkState>,
     p2p_network_handle: usize,
     http_network_handle: usize,
+    admin_network_handle: Option<usize>,
 
     // info on the burn chain we're tracking
     pub burnchain: Burnchain,
@@ -285,6 +286,13 @@ pub struct PeerNetwork {
     public_ip_reply_handle: Option<ReplyHandleP2P>,
     public_ip_retries: u64,
 
+    // mempool sync state -- the peer we're currently syncing with, the txids it has sent us so
+    // far, and when we last finished syncing.
+    mempool_sync_event_id: Option<usize>,
+    mempool_sync_reply_handle: Option<ReplyHandleP2P>,
+    mempool_sync_txids: HashSet<Txid>,
+    mempool_sync_last_ts: u64,
+
     // how many loops of the state-machine have occured?
     // Used to coordinate with the chain synchronization logic to ensure that the node has at least
     // begun to download blocks after fetching the next reward cycles' sortitions.
@@ -367,6 +375,7 @@ impl PeerNetwork {
             network: None,
             p2p_network_handle: 0,
             http_network_handle: 0,
+            admin_network_handle: None,
 
             burnchain: burnchain,
             connection_opts: connection_opts,
@@ -409,6 +418,11 @@ impl PeerNetwork {
             public_ip_reply_handle: None,
             public_ip_retries: 0,
 
+            mempool_sync_event_id: None,
+            mempool_sync_reply_handle: None,
+            mempool_sync_txids: HashSet::new(),
+            mempool_sync_last_ts: 0,
+
             num_state_machine_passes: 0,
             num_inv_sync_passes: 0,
             num_downloader_passes: 0,
@@ -488,6 +502,34 @@ impl PeerNetwork {
         Ok(())
     }
 
+    /// start serving the admin API, to clients that present the given bearer token.
+    /// Must be called after bind().
+    pub fn bind_admin(
+        &mut self,
+        admin_addr: &SocketAddr,
+        auth_token: String,
+    ) -> Result<(), net_error> {
+        let admin_handle = match self.network {
+            Some(ref mut network) => network.bind(admin_addr)?,
+            None => {
+                return Err(net_error::NotConnected);
+            }
+        };
+
+        test_debug!(
+            "{:?}: bound admin API on {:?}",
+            &self.local_peer,
+            admin_addr
+        );
+
+        self.admin_network_handle = Some(admin_handle);
+        PeerNetwork::with_http(self, |_, ref mut http| {
+            http.set_admin_server_handle(admin_handle, auth_token);
+        });
+
+        Ok(())
+    }
+
     /// Run a closure with the network state
     pub fn with_network_state<F, R>(
         peer_network: &mut PeerNetwork,
@@ -1137,6 +1179,20 @@ impl PeerNetwork {
             disconnect.push(event_id);
 
             let now = get_epoch_time_secs();
+            if let Some(ref neighbor_info) = neighbor_info_opt {
+                if neighbor_info.denied > 0
+                    && (neighbor_info.denied as u64) > now + DENY_BAN_DURATION
+                {
+                    // already banned for longer than we would (e.g. by the node operator);
+                    // don't shorten the ban
+                    debug!(
+                        "Peer {:?} is already banned until {}",
+                        &neighbor_key, neighbor_info.denied
+                    );
+                    continue;
+                }
+            }
+
             let penalty = if let Some(neighbor_info) = neighbor_info_opt {
                 if neighbor_info.denied < 0
                     || (neighbor_info.denied as u64) < now + DENY_MIN_BAN_DURATION
@@ -1622,6 +1678,7 @@ impl PeerNetwork {
         sortdb: &SortitionDB,
         pox_id: &PoxId,
         chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
         header_cache: &mut BlockHeaderCache,
         chain_view: &BurnchainView,
         event_id: usize,
@@ -1662,6 +1719,7 @@ impl PeerNetwork {
             sortdb,
             pox_id,
             chainstate,
+            mempool,
             header_cache,
             chain_view,
         );
@@ -1725,6 +1783,7 @@ impl PeerNetwork {
         &mut self,
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
         poll_state: &mut NetworkPollState,
     ) -> (Vec<usize>, HashMap<usize, Vec<StacksMessage>>) {
         let mut to_remove = vec![];
@@ -1755,6 +1814,7 @@ impl PeerNetwork {
                         sortdb,
                         &self.pox_id,
                         chainstate,
+                        mempool,
                         &mut self.header_cache,
                         &self.chain_view,
                         *event_id,
@@ -3326,6 +3386,193 @@ impl PeerNetwork {
         Ok(())
     }
 
+    /// Pick an authenticated outbound peer at random to sync our mempool with.  Only peers that
+    /// advertise mempool sync in their handshake are asked, since older peers would treat the
+    /// request as an unknown message.
+    fn choose_mempool_sync_peer(&self) -> Option<usize> {
+        let candidates: Vec<usize> = self
+            .peers
+            .iter()
+            .filter(|(_, convo)| {
+                convo.is_authenticated() && convo.is_outbound() && convo.supports_mempool_sync()
+            })
+            .map(|(event_id, _)| *event_id)
+            .collect();
+
+        if candidates.len() == 0 {
+            return None;
+        }
+        Some(candidates[thread_rng().gen::<usize>() % candidates.len()])
+    }
+
+    /// Ask a peer for the next batch of mempool transactions we don't have.  The request
+    /// summarizes our mempool, as well as everything this peer has already sent us in this sync
+    /// (which may not have been stored yet).
+    /// Return Ok(true) if we sent a request, Ok(false) if there's no one to ask.
+    fn begin_mempool_sync(&mut self, mempool: &MemPoolDB) -> Result<bool, net_error> {
+        let event_id = match self
+            .mempool_sync_event_id
+            .or_else(|| self.choose_mempool_sync_peer())
+        {
+            Some(event_id) => event_id,
+            None => {
+                return Ok(false);
+            }
+        };
+
+        let max_local_txids =
+            (MEMPOOL_SYNC_TAGS_MAX as u64).saturating_sub(self.mempool_sync_txids.len() as u64);
+        let local_txids = MemPoolDB::get_txids_by_fee_rate(mempool.conn(), max_local_txids)?;
+        let sync_data =
+            MemPoolSyncData::from_txids(self.mempool_sync_txids.iter().chain(local_txids.iter()));
+
+        let mut rh = match self.peers.get_mut(&event_id) {
+            Some(convo) => {
+                debug!(
+                    "{:?}: Ask {:?} for missing mempool transactions ({} tags)",
+                    &self.local_peer,
+                    &convo,
+                    sync_data.tags.len()
+                );
+                let sync_request = convo.sign_message(
+                    &self.chain_view,
+                    &self.local_peer.private_key,
+                    StacksMessageType::MemPoolSyncRequest(sync_data),
+                )?;
+                convo.send_signed_request(sync_request, self.connection_opts.timeout)?
+            }
+            None => {
+                return Err(net_error::PeerNotConnected);
+            }
+        };
+
+        self.saturate_p2p_socket(event_id, &mut rh)?;
+        self.mempool_sync_event_id = Some(event_id);
+        self.mempool_sync_reply_handle = Some(rh);
+        Ok(true)
+    }
+
+    /// Stop syncing our mempool with the current peer, and wait for the next sync interval.
+    fn finish_mempool_sync(&mut self) -> () {
+        self.mempool_sync_event_id = None;
+        self.mempool_sync_reply_handle = None;
+        self.mempool_sync_txids.clear();
+        self.mempool_sync_last_ts = get_epoch_time_secs();
+    }
+
+    /// Periodically pull the transactions we're missing from a peer's mempool.
+    /// We keep asking the same peer for more until it has nothing left to send us.
+    /// Transactions we receive are handed back in the network result, so the relayer can store
+    /// them (but it won't forward them).
+    fn do_mempool_sync(
+        &mut self,
+        mempool: &MemPoolDB,
+        network_result: &mut NetworkResult,
+    ) -> Result<(), net_error> {
+        if cfg!(test) && self.connection_opts.disable_mempool_sync {
+            test_debug!("{:?}: mempool sync is disabled", &self.local_peer);
+            return Ok(());
+        }
+
+        if self.mempool_sync_reply_handle.is_none() {
+            if self.mempool_sync_event_id.is_none()
+                && self.mempool_sync_last_ts + self.connection_opts.mempool_sync_interval
+                    > get_epoch_time_secs()
+            {
+                // not time yet
+                return Ok(());
+            }
+
+            match self.begin_mempool_sync(mempool) {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!(
+                        "{:?}: No outbound neighbors to sync our mempool with",
+                        &self.local_peer
+                    );
+                    self.finish_mempool_sync();
+                    return Ok(());
+                }
+                Err(net_error::DBError(e)) => {
+                    self.finish_mempool_sync();
+                    return Err(net_error::DBError(e));
+                }
+                Err(e) => {
+                    debug!(
+                        "{:?}: Failed to begin mempool sync: {:?}",
+                        &self.local_peer, &e
+                    );
+                    self.finish_mempool_sync();
+                    return Ok(());
+                }
+            }
+        }
+
+        let mut rh = match self.mempool_sync_reply_handle.take() {
+            Some(rh) => rh,
+            None => {
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = self.saturate_p2p_socket(rh.get_event_id(), &mut rh) {
+            debug!(
+                "{:?}: Failed to send mempool sync request: {:?}",
+                &self.local_peer, &e
+            );
+            self.finish_mempool_sync();
+            return Ok(());
+        }
+
+        let event_id = rh.get_event_id();
+        match rh.try_send_recv() {
+            Ok(message) => match message.payload {
+                StacksMessageType::MemPoolSyncResponse(data) => {
+                    let mut num_new = 0;
+                    for tx in data.txs.into_iter() {
+                        if self.mempool_sync_txids.insert(tx.txid()) {
+                            network_result.synced_transactions.push(tx);
+                            num_new += 1;
+                        }
+                    }
+
+                    debug!(
+                        "{:?}: Got {} new mempool transaction(s) from event {}",
+                        &self.local_peer, num_new, event_id
+                    );
+
+                    if num_new == 0
+                        || self.mempool_sync_txids.len() >= MEMPOOL_SYNC_TAGS_MAX as usize
+                    {
+                        // peer has nothing more for us
+                        self.finish_mempool_sync();
+                    }
+                }
+                other_payload => {
+                    debug!(
+                        "{:?}: Got unexpected reply to mempool sync request: {:?}",
+                        &self.local_peer, &other_payload
+                    );
+                    self.finish_mempool_sync();
+                }
+            },
+            Err(req_res) => match req_res {
+                Ok(same_req) => {
+                    // still waiting
+                    self.mempool_sync_reply_handle = Some(same_req);
+                }
+                Err(e) => {
+                    debug!(
+                        "{:?}: Failed to get a mempool sync response: {:?}",
+                        &self.local_peer, &e
+                    );
+                    self.finish_mempool_sync();
+                }
+            },
+        }
+        Ok(())
+    }
+
     /// Given an event ID, find the other event ID corresponding
     /// to the same remote peer.  There will be at most two such events
     /// -- one registered as the inbound connection, and one registered as the
@@ -4270,6 +4517,7 @@ impl PeerNetwork {
         network_result: &mut NetworkResult,
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
+        mempool: &MemPoolDB,
         mut dns_client_opt: Option<&mut DNSClient>,
         download_backpressure: bool,
         ibd: bool,
@@ -4291,7 +4539,7 @@ impl PeerNetwork {
 
         // run existing conversations, clear out broken ones, and get back messages forwarded to us
         let (error_events, unsolicited_messages) =
-            self.process_ready_sockets(sortdb, chainstate, &mut poll_state);
+            self.process_ready_sockets(sortdb, chainstate, mempool, &mut poll_state);
         for error_event in error_events {
             debug!(
                 "{:?}: Failed connection on event {}",
@@ -4342,6 +4590,9 @@ impl PeerNetwork {
         // download attachments
         self.do_attachment_downloads(chainstate, dns_client_opt, network_result)?;
 
+        // fetch any transactions we're missing from our peers' mempools
+        self.do_mempool_sync(mempool, network_result)?;
+
         // remove timed-out requests from other threads
         for (_, convo) in self.peers.iter_mut() {
             convo.clear_timeouts();
@@ -4486,6 +4737,22 @@ impl PeerNetwork {
 
         // (HTTP-uploaded transactions are already in the mempool)
 
+        // transactions fetched from our peers' mempools.  Only keep the ones we stored.
+        let synced_txs = mem::replace(&mut network_result.synced_transactions, vec![]);
+        for tx in synced_txs.into_iter() {
+            if PeerNetwork::store_transaction(
+                mempool,
+                sortdb,
+                chainstate,
+                &canonical_consensus_hash,
+                &canonical_block_hash,
+                tx.clone(),
+                event_observer,
+            ) {
+                network_result.synced_transactions.push(tx);
+            }
+        }
+
         network_result.pushed_transactions.extend(ret);
         Ok(())
     }
@@ -4527,6 +4794,11 @@ impl PeerNetwork {
         let http_poll_state = poll_states
             .remove(&self.http_network_handle)
             .expect("BUG: no poll state for http network handle");
+        let admin_poll_state = self.admin_network_handle.map(|admin_network_handle| {
+            poll_states
+                .remove(&admin_network_handle)
+                .expect("BUG: no poll state for admin network handle")
+        });
 
         let mut network_result = NetworkResult::new(
             self.num_state_machine_passes,
@@ -4571,6 +4843,7 @@ impl PeerNetwork {
                     chainstate,
                     mempool,
                     http_poll_state,
+                    admin_poll_state,
                     handler_args,
                 )
             })?;
@@ -4582,6 +4855,7 @@ impl PeerNetwork {
             &mut network_result,
             sortdb,
             chainstate,
+            mempool,
             dns_client_opt,
             download_backpressure,
             ibd,
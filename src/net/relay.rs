@@ -1284,6 +1284,9 @@ impl Relayer {
             }
         }
 
+        // transactions synced from our peers' mempools are stored, but not forwarded
+        mempool_txs_added.extend(network_result.synced_transactions.drain(..));
+
         let mut processed_unconfirmed_state = Default::default();
 
         // finally, refresh the unconfirmed chainstate, if need be
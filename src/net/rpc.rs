@@ -17,6 +17,8 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -24,6 +26,7 @@ use std::io;
 use std::io::prelude::*;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 use std::{convert::TryFrom, fmt};
 
@@ -42,9 +45,11 @@ use burnchains::BurnchainView;
 use burnchains::*;
 use chainstate::burn::db::sortdb::SortitionDB;
 use chainstate::burn::ConsensusHash;
+use chainstate::stacks::boot::{BnsNameInfo, BnsNamespaceInfo, RewardSetEntrySource};
 use chainstate::stacks::db::blocks::CheckError;
 use chainstate::stacks::db::{
-    blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, BlockStreamData, StacksChainState,
+    blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, BlockStreamData, StacksChainState, TransactionEventEntry,
+    TransactionLogEntry, TRANSACTION_LOG,
 };
 use chainstate::stacks::Error as chain_error;
 use chainstate::stacks::*;
@@ -52,10 +57,12 @@ use clarity_vm::clarity::ClarityConnection;
 use core::mempool::*;
 use monitoring;
 use net::atlas::{AtlasDB, Attachment, MAX_ATTACHMENT_INV_PAGES_PER_REQUEST};
+use net::chat::ConversationP2P;
 use net::connection::ConnectionHttp;
 use net::connection::ConnectionOptions;
 use net::connection::ReplyHandleHttp;
 use net::db::PeerDB;
+use net::event_stream::{EventStreamHub, EventStreamKey, EventStreamSubscription};
 use net::http::*;
 use net::p2p::PeerMap;
 use net::p2p::PeerNetwork;
@@ -68,6 +75,7 @@ use net::HttpResponseMetadata;
 use net::HttpResponseType;
 use net::MicroblocksData;
 use net::NeighborAddress;
+use net::NeighborKey;
 use net::NeighborsData;
 use net::PeerAddress;
 use net::PeerHost;
@@ -81,12 +89,29 @@ use net::UrlString;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_NEIGHBORS_DATA_LEN;
 use net::{
-    AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ContractSrcResponse,
-    GetAttachmentResponse, GetAttachmentsInvResponse, MapEntryResponse,
+    AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ConfirmedTransactionBlock,
+    ConfirmedTransactionResponse, ContractSrcResponse, GetAttachmentResponse,
+    GetAttachmentsInvResponse, MapEntryResponse, SimulateTransactionResponse,
 };
+use net::{AccountHistoryEntryResponse, AccountHistoryResponse};
 use net::{BlocksData, GetIsTraitImplementedResponse};
+use net::{EventLogEvent, EventLogReceipt, EventsResponse};
+use net::{
+    RPCAdminBanPeerData, RPCAdminDropTransactionsData, RPCAdminMempoolGarbageCollectData,
+    RPCAdminMiningStatusData, RPCAdminNeighborStats, RPCAdminNeighborStatsData, RPCAdminPeer,
+    RPCAdminPeersData,
+};
+use net::{
+    RPCBnsNameData, RPCBnsNamespaceData, RPCBnsOwnerNamesData, RPCBnsPriceFunction,
+    RPCBnsZonefileData,
+};
+use net::{RPCDelegationInfo, RPCStackerInfoData, RPCStackingCycle, RPCStackingInfo};
 use net::{RPCNeighbor, RPCNeighborsInfo};
 use net::{RPCPeerInfoData, RPCPoxInfoData};
+use net::{RPCRewardSetData, RPCRewardSetEntry, RPCStackingType};
+use net::{
+    DENY_BAN_DURATION, MAX_ACCOUNT_HISTORY_RANGE, MAX_EVENTS_PER_REQUEST, MAX_EVENT_BLOCK_RANGE,
+};
 use util::db::DBConn;
 use util::db::Error as db_error;
 use util::get_epoch_time_secs;
@@ -130,6 +155,15 @@ pub struct RPCHandlerArgs<'a> {
     pub cost_estimator: Option<&'a dyn CostEstimator>,
     pub fee_estimator: Option<&'a dyn FeeEstimator>,
     pub cost_metric: Option<&'a dyn CostMetric>,
+    pub event_stream: Option<&'a EventStreamHub>,
+    /// set by the admin API to pause the node's miner
+    pub mining_paused: Option<&'a AtomicBool>,
+}
+
+/// Data that continues to be sent to the client after the response preamble
+enum ReplyStreamData {
+    Blocks(BlockStreamData),
+    Events(EventStreamSubscription),
 }
 
 pub struct ConversationHttp {
@@ -149,7 +183,7 @@ pub struct ConversationHttp {
     // ongoing block streams
     reply_streams: VecDeque<(
         ReplyHandleHttp,
-        Option<(HttpChunkedTransferWriterState, BlockStreamData)>,
+        Option<(HttpChunkedTransferWriterState, ReplyStreamData)>,
         bool,
     )>,
 
@@ -418,6 +452,181 @@ impl RPCPoxInfoData {
     }
 }
 
+impl RPCRewardSetData {
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+    ) -> Result<RPCRewardSetData, net_error> {
+        let reward_set = chainstate.get_reward_set_entries(sortdb, tip, reward_cycle)?;
+        let is_pox_active = chainstate.is_pox_active(sortdb, tip, reward_cycle as u128)?;
+        let total_ustx_stacked =
+            chainstate.get_total_ustx_stacked(sortdb, tip, reward_cycle as u128)?;
+        let liquid_ustx = chainstate.get_liquid_ustx(tip);
+        let min_threshold_ustx = StacksChainState::get_threshold_from_participation(
+            liquid_ustx,
+            total_ustx_stacked,
+            burnchain.pox_constants.reward_slots() as u128,
+        ) as u64;
+
+        let mut entries = vec![];
+        for (pox_addr, total_ustx) in reward_set.into_iter() {
+            let (stacking_type, stacked_by) = match chainstate.get_reward_set_entry_source(
+                sortdb,
+                tip,
+                &pox_addr,
+                reward_cycle,
+            )? {
+                Some(RewardSetEntrySource::Solo(stacker)) => {
+                    (RPCStackingType::Solo, Some(stacker.to_string()))
+                }
+                Some(RewardSetEntrySource::Delegated(delegate)) => {
+                    (RPCStackingType::Delegated, Some(delegate.to_string()))
+                }
+                None => (RPCStackingType::Unknown, None),
+            };
+
+            entries.push(RPCRewardSetEntry {
+                pox_address: pox_addr.to_string(),
+                bitcoin_address: pox_addr.to_b58(),
+                total_ustx: total_ustx as u64,
+                stacking_type,
+                stacked_by,
+            });
+        }
+
+        Ok(RPCRewardSetData {
+            reward_cycle,
+            is_pox_active,
+            total_ustx_stacked: total_ustx_stacked as u64,
+            min_threshold_ustx,
+            entries,
+        })
+    }
+}
+
+impl RPCStackerInfoData {
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        stacker: &PrincipalData,
+    ) -> Result<RPCStackerInfoData, net_error> {
+        let (locked_ustx, unlock_height) = chainstate
+            .maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let key = ClarityDatabase::make_key_for_account_balance(stacker);
+                    let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
+                    let balance = clarity_db
+                        .get::<STXBalance>(&key)
+                        .unwrap_or_else(|| STXBalance::zero());
+                    balance.get_locked_balance_at_burn_block(burn_block_height)
+                })
+            })?
+            .ok_or(net_error::NotFoundError)?;
+
+        let cycle_length = burnchain.pox_constants.reward_cycle_length as u64;
+        let stacking = chainstate
+            .get_stacker_info(sortdb, tip, stacker)?
+            .map(|info| {
+                let end_cycle = info.first_reward_cycle + info.lock_period;
+                RPCStackingInfo {
+                    amount_ustx: info.amount_ustx as u64,
+                    pox_address: info.pox_addr.to_string(),
+                    bitcoin_address: info.pox_addr.clone().to_b58(),
+                    first_reward_cycle: info.first_reward_cycle,
+                    lock_period: info.lock_period,
+                    // same as the PoX contract's (reward-cycle-to-burn-height)
+                    unlock_burn_height: burnchain.first_block_height + end_cycle * cycle_length,
+                    reward_cycles: (info.first_reward_cycle..end_cycle)
+                        .map(|reward_cycle| RPCStackingCycle {
+                            reward_cycle,
+                            start_burn_height: burnchain.reward_cycle_to_block_height(reward_cycle),
+                        })
+                        .collect(),
+                }
+            });
+
+        let delegation = chainstate
+            .get_delegation_info(sortdb, tip, stacker)?
+            .map(|info| RPCDelegationInfo {
+                amount_ustx: info.amount_ustx as u64,
+                delegated_to: info.delegated_to.to_string(),
+                until_burn_height: info.until_burn_height,
+                pox_address: info.pox_addr.map(|addr| addr.to_string()),
+            });
+
+        Ok(RPCStackerInfoData {
+            stacker: stacker.to_string(),
+            locked_ustx: locked_ustx as u64,
+            unlock_height,
+            stacking,
+            delegation,
+        })
+    }
+}
+
+/// Look up a BNS zonefile in the Atlas store by its hash.  Names without a zonefile have an empty
+/// zonefile hash.
+fn find_bns_zonefile(
+    atlasdb: &mut AtlasDB,
+    zonefile_hash: &[u8],
+) -> Result<Option<Attachment>, net_error> {
+    match Hash160::from_bytes(zonefile_hash) {
+        Some(content_hash) => Ok(atlasdb.find_attachment(&content_hash)?),
+        None => Ok(None),
+    }
+}
+
+impl RPCBnsNameData {
+    pub fn from_name_info(
+        atlasdb: &mut AtlasDB,
+        name: &str,
+        namespace: &str,
+        name_info: BnsNameInfo,
+    ) -> Result<RPCBnsNameData, net_error> {
+        let zonefile = find_bns_zonefile(atlasdb, &name_info.zonefile_hash)?
+            .map(|attachment| to_hex(&attachment.content));
+        Ok(RPCBnsNameData {
+            name: format!("{}.{}", name, namespace),
+            namespace: namespace.to_string(),
+            owner: name_info.owner.to_string(),
+            zonefile_hash: to_hex(&name_info.zonefile_hash),
+            zonefile,
+            lease_started_at: name_info.lease_started_at,
+            lease_ending_at: name_info.lease_ending_at,
+        })
+    }
+}
+
+impl RPCBnsNamespaceData {
+    pub fn from_namespace_info(namespace: &str, info: BnsNamespaceInfo) -> RPCBnsNamespaceData {
+        let price_function = info.price_function;
+        RPCBnsNamespaceData {
+            namespace: namespace.to_string(),
+            namespace_import: info.namespace_import.to_string(),
+            revealed_at: info.revealed_at,
+            launched_at: info.launched_at,
+            lifetime: info.lifetime,
+            can_update_price_function: info.can_update_price_function,
+            price_function: RPCBnsPriceFunction {
+                buckets: price_function
+                    .buckets
+                    .into_iter()
+                    .map(|bucket| bucket as u64)
+                    .collect(),
+                base: price_function.base as u64,
+                coeff: price_function.coeff as u64,
+                nonalpha_discount: price_function.nonalpha_discount as u64,
+                no_vowel_discount: price_function.no_vowel_discount as u64,
+            },
+        }
+    }
+}
+
 impl RPCNeighborsInfo {
     /// Load neighbor address information from the peer network
     pub fn from_p2p(
@@ -476,6 +685,50 @@ impl RPCNeighborsInfo {
     }
 }
 
+impl RPCAdminNeighborStats {
+    pub fn from_conversation(convo: &ConversationP2P) -> RPCAdminNeighborStats {
+        let nk = convo.to_neighbor_key();
+        let stats = &convo.stats;
+        RPCAdminNeighborStats {
+            network_id: nk.network_id,
+            peer_version: nk.peer_version,
+            addrbytes: nk.addrbytes,
+            port: nk.port,
+            outbound: stats.outbound,
+            first_contact_time: stats.first_contact_time,
+            last_contact_time: stats.last_contact_time,
+            last_send_time: stats.last_send_time,
+            last_recv_time: stats.last_recv_time,
+            last_handshake_time: stats.last_handshake_time,
+            bytes_tx: stats.bytes_tx,
+            bytes_rx: stats.bytes_rx,
+            msgs_tx: stats.msgs_tx,
+            msgs_rx: stats.msgs_rx,
+            msgs_rx_unsolicited: stats.msgs_rx_unsolicited,
+            msgs_err: stats.msgs_err,
+            health_score: stats.get_health_score(),
+            msg_rx_counts: stats
+                .msg_rx_counts
+                .iter()
+                .map(|(msg_id, count)| (format!("{:?}", msg_id), *count))
+                .collect::<BTreeMap<String, u64>>(),
+        }
+    }
+}
+
+/// Find the key of the connected neighbor at the given address, if any.
+fn find_connected_neighbor(
+    network: &PeerNetwork,
+    addrbytes: &PeerAddress,
+    port: u16,
+) -> Option<NeighborKey> {
+    network
+        .events
+        .keys()
+        .find(|nk| nk.addrbytes == *addrbytes && nk.port == port)
+        .cloned()
+}
+
 impl ConversationHttp {
     pub fn new(
         peer_addr: SocketAddr,
@@ -511,6 +764,21 @@ impl ConversationHttp {
         self.reply_streams.len()
     }
 
+    /// Is this conversation streaming events to the client?
+    pub fn is_streaming_events(&self) -> bool {
+        self.reply_streams
+            .iter()
+            .any(|(_, stream_opt, _)| match stream_opt {
+                Some((_, ReplyStreamData::Events(_))) => true,
+                _ => false,
+            })
+    }
+
+    /// Make this an admin API conversation, authenticated with the given bearer token
+    pub fn set_admin_auth_token(&mut self, auth_token: String) -> () {
+        self.connection.protocol.set_admin_auth_token(auth_token);
+    }
+
     /// What's our outbound URL?
     pub fn get_url(&self) -> Option<&UrlString> {
         self.outbound_url.as_ref()
@@ -661,6 +929,240 @@ impl ConversationHttp {
         }
     }
 
+    /// Handle a GET on a reward cycle's reward set.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_reward_set<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response =
+            match RPCRewardSetData::from_db(sortdb, chainstate, tip, burnchain, reward_cycle) {
+                Ok(reward_set) => HttpResponseType::RewardSet(response_metadata, reward_set),
+                Err(net_error::NotFoundError) => {
+                    debug!("Chain tip not found during get reward set: {:?}", req);
+                    HttpResponseType::NotFound(
+                        response_metadata,
+                        "Failed to find chain tip".to_string(),
+                    )
+                }
+                Err(e) => {
+                    warn!("Failed to get reward set {:?}: {:?}", req, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        "Failed to query reward set".to_string(),
+                    )
+                }
+            };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET on a principal's stacking state and lock schedule.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_stacker_info<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        stacker: &PrincipalData,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response =
+            match RPCStackerInfoData::from_db(sortdb, chainstate, tip, burnchain, stacker) {
+                Ok(stacker_info) => HttpResponseType::StackerInfo(response_metadata, stacker_info),
+                Err(net_error::NotFoundError) => {
+                    debug!("Chain tip not found during get stacker info: {:?}", req);
+                    HttpResponseType::NotFound(
+                        response_metadata,
+                        "Failed to find chain tip".to_string(),
+                    )
+                }
+                Err(e) => {
+                    warn!("Failed to get stacker info {:?}: {:?}", req, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        "Failed to query stacker info".to_string(),
+                    )
+                }
+            };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET on a BNS name's resolution, joined with its zonefile from the Atlas store.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_bns_name<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        atlasdb: &mut AtlasDB,
+        name: &str,
+        namespace: &str,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response = match chainstate
+            .get_bns_name(sortdb, tip, namespace.as_bytes(), name.as_bytes())
+            .map_err(net_error::from)
+            .and_then(|res| match res {
+                Ok(name_info) => {
+                    RPCBnsNameData::from_name_info(atlasdb, name, namespace, name_info).map(Ok)
+                }
+                Err(e) => Ok(Err(e)),
+            }) {
+            Ok(Ok(name_data)) => HttpResponseType::BnsName(response_metadata, name_data),
+            Ok(Err(e)) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("Name '{}.{}' does not resolve: {}", name, namespace, e),
+            ),
+            Err(e) => {
+                warn!("Failed to resolve BNS name {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to resolve BNS name".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET on a BNS name's current zonefile from the Atlas store.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_bns_zonefile<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        atlasdb: &mut AtlasDB,
+        name: &str,
+        namespace: &str,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response = match chainstate
+            .get_bns_name(sortdb, tip, namespace.as_bytes(), name.as_bytes())
+            .map_err(net_error::from)
+            .and_then(|res| match res {
+                Ok(name_info) => find_bns_zonefile(atlasdb, &name_info.zonefile_hash)
+                    .map(|zonefile_opt| Ok((name_info.zonefile_hash, zonefile_opt))),
+                Err(e) => Ok(Err(e)),
+            }) {
+            Ok(Ok((zonefile_hash, Some(zonefile)))) => HttpResponseType::BnsZonefile(
+                response_metadata,
+                RPCBnsZonefileData {
+                    name: format!("{}.{}", name, namespace),
+                    zonefile_hash: to_hex(&zonefile_hash),
+                    zonefile: to_hex(&zonefile.content),
+                },
+            ),
+            Ok(Ok((_, None))) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("No zonefile found for name '{}.{}'", name, namespace),
+            ),
+            Ok(Err(e)) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("Name '{}.{}' does not resolve: {}", name, namespace, e),
+            ),
+            Err(e) => {
+                warn!("Failed to load BNS zonefile {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load BNS zonefile".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET on a BNS namespace's properties.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_bns_namespace<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        namespace: &str,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response = match chainstate.get_bns_namespace(sortdb, tip, namespace.as_bytes()) {
+            Ok(Some(info)) => HttpResponseType::BnsNamespace(
+                response_metadata,
+                RPCBnsNamespaceData::from_namespace_info(namespace, info),
+            ),
+            Ok(None) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("Namespace '{}' not found", namespace),
+            ),
+            Err(e) => {
+                warn!("Failed to get BNS namespace {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query BNS namespace".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle a GET on the BNS names owned by a principal.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_bns_owner_names<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        owner: &PrincipalData,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let response = match chainstate.get_bns_owner_name(sortdb, tip, owner) {
+            Ok(name_opt) => HttpResponseType::BnsOwnerNames(
+                response_metadata,
+                RPCBnsOwnerNamesData {
+                    owner: owner.to_string(),
+                    names: name_opt
+                        .into_iter()
+                        .map(|(namespace, name)| {
+                            format!(
+                                "{}.{}",
+                                String::from_utf8_lossy(&name),
+                                String::from_utf8_lossy(&namespace)
+                            )
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to get BNS names {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query BNS names".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
     fn handle_getattachmentsinv<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
@@ -751,33 +1253,326 @@ impl ConversationHttp {
         }
     }
 
+    /// Handle a GET event stream.  Subscribe the client to the node's events and send back the
+    /// response preamble.  The events themselves are streamed as they are published.
+    fn handle_get_event_stream<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        keys: &Vec<EventStreamKey>,
+        handler_args: &RPCHandlerArgs,
+    ) -> Result<Option<EventStreamSubscription>, net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let hub = match handler_args.event_stream {
+            Some(hub) => hub,
+            None => {
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Event streaming is not enabled on this node".to_string(),
+                );
+                return response.send(http, fd).and_then(|_| Ok(None));
+            }
+        };
+
+        match hub.subscribe(keys.clone()) {
+            Some(subscription) => {
+                let response = HttpResponseType::EventStream(response_metadata);
+                response.send(http, fd).and_then(|_| Ok(Some(subscription)))
+            }
+            None => {
+                let response = HttpResponseType::ServiceUnavailable(
+                    response_metadata,
+                    "Too many event stream clients".to_string(),
+                );
+                response.send(http, fd).and_then(|_| Ok(None))
+            }
+        }
+    }
+
     /// Handle a GET neighbors
     /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
-    fn handle_getneighbors<W: Write>(
+    /// Handle an admin request for all peers in the peer DB.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin_get_peers<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
         network: &PeerNetwork,
     ) -> Result<(), net_error> {
-        let epoch = network.get_current_epoch();
-
         let response_metadata = HttpResponseMetadata::from(req);
-        let neighbor_data = RPCNeighborsInfo::from_p2p(
-            network.local_peer.network_id,
-            epoch.network_epoch,
-            &network.peers,
-            &network.chain_view,
-            &network.peerdb,
-        )?;
-        let response = HttpResponseType::Neighbors(response_metadata, neighbor_data);
+        let response = match PeerDB::get_all_peers(network.peerdb.conn()) {
+            Ok(neighbors) => {
+                let peers = neighbors
+                    .into_iter()
+                    .map(|neighbor| {
+                        let connected = find_connected_neighbor(
+                            network,
+                            &neighbor.addr.addrbytes,
+                            neighbor.addr.port,
+                        )
+                        .is_some();
+                        RPCAdminPeer {
+                            network_id: neighbor.addr.network_id,
+                            peer_version: neighbor.addr.peer_version,
+                            addrbytes: neighbor.addr.addrbytes,
+                            port: neighbor.addr.port,
+                            public_key_hash: Hash160::from_node_public_key(&neighbor.public_key),
+                            last_contact_time: neighbor.last_contact_time,
+                            allowed: neighbor.allowed,
+                            denied: neighbor.denied,
+                            connected,
+                        }
+                    })
+                    .collect();
+                HttpResponseType::AdminPeers(response_metadata, RPCAdminPeersData { peers })
+            }
+            Err(e) => {
+                warn!("Failed to load peers from the peer DB: {:?}", &e);
+                HttpResponseType::ServerError(response_metadata, "Failed to load peers".to_string())
+            }
+        };
         response.send(http, fd)
     }
 
-    /// Handle a not-found
-    fn handle_notfound<W: Write>(
+    /// Handle an admin request to ban (or unban) a peer.  The ban is recorded in the peer DB's
+    /// deny list.  If we're talking to the peer, it will be disconnected the next time the p2p
+    /// state machine processes bans.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin_ban_peer<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
-        response_metadata: HttpResponseMetadata,
+        req: &HttpRequestType,
+        network: &mut PeerNetwork,
+        addrbytes: &PeerAddress,
+        port: u16,
+        duration: Option<u64>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let denied = match duration {
+            Some(0) => 0,
+            Some(duration) => get_epoch_time_secs().saturating_add(duration),
+            None => get_epoch_time_secs() + DENY_BAN_DURATION,
+        };
+
+        let network_id = network.local_peer.network_id;
+        let res = network.peerdb.tx_begin().and_then(|mut tx| {
+            PeerDB::set_deny_peer(&mut tx, network_id, addrbytes, port, denied)?;
+            tx.commit().map_err(db_error::SqliteError)
+        });
+
+        let response = match res {
+            Ok(_) => {
+                let connected = match find_connected_neighbor(network, addrbytes, port) {
+                    Some(nk) => {
+                        if denied > 0 {
+                            if let Some(event_id) = network.events.get(&nk) {
+                                network.bans.insert(*event_id);
+                            }
+                            network.relayer_stats.process_neighbor_ban(&nk);
+                        }
+                        true
+                    }
+                    None => false,
+                };
+                if denied > 0 {
+                    info!(
+                        "Admin: banned peer {:?}:{} until {} (connected: {})",
+                        addrbytes, port, denied, connected
+                    );
+                } else {
+                    info!("Admin: lifted ban on peer {:?}:{}", addrbytes, port);
+                }
+                HttpResponseType::AdminPeerBanned(
+                    response_metadata,
+                    RPCAdminBanPeerData {
+                        addrbytes: addrbytes.clone(),
+                        port,
+                        denied,
+                        connected,
+                    },
+                )
+            }
+            Err(e) => {
+                warn!("Failed to ban peer {:?}:{}: {:?}", addrbytes, port, &e);
+                HttpResponseType::ServerError(response_metadata, "Failed to ban peer".to_string())
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle an admin request for the stats of all connected peers.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin_get_neighbor_stats<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &PeerNetwork,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let neighbors = network
+            .peers
+            .values()
+            .map(RPCAdminNeighborStats::from_conversation)
+            .collect();
+        let response = HttpResponseType::AdminNeighborStats(
+            response_metadata,
+            RPCAdminNeighborStatsData { neighbors },
+        );
+        response.send(http, fd)
+    }
+
+    /// Handle an admin request to drop transactions from the mempool.  Only the transactions that
+    /// were actually in the mempool are reported as dropped.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin_drop_transactions<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        mempool: &mut MemPoolDB,
+        txids: &Vec<Txid>,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let dropped: Vec<Txid> = txids
+            .iter()
+            .filter(|txid| mempool.has_tx(txid))
+            .cloned()
+            .collect();
+
+        let response = match mempool.drop_txs(&dropped) {
+            Ok(_) => {
+                info!("Admin: dropped {} mempool transaction(s)", dropped.len());
+                let dropped_hex = dropped.iter().map(|txid| txid.to_hex()).collect();
+                if let Some(event_observer) = event_observer {
+                    event_observer.mempool_txs_dropped(dropped, MemPoolDropReason::ADMIN_DROP);
+                }
+                HttpResponseType::AdminDroppedTransactions(
+                    response_metadata,
+                    RPCAdminDropTransactionsData {
+                        dropped: dropped_hex,
+                    },
+                )
+            }
+            Err(e) => {
+                warn!("Failed to drop mempool transactions: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to drop transactions".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle an admin request to garbage-collect the mempool now, rather than waiting for the
+    /// next batch of transactions to be relayed.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin_mempool_gc<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        mempool: &mut MemPoolDB,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let chain_height = match chainstate.get_stacks_chain_tip(sortdb) {
+            Ok(Some(tip)) => tip.height,
+            Ok(None) => 0,
+            Err(e) => {
+                warn!("Failed to load Stacks chain tip: {:?}", &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load Stacks chain tip".to_string(),
+                );
+                return response.send(http, fd);
+            }
+        };
+
+        let min_height = chain_height.saturating_sub(MEMPOOL_MAX_TRANSACTION_AGE);
+        let res = mempool.tx_begin().and_then(|mut mempool_tx| {
+            MemPoolDB::garbage_collect(&mut mempool_tx, min_height, event_observer)?;
+            mempool_tx.commit()
+        });
+
+        let response = match res {
+            Ok(_) => {
+                info!(
+                    "Admin: removed all mempool transactions beneath block height {}",
+                    min_height
+                );
+                HttpResponseType::AdminMempoolGarbageCollected(
+                    response_metadata,
+                    RPCAdminMempoolGarbageCollectData { min_height },
+                )
+            }
+            Err(e) => {
+                warn!("Failed to garbage-collect the mempool: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to garbage-collect the mempool".to_string(),
+                )
+            }
+        };
+        response.send(http, fd)
+    }
+
+    /// Handle an admin request to pause or resume mining.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_admin_set_mining_paused<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        paused: bool,
+        mining_paused: Option<&AtomicBool>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let response = match mining_paused {
+            Some(mining_paused) => {
+                mining_paused.store(paused, Ordering::SeqCst);
+                info!(
+                    "Admin: mining is {}",
+                    if paused { "paused" } else { "resumed" }
+                );
+                HttpResponseType::AdminMiningStatus(
+                    response_metadata,
+                    RPCAdminMiningStatusData { paused },
+                )
+            }
+            None => HttpResponseType::ServiceUnavailable(
+                response_metadata,
+                "This node cannot pause or resume mining".to_string(),
+            ),
+        };
+        response.send(http, fd)
+    }
+
+    fn handle_getneighbors<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &PeerNetwork,
+    ) -> Result<(), net_error> {
+        let epoch = network.get_current_epoch();
+
+        let response_metadata = HttpResponseMetadata::from(req);
+        let neighbor_data = RPCNeighborsInfo::from_p2p(
+            network.local_peer.network_id,
+            epoch.network_epoch,
+            &network.peers,
+            &network.chain_view,
+            &network.peerdb,
+        )?;
+        let response = HttpResponseType::Neighbors(response_metadata, neighbor_data);
+        response.send(http, fd)
+    }
+
+    /// Handle a not-found
+    fn handle_notfound<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        response_metadata: HttpResponseMetadata,
         msg: String,
     ) -> Result<Option<BlockStreamData>, net_error> {
         let response = HttpResponseType::NotFound(response_metadata, msg);
@@ -797,6 +1592,17 @@ impl ConversationHttp {
         return response.send(http, fd).and_then(|_| Ok(None));
     }
 
+    /// Handle a request for block data that this node has pruned
+    fn handle_pruned<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        response_metadata: HttpResponseMetadata,
+        msg: String,
+    ) -> Result<Option<BlockStreamData>, net_error> {
+        let response = HttpResponseType::Error(response_metadata, 410, msg);
+        return response.send(http, fd).and_then(|_| Ok(None));
+    }
+
     /// Handle a GET block.  Start streaming the reply.
     /// The response's preamble (but not the block data) will be synchronously written to the fd
     /// (so use a fd that can buffer!)
@@ -812,6 +1618,27 @@ impl ConversationHttp {
         monitoring::increment_stx_blocks_served_counter();
         let response_metadata = HttpResponseMetadata::from(req);
 
+        // did we have this block, but pruned it?
+        match StacksChainState::is_block_pruned(chainstate.db(), index_block_hash) {
+            Ok(false) => {}
+            Ok(true) => {
+                return ConversationHttp::handle_pruned(
+                    http,
+                    fd,
+                    response_metadata,
+                    format!("Block {} has been pruned", index_block_hash.to_hex()),
+                );
+            }
+            Err(e) => {
+                return ConversationHttp::handle_server_error(
+                    http,
+                    fd,
+                    response_metadata,
+                    format!("Failed to query block {:?}: {:?}", req, &e),
+                );
+            }
+        }
+
         // do we have this block?
         match StacksChainState::has_block_indexed(&chainstate.blocks_path, index_block_hash) {
             Ok(false) => {
@@ -855,6 +1682,33 @@ impl ConversationHttp {
         monitoring::increment_stx_confirmed_micro_blocks_served_counter();
         let response_metadata = HttpResponseMetadata::from(req);
 
+        // the microblocks are pruned along with the anchor block that confirms them
+        match StacksChainState::is_block_pruned(chainstate.db(), index_anchor_block_hash) {
+            Ok(false) => {}
+            Ok(true) => {
+                return ConversationHttp::handle_pruned(
+                    http,
+                    fd,
+                    response_metadata,
+                    format!(
+                        "Microblock stream confirmed by anchor block {} has been pruned",
+                        &index_anchor_block_hash
+                    ),
+                );
+            }
+            Err(e) => {
+                return ConversationHttp::handle_server_error(
+                    http,
+                    fd,
+                    response_metadata,
+                    format!(
+                        "Failed to query confirmed microblock stream {:?}: {:?}",
+                        req, &e
+                    ),
+                );
+            }
+        }
+
         match chainstate.has_processed_microblocks(index_anchor_block_hash) {
             Ok(true) => {}
             Ok(false) => {
@@ -960,6 +1814,33 @@ impl ConversationHttp {
         monitoring::increment_stx_micro_blocks_served_counter();
         let response_metadata = HttpResponseMetadata::from(req);
 
+        // did we have this microblock stream, but pruned it?
+        match StacksChainState::is_microblock_pruned(chainstate.db(), tail_index_microblock_hash) {
+            Ok(false) => {}
+            Ok(true) => {
+                return ConversationHttp::handle_pruned(
+                    http,
+                    fd,
+                    response_metadata,
+                    format!(
+                        "Microblock stream ending with {} has been pruned",
+                        &tail_index_microblock_hash
+                    ),
+                );
+            }
+            Err(e) => {
+                return ConversationHttp::handle_server_error(
+                    http,
+                    fd,
+                    response_metadata,
+                    format!(
+                        "Failed to serve confirmed microblock stream {:?}: {:?}",
+                        req, &e
+                    ),
+                );
+            }
+        }
+
         // do we have this processed microblock stream?
         match StacksChainState::has_processed_microblocks_indexed(
             chainstate.db(),
@@ -1109,6 +1990,95 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a GET on an account's balance and nonce history over a range of block heights in the
+    /// fork ending at the given chain tip.  Optionally supplies MARF proofs for each entry.
+    fn handle_get_account_history<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        account: &PrincipalData,
+        start_height: u64,
+        end_height: u64,
+        with_proof: bool,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if end_height < start_height || end_height - start_height >= MAX_ACCOUNT_HISTORY_RANGE {
+            let response = HttpResponseType::BadRequest(
+                response_metadata,
+                format!(
+                    "Invalid block range: must span between 1 and {} blocks",
+                    MAX_ACCOUNT_HISTORY_RANGE
+                ),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
+
+        let response = match chainstate.get_account_history(
+            &sortdb.index_conn(),
+            tip,
+            account,
+            start_height,
+            end_height,
+            with_proof,
+        ) {
+            Ok(entries) => {
+                let history = entries
+                    .into_iter()
+                    .map(|entry| {
+                        let (balance_proof, nonce_proof) = if with_proof {
+                            (
+                                Some(
+                                    entry
+                                        .balance_proof
+                                        .map(|proof| format!("0x{}", proof.to_hex()))
+                                        .unwrap_or("".into()),
+                                ),
+                                Some(
+                                    entry
+                                        .nonce_proof
+                                        .map(|proof| format!("0x{}", proof.to_hex()))
+                                        .unwrap_or("".into()),
+                                ),
+                            )
+                        } else {
+                            (None, None)
+                        };
+                        AccountHistoryEntryResponse {
+                            index_block_hash: entry.index_block_hash,
+                            block_height: entry.block_height,
+                            burn_block_height: entry.burn_block_height,
+                            balance: format!("0x{}", to_hex(&entry.unlocked.to_be_bytes())),
+                            locked: format!("0x{}", to_hex(&entry.locked.to_be_bytes())),
+                            unlock_height: entry.unlock_height,
+                            nonce: entry.nonce,
+                            balance_proof,
+                            nonce_proof,
+                        }
+                    })
+                    .collect();
+                HttpResponseType::GetAccountHistory(
+                    response_metadata,
+                    AccountHistoryResponse { history },
+                )
+            }
+            Err(chain_error::NoSuchBlockError) => {
+                HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+            }
+            Err(e) => {
+                warn!("Failed to load history of {}: {:?}", account, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load account history".to_string(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET on a smart contract's data map, given the current chain tip.  Optionally
     /// supplies a MARF proof for the value.
     fn handle_get_map_entry<W: Write>(
@@ -1536,30 +2506,375 @@ impl ConversationHttp {
         return response.send(http, fd).map(|_| ());
     }
 
-    /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
-    /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
-    /// will return different values here.
-    /// tip_opt is given by the HTTP request as the optional query parameter for the chain tip
-    /// hash.  It will be None if there was no paramter given.
-    /// The order of chain tips this method prefers is as follows:
-    /// * tip_opt, if it's Some(..),
-    /// * the unconfirmed canonical stacks chain tip, if initialized
-    /// * the confirmed canonical stacks chain tip
-    fn handle_load_stacks_chain_tip<W: Write>(
+    /// Handle a GET confirmed transaction, which is looked up in the transaction log.
+    /// The response will be synchronously written to the fd.
+    fn handle_gettransaction<W: Write>(
         http: &mut StacksHttp,
         fd: &mut W,
         req: &HttpRequestType,
-        tip_opt: Option<&StacksBlockId>,
         sortdb: &SortitionDB,
         chainstate: &StacksChainState,
-    ) -> Result<Option<StacksBlockId>, net_error> {
-        match tip_opt {
-            Some(tip) => Ok(Some(*tip).clone()),
-            None => match chainstate.get_stacks_chain_tip(sortdb)? {
-                Some(tip) => Ok(Some(StacksBlockHeader::make_index_block_hash(
-                    &tip.consensus_hash,
-                    &tip.anchored_block_hash,
-                ))),
+        txid: &Txid,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let entries = match StacksChainState::get_logged_transaction(chainstate.db(), txid) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Failed to query transaction log for {}: {:?}", txid, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to query transaction {}", txid),
+                );
+                return response.send(http, fd).map(|_| ());
+            }
+        };
+
+        if entries.len() == 0 {
+            let msg = if *TRANSACTION_LOG {
+                format!("No such confirmed transaction {}", txid)
+            } else {
+                format!(
+                    "No such confirmed transaction {} (the transaction log is disabled)",
+                    txid
+                )
+            };
+            return ConversationHttp::handle_notfound(http, fd, response_metadata, msg).map(|_| ());
+        }
+
+        let response = match ConversationHttp::make_confirmed_transaction_response(
+            sortdb, chainstate, entries,
+        ) {
+            Ok(Some(confirmed)) => {
+                HttpResponseType::ConfirmedTransaction(response_metadata, confirmed)
+            }
+            Ok(None) => {
+                let msg = format!("No such confirmed transaction {} in a known block", txid);
+                return ConversationHttp::handle_notfound(http, fd, response_metadata, msg)
+                    .map(|_| ());
+            }
+            Err(e) => {
+                warn!("Failed to load blocks of transaction {}: {:?}", txid, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to query transaction {}", txid),
+                )
+            }
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Find the block of each transaction log entry, and whether or not it is on the canonical
+    /// Stacks fork.  Returns None if none of the entries' blocks are known.
+    fn make_confirmed_transaction_response(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        entries: Vec<TransactionLogEntry>,
+    ) -> Result<Option<ConfirmedTransactionResponse>, chain_error> {
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+        let canonical_tip =
+            StacksBlockHeader::make_index_block_hash(&tip_consensus_hash, &tip_block_hash);
+        let index_conn = chainstate.index_conn()?;
+
+        let mut blocks = vec![];
+        let mut tx = String::new();
+        for entry in entries.into_iter() {
+            let header_info =
+                match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    chainstate.db(),
+                    &entry.index_block_hash,
+                )? {
+                    Some(header_info) => header_info,
+                    None => {
+                        warn!(
+                            "Transaction {} was logged in unknown block {}",
+                            &entry.txid, &entry.index_block_hash
+                        );
+                        continue;
+                    }
+                };
+            let canonical = index_conn
+                .get_ancestor_block_height(&entry.index_block_hash, &canonical_tip)?
+                .is_some();
+
+            tx = entry.tx_hex;
+            blocks.push(ConfirmedTransactionBlock {
+                index_block_hash: entry.index_block_hash,
+                block_hash: header_info.anchored_header.block_hash(),
+                consensus_hash: header_info.consensus_hash,
+                block_height: header_info.block_height,
+                result: entry.result,
+                canonical,
+            });
+        }
+
+        if blocks.len() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(ConfirmedTransactionResponse { tx, blocks }))
+    }
+
+    /// Handle a GET of a page of the event log's events for a contract or a principal.  Only
+    /// events in blocks on the canonical Stacks fork are returned, from the most recent block to
+    /// the oldest.  `query_page` loads a page of matching events across all forks.
+    /// The response will be synchronously written to the fd.
+    fn handle_get_events_page<W: Write, F>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        before_height: Option<u64>,
+        limit: Option<u32>,
+        query_page: F,
+    ) -> Result<(), net_error>
+    where
+        F: Fn(u64, u32, u32) -> Result<Vec<TransactionEventEntry>, chain_error>,
+    {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let limit = match limit {
+            Some(limit) => cmp::min(limit, MAX_EVENTS_PER_REQUEST),
+            None => MAX_EVENTS_PER_REQUEST,
+        };
+
+        let response = match ConversationHttp::load_canonical_events_page(
+            sortdb,
+            chainstate,
+            before_height,
+            limit,
+            query_page,
+        )
+        .and_then(|entries| ConversationHttp::make_events_response(chainstate, entries))
+        {
+            Ok(events) => HttpResponseType::Events(response_metadata, events),
+            Err(e) => {
+                warn!("Failed to query event log: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query event log".to_string(),
+                )
+            }
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Load up to `limit` event log entries on the canonical Stacks fork, from blocks below
+    /// `before_height` (or the canonical chain tip, if not given).  A block's events are never
+    /// split across pages, so more than `limit` entries may be returned.
+    fn load_canonical_events_page<F>(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        before_height: Option<u64>,
+        limit: u32,
+        query_page: F,
+    ) -> Result<Vec<TransactionEventEntry>, chain_error>
+    where
+        F: Fn(u64, u32, u32) -> Result<Vec<TransactionEventEntry>, chain_error>,
+    {
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+        let canonical_tip =
+            StacksBlockHeader::make_index_block_hash(&tip_consensus_hash, &tip_block_hash);
+        let before_height = match before_height {
+            Some(height) => height,
+            None => {
+                StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    chainstate.db(),
+                    &canonical_tip,
+                )?
+                .map(|header_info| header_info.block_height)
+                .unwrap_or(0)
+                    + 1
+            }
+        };
+        let index_conn = chainstate.index_conn()?;
+
+        let mut canonical_blocks = HashMap::new();
+        let mut entries: Vec<TransactionEventEntry> = vec![];
+        let mut offset = 0;
+        'pages: loop {
+            let page = query_page(before_height, limit, offset)?;
+            if page.len() == 0 {
+                break;
+            }
+            offset += page.len() as u32;
+
+            for entry in page.into_iter() {
+                if entries.len() >= limit as usize
+                    && entries.last().map(|last| last.block_height) != Some(entry.block_height)
+                {
+                    break 'pages;
+                }
+                let canonical = match canonical_blocks.get(&entry.index_block_hash) {
+                    Some(canonical) => *canonical,
+                    None => {
+                        let canonical = index_conn
+                            .get_ancestor_block_height(&entry.index_block_hash, &canonical_tip)?
+                            .is_some();
+                        canonical_blocks.insert(entry.index_block_hash.clone(), canonical);
+                        canonical
+                    }
+                };
+                if canonical {
+                    entries.push(entry);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Handle a GET of the event log's events for a range of blocks on the canonical Stacks
+    /// fork, from the oldest block to the most recent.
+    /// The response will be synchronously written to the fd.
+    fn handle_get_block_range_events<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if end_height < start_height || end_height - start_height >= MAX_EVENT_BLOCK_RANGE {
+            let response = HttpResponseType::BadRequest(
+                response_metadata,
+                format!(
+                    "Invalid block range: must span between 1 and {} blocks",
+                    MAX_EVENT_BLOCK_RANGE
+                ),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
+
+        let response = match ConversationHttp::load_canonical_block_range_events(
+            sortdb,
+            chainstate,
+            start_height,
+            end_height,
+        )
+        .and_then(|entries| ConversationHttp::make_events_response(chainstate, entries))
+        {
+            Ok(events) => HttpResponseType::Events(response_metadata, events),
+            Err(e) => {
+                warn!(
+                    "Failed to query event log for blocks {}-{}: {:?}",
+                    start_height, end_height, &e
+                );
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query event log".to_string(),
+                )
+            }
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
+    fn load_canonical_block_range_events(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<TransactionEventEntry>, chain_error> {
+        let (tip_consensus_hash, tip_block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+        let canonical_tip =
+            StacksBlockHeader::make_index_block_hash(&tip_consensus_hash, &tip_block_hash);
+        let index_conn = chainstate.index_conn()?;
+
+        let mut canonical_blocks = HashMap::new();
+        let mut entries = vec![];
+        for entry in StacksChainState::get_logged_block_range_events(
+            chainstate.db(),
+            start_height,
+            end_height,
+        )?
+        .into_iter()
+        {
+            let canonical = match canonical_blocks.get(&entry.index_block_hash) {
+                Some(canonical) => *canonical,
+                None => {
+                    let canonical = index_conn
+                        .get_ancestor_block_height(&entry.index_block_hash, &canonical_tip)?
+                        .is_some();
+                    canonical_blocks.insert(entry.index_block_hash.clone(), canonical);
+                    canonical
+                }
+            };
+            if canonical {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Pair up event log entries with the receipts of the transactions that emitted them.
+    fn make_events_response(
+        chainstate: &StacksChainState,
+        entries: Vec<TransactionEventEntry>,
+    ) -> Result<EventsResponse, chain_error> {
+        let mut events = vec![];
+        let mut receipts = vec![];
+        let mut seen_receipts = HashSet::new();
+        for entry in entries.into_iter() {
+            if seen_receipts.insert((entry.txid.clone(), entry.index_block_hash.clone())) {
+                match StacksChainState::get_logged_receipt(
+                    chainstate.db(),
+                    &entry.txid,
+                    &entry.index_block_hash,
+                )? {
+                    Some(receipt) => receipts.push(EventLogReceipt {
+                        txid: format!("0x{}", &receipt.txid),
+                        index_block_hash: receipt.index_block_hash,
+                        block_height: receipt.block_height,
+                        tx_index: receipt.tx_index,
+                        result: format!("0x{}", &receipt.result),
+                        post_condition_aborted: receipt.post_condition_aborted,
+                        execution_cost: receipt.execution_cost,
+                    }),
+                    None => {
+                        warn!(
+                            "Event of transaction {} in block {} has no logged receipt",
+                            &entry.txid, &entry.index_block_hash
+                        );
+                    }
+                }
+            }
+            events.push(EventLogEvent {
+                index_block_hash: entry.index_block_hash,
+                block_height: entry.block_height,
+                tx_index: entry.tx_index,
+                event: entry.event,
+            });
+        }
+        Ok(EventsResponse { events, receipts })
+    }
+
+    /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
+    /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
+    /// will return different values here.
+    /// tip_opt is given by the HTTP request as the optional query parameter for the chain tip
+    /// hash.  It will be None if there was no paramter given.
+    /// The order of chain tips this method prefers is as follows:
+    /// * tip_opt, if it's Some(..),
+    /// * the unconfirmed canonical stacks chain tip, if initialized
+    /// * the confirmed canonical stacks chain tip
+    fn handle_load_stacks_chain_tip<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip_opt: Option<&StacksBlockId>,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<StacksBlockId>, net_error> {
+        match tip_opt {
+            Some(tip) => Ok(Some(*tip).clone()),
+            None => match chainstate.get_stacks_chain_tip(sortdb)? {
+                Some(tip) => Ok(Some(StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                ))),
                 None => {
                     let response_metadata = HttpResponseMetadata::from(req);
                     warn!("Failed to load Stacks chain tip");
@@ -1763,6 +3078,133 @@ impl ConversationHttp {
         response.send(http, fd).and_then(|_| Ok(accepted))
     }
 
+    /// Handle a POST to simulate a transaction against the given chain tip.  The transaction is
+    /// processed in a throwaway block built on the tip (or in the unconfirmed state, if that is
+    /// the tip and it is writable), which is rolled back afterwards.  The transaction may not
+    /// exceed the connection options' `simulate_transaction_limit`, since the chainstate is
+    /// locked while it runs.  Returns a SimulateTransactionResponse with the transaction's
+    /// result, events, and cost.
+    fn handle_simulate_transaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        tx: &StacksTransaction,
+        options: &ConnectionOptions,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if !options.enable_transaction_simulation {
+            let response = HttpResponseType::NotFound(
+                response_metadata,
+                "Transaction simulation is not enabled on this node".to_string(),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
+
+        let burn_dbconn = sortdb.index_conn();
+        let mainnet = chainstate.mainnet;
+
+        let is_writable_unconfirmed_tip = match chainstate.unconfirmed_state {
+            Some(ref unconfirmed) => {
+                unconfirmed.unconfirmed_chain_tip == *tip && unconfirmed.is_writable()
+            }
+            None => false,
+        };
+        let tip_hashes = if is_writable_unconfirmed_tip {
+            None
+        } else {
+            match chainstate.get_block_header_hashes(tip)? {
+                Some(hashes) => Some(hashes),
+                None => {
+                    let response =
+                        HttpResponseType::NotFound(response_metadata, "Chain tip not found".into());
+                    return response.send(http, fd).map(|_| ());
+                }
+            }
+        };
+
+        let mut clarity_tx = match tip_hashes {
+            Some((consensus_hash, block_hash)) => chainstate.block_begin(
+                &burn_dbconn,
+                &consensus_hash,
+                &block_hash,
+                &MINER_BLOCK_CONSENSUS_HASH,
+                &MINER_BLOCK_HEADER_HASH,
+            ),
+            None => chainstate
+                .begin_unconfirmed(&burn_dbconn)
+                .expect("BUG: writable unconfirmed state could not begin a Clarity tx"),
+        };
+        let epoch = clarity_tx.get_epoch();
+        let cost_limit = options.simulate_transaction_limit.clone();
+        let cost_track_res = clarity_tx.connection().as_transaction(|clarity_tx| {
+            clarity_tx.with_clarity_db(|clarity_db| {
+                LimitedCostTracker::new_mid_block(mainnet, cost_limit, clarity_db, epoch).map_err(
+                    |_| ClarityRuntimeError::from(InterpreterError::CostContractLoadFailure).into(),
+                )
+            })
+        });
+        let cost_track = match cost_track_res {
+            Ok(cost_track) => cost_track,
+            Err(e) => {
+                clarity_tx.rollback_block();
+                warn!(
+                    "Failed to load cost contract to simulate transaction: {:?}",
+                    &e
+                );
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load cost contract".to_string(),
+                );
+                return response.send(http, fd).map(|_| ());
+            }
+        };
+
+        let (result, _) = clarity_tx.with_temporary_cost_tracker(cost_track, |clarity_tx| {
+            StacksChainState::process_transaction(clarity_tx, tx, true)
+        });
+        clarity_tx.rollback_block();
+
+        let simulation = match result {
+            Ok((fee, receipt)) => {
+                let txid = tx.txid();
+                let committed = !receipt.post_condition_aborted;
+                SimulateTransactionResponse {
+                    okay: true,
+                    result: Some(format!("0x{}", receipt.result.serialize())),
+                    post_condition_aborted: Some(receipt.post_condition_aborted),
+                    fee: Some(fee),
+                    events: Some(
+                        receipt
+                            .events
+                            .iter()
+                            .enumerate()
+                            .map(|(event_index, event)| {
+                                event.json_serialize(event_index, &txid, committed)
+                            })
+                            .collect(),
+                    ),
+                    execution_cost: Some(receipt.execution_cost),
+                    cause: None,
+                }
+            }
+            Err(e) => SimulateTransactionResponse {
+                okay: false,
+                result: None,
+                post_condition_aborted: None,
+                fee: None,
+                events: None,
+                execution_cost: None,
+                cause: Some(e.to_string()),
+            },
+        };
+
+        let response = HttpResponseType::TransactionSimulation(response_metadata, simulation);
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a block.  Directly submit a Stacks block to this node's chain state.
     /// Indicate whether or not the block was accepted (i.e. it was new, and valid)
     fn handle_post_block<W: Write>(
@@ -1949,6 +3391,7 @@ impl ConversationHttp {
         let mut reply = self.connection.make_relay_handle(self.conn_id)?;
         let keep_alive = req.metadata().keep_alive;
         let mut ret = None;
+        let mut event_stream_opt = None;
 
         let stream_opt = match req {
             HttpRequestType::GetInfo(ref _md) => {
@@ -1983,33 +3426,165 @@ impl ConversationHttp {
                 }
                 None
             }
-            HttpRequestType::GetNeighbors(ref _md) => {
-                ConversationHttp::handle_getneighbors(
+            HttpRequestType::GetRewardSet(ref _md, reward_cycle, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
-                    network,
-                )?;
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_reward_set(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &network.burnchain,
+                        reward_cycle,
+                    )?;
+                }
                 None
             }
-            HttpRequestType::GetBlock(ref _md, ref index_block_hash) => {
-                ConversationHttp::handle_getblock(
+            HttpRequestType::GetStackerInfo(ref _md, ref stacker, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
-                    index_block_hash,
+                    tip_opt.as_ref(),
+                    sortdb,
                     chainstate,
-                )?
+                )? {
+                    ConversationHttp::handle_get_stacker_info(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &network.burnchain,
+                        stacker,
+                    )?;
+                }
+                None
             }
-            HttpRequestType::GetMicroblocksIndexed(ref _md, ref index_head_hash) => {
-                ConversationHttp::handle_getmicroblocks_indexed(
+            HttpRequestType::GetBnsName(ref _md, ref name, ref namespace, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
-                    index_head_hash,
+                    tip_opt.as_ref(),
+                    sortdb,
                     chainstate,
-                )?
-            }
+                )? {
+                    ConversationHttp::handle_get_bns_name(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &mut network.atlasdb,
+                        name,
+                        namespace,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetBnsZonefile(ref _md, ref name, ref namespace, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_bns_zonefile(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &mut network.atlasdb,
+                        name,
+                        namespace,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetBnsNamespace(ref _md, ref namespace, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_bns_namespace(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        namespace,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetBnsOwnerNames(ref _md, ref owner, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_bns_owner_names(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        owner,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetNeighbors(ref _md) => {
+                ConversationHttp::handle_getneighbors(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                )?;
+                None
+            }
+            HttpRequestType::GetBlock(ref _md, ref index_block_hash) => {
+                ConversationHttp::handle_getblock(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    index_block_hash,
+                    chainstate,
+                )?
+            }
+            HttpRequestType::GetMicroblocksIndexed(ref _md, ref index_head_hash) => {
+                ConversationHttp::handle_getmicroblocks_indexed(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    index_head_hash,
+                    chainstate,
+                )?
+            }
             HttpRequestType::GetMicroblocksConfirmed(ref _md, ref anchor_index_block_hash) => {
                 ConversationHttp::handle_getmicroblocks_confirmed(
                     &mut self.connection.protocol,
@@ -2042,6 +3617,17 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::GetTransaction(ref _md, ref txid) => {
+                ConversationHttp::handle_gettransaction(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    txid,
+                )?;
+                None
+            }
             HttpRequestType::GetAccount(ref _md, ref principal, ref tip_opt, ref with_proof) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -2064,6 +3650,37 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetAccountHistory(
+                ref _md,
+                ref principal,
+                ref start_height,
+                ref end_height,
+                ref tip_opt,
+                ref with_proof,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_account_history(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        principal,
+                        *start_height,
+                        *end_height,
+                        *with_proof,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetMapEntry(
                 ref _md,
                 ref contract_addr,
@@ -2241,6 +3858,28 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::SimulateTransaction(ref _md, ref tx, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_simulate_transaction(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        tx,
+                        &self.connection.options,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetAttachment(ref _md, ref content_hash) => {
                 ConversationHttp::handle_getattachment(
                     &mut self.connection.protocol,
@@ -2354,6 +3993,148 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetEventStream(ref _md, ref keys) => {
+                event_stream_opt = ConversationHttp::handle_get_event_stream(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    keys,
+                    handler_opts,
+                )?;
+                None
+            }
+            HttpRequestType::GetContractEvents(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref before_height,
+                ref limit,
+            ) => {
+                let contract_identifier = QualifiedContractIdentifier::new(
+                    contract_addr.clone().into(),
+                    contract_name.clone(),
+                );
+                ConversationHttp::handle_get_events_page(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    *before_height,
+                    *limit,
+                    |before_height, limit, offset| {
+                        StacksChainState::get_logged_contract_events(
+                            chainstate.db(),
+                            &contract_identifier,
+                            before_height,
+                            limit,
+                            offset,
+                        )
+                    },
+                )?;
+                None
+            }
+            HttpRequestType::GetPrincipalEvents(
+                ref _md,
+                ref principal,
+                ref before_height,
+                ref limit,
+            ) => {
+                ConversationHttp::handle_get_events_page(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    *before_height,
+                    *limit,
+                    |before_height, limit, offset| {
+                        StacksChainState::get_logged_principal_events(
+                            chainstate.db(),
+                            principal,
+                            before_height,
+                            limit,
+                            offset,
+                        )
+                    },
+                )?;
+                None
+            }
+            HttpRequestType::GetBlockRangeEvents(ref _md, ref start_height, ref end_height) => {
+                ConversationHttp::handle_get_block_range_events(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    *start_height,
+                    *end_height,
+                )?;
+                None
+            }
+            HttpRequestType::AdminGetPeers(ref _md) => {
+                ConversationHttp::handle_admin_get_peers(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                )?;
+                None
+            }
+            HttpRequestType::AdminBanPeer(ref _md, ref addrbytes, ref port, ref duration) => {
+                ConversationHttp::handle_admin_ban_peer(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    addrbytes,
+                    *port,
+                    duration.clone(),
+                )?;
+                None
+            }
+            HttpRequestType::AdminGetNeighborStats(ref _md) => {
+                ConversationHttp::handle_admin_get_neighbor_stats(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                )?;
+                None
+            }
+            HttpRequestType::AdminDropTransactions(ref _md, ref txids) => {
+                ConversationHttp::handle_admin_drop_transactions(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    mempool,
+                    txids,
+                    handler_opts.event_observer,
+                )?;
+                None
+            }
+            HttpRequestType::AdminMempoolGarbageCollect(ref _md) => {
+                ConversationHttp::handle_admin_mempool_gc(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    mempool,
+                    handler_opts.event_observer,
+                )?;
+                None
+            }
+            HttpRequestType::AdminSetMiningPaused(ref _md, ref paused) => {
+                ConversationHttp::handle_admin_set_mining_paused(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    *paused,
+                    handler_opts.mining_paused,
+                )?;
+                None
+            }
             HttpRequestType::ClientError(ref _md, ref err) => {
                 let response_metadata = HttpResponseMetadata::from(&req);
                 let response = match err {
@@ -2364,6 +4145,9 @@ impl ConversationHttp {
                     ClientError::NotFound(path) => {
                         HttpResponseType::NotFound(response_metadata, path.clone())
                     }
+                    ClientError::Unauthorized(msg) => {
+                        HttpResponseType::Unauthorized(response_metadata, msg.clone())
+                    }
                 };
 
                 response
@@ -2373,6 +4157,10 @@ impl ConversationHttp {
             }
         };
 
+        let stream_opt = stream_opt
+            .map(ReplyStreamData::Blocks)
+            .or(event_stream_opt.map(ReplyStreamData::Events));
+
         match stream_opt {
             None => {
                 self.reply_streams.push_back((reply, None, keep_alive));
@@ -2416,7 +4204,7 @@ impl ConversationHttp {
 
                 // if we're streaming, make some progress on the stream
                 match stream_opt {
-                    Some((ref mut http_chunk_state, ref mut stream)) => {
+                    Some((ref mut http_chunk_state, ReplyStreamData::Blocks(ref mut stream))) => {
                         let mut encoder =
                             HttpChunkedTransferWriter::from_writer_state(reply, http_chunk_state);
                         match stream.stream_to(chainstate, &mut encoder, STREAM_CHUNK_SIZE) {
@@ -2461,6 +4249,57 @@ impl ConversationHttp {
                             }
                         }
                     }
+                    Some((
+                        ref mut http_chunk_state,
+                        ReplyStreamData::Events(ref mut subscription),
+                    )) => {
+                        // only hand over more events once the connection has taken the ones we
+                        // already gave it
+                        match reply.try_flush_buffered() {
+                            Ok(true) => {
+                                let mut encoder = HttpChunkedTransferWriter::from_writer_state(
+                                    reply,
+                                    http_chunk_state,
+                                );
+                                match subscription.stream_to(&mut encoder, STREAM_CHUNK_SIZE) {
+                                    Ok(nw) => {
+                                        if nw > 0 {
+                                            test_debug!("streamed {} bytes of events", nw);
+                                        }
+                                        encoder.flush_buffered().map_err(net_error::WriteError)?;
+                                    }
+                                    Err(net_error::WriteError(e)) => {
+                                        warn!("Broken HTTP connection: {:?}", &e);
+                                        broken = true;
+                                    }
+                                    Err(e) => {
+                                        // the client fell too far behind -- end the stream
+                                        debug!("Ending event stream: {:?}", &e);
+                                        if !encoder.corked() {
+                                            encoder.flush().map_err(net_error::WriteError)?;
+                                            encoder.cork();
+                                        }
+
+                                        match reply.try_flush() {
+                                            Ok(res) => {
+                                                drained_handle = res;
+                                            }
+                                            Err(e) => {
+                                                warn!("Broken HTTP connection: {:?}", &e);
+                                                broken = true;
+                                            }
+                                        }
+                                        drained_stream = true;
+                                    }
+                                }
+                            }
+                            Ok(false) => {}
+                            Err(e) => {
+                                warn!("Broken HTTP connection: {:?}", &e);
+                                broken = true;
+                            }
+                        }
+                    }
                     None => {
                         // not streamed; all data is buffered
                         drained_stream = true;
@@ -2748,13 +4587,95 @@ impl ConversationHttp {
         )
     }
 
-    /// Make a new getneighbors request to this endpoint
-    pub fn new_getneighbors(&self) -> HttpRequestType {
-        HttpRequestType::GetNeighbors(HttpRequestMetadata::from_host(self.peer_host.clone()))
-    }
-
-    /// Make a new getblock request to this endpoint
-    pub fn new_getblock(&self, index_block_hash: StacksBlockId) -> HttpRequestType {
+    /// Make a new request for a reward cycle's reward set
+    pub fn new_get_reward_set(
+        &self,
+        reward_cycle: u64,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetRewardSet(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            reward_cycle,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for a principal's stacking state
+    pub fn new_get_stacker_info(
+        &self,
+        stacker: PrincipalData,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetStackerInfo(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            stacker,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to resolve a BNS name
+    pub fn new_get_bns_name(
+        &self,
+        name: String,
+        namespace: String,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBnsName(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            name,
+            namespace,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for a BNS name's zonefile
+    pub fn new_get_bns_zonefile(
+        &self,
+        name: String,
+        namespace: String,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBnsZonefile(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            name,
+            namespace,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for a BNS namespace's properties
+    pub fn new_get_bns_namespace(
+        &self,
+        namespace: String,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBnsNamespace(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            namespace,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request for the BNS names owned by a principal
+    pub fn new_get_bns_owner_names(
+        &self,
+        owner: PrincipalData,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBnsOwnerNames(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            owner,
+            tip_opt,
+        )
+    }
+
+    /// Make a new getneighbors request to this endpoint
+    pub fn new_getneighbors(&self) -> HttpRequestType {
+        HttpRequestType::GetNeighbors(HttpRequestMetadata::from_host(self.peer_host.clone()))
+    }
+
+    /// Make a new getblock request to this endpoint
+    pub fn new_getblock(&self, index_block_hash: StacksBlockId) -> HttpRequestType {
         HttpRequestType::GetBlock(
             HttpRequestMetadata::from_host(self.peer_host.clone()),
             index_block_hash,
@@ -2804,6 +4725,59 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new get-tx request
+    pub fn new_gettransaction(&self, txid: Txid) -> HttpRequestType {
+        HttpRequestType::GetTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            txid,
+        )
+    }
+
+    /// Make a new request for a page of a contract's events
+    pub fn new_get_contract_events(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        before_height: Option<u64>,
+        limit: Option<u32>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetContractEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            before_height,
+            limit,
+        )
+    }
+
+    /// Make a new request for a page of a principal's events
+    pub fn new_get_principal_events(
+        &self,
+        principal: PrincipalData,
+        before_height: Option<u64>,
+        limit: Option<u32>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetPrincipalEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            principal,
+            before_height,
+            limit,
+        )
+    }
+
+    /// Make a new request for the events in a range of blocks
+    pub fn new_get_block_range_events(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBlockRangeEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            start_height,
+            end_height,
+        )
+    }
+
     /// Make a new post-transaction request
     pub fn new_post_transaction(&self, tx: StacksTransaction) -> HttpRequestType {
         HttpRequestType::PostTransaction(
@@ -2813,6 +4787,19 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new simulate-transaction request
+    pub fn new_simulate_transaction(
+        &self,
+        tx: StacksTransaction,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            tx,
+            tip_opt,
+        )
+    }
+
     /// Make a new post-block request
     pub fn new_post_block(&self, ch: ConsensusHash, block: StacksBlock) -> HttpRequestType {
         HttpRequestType::PostBlock(
@@ -2850,6 +4837,25 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for an account's balance and nonce history
+    pub fn new_getaccount_history(
+        &self,
+        principal: PrincipalData,
+        start_height: u64,
+        end_height: u64,
+        tip_opt: Option<StacksBlockId>,
+        with_proof: bool,
+    ) -> HttpRequestType {
+        HttpRequestType::GetAccountHistory(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            principal,
+            start_height,
+            end_height,
+            tip_opt,
+            with_proof,
+        )
+    }
+
     /// Make a new request for a data map
     pub fn new_getmapentry(
         &self,
@@ -2936,6 +4942,56 @@ impl ConversationHttp {
             pages_indexes,
         )
     }
+
+    /// Make a new admin request for all peers in the peer DB
+    pub fn new_admin_get_peers(&self) -> HttpRequestType {
+        HttpRequestType::AdminGetPeers(HttpRequestMetadata::from_host(self.peer_host.clone()))
+    }
+
+    /// Make a new admin request to ban a peer.  A duration of 0 lifts the ban.
+    pub fn new_admin_ban_peer(
+        &self,
+        addrbytes: PeerAddress,
+        port: u16,
+        duration: Option<u64>,
+    ) -> HttpRequestType {
+        HttpRequestType::AdminBanPeer(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            addrbytes,
+            port,
+            duration,
+        )
+    }
+
+    /// Make a new admin request for the stats of all connected peers
+    pub fn new_admin_get_neighbor_stats(&self) -> HttpRequestType {
+        HttpRequestType::AdminGetNeighborStats(HttpRequestMetadata::from_host(
+            self.peer_host.clone(),
+        ))
+    }
+
+    /// Make a new admin request to drop transactions from the mempool
+    pub fn new_admin_drop_transactions(&self, txids: Vec<Txid>) -> HttpRequestType {
+        HttpRequestType::AdminDropTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            txids,
+        )
+    }
+
+    /// Make a new admin request to garbage-collect the mempool
+    pub fn new_admin_mempool_gc(&self) -> HttpRequestType {
+        HttpRequestType::AdminMempoolGarbageCollect(HttpRequestMetadata::from_host(
+            self.peer_host.clone(),
+        ))
+    }
+
+    /// Make a new admin request to pause or resume mining
+    pub fn new_admin_set_mining_paused(&self, paused: bool) -> HttpRequestType {
+        HttpRequestType::AdminSetMiningPaused(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            paused,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -2944,6 +5000,8 @@ mod test {
     use std::convert::TryInto;
     use std::iter::FromIterator;
 
+    use rusqlite::types::ToSql;
+
     use address::*;
     use burnchains::Burnchain;
     use burnchains::BurnchainView;
@@ -2952,6 +5010,7 @@ mod test {
     use chainstate::stacks::db::blocks::test::*;
     use chainstate::stacks::db::BlockStreamData;
     use chainstate::stacks::db::StacksChainState;
+    use chainstate::stacks::events::*;
     use chainstate::stacks::miner::*;
     use chainstate::stacks::test::*;
     use chainstate::stacks::Error as chain_error;
@@ -4023,31 +6082,63 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_getblock() {
+    fn test_rpc_confirmed_transaction() {
+        let logged_tx = RefCell::new(None);
+        let logged_block = RefCell::new(StacksBlockId([0u8; 32]));
+
         test_rpc(
-            "test_rpc_missing_getblock",
-            40060,
-            40061,
-            50060,
-            50061,
+            "test_rpc_confirmed_transaction",
+            40190,
+            40191,
+            50190,
+            50191,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
+                peer_server.sortdb = Some(sortdb);
+                let tip = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
 
-                // now ask for it
-                convo_client.new_getblock(index_block_hash)
+                // log a transaction as though it were processed in the canonical tip
+                let tx = make_codec_test_block(1).txs[0].clone();
+                let txid = tx.txid();
+                peer_server
+                    .chainstate()
+                    .db()
+                    .execute(
+                        "INSERT INTO transactions (txid, index_block_hash, tx_hex, result) VALUES (?1, ?2, ?3, ?4)",
+                        &[
+                            &txid as &dyn ToSql,
+                            &tip,
+                            &to_hex(&tx.serialize_to_vec()),
+                            &"(ok true)",
+                        ],
+                    )
+                    .unwrap();
+
+                *logged_tx.borrow_mut() = Some(tx);
+                *logged_block.borrow_mut() = tip;
+
+                convo_client.new_gettransaction(txid)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::ConfirmedTransaction(response_md, confirmed) => {
+                        let tx = StacksTransaction::consensus_deserialize(
+                            &mut &hex_bytes(&confirmed.tx).unwrap()[..],
+                        )
+                        .unwrap();
+                        assert_eq!(Some(tx), *logged_tx.borrow());
+                        assert_eq!(confirmed.blocks.len(), 1);
+                        assert_eq!(confirmed.blocks[0].index_block_hash, *logged_block.borrow());
+                        assert_eq!(confirmed.blocks[0].result, "(ok true)");
+                        assert!(confirmed.blocks[0].canonical);
+                        true
+                    }
                     _ => {
                         error!("Invalid response: {:?}", &http_response);
                         false
@@ -4059,26 +6150,35 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_index_getmicroblocks() {
+    fn test_rpc_confirmed_transaction_unknown_block() {
         test_rpc(
-            "test_rpc_missing_index_getmicroblocks",
-            40070,
-            40071,
-            50070,
-            50071,
+            "test_rpc_confirmed_transaction_unknown_block",
+            40220,
+            40221,
+            50220,
+            50221,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
-                );
+                // log a transaction as though it were processed in a block this node doesn't have
+                let tx = make_codec_test_block(1).txs[0].clone();
+                let txid = tx.txid();
+                peer_server
+                    .chainstate()
+                    .db()
+                    .execute(
+                        "INSERT INTO transactions (txid, index_block_hash, tx_hex, result) VALUES (?1, ?2, ?3, ?4)",
+                        &[
+                            &txid as &dyn ToSql,
+                            &StacksBlockId([0x11; 32]),
+                            &to_hex(&tx.serialize_to_vec()),
+                            &"(ok true)",
+                        ],
+                    )
+                    .unwrap();
 
-                // now ask for it
-                convo_client.new_getmicroblocks_indexed(index_block_hash)
+                convo_client.new_gettransaction(txid)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
                 let req_md = http_request.metadata().clone();
@@ -4095,31 +6195,65 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_confirmed_getmicroblocks() {
+    fn test_rpc_simulate_transaction() {
         test_rpc(
-            "test_rpc_missing_confirmed_getmicroblocks",
-            40070,
-            40071,
-            50070,
-            50071,
+            "test_rpc_simulate_transaction",
+            40198,
+            40199,
+            50198,
+            50199,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
-                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
-                let index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &peer_server_consensus_hash,
-                    &peer_server_block_hash,
+                // STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW has not sent any transactions yet
+                let privk = StacksPrivateKey::from_hex(
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
+                )
+                .unwrap();
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk).unwrap(),
+                    TransactionPayload::TokenTransfer(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap()
+                            .to_account_principal(),
+                        123,
+                        TokenTransferMemo([0u8; 34]),
+                    ),
                 );
-
-                // now ask for it
-                convo_client.new_getmicroblocks_confirmed(index_block_hash)
+                tx.chain_id = 0x80000000;
+                tx.auth.set_origin_nonce(0);
+                tx.set_tx_fee(10);
+
+                let mut tx_signer = StacksTransactionSigner::new(&tx);
+                tx_signer.sign_origin(&privk).unwrap();
+                convo_server
+                    .connection
+                    .options
+                    .enable_transaction_simulation = true;
+                convo_client.new_simulate_transaction(tx_signer.get_tx().unwrap(), None)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::TransactionSimulation(response_md, simulation) => {
+                        assert!(simulation.okay);
+                        assert!(simulation.cause.is_none());
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&simulation.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay_true()
+                        );
+                        assert_eq!(simulation.post_condition_aborted, Some(false));
+                        assert_eq!(simulation.fee, Some(10));
+
+                        let events = simulation.events.clone().unwrap();
+                        assert_eq!(events.len(), 1);
+                        assert_eq!(events[0]["type"], "stx_transfer_event");
+                        assert_eq!(events[0]["stx_transfer_event"]["amount"], "123");
+                        true
+                    }
                     _ => {
                         error!("Invalid response: {:?}", &http_response);
                         false
@@ -4131,50 +6265,47 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_missing_unconfirmed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
-
+    fn test_rpc_simulate_transaction_disabled() {
         test_rpc(
-            "test_rpc_missing_unconfirmed_microblocks",
-            40080,
-            40081,
-            50080,
-            50081,
+            "test_rpc_simulate_transaction_disabled",
+            40214,
+            40215,
+            50214,
+            50215,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
                 let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
                 )
                 .unwrap();
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk).unwrap(),
+                    TransactionPayload::TokenTransfer(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap()
+                            .to_account_principal(),
+                        123,
+                        TokenTransferMemo([0u8; 34]),
+                    ),
+                );
+                tx.chain_id = 0x80000000;
+                tx.auth.set_origin_nonce(0);
+                tx.set_tx_fee(10);
 
-                let consensus_hash = ConsensusHash([0x02; 20]);
-                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
-                let index_block_hash =
-                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
-                mblocks.truncate(15);
-
-                for mblock in mblocks.iter() {
-                    store_staging_microblock(
-                        peer_server.chainstate(),
-                        &consensus_hash,
-                        &anchored_block_hash,
-                        &mblock,
-                    );
-                }
-
-                *server_microblocks_cell.borrow_mut() = mblocks;
-
-                // start at seq 16 (which doesn't exist)
-                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 16)
+                let mut tx_signer = StacksTransactionSigner::new(&tx);
+                tx_signer.sign_origin(&privk).unwrap();
+                convo_client.new_simulate_transaction(tx_signer.get_tx().unwrap(), None)
             },
             |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::NotFound(response_md, msg) => true,
+                    HttpResponseType::NotFound(response_md, msg) => {
+                        assert!(msg.contains("not enabled"));
+                        true
+                    }
                     _ => {
                         error!("Invalid response: {:?}", &http_response);
                         false
@@ -4186,28 +6317,350 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_contract_src() {
+    fn test_rpc_contract_events() {
+        let logged_block = RefCell::new(StacksBlockId([0u8; 32]));
+
         test_rpc(
-            "test_rpc_get_contract_src",
-            40090,
-            40091,
-            50090,
-            50091,
+            "test_rpc_contract_events",
+            40194,
+            40195,
+            50194,
+            50195,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                convo_client.new_getcontractsrc(
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
+                peer_server.sortdb = Some(sortdb);
+                let tip = StacksBlockHeader::make_index_block_hash(&consensus_hash, &block_hash);
+
+                // log an event as though it were emitted in the canonical tip, and another in a
+                // block that isn't on the canonical fork
+                let contract_addr =
                     StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
-                        .unwrap(),
-                    "hello-world".try_into().unwrap(),
-                    None,
-                    false,
-                )
-            },
-            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
-                let req_md = http_request.metadata().clone();
-                match http_response {
+                        .unwrap();
+                let contract_id = QualifiedContractIdentifier::new(
+                    contract_addr.clone().into(),
+                    "hello-world".into(),
+                );
+                let event = StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+                    key: (contract_id.clone(), "print".to_string()),
+                    value: Value::Int(1),
+                });
+                StacksChainState::instantiate_event_log(peer_server.chainstate().db()).unwrap();
+                for block in [tip.clone(), StacksBlockId([0x22; 32])].iter() {
+                    peer_server
+                        .chainstate()
+                        .db()
+                        .execute(
+                            "INSERT INTO transaction_events (txid, index_block_hash, block_height, tx_index, event_index, contract_identifier, event) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                            &[
+                                &Txid([0x11; 32]) as &dyn ToSql,
+                                block,
+                                &1,
+                                &0,
+                                &0,
+                                &contract_id.to_string(),
+                                &event.json_serialize(0, &Txid([0x11; 32]), true).to_string(),
+                            ],
+                        )
+                        .unwrap();
+                }
+                peer_server
+                    .chainstate()
+                    .db()
+                    .execute(
+                        "INSERT INTO transaction_receipts (txid, index_block_hash, block_height, tx_index, result, post_condition_aborted, execution_cost) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        &[
+                            &Txid([0x11; 32]) as &dyn ToSql,
+                            &tip,
+                            &1,
+                            &0,
+                            &ClaritySerializable::serialize(&Value::okay_true()),
+                            &false,
+                            &serde_json::to_string(&ExecutionCost::zero()).unwrap(),
+                        ],
+                    )
+                    .unwrap();
+
+                *logged_block.borrow_mut() = tip;
+
+                convo_client.new_get_contract_events(
+                    contract_addr,
+                    "hello-world".into(),
+                    None,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::Events(response_md, events) => {
+                        assert_eq!(events.events.len(), 1);
+                        assert_eq!(events.events[0].index_block_hash, *logged_block.borrow());
+                        assert_eq!(events.events[0].event["type"], "contract_event");
+                        assert_eq!(events.receipts.len(), 1);
+                        assert_eq!(
+                            events.receipts[0].result,
+                            format!("0x{}", ClaritySerializable::serialize(&Value::okay_true()))
+                        );
+                        assert_eq!(events.receipts[0].execution_cost, ExecutionCost::zero());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_bad_block_range_events() {
+        test_rpc(
+            "test_rpc_bad_block_range_events",
+            40196,
+            40197,
+            50196,
+            50197,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_block_range_events(10, 10 + MAX_EVENT_BLOCK_RANGE)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::BadRequest(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_transaction() {
+        test_rpc(
+            "test_rpc_missing_transaction",
+            40192,
+            40193,
+            50192,
+            50193,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_gettransaction(Txid([0x11; 32]))
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_getblock() {
+        test_rpc(
+            "test_rpc_missing_getblock",
+            40060,
+            40061,
+            50060,
+            50061,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getblock(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_index_getmicroblocks() {
+        test_rpc(
+            "test_rpc_missing_index_getmicroblocks",
+            40070,
+            40071,
+            50070,
+            50071,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getmicroblocks_indexed(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_confirmed_getmicroblocks() {
+        test_rpc(
+            "test_rpc_missing_confirmed_getmicroblocks",
+            40070,
+            40071,
+            50070,
+            50071,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_server_block_hash = BlockHeaderHash([0x04; 32]);
+                let peer_server_consensus_hash = ConsensusHash([0x02; 20]);
+                let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &peer_server_consensus_hash,
+                    &peer_server_block_hash,
+                );
+
+                // now ask for it
+                convo_client.new_getmicroblocks_confirmed(index_block_hash)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_unconfirmed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            "test_rpc_missing_unconfirmed_microblocks",
+            40080,
+            40081,
+            50080,
+            50081,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let consensus_hash = ConsensusHash([0x02; 20]);
+                let anchored_block_hash = BlockHeaderHash([0x03; 32]);
+                let index_block_hash =
+                    StacksBlockHeader::make_index_block_hash(&consensus_hash, &anchored_block_hash);
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &anchored_block_hash);
+                mblocks.truncate(15);
+
+                for mblock in mblocks.iter() {
+                    store_staging_microblock(
+                        peer_server.chainstate(),
+                        &consensus_hash,
+                        &anchored_block_hash,
+                        &mblock,
+                    );
+                }
+
+                *server_microblocks_cell.borrow_mut() = mblocks;
+
+                // start at seq 16 (which doesn't exist)
+                convo_client.new_getmicroblocks_unconfirmed(index_block_hash, 16)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_src() {
+        test_rpc(
+            "test_rpc_get_contract_src",
+            40090,
+            40091,
+            50090,
+            50091,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getcontractsrc(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap(),
+                    "hello-world".try_into().unwrap(),
+                    None,
+                    false,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
                     HttpResponseType::GetContractSrc(response_md, data) => {
                         assert_eq!(data.source, TEST_CONTRACT);
                         true
@@ -4304,6 +6757,293 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_get_account_history() {
+        test_rpc(
+            "test_rpc_get_account_history",
+            40200,
+            40201,
+            50200,
+            50201,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getaccount_history(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    0,
+                    50,
+                    None,
+                    true,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetAccountHistory(response_md, data) => {
+                        assert!(data.history.len() > 0);
+                        for (i, entry) in data.history.iter().enumerate() {
+                            assert!(entry.balance_proof.is_some());
+                            assert!(entry.nonce_proof.is_some());
+                            if i > 0 {
+                                let prev = &data.history[i - 1];
+                                assert!(entry.block_height > prev.block_height);
+                                assert!(entry.nonce >= prev.nonce);
+                            }
+                        }
+
+                        // the last entry is the chain tip's state
+                        let last = data.history.last().unwrap();
+                        assert_eq!(last.nonce, 2);
+                        let balance = u128::from_str_radix(&last.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_reward_set() {
+        let server_reward_set = RefCell::new(None);
+        test_rpc(
+            "test_rpc_get_reward_set",
+            40202,
+            40203,
+            50202,
+            50203,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let reward_set = RPCRewardSetData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                    1,
+                )
+                .unwrap();
+                *server_reward_set.borrow_mut() = Some(reward_set);
+                convo_client.new_get_reward_set(1, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::RewardSet(response_md, reward_set) => {
+                        assert_eq!(reward_set.reward_cycle, 1);
+                        assert_eq!(Some((*reward_set).clone()), *server_reward_set.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_stacker_info() {
+        test_rpc(
+            "test_rpc_get_stacker_info",
+            40204,
+            40205,
+            50204,
+            50205,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_stacker_info(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::StackerInfo(response_md, stacker_info) => {
+                        assert_eq!(
+                            stacker_info.stacker,
+                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R"
+                        );
+                        assert_eq!(stacker_info.locked_ustx, 0);
+                        assert_eq!(stacker_info.stacking, None);
+                        assert_eq!(stacker_info.delegation, None);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_bns_owner_names() {
+        test_rpc(
+            "test_rpc_get_bns_owner_names",
+            40206,
+            40207,
+            50206,
+            50207,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_bns_owner_names(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::BnsOwnerNames(response_md, owner_names) => {
+                        assert_eq!(
+                            owner_names.owner,
+                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R"
+                        );
+                        assert!(owner_names.names.is_empty());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_bns_name_not_found() {
+        test_rpc(
+            "test_rpc_get_bns_name_not_found",
+            40208,
+            40209,
+            50208,
+            50209,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_bns_name("alice".to_string(), "btc".to_string(), None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => {
+                        assert!(msg.contains("name not found"));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_admin_get_peers() {
+        test_rpc(
+            "test_rpc_admin_get_peers",
+            40210,
+            40211,
+            50210,
+            50211,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.set_admin_auth_token("hunter2".to_string());
+                convo_server.set_admin_auth_token("hunter2".to_string());
+                convo_client.new_admin_get_peers()
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::AdminPeers(response_md, data) => {
+                        assert!(data
+                            .peers
+                            .iter()
+                            .any(|peer| peer.port == peer_client.config.server_port));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_admin_set_mining_paused_unsupported() {
+        test_rpc(
+            "test_rpc_admin_set_mining_paused_unsupported",
+            40212,
+            40213,
+            50212,
+            50213,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.set_admin_auth_token("hunter2".to_string());
+                convo_server.set_admin_auth_token("hunter2".to_string());
+                convo_client.new_admin_set_mining_paused(true)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::ServiceUnavailable(response_md, msg) => {
+                        assert!(msg.contains("cannot pause or resume mining"));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_get_account_unconfirmed() {
@@ -73,6 +73,10 @@ pub struct HttpPeer {
     // server network handle
     pub http_server_handle: usize,
 
+    // admin API server network handle, and the bearer token its clients must present
+    pub admin_server_handle: Option<usize>,
+    admin_auth_token: Option<String>,
+
     // connection options
     pub connection_opts: ConnectionOptions,
 }
@@ -86,6 +90,9 @@ impl HttpPeer {
             connecting: HashMap::new(),
             http_server_handle: server_handle,
 
+            admin_server_handle: None,
+            admin_auth_token: None,
+
             connection_opts: conn_opts,
         }
     }
@@ -94,6 +101,12 @@ impl HttpPeer {
         self.http_server_handle = h;
     }
 
+    /// Serve the admin API on the given server handle, to clients that present the given token
+    pub fn set_admin_server_handle(&mut self, h: usize, auth_token: String) -> () {
+        self.admin_server_handle = Some(h);
+        self.admin_auth_token = Some(auth_token);
+    }
+
     /// Is there a HTTP conversation open to this data_url that is not in progress?
     pub fn find_free_conversation(&self, data_url: &UrlString) -> Option<usize> {
         for (event_id, convo) in self.peers.iter() {
@@ -313,6 +326,11 @@ impl HttpPeer {
         }
 
         for (event_id, convo) in self.peers.iter() {
+            if convo.is_streaming_events() {
+                // long-lived by design; a dead client is detected when we fail to write to it
+                continue;
+            }
+
             let mut last_request_time = convo.get_last_request_time();
             if last_request_time == 0 {
                 // never got a request
@@ -365,22 +383,26 @@ impl HttpPeer {
         Ok(())
     }
 
-    /// Process new inbound HTTP connections we just accepted.
+    /// Process new inbound HTTP connections we just accepted on the given server handle.
+    /// Connections accepted on the admin server handle become admin API conversations.
     /// Returns the event IDs of sockets we need to register
     fn process_new_sockets(
         &mut self,
         network_state: &mut NetworkState,
         chainstate: &mut StacksChainState,
         poll_state: &mut NetworkPollState,
+        server_handle: usize,
     ) -> Result<Vec<usize>, net_error> {
         let mut registered = vec![];
+        let admin_auth_token = if Some(server_handle) == self.admin_server_handle {
+            self.admin_auth_token.clone()
+        } else {
+            None
+        };
 
         for (hint_event_id, client_sock) in poll_state.new.drain() {
-            let event_id = match network_state.register(
-                self.http_server_handle,
-                hint_event_id,
-                &client_sock,
-            ) {
+            let event_id = match network_state.register(server_handle, hint_event_id, &client_sock)
+            {
                 Ok(event_id) => event_id,
                 Err(e) => {
                     warn!(
@@ -408,6 +430,11 @@ impl HttpPeer {
                 // NOTE: register_http will deregister the socket for us
                 continue;
             }
+            if let Some(ref auth_token) = admin_auth_token {
+                if let Some(convo) = self.peers.get_mut(&event_id) {
+                    convo.set_admin_auth_token(auth_token.clone());
+                }
+            }
             registered.push(event_id);
         }
 
@@ -635,6 +662,16 @@ impl HttpPeer {
                     close.push(*event_id);
                 }
             }
+            if convo.is_streaming_events() {
+                // events arrive without any activity on the socket, so push them out here
+                if let Some(client_sock) = self.sockets.get_mut(event_id) {
+                    if let Err(_e) = HttpPeer::saturate_http_socket(client_sock, convo, chainstate)
+                    {
+                        info!("Broken HTTP event stream {:?}: {:?}", convo, &_e);
+                        close.push(*event_id);
+                    }
+                }
+            }
             if convo.is_drained() && !convo.is_keep_alive() {
                 // did some work, but nothing more to do and we're not keep-alive
                 debug!("Close drained HTTP connection {:?}", convo);
@@ -659,10 +696,31 @@ impl HttpPeer {
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         mut poll_state: NetworkPollState,
+        admin_poll_state: Option<NetworkPollState>,
         handler_args: &RPCHandlerArgs,
     ) -> Result<Vec<StacksMessageType>, net_error> {
         // set up new inbound conversations
-        self.process_new_sockets(network_state, chainstate, &mut poll_state)?;
+        let http_server_handle = self.http_server_handle;
+        self.process_new_sockets(
+            network_state,
+            chainstate,
+            &mut poll_state,
+            http_server_handle,
+        )?;
+
+        // set up new inbound admin API conversations.  Once set up, they're driven just like
+        // any other conversation.
+        if let (Some(mut admin_poll_state), Some(admin_server_handle)) =
+            (admin_poll_state, self.admin_server_handle)
+        {
+            self.process_new_sockets(
+                network_state,
+                chainstate,
+                &mut admin_poll_state,
+                admin_server_handle,
+            )?;
+            poll_state.ready.append(&mut admin_poll_state.ready);
+        }
 
         // set up connected sockets
         self.process_connecting_sockets(network_state, chainstate, &mut poll_state);
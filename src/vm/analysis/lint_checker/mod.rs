@@ -0,0 +1,392 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use vm::analysis::types::ContractAnalysis;
+use vm::diagnostic::{DiagnosableError, Diagnostic};
+use vm::functions::define::DefineFunctionsParsed;
+use vm::functions::NativeFunctions;
+use vm::representations::SymbolicExpressionType::{Atom, List};
+use vm::representations::{ClarityName, SymbolicExpression};
+use vm::variables::NativeVariables;
+
+#[cfg(test)]
+mod tests;
+
+///
+/// An opt-in static-analysis pass that looks for common contract pitfalls.
+///  Unlike the other passes, it never rejects a contract: each pitfall
+///  it finds is reported as a warning.
+///
+pub struct LintChecker {
+    warnings: Vec<Diagnostic>,
+    /// names that are used anywhere other than in their own definition
+    referenced: HashSet<ClarityName>,
+    maps_read: HashSet<ClarityName>,
+    maps_written: HashSet<ClarityName>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LintWarning {
+    UncheckedContractCall(ClarityName),
+    UnwrapPanicOnArgument(ClarityName),
+    AsContractTransferToTxSender(NativeFunctions),
+    UnusedPrivateFunction(ClarityName),
+    UnusedConstant(ClarityName),
+    UnusedDataVar(ClarityName),
+    MapNeverRead(ClarityName),
+}
+
+impl DiagnosableError for LintWarning {
+    fn message(&self) -> String {
+        match self {
+            LintWarning::UncheckedContractCall(name) => format!(
+                "the response of contract-call? bound to '{}' is never checked",
+                name
+            ),
+            LintWarning::UnwrapPanicOnArgument(name) => format!(
+                "unwrap-panic on the function argument '{}' aborts the transaction without an error code",
+                name
+            ),
+            LintWarning::AsContractTransferToTxSender(function) => format!(
+                "{} from tx-sender to tx-sender inside as-contract transfers from the contract to itself",
+                function.get_name()
+            ),
+            LintWarning::UnusedPrivateFunction(name) => {
+                format!("private function '{}' is never used", name)
+            }
+            LintWarning::UnusedConstant(name) => format!("constant '{}' is never used", name),
+            LintWarning::UnusedDataVar(name) => format!("data var '{}' is never used", name),
+            LintWarning::MapNeverRead(name) => {
+                format!("map '{}' is written but never read", name)
+            }
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match self {
+            LintWarning::UncheckedContractCall(_) => {
+                Some("use try!, unwrap!, or match to handle an error response".into())
+            }
+            LintWarning::UnwrapPanicOnArgument(_) => {
+                Some("use unwrap! or asserts! to return an error code instead".into())
+            }
+            LintWarning::AsContractTransferToTxSender(_) => Some(
+                "bind tx-sender to a variable outside of as-contract and transfer to it instead"
+                    .into(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl LintChecker {
+    pub fn run(contract_analysis: &ContractAnalysis) -> Vec<Diagnostic> {
+        let mut checker = LintChecker {
+            warnings: vec![],
+            referenced: HashSet::new(),
+            maps_read: HashSet::new(),
+            maps_written: HashSet::new(),
+        };
+        let mut definitions = vec![];
+
+        for expr in contract_analysis.expressions.iter() {
+            if let Some(definition) = checker.check_top_level(expr) {
+                definitions.push(definition);
+            }
+        }
+
+        for (name_expr, define_type) in definitions.into_iter() {
+            let name = match name_expr.match_atom() {
+                Some(name) => name.clone(),
+                None => continue,
+            };
+            let warning = match define_type {
+                DefinitionType::PrivateFunction if !checker.referenced.contains(&name) => {
+                    LintWarning::UnusedPrivateFunction(name)
+                }
+                DefinitionType::Constant if !checker.referenced.contains(&name) => {
+                    LintWarning::UnusedConstant(name)
+                }
+                DefinitionType::DataVar if !checker.referenced.contains(&name) => {
+                    LintWarning::UnusedDataVar(name)
+                }
+                DefinitionType::Map
+                    if checker.maps_written.contains(&name)
+                        && !checker.maps_read.contains(&name) =>
+                {
+                    LintWarning::MapNeverRead(name)
+                }
+                _ => continue,
+            };
+            checker.warn(warning, name_expr);
+        }
+
+        checker.warnings
+    }
+
+    fn warn(&mut self, warning: LintWarning, expr: &SymbolicExpression) {
+        let mut diagnostic = Diagnostic::warning(&warning);
+        diagnostic.spans = vec![expr.span.clone()];
+        self.warnings.push(diagnostic);
+    }
+
+    /// Check a top-level expression, returning the name of what it defines if that
+    ///  definition could be unused.
+    fn check_top_level<'a>(
+        &mut self,
+        expr: &'a SymbolicExpression,
+    ) -> Option<(&'a SymbolicExpression, DefinitionType)> {
+        use vm::functions::define::DefineFunctionsParsed::*;
+        let define_type = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(define_type)) => define_type,
+            Ok(None) => {
+                self.check_expression(expr, &Context::top_level());
+                return None;
+            }
+            // not reachable after the type checker has run
+            Err(_) => return None,
+        };
+        // the name of every definition is its first argument
+        let name_expr = expr.match_list().and_then(|list| list.get(1));
+
+        match define_type {
+            Constant { value, .. } => {
+                self.check_expression(value, &Context::top_level());
+                name_expr.map(|name| (name, DefinitionType::Constant))
+            }
+            PersistedVariable { initial, .. } => {
+                self.check_expression(initial, &Context::top_level());
+                name_expr.map(|name| (name, DefinitionType::DataVar))
+            }
+            Map { .. } => name_expr.map(|name| (name, DefinitionType::Map)),
+            PrivateFunction { signature, body } => {
+                self.check_expression(body, &Context::top_level());
+                signature
+                    .first()
+                    .map(|name| (name, DefinitionType::PrivateFunction))
+            }
+            PublicFunction { signature, body } | ReadOnlyFunction { signature, body } => {
+                // arguments to public and read-only functions are supplied by the caller
+                let arguments = signature[1..]
+                    .iter()
+                    .filter_map(|arg| arg.match_list()?.first()?.match_atom())
+                    .cloned()
+                    .collect();
+                let context = Context {
+                    arguments,
+                    in_as_contract: false,
+                };
+                self.check_expression(body, &context);
+                None
+            }
+            BoundedFungibleToken { max_supply, .. } => {
+                self.check_expression(max_supply, &Context::top_level());
+                None
+            }
+            NonFungibleToken { .. }
+            | UnboundedFungibleToken { .. }
+            | Trait { .. }
+            | UseTrait { .. }
+            | ImplTrait { .. } => None,
+        }
+    }
+
+    fn check_expression(&mut self, expr: &SymbolicExpression, context: &Context) {
+        match expr.expr {
+            Atom(ref name) => {
+                self.referenced.insert(name.clone());
+            }
+            List(ref list) => self.check_function_application(expr, list, context),
+            _ => {}
+        }
+    }
+
+    fn check_all(&mut self, exprs: &[SymbolicExpression], context: &Context) {
+        for expr in exprs.iter() {
+            self.check_expression(expr, context);
+        }
+    }
+
+    fn check_function_application(
+        &mut self,
+        expr: &SymbolicExpression,
+        list: &[SymbolicExpression],
+        context: &Context,
+    ) {
+        let (function_name, args) = match list.split_first() {
+            Some(x) => x,
+            None => return,
+        };
+        match function_name
+            .match_atom()
+            .and_then(|name| NativeFunctions::lookup_by_name(name))
+        {
+            Some(function) => self.check_native_function(expr, function, args, context),
+            None => self.check_all(list, context),
+        }
+    }
+
+    fn check_native_function(
+        &mut self,
+        expr: &SymbolicExpression,
+        function: NativeFunctions,
+        args: &[SymbolicExpression],
+        context: &Context,
+    ) {
+        use vm::functions::NativeFunctions::*;
+        match function {
+            Unwrap | UnwrapErr => {
+                if let Some(argument) = args.first().and_then(|arg| context.find_argument(arg)) {
+                    self.warn(LintWarning::UnwrapPanicOnArgument(argument), expr);
+                }
+                self.check_all(args, context)
+            }
+            AsContract => {
+                let context = Context {
+                    arguments: context.arguments.clone(),
+                    in_as_contract: true,
+                };
+                self.check_all(args, &context)
+            }
+            StxTransfer | TransferToken | TransferAsset => {
+                // the sender and recipient are the last two arguments of each of these.
+                //  inside as-contract, a transfer from tx-sender to tx-sender is the
+                //  contract paying itself, rather than the caller it was likely meant for.
+                let is_self_transfer =
+                    args.len() >= 2 && args[args.len() - 2..].iter().all(is_tx_sender);
+                if context.in_as_contract && is_self_transfer {
+                    self.warn(LintWarning::AsContractTransferToTxSender(function), expr);
+                }
+                self.check_all(args, context)
+            }
+            FetchEntry | SetEntry | InsertEntry | DeleteEntry => {
+                if let Some(map_name) = args.first().and_then(|arg| arg.match_atom()) {
+                    if function == FetchEntry {
+                        self.maps_read.insert(map_name.clone());
+                    } else {
+                        self.maps_written.insert(map_name.clone());
+                    }
+                }
+                self.check_all(args.get(1..).unwrap_or(&[]), context)
+            }
+            ContractCall => {
+                // the second argument names a function of the other contract
+                if let Some(contract) = args.first() {
+                    self.check_expression(contract, context);
+                }
+                self.check_all(args.get(2..).unwrap_or(&[]), context)
+            }
+            TupleGet => self.check_all(args.get(1..).unwrap_or(&[]), context),
+            TupleCons => {
+                for pair in args.iter() {
+                    if let Some(value) = pair.match_list().and_then(|pair| pair.get(1)) {
+                        self.check_expression(value, context);
+                    }
+                }
+            }
+            Match => {
+                // skip the names that the branches bind
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 1 && !(args.len() == 5 && i == 3) {
+                        self.check_expression(arg, context);
+                    }
+                }
+            }
+            Let => self.check_let(args, context),
+            _ => self.check_all(args, context),
+        }
+    }
+
+    fn check_let(&mut self, args: &[SymbolicExpression], context: &Context) {
+        let (bindings, body) = match args.split_first() {
+            Some((bindings, body)) => (bindings.match_list().unwrap_or(&[]), body),
+            None => return,
+        };
+        for (i, binding) in bindings.iter().enumerate() {
+            let (name, value) = match binding.match_list() {
+                Some(&[ref name, ref value]) => (name, value),
+                _ => continue,
+            };
+            if let (Some(name), true) = (name.match_atom(), is_contract_call(value)) {
+                // the binding is in scope for the later bindings and the body
+                let used = bindings[i + 1..]
+                    .iter()
+                    .chain(body.iter())
+                    .any(|expr| mentions(expr, name));
+                if !used {
+                    self.warn(LintWarning::UncheckedContractCall(name.clone()), value);
+                }
+            }
+            self.check_expression(value, context);
+        }
+        self.check_all(body, context)
+    }
+}
+
+enum DefinitionType {
+    PrivateFunction,
+    Constant,
+    DataVar,
+    Map,
+}
+
+struct Context {
+    /// the arguments of the enclosing public or read-only function
+    arguments: HashSet<ClarityName>,
+    in_as_contract: bool,
+}
+
+impl Context {
+    fn top_level() -> Context {
+        Context {
+            arguments: HashSet::new(),
+            in_as_contract: false,
+        }
+    }
+
+    /// Find an argument of the enclosing function that `expr` is computed from, if any.
+    fn find_argument(&self, expr: &SymbolicExpression) -> Option<ClarityName> {
+        match expr.expr {
+            Atom(ref name) if self.arguments.contains(name) => Some(name.clone()),
+            List(ref list) => list.iter().find_map(|expr| self.find_argument(expr)),
+            _ => None,
+        }
+    }
+}
+
+fn is_tx_sender(expr: &SymbolicExpression) -> bool {
+    expr.match_atom()
+        .and_then(|name| NativeVariables::lookup_by_name(name))
+        == Some(NativeVariables::TxSender)
+}
+
+fn is_contract_call(expr: &SymbolicExpression) -> bool {
+    expr.match_list()
+        .and_then(|list| list.first())
+        .and_then(|function| function.match_atom())
+        .and_then(|name| NativeFunctions::lookup_by_name(name))
+        == Some(NativeFunctions::ContractCall)
+}
+
+fn mentions(expr: &SymbolicExpression, name: &ClarityName) -> bool {
+    match expr.expr {
+        Atom(ref atom) => atom == name,
+        List(ref list) => list.iter().any(|expr| mentions(expr, name)),
+        _ => false,
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use vm::analysis::lint_checker::LintWarning::*;
+use vm::analysis::lint_checker::{LintChecker, LintWarning};
+use vm::analysis::mem_type_check;
+use vm::diagnostic::{DiagnosableError, Level};
+use vm::functions::NativeFunctions;
+
+fn lint(contract: &str) -> Vec<String> {
+    let analysis = mem_type_check(contract).unwrap().1;
+    LintChecker::run(&analysis)
+        .into_iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.level, Level::Warning);
+            diagnostic.message
+        })
+        .collect()
+}
+
+fn check_warnings(tests: &[(&str, Vec<LintWarning>)]) {
+    for (contract, warnings) in tests.iter() {
+        let expected: Vec<_> = warnings.iter().map(|w| w.message()).collect();
+        assert_eq!(lint(contract), expected, "Check contract:\n {}", contract);
+    }
+}
+
+#[test]
+fn test_clean_contract() {
+    let contract = "(define-constant err-unknown (err u1))
+         (define-data-var counter uint u0)
+         (define-map balances principal uint)
+         (define-private (get-balance (who principal))
+           (default-to u0 (map-get? balances who)))
+         (define-public (deposit (amount uint))
+           (begin
+             (var-set counter (+ (var-get counter) u1))
+             (map-set balances tx-sender (+ amount (get-balance tx-sender)))
+             (ok true)))
+         (define-public (withdraw (amount uint))
+           (let ((recipient tx-sender))
+             (unwrap! (map-get? balances recipient) err-unknown)
+             (as-contract (stx-transfer? amount tx-sender recipient))))";
+    assert_eq!(lint(contract), Vec::<String>::new());
+}
+
+#[test]
+fn test_unchecked_contract_call() {
+    let trait_def = "(define-trait t ((foo () (response bool uint))))";
+    check_warnings(&[
+        (
+            &format!("{} (define-public (call (c <t>)) (let ((r (contract-call? c foo))) (ok true)))", trait_def),
+            vec![UncheckedContractCall("r".into())],
+        ),
+        (
+            &format!("{} (define-public (call (c <t>)) (let ((r (contract-call? c foo))) (ok (is-ok r))))", trait_def),
+            vec![],
+        ),
+        (
+            &format!("{} (define-public (call (c <t>)) (let ((r (contract-call? c foo)) (s (is-ok r))) (ok s)))", trait_def),
+            vec![],
+        ),
+        (
+            &format!("{} (define-public (call (c <t>)) (ok (try! (contract-call? c foo))))", trait_def),
+            vec![],
+        ),
+    ]);
+}
+
+#[test]
+fn test_unwrap_panic_on_argument() {
+    check_warnings(&[
+        (
+            "(define-public (foo (x (optional uint))) (ok (unwrap-panic x)))",
+            vec![UnwrapPanicOnArgument("x".into())],
+        ),
+        (
+            "(define-map m uint uint) (define-read-only (foo (k uint)) (unwrap-panic (map-get? m k)))",
+            vec![UnwrapPanicOnArgument("k".into())],
+        ),
+        (
+            "(define-read-only (foo (x (response uint uint))) (unwrap-err-panic x))",
+            vec![UnwrapPanicOnArgument("x".into())],
+        ),
+        (
+            "(define-public (foo (x (optional uint))) (ok (unwrap! x (err u1))))",
+            vec![],
+        ),
+        (
+            "(define-map m uint uint) (define-read-only (foo) (unwrap-panic (map-get? m u1)))",
+            vec![],
+        ),
+    ]);
+}
+
+#[test]
+fn test_as_contract_transfer_to_tx_sender() {
+    check_warnings(&[
+        (
+            "(define-public (foo (amount uint)) (as-contract (stx-transfer? amount tx-sender tx-sender)))",
+            vec![AsContractTransferToTxSender(NativeFunctions::StxTransfer)],
+        ),
+        (
+            "(define-fungible-token tok) (define-public (foo (amount uint)) (as-contract (ft-transfer? tok amount tx-sender tx-sender)))",
+            vec![AsContractTransferToTxSender(NativeFunctions::TransferToken)],
+        ),
+        (
+            "(define-non-fungible-token nft uint) (define-public (foo) (as-contract (begin (nft-transfer? nft u1 tx-sender tx-sender))))",
+            vec![AsContractTransferToTxSender(NativeFunctions::TransferAsset)],
+        ),
+        (
+            "(define-public (foo (amount uint)) (stx-transfer? amount tx-sender tx-sender))",
+            vec![],
+        ),
+        // returning escrowed tokens to the contract
+        (
+            "(define-fungible-token tok) (define-public (foo (amount uint)) (let ((sender tx-sender)) (as-contract (ft-transfer? tok amount sender tx-sender))))",
+            vec![],
+        ),
+    ]);
+}
+
+#[test]
+fn test_unused_definitions() {
+    check_warnings(&[
+        (
+            "(define-private (foo) u1) (define-constant bar u2) (define-data-var baz uint u3)",
+            vec![
+                UnusedPrivateFunction("foo".into()),
+                UnusedConstant("bar".into()),
+                UnusedDataVar("baz".into()),
+            ],
+        ),
+        (
+            "(define-private (foo) u1) (define-constant bar u2) (define-data-var baz uint u3)
+             (define-read-only (get-all) (+ (foo) bar (var-get baz)))",
+            vec![],
+        ),
+        (
+            "(define-constant bar u2) (define-data-var baz uint bar)",
+            vec![UnusedDataVar("baz".into())],
+        ),
+        // tuple keys and let bindings don't refer to definitions
+        (
+            "(define-data-var owner principal tx-sender)
+             (define-read-only (foo) (let ((x {owner: tx-sender})) (get owner x)))",
+            vec![UnusedDataVar("owner".into())],
+        ),
+    ]);
+}
+
+#[test]
+fn test_map_never_read() {
+    check_warnings(&[
+        (
+            "(define-map m uint uint) (define-public (foo) (ok (map-set m u1 u2)))",
+            vec![MapNeverRead("m".into())],
+        ),
+        (
+            "(define-map m uint uint) (define-public (foo) (ok (and (map-insert m u1 u2) (map-delete m u1))))",
+            vec![MapNeverRead("m".into())],
+        ),
+        (
+            "(define-map m uint uint) (define-public (foo) (ok (map-set m u1 u2))) (define-read-only (bar) (map-get? m u1))",
+            vec![],
+        ),
+        // a map that is never used at all isn't a pitfall of this kind
+        ("(define-map m uint uint)", vec![]),
+    ]);
+}
+
+#[test]
+fn test_spans() {
+    let contract = "(define-constant bar u2)
+(define-public (foo (x (optional uint)))
+  (ok (unwrap-panic x)))";
+    let analysis = mem_type_check(contract).unwrap().1;
+    let warnings = LintChecker::run(&analysis);
+    assert_eq!(warnings.len(), 2);
+
+    assert_eq!(
+        warnings[0].message,
+        UnwrapPanicOnArgument("x".into()).message()
+    );
+    assert_eq!(warnings[0].spans[0].start_line, 3);
+    assert_eq!(warnings[0].spans[0].start_column, 7);
+    assert_eq!(warnings[0].spans[0].end_line, 3);
+    assert_eq!(warnings[0].spans[0].end_column, 22);
+
+    assert_eq!(warnings[1].message, UnusedConstant("bar".into()).message());
+    assert_eq!(warnings[1].spans[0].start_line, 1);
+    assert_eq!(warnings[1].spans[0].start_column, 18);
+    assert_eq!(warnings[1].spans[0].end_column, 20);
+}
@@ -23,8 +23,8 @@ use vm::costs::{cost_functions, runtime_cost, CostTracker, LimitedCostTracker};
 use vm::functions::define::DefineFunctions;
 use vm::functions::NativeFunctions;
 use vm::representations::PreSymbolicExpressionType::{
-    Atom, AtomValue, FieldIdentifier, List, SugaredContractIdentifier, SugaredFieldIdentifier,
-    TraitReference, Tuple,
+    Atom, AtomValue, Comment, FieldIdentifier, List, SugaredContractIdentifier,
+    SugaredFieldIdentifier, TraitReference, Tuple,
 };
 use vm::representations::{ClarityName, PreSymbolicExpression};
 use vm::types::Value;
@@ -262,7 +262,8 @@ impl<'a> DefinitionSorter {
             AtomValue(_)
             | FieldIdentifier(_)
             | SugaredContractIdentifier(_)
-            | SugaredFieldIdentifier(_, _) => Ok(()),
+            | SugaredFieldIdentifier(_, _)
+            | Comment(_) => Ok(()),
         }
     }
 
@@ -0,0 +1,457 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use vm::ast::errors::ParseResult;
+use vm::ast::parser;
+use vm::representations::PreSymbolicExpression;
+use vm::representations::PreSymbolicExpressionType::{Comment, List, Tuple};
+
+/// The formatter breaks any expression that would overflow this width across lines
+pub const MAX_LINE_WIDTH: usize = 80;
+const INDENT: usize = 2;
+/// Arguments of a function call are aligned under its first argument if the function's name
+///   is at most this long, or if the arguments fit when aligned. Otherwise they're indented.
+const MAX_ALIGNED_NAME_LENGTH: usize = 8;
+
+/// Get the number of arguments that stay on the opening line of a form whose remaining
+///   arguments form an indented body, or None if `name` isn't one of those forms.
+fn body_form_header_length(name: &str) -> Option<usize> {
+    match name {
+        "define-public"
+        | "define-private"
+        | "define-read-only"
+        | "define-constant"
+        | "define-map"
+        | "define-fungible-token"
+        | "define-non-fungible-token"
+        | "define-trait"
+        | "let"
+        | "at-block" => Some(1),
+        "define-data-var" => Some(2),
+        "begin" | "as-contract" => Some(0),
+        _ => None,
+    }
+}
+
+/// Function definitions always put their body on its own line.
+fn always_breaks(children: &[PreSymbolicExpression]) -> bool {
+    match children.first().and_then(|head| head.match_atom()) {
+        Some(name) => match name.as_str() {
+            "define-public" | "define-private" | "define-read-only" => true,
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+/// Did the source have a blank line between `prev` and `next`?
+fn blank_line_between(prev: Option<&PreSymbolicExpression>, next: &PreSymbolicExpression) -> bool {
+    match prev {
+        Some(prev) => next.span.start_line > prev.span.end_line + 1,
+        None => false,
+    }
+}
+
+/// Is `expr` a comment on the same line as `prev` ends?
+fn is_trailing_comment(prev: Option<&PreSymbolicExpression>, expr: &PreSymbolicExpression) -> bool {
+    match prev {
+        Some(prev) => expr.match_comment().is_some() && prev.span.end_line == expr.span.start_line,
+        None => false,
+    }
+}
+
+/// The column the next character appended to `out` will be at, if `out` started at `start_column`
+fn current_column(out: &str, start_column: usize) -> usize {
+    match out.rfind('\n') {
+        Some(ix) => out.len() - ix - 1,
+        None => start_column + out.len(),
+    }
+}
+
+fn new_line(out: &mut String, blank_line: bool, column: usize) {
+    out.push('\n');
+    if blank_line {
+        out.push('\n');
+    }
+    out.push_str(&" ".repeat(column));
+}
+
+/// Are `a` and `b` the same program, with the same comments?
+fn same_expressions(a: &[PreSymbolicExpression], b: &[PreSymbolicExpression]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| match (&a.pre_expr, &b.pre_expr) {
+                (List(a), List(b)) | (Tuple(a), Tuple(b)) => same_expressions(a, b),
+                (Comment(a), Comment(b)) => a.trim_end() == b.trim_end(),
+                (a, b) => a == b,
+            })
+}
+
+struct Formatter<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Formatter<'a> {
+    /// Get the source text of an expression that isn't a list, tuple, or comment.
+    fn token_text(&self, expr: &PreSymbolicExpression) -> &'a str {
+        let line = self.lines[(expr.span.start_line - 1) as usize];
+        let token = &line[(expr.span.start_column - 1) as usize..];
+
+        let end = if token.starts_with('"') || token.starts_with("u\"") {
+            // string literals end at the first unescaped quote after the opening one
+            let open = token
+                .find('"')
+                .expect("BUG: string literal without a quote");
+            let mut escaped = false;
+            let mut end = token.len();
+            for (ix, c) in token[open + 1..].char_indices() {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    end = open + 1 + ix + 1;
+                    break;
+                }
+            }
+            end
+        } else {
+            token
+                .find(|c: char| c.is_whitespace() || "(){},:;".contains(c))
+                .unwrap_or(token.len())
+        };
+        &token[..end]
+    }
+
+    /// Render `expr` on a single line, or None if it contains a comment (which must end its line).
+    fn flat(&self, expr: &PreSymbolicExpression) -> Option<String> {
+        match expr.pre_expr {
+            Comment(_) => None,
+            List(ref children) => {
+                let children: Option<Vec<_>> =
+                    children.iter().map(|child| self.flat(child)).collect();
+                Some(format!("({})", children?.join(" ")))
+            }
+            Tuple(ref children) => {
+                let entries: Option<Vec<_>> = children
+                    .chunks(2)
+                    .map(|entry| {
+                        let key = self.flat(&entry[0])?;
+                        let value = self.flat(entry.get(1)?)?;
+                        Some(format!("{}: {}", key, value))
+                    })
+                    .collect();
+                Some(format!("{{ {} }}", entries?.join(", ")))
+            }
+            _ => Some(self.token_text(expr).to_string()),
+        }
+    }
+
+    /// Render `expr` starting at `column`, followed on its last line by `trailing` characters
+    ///   (the closing delimiters of the expressions containing it).
+    fn format(&self, expr: &PreSymbolicExpression, column: usize, trailing: usize) -> String {
+        match expr.pre_expr {
+            Comment(ref text) => text.trim_end().to_string(),
+            List(ref children) => {
+                if !always_breaks(children) {
+                    if let Some(flat) = self.flat(expr) {
+                        if column + flat.len() + trailing <= MAX_LINE_WIDTH {
+                            return flat;
+                        }
+                    }
+                }
+                self.format_list(children, column, trailing)
+            }
+            Tuple(ref children) => {
+                if let Some(flat) = self.flat(expr) {
+                    if column + flat.len() + trailing <= MAX_LINE_WIDTH {
+                        return flat;
+                    }
+                }
+                self.format_tuple(children, column, trailing)
+            }
+            _ => self.token_text(expr).to_string(),
+        }
+    }
+
+    /// Decide how to break a list starting at `column`: how many of its (non-comment) children
+    ///   stay on the opening line, and which column the others are indented to.
+    fn list_layout(&self, children: &[PreSymbolicExpression], column: usize) -> (usize, usize) {
+        let mut code = children
+            .iter()
+            .filter(|child| child.match_comment().is_none());
+        let name = match code.next().and_then(|head| head.match_atom()) {
+            Some(name) => name.as_str(),
+            // a list of data, like `let` bindings: align every element
+            None => return (1, column + 1),
+        };
+
+        if let Some(header_length) = body_form_header_length(name) {
+            return (1 + header_length, column + INDENT);
+        }
+
+        let aligned_column = column + 1 + name.len() + 1;
+        let args_fit_aligned = code.all(|arg| match self.flat(arg) {
+            Some(flat) => aligned_column + flat.len() <= MAX_LINE_WIDTH,
+            None => false,
+        });
+        if name.len() <= MAX_ALIGNED_NAME_LENGTH || args_fit_aligned {
+            (2, aligned_column)
+        } else {
+            (1, column + INDENT)
+        }
+    }
+
+    fn format_list(
+        &self,
+        children: &[PreSymbolicExpression],
+        column: usize,
+        trailing: usize,
+    ) -> String {
+        let (same_line, indent) = self.list_layout(children, column);
+        let last_ix = children.len().saturating_sub(1);
+
+        let mut out = String::from("(");
+        let mut placed = 0;
+        // set when a comment ends the current line
+        let mut line_ended = false;
+        let mut prev = None;
+        for (ix, child) in children.iter().enumerate() {
+            let is_comment = child.match_comment().is_some();
+            if is_trailing_comment(prev, child) && !line_ended {
+                out.push(' ');
+            } else if !is_comment && placed < same_line && !line_ended {
+                if placed > 0 {
+                    out.push(' ');
+                }
+            } else {
+                new_line(&mut out, blank_line_between(prev, child), indent);
+            }
+
+            let child_trailing = if ix == last_ix { trailing + 1 } else { 0 };
+            let child_column = current_column(&out, column);
+            out.push_str(&self.format(child, child_column, child_trailing));
+
+            if is_comment {
+                line_ended = true;
+            } else {
+                placed += 1;
+                line_ended = false;
+            }
+            prev = Some(child);
+        }
+
+        if line_ended {
+            new_line(&mut out, false, column);
+        }
+        out.push(')');
+        out
+    }
+
+    fn format_tuple(
+        &self,
+        children: &[PreSymbolicExpression],
+        column: usize,
+        trailing: usize,
+    ) -> String {
+        let indent = column + INDENT;
+        let num_code = children
+            .iter()
+            .filter(|child| child.match_comment().is_none())
+            .count();
+
+        let mut out = String::from("{");
+        let mut placed = 0;
+        let mut line_ended = false;
+        let mut prev = None;
+        for child in children.iter() {
+            if child.match_comment().is_some() {
+                if is_trailing_comment(prev, child) && !line_ended {
+                    out.push(' ');
+                } else {
+                    new_line(&mut out, blank_line_between(prev, child), indent);
+                }
+                out.push_str(child.match_comment().unwrap_or("").trim_end());
+                line_ended = true;
+            } else if placed % 2 == 0 {
+                // a key
+                if placed == 0 && !line_ended {
+                    out.push(' ');
+                } else {
+                    new_line(&mut out, blank_line_between(prev, child), indent);
+                }
+                out.push_str(&self.format(child, current_column(&out, column), 0));
+                out.push(':');
+                placed += 1;
+                line_ended = false;
+            } else {
+                // a value
+                if line_ended {
+                    new_line(&mut out, false, indent + INDENT);
+                } else {
+                    out.push(' ');
+                }
+                let is_last = placed + 1 == num_code;
+                let value_trailing = if is_last { trailing + 2 } else { 1 };
+                out.push_str(&self.format(child, current_column(&out, column), value_trailing));
+                if !is_last {
+                    out.push(',');
+                }
+                placed += 1;
+                line_ended = false;
+            }
+            prev = Some(child);
+        }
+
+        if line_ended {
+            new_line(&mut out, false, column);
+            out.push('}');
+        } else {
+            out.push_str(" }");
+        }
+        out
+    }
+}
+
+/// Format Clarity source code: each top-level expression starts a line, expressions too wide
+///   for `MAX_LINE_WIDTH` are broken across lines and indented, and comments and single blank
+///   lines between expressions are kept. Formatting is deterministic, and formatted code is
+///   left unchanged by formatting it again.
+pub fn format_source(source: &str) -> ParseResult<String> {
+    let expressions = parser::parse_with_comments(source)?;
+    let formatter = Formatter {
+        lines: source.split('\n').collect(),
+    };
+
+    let mut out = String::new();
+    let mut prev = None;
+    for expr in expressions.iter() {
+        if is_trailing_comment(prev, expr) {
+            out.push(' ');
+        } else if prev.is_some() {
+            new_line(&mut out, blank_line_between(prev, expr), 0);
+        }
+        out.push_str(&formatter.format(expr, 0, 0));
+        prev = Some(expr);
+    }
+    if out.len() > 0 {
+        out.push('\n');
+    }
+
+    let reparsed = parser::parse_with_comments(&out)?;
+    if !same_expressions(&expressions, &reparsed) {
+        panic!("BUG: formatting changed the program:\n{}", out);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_formats_to(source: &str, expected: &str) {
+        let formatted = format_source(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_flat_expressions() {
+        assert_formats_to(
+            "(define-constant   owner\n  tx-sender)\n(define-map balances {owner: principal}   {amount: uint})",
+            "(define-constant owner tx-sender)\n(define-map balances { owner: principal } { amount: uint })\n",
+        );
+        assert_formats_to(
+            "(print  u\"a \\\"b\\\" (c)\" )\n(print\n'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR.token)",
+            "(print u\"a \\\"b\\\" (c)\")\n(print 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR.token)\n",
+        );
+        assert_formats_to("", "");
+    }
+
+    #[test]
+    fn test_function_layout() {
+        assert_formats_to(
+            "(define-read-only (get-balance (owner principal)) (default-to u0 (get amount (map-get? balances {owner: owner}))))",
+            "(define-read-only (get-balance (owner principal))
+  (default-to u0 (get amount (map-get? balances { owner: owner }))))
+",
+        );
+        assert_formats_to(
+            "(define-public (transfer (amount uint) (sender principal) (recipient principal))
+    (let ((sender-balance (get-balance sender)) (recipient-balance (get-balance recipient)))
+    (asserts! (>= sender-balance amount) (err u1))
+    (map-set balances {owner: sender} {amount: (- sender-balance amount)})
+    (ok (map-set balances {owner: recipient} {amount: (+ recipient-balance amount), updated-at: block-height}))))",
+            "(define-public (transfer (amount uint) (sender principal) (recipient principal))
+  (let ((sender-balance (get-balance sender))
+        (recipient-balance (get-balance recipient)))
+    (asserts! (>= sender-balance amount) (err u1))
+    (map-set balances { owner: sender } { amount: (- sender-balance amount) })
+    (ok (map-set balances
+                 { owner: recipient }
+                 { amount: (+ recipient-balance amount),
+                   updated-at: block-height }))))
+",
+        );
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        assert_formats_to(
+            ";; a token
+
+;; the owner
+(define-constant owner tx-sender) ;; set at deploy time
+
+
+
+(define-private (check (amount uint))
+  ;; must be positive
+  (begin (asserts! (> amount u0) (err u1)) ;; checked
+    (ok amount) ;; done
+  ))
+(define-constant limits {
+  ;; per block
+  block: u10, ;; small
+  total: u100 })
+",
+            ";; a token
+
+;; the owner
+(define-constant owner tx-sender) ;; set at deploy time
+
+(define-private (check (amount uint))
+  ;; must be positive
+  (begin
+    (asserts! (> amount u0) (err u1)) ;; checked
+    (ok amount) ;; done
+  ))
+(define-constant limits
+  {
+    ;; per block
+    block: u10, ;; small
+    total: u100 })
+",
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(format_source("(define-constant x (+ 1 2)").is_err());
+        assert!(format_source("(foo))").is_err());
+    }
+}
@@ -20,6 +20,8 @@ pub mod parser;
 pub mod traits_resolver;
 
 pub mod errors;
+#[cfg(feature = "developer-mode")]
+pub mod formatter;
 pub mod stack_depth_checker;
 pub mod sugar_expander;
 pub mod types;
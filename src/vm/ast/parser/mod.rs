@@ -43,11 +43,13 @@ pub enum LexItem {
     CommaSeparator,
     ColonSeparator,
     Whitespace,
+    Comment(String),
 }
 
 #[derive(Debug)]
 enum TokenType {
     Whitespace,
+    Comment,
     Comma,
     Colon,
     LParens,
@@ -140,7 +142,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
             r##""(?P<value>((\\")|([[ -~]&&[^"]]))*)""##,
             TokenType::StringASCIILiteral,
         ),
-        LexMatcher::new(";;[ -~]*", TokenType::Whitespace), // ;; comments.
+        LexMatcher::new(";;[ -~]*", TokenType::Comment),
         LexMatcher::new("[\n]+", TokenType::Whitespace),
         LexMatcher::new("[ \t]+", TokenType::Whitespace),
         LexMatcher::new("[,]", TokenType::Comma),
@@ -199,14 +201,16 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
     let mut column_pos: u32 = 1;
     let mut did_match = true;
     while did_match && munch_index < input.len() {
-        if let Some(next_line_ix) = next_line_break {
-            if munch_index > next_line_ix {
-                next_line_break = line_indices.pop();
-                column_pos = 1;
-                current_line = current_line
-                    .checked_add(1)
-                    .ok_or(ParseError::new(ParseErrors::ProgramTooLarge))?;
+        // a single whitespace token can span several line breaks
+        while let Some(next_line_ix) = next_line_break {
+            if munch_index <= next_line_ix {
+                break;
             }
+            next_line_break = line_indices.pop();
+            column_pos = 1;
+            current_line = current_line
+                .checked_add(1)
+                .ok_or(ParseError::new(ParseErrors::ProgramTooLarge))?;
         }
 
         did_match = false;
@@ -227,6 +231,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             TokenType::RParens => Ok(()),
                             TokenType::RCurly => Ok(()),
                             TokenType::Whitespace => Ok(()),
+                            TokenType::Comment => Ok(()),
                             TokenType::Comma => Ok(()),
                             TokenType::Colon => Ok(()),
                             _ => Err(ParseError::new(ParseErrors::SeparatorExpected(
@@ -240,6 +245,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             TokenType::RParens => Ok(()),
                             TokenType::RCurly => Ok(()),
                             TokenType::Whitespace => Ok(()),
+                            TokenType::Comment => Ok(()),
                             TokenType::Comma => Ok(()),
                             TokenType::Colon => Ok(()),
                             _ => Err(ParseError::new(ParseErrors::SeparatorExpectedAfterColon(
@@ -262,6 +268,12 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                         context = LexContext::ExpectNothing;
                         Ok(LexItem::Whitespace)
                     }
+                    TokenType::Comment => {
+                        context = LexContext::ExpectNothing;
+                        Ok(LexItem::Comment(
+                            current_slice[..whole_match.end()].to_string(),
+                        ))
+                    }
                     TokenType::Comma => {
                         context = LexContext::ExpectNothing;
                         Ok(LexItem::CommaSeparator)
@@ -473,7 +485,14 @@ fn handle_expression(
     }
 }
 
-pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+pub fn parse_lexed(input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+    parse_lexed_inner(input, false)
+}
+
+fn parse_lexed_inner(
+    mut input: Vec<(LexItem, u32, u32)>,
+    keep_comments: bool,
+) -> ParseResult<Vec<PreSymbolicExpression>> {
     let mut parse_stack = Vec::new();
 
     let mut output_list = Vec::new();
@@ -538,7 +557,15 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                     match parse_context {
                         ParseContext::CollectTuple => {
                             let mut checked_list = Vec::new();
-                            for (index, item) in tuple_list.into_iter().enumerate() {
+                            let mut index = 0;
+                            for item in tuple_list.into_iter() {
+                                // comments can go anywhere in a tuple
+                                if let ParseStackItem::Expression(ref e) = item {
+                                    if e.match_comment().is_some() {
+                                        checked_list.push(e.clone());
+                                        continue;
+                                    }
+                                }
                                 // check that tuple items are (expr, colon, expr, comma)
                                 match index % 4 {
                                     0 | 2 => {
@@ -565,6 +592,7 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                                     }
                                     _ => unreachable!("More than four modulos of four."),
                                 }?;
+                                index += 1;
                             }
                             let mut pre_expr =
                                 PreSymbolicExpression::tuple(checked_list.into_boxed_slice());
@@ -666,6 +694,14 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                     }
                 };
             }
+            LexItem::Comment(text) => {
+                if keep_comments {
+                    let end_column = column_pos + (text.len() as u32) - 1;
+                    let mut pre_expr = PreSymbolicExpression::comment(text);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                }
+            }
             LexItem::Whitespace => (),
         };
     }
@@ -693,6 +729,13 @@ pub fn parse(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
     parse_lexed(lexed)
 }
 
+/// Like `parse`, but keeps each `;;` comment as a `PreSymbolicExpressionType::Comment`
+///   alongside the expressions, for tools (like the formatter) that rewrite source code.
+pub fn parse_with_comments(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
+    let lexed = lex(input)?;
+    parse_lexed_inner(lexed, true)
+}
+
 #[cfg(test)]
 mod test {
     use vm::ast;
@@ -881,6 +924,75 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_with_comments() {
+        let input = ";; header
+
+
+(+ x ;; trailing
+  {a: 1, ;; inside a tuple
+   b: 2})";
+        let mut header = PreSymbolicExpression::comment(";; header".to_string());
+        header.set_span(1, 1, 1, 9);
+        let mut trailing = PreSymbolicExpression::comment(";; trailing".to_string());
+        trailing.set_span(4, 6, 4, 16);
+        let mut inside = PreSymbolicExpression::comment(";; inside a tuple".to_string());
+        inside.set_span(5, 10, 5, 26);
+        let program = vec![
+            header,
+            make_list(
+                4,
+                1,
+                6,
+                9,
+                Box::new([
+                    make_atom("+", 4, 2, 4, 2),
+                    make_atom("x", 4, 4, 4, 4),
+                    trailing,
+                    make_tuple(
+                        5,
+                        3,
+                        6,
+                        8,
+                        Box::new([
+                            make_atom("a", 5, 4, 5, 4),
+                            make_atom_value(Value::Int(1), 5, 7, 5, 7),
+                            inside,
+                            make_atom("b", 6, 4, 6, 4),
+                            make_atom_value(Value::Int(2), 6, 7, 6, 7),
+                        ]),
+                    ),
+                ]),
+            ),
+        ];
+        assert_eq!(Ok(program), ast::parser::parse_with_comments(input));
+
+        // without comments, the lines after several line breaks are still counted
+        let program = vec![make_list(
+            4,
+            1,
+            6,
+            9,
+            Box::new([
+                make_atom("+", 4, 2, 4, 2),
+                make_atom("x", 4, 4, 4, 4),
+                make_tuple(
+                    5,
+                    3,
+                    6,
+                    8,
+                    Box::new([
+                        make_atom("a", 5, 4, 5, 4),
+                        make_atom_value(Value::Int(1), 5, 7, 5, 7),
+                        make_atom("b", 6, 4, 6, 4),
+                        make_atom_value(Value::Int(2), 6, 7, 6, 7),
+                    ]),
+                ),
+            ]),
+        )];
+        assert_eq!(Ok(program), ast::parser::parse(input));
+    }
+
     #[test]
     fn test_parse_tuple_literal() {
         let input = "{id: 1337 }";
@@ -114,6 +114,7 @@ impl SugarExpander {
                         return Err(ParseErrors::TraitReferenceUnknown(name.to_string()).into());
                     }
                 }
+                PreSymbolicExpressionType::Comment(_) => continue,
             };
             // expr.id will be set by the subsequent expression identifier pass.
             expr.span = pre_expr.span.clone();
@@ -273,6 +273,10 @@ impl CallableType {
 }
 
 impl FunctionIdentifier {
+    pub fn is_native(&self) -> bool {
+        self.identifier.starts_with("_native_:")
+    }
+
     fn new_native_function(name: &str) -> FunctionIdentifier {
         let identifier = format!("_native_:{}", name);
         FunctionIdentifier {
@@ -23,6 +23,7 @@ use vm::ast;
 use vm::ast::ContractAST;
 use vm::callables::{DefinedFunction, FunctionIdentifier};
 use vm::contracts::Contract;
+use vm::costs::profiler::CostProfiler;
 use vm::costs::{
     cost_functions, runtime_cost, ClarityCostFunctionReference, CostErrors, CostTracker,
     ExecutionCost, LimitedCostTracker,
@@ -54,6 +55,7 @@ use serde::Serialize;
 use vm::costs::cost_functions::ClarityCostFunction;
 
 use vm::coverage::CoverageReporter;
+use vm::debug::EvalHook;
 
 pub const MAX_CONTEXT_DEPTH: u16 = 256;
 
@@ -195,6 +197,9 @@ pub struct GlobalContext<'a> {
     pub cost_track: LimitedCostTracker,
     pub mainnet: bool,
     pub coverage_reporting: Option<CoverageReporter>,
+    /// If set, this hook is invoked before and after every expression is evaluated
+    pub eval_hook: Option<Box<dyn EvalHook>>,
+    pub cost_profiler: Option<CostProfiler>,
     /// This is the epoch of the the block that this transaction is executing within.
     epoch_id: StacksEpochId,
 }
@@ -563,6 +568,22 @@ impl<'a> OwnedEnvironment<'a> {
         self.context.coverage_reporting.take()
     }
 
+    pub fn set_eval_hook(&mut self, hook: Box<dyn EvalHook>) {
+        self.context.eval_hook = Some(hook)
+    }
+
+    pub fn take_eval_hook(&mut self) -> Option<Box<dyn EvalHook>> {
+        self.context.eval_hook.take()
+    }
+
+    pub fn set_cost_profiler(&mut self, profiler: CostProfiler) {
+        self.context.cost_profiler = Some(profiler)
+    }
+
+    pub fn take_cost_profiler(&mut self) -> Option<CostProfiler> {
+        self.context.cost_profiler.take()
+    }
+
     pub fn new_free(
         mainnet: bool,
         database: ClarityDatabase<'a>,
@@ -716,7 +737,6 @@ impl<'a> OwnedEnvironment<'a> {
         .unwrap();
     }
 
-    #[cfg(test)]
     pub fn eval_raw(
         &mut self,
         program: &str,
@@ -768,11 +788,12 @@ impl CostTracker for Environment<'_, '_> {
         cost_function: ClarityCostFunction,
         input: &[u64],
     ) -> std::result::Result<ExecutionCost, CostErrors> {
-        self.global_context
-            .cost_track
-            .compute_cost(cost_function, input)
+        self.global_context.compute_cost(cost_function, input)
     }
     fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
+        if let Some(ref mut profiler) = self.global_context.cost_profiler {
+            profiler.report_cost(self.call_stack.frames(), &cost);
+        }
         self.global_context.cost_track.add_cost(cost)
     }
     fn add_memory(&mut self, memory: u64) -> std::result::Result<(), CostErrors> {
@@ -802,10 +823,17 @@ impl CostTracker for GlobalContext<'_> {
         cost_function: ClarityCostFunction,
         input: &[u64],
     ) -> std::result::Result<ExecutionCost, CostErrors> {
-        self.cost_track.compute_cost(cost_function, input)
+        let cost = self.cost_track.compute_cost(cost_function, input)?;
+        if let Some(ref mut profiler) = self.cost_profiler {
+            profiler.report_cost_function(cost_function);
+        }
+        Ok(cost)
     }
 
     fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
+        if let Some(ref mut profiler) = self.cost_profiler {
+            profiler.report_cost(&[], &cost);
+        }
         self.cost_track.add_cost(cost)
     }
     fn add_memory(&mut self, memory: u64) -> std::result::Result<(), CostErrors> {
@@ -1389,6 +1417,8 @@ impl<'a> GlobalContext<'a> {
             mainnet,
             epoch_id,
             coverage_reporting: None,
+            eval_hook: None,
+            cost_profiler: None,
         }
     }
 
@@ -1703,6 +1733,11 @@ impl CallStack {
         }
     }
 
+    /// The functions currently being applied, outermost first.
+    pub fn frames(&self) -> &[FunctionIdentifier] {
+        &self.stack
+    }
+
     #[cfg(feature = "developer-mode")]
     pub fn make_stack_trace(&self) -> StackTrace {
         self.stack.clone()
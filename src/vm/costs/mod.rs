@@ -41,6 +41,7 @@ use vm::{ast, eval_all, ClarityName, SymbolicExpression, Value};
 
 pub mod constants;
 pub mod cost_functions;
+pub mod profiler;
 
 type Result<T> = std::result::Result<T, CostErrors>;
 
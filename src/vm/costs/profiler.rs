@@ -0,0 +1,270 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use vm::callables::FunctionIdentifier;
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::ExecutionCost;
+use vm::types::QualifiedContractIdentifier;
+use vm::SymbolicExpression;
+
+/// Stack frame used for costs charged outside of any user-defined function
+pub const TOP_LEVEL_FRAME: &str = "<top-level>";
+/// Leaf frame used for costs charged without first being computed from a cost function
+pub const UNKNOWN_COST_FUNCTION: &str = "<unknown>";
+
+/// The dimensions of an `ExecutionCost` that a folded-stacks profile can be weighted by.
+pub const COST_DIMENSIONS: &[&str] = &[
+    "runtime",
+    "read_count",
+    "read_length",
+    "write_count",
+    "write_length",
+];
+
+/// The CostProfiler records every cost charged to a `GlobalContext`, attributing it to
+///   the cost function that computed it, the user-defined functions on the call stack,
+///   and the source line of the innermost expression being evaluated.
+pub struct CostProfiler {
+    /// (contract, line) of each expression being evaluated, innermost last
+    locations: Vec<(QualifiedContractIdentifier, u32)>,
+    /// the cost function whose cost is about to be charged
+    pending_cost_function: Option<ClarityCostFunction>,
+    /// cost per (user-defined call stack, cost function)
+    stacks: HashMap<(Vec<String>, String), ExecutionCost>,
+    /// invocations and cost per cost function
+    cost_functions: HashMap<String, (u64, ExecutionCost)>,
+    /// cost per (contract, line)
+    lines: HashMap<(String, u32), ExecutionCost>,
+    total: ExecutionCost,
+}
+
+#[derive(Serialize)]
+pub struct CostFunctionProfile {
+    pub name: String,
+    pub invocations: u64,
+    pub cost: ExecutionCost,
+}
+
+#[derive(Serialize)]
+pub struct FunctionProfile {
+    pub function: String,
+    /// cost charged while this function was the innermost user-defined function
+    pub self_cost: ExecutionCost,
+    /// cost charged while this function was anywhere on the call stack
+    pub total_cost: ExecutionCost,
+}
+
+#[derive(Serialize)]
+pub struct LineProfile {
+    pub contract: String,
+    pub line: u32,
+    pub cost: ExecutionCost,
+}
+
+#[derive(Serialize)]
+pub struct CostProfile {
+    pub total: ExecutionCost,
+    pub cost_functions: Vec<CostFunctionProfile>,
+    pub functions: Vec<FunctionProfile>,
+    pub lines: Vec<LineProfile>,
+}
+
+fn accumulate(into: &mut ExecutionCost, cost: &ExecutionCost) {
+    // each of these is a sub-total of the cost tracker's total, which would have already
+    //  failed with CostOverflow if this overflowed.
+    let _ = into.add(cost);
+}
+
+/// Get the given dimension of a cost. Returns None if `dimension` isn't in `COST_DIMENSIONS`.
+pub fn cost_dimension(cost: &ExecutionCost, dimension: &str) -> Option<u64> {
+    match dimension {
+        "runtime" => Some(cost.runtime),
+        "read_count" => Some(cost.read_count),
+        "read_length" => Some(cost.read_length),
+        "write_count" => Some(cost.write_count),
+        "write_length" => Some(cost.write_length),
+        _ => None,
+    }
+}
+
+/// Sort most expensive first, by runtime and then by read count
+fn by_cost_desc(a: &ExecutionCost, b: &ExecutionCost) -> std::cmp::Ordering {
+    (b.runtime, b.read_count).cmp(&(a.runtime, a.read_count))
+}
+
+impl CostProfiler {
+    pub fn new() -> CostProfiler {
+        CostProfiler {
+            locations: vec![],
+            pending_cost_function: None,
+            stacks: HashMap::new(),
+            cost_functions: HashMap::new(),
+            lines: HashMap::new(),
+            total: ExecutionCost::zero(),
+        }
+    }
+
+    pub fn enter_expression(
+        &mut self,
+        expr: &SymbolicExpression,
+        contract: &QualifiedContractIdentifier,
+    ) {
+        self.locations.push((contract.clone(), expr.start_line()));
+    }
+
+    pub fn exit_expression(&mut self) {
+        self.locations.pop();
+    }
+
+    /// Called when a cost function is evaluated, before its cost is charged.
+    pub fn report_cost_function(&mut self, cost_function: ClarityCostFunction) {
+        self.pending_cost_function = Some(cost_function);
+    }
+
+    /// Called when a cost is charged. `call_stack` is the stack of functions being
+    ///   applied, outermost first, or empty if the charge happens outside of any.
+    pub fn report_cost(&mut self, call_stack: &[FunctionIdentifier], cost: &ExecutionCost) {
+        let cost_function = match self.pending_cost_function.take() {
+            Some(cost_function) => cost_function.get_name(),
+            None => UNKNOWN_COST_FUNCTION.to_string(),
+        };
+
+        let mut frames: Vec<String> = call_stack
+            .iter()
+            .filter(|frame| !frame.is_native())
+            .map(|frame| frame.to_string())
+            .collect();
+        if frames.len() == 0 {
+            frames.push(TOP_LEVEL_FRAME.to_string());
+        }
+
+        if let Some((contract, line)) = self.locations.last() {
+            accumulate(
+                self.lines
+                    .entry((contract.to_string(), *line))
+                    .or_insert_with(ExecutionCost::zero),
+                cost,
+            );
+        }
+
+        let (invocations, cost_function_total) = self
+            .cost_functions
+            .entry(cost_function.clone())
+            .or_insert_with(|| (0, ExecutionCost::zero()));
+        *invocations += 1;
+        accumulate(cost_function_total, cost);
+
+        accumulate(
+            self.stacks
+                .entry((frames, cost_function))
+                .or_insert_with(ExecutionCost::zero),
+            cost,
+        );
+        accumulate(&mut self.total, cost);
+    }
+
+    pub fn get_total(&self) -> &ExecutionCost {
+        &self.total
+    }
+
+    /// Render the profile as folded stacks (one `frame;frame;...;cost-function weight` line
+    ///   per distinct stack), as consumed by flamegraph.pl and compatible tools.
+    pub fn to_folded(&self, dimension: &str) -> Option<String> {
+        cost_dimension(&self.total, dimension)?;
+
+        let mut lines = vec![];
+        for ((frames, cost_function), cost) in self.stacks.iter() {
+            let weight = cost_dimension(cost, dimension).expect("BUG: unchecked cost dimension");
+            if weight == 0 {
+                continue;
+            }
+            lines.push(format!("{};{} {}", frames.join(";"), cost_function, weight));
+        }
+        lines.sort();
+
+        let mut folded = lines.join("\n");
+        if folded.len() > 0 {
+            folded.push('\n');
+        }
+        Some(folded)
+    }
+
+    /// Summarize the profile by cost function, by user-defined function, and by source line.
+    pub fn report(&self) -> CostProfile {
+        let mut cost_functions: Vec<_> = self
+            .cost_functions
+            .iter()
+            .map(|(name, (invocations, cost))| CostFunctionProfile {
+                name: name.clone(),
+                invocations: *invocations,
+                cost: cost.clone(),
+            })
+            .collect();
+        cost_functions.sort_by(|a, b| by_cost_desc(&a.cost, &b.cost).then(a.name.cmp(&b.name)));
+
+        let mut functions: HashMap<&str, FunctionProfile> = HashMap::new();
+        for ((frames, _), cost) in self.stacks.iter() {
+            let mut seen = HashSet::new();
+            for frame in frames.iter() {
+                // count recursive calls towards a function's total only once
+                if !seen.insert(frame) {
+                    continue;
+                }
+                let profile = functions
+                    .entry(frame.as_str())
+                    .or_insert_with(|| FunctionProfile {
+                        function: frame.clone(),
+                        self_cost: ExecutionCost::zero(),
+                        total_cost: ExecutionCost::zero(),
+                    });
+                accumulate(&mut profile.total_cost, cost);
+            }
+            if let Some(innermost) = frames.last() {
+                if let Some(profile) = functions.get_mut(innermost.as_str()) {
+                    accumulate(&mut profile.self_cost, cost);
+                }
+            }
+        }
+        let mut functions: Vec<_> = functions.into_iter().map(|(_, profile)| profile).collect();
+        functions.sort_by(|a, b| {
+            by_cost_desc(&a.self_cost, &b.self_cost).then(a.function.cmp(&b.function))
+        });
+
+        let mut lines: Vec<_> = self
+            .lines
+            .iter()
+            .map(|((contract, line), cost)| LineProfile {
+                contract: contract.clone(),
+                line: *line,
+                cost: cost.clone(),
+            })
+            .collect();
+        lines.sort_by(|a, b| {
+            by_cost_desc(&a.cost, &b.cost)
+                .then(a.contract.cmp(&b.contract))
+                .then(a.line.cmp(&b.line))
+        });
+
+        CostProfile {
+            total: self.total.clone(),
+            cost_functions,
+            functions,
+            lines,
+        }
+    }
+}
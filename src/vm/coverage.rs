@@ -157,6 +157,24 @@ impl CoverageReporter {
         lines
     }
 
+    /// Count how many of a contract's executable lines have been executed so far.
+    /// Returns (executed lines, executable lines).
+    pub fn line_coverage(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        ast: &[SymbolicExpression],
+    ) -> (usize, usize) {
+        let executable_lines = CoverageReporter::executable_lines(ast);
+        let executed_lines = match self.executed_lines.get(contract) {
+            Some(execution_map) => executable_lines
+                .iter()
+                .filter(|line| execution_map.contains_key(line))
+                .count(),
+            None => 0,
+        };
+        (executed_lines, executable_lines.len())
+    }
+
     pub fn register_src_file<P: AsRef<std::path::Path> + Copy>(
         contract: &QualifiedContractIdentifier,
         src_file_name: &str,
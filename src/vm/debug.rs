@@ -0,0 +1,589 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Write;
+use std::rc::Rc;
+
+use vm::contexts::{Environment, LocalContext};
+use vm::errors::{Error, InterpreterResult as Result};
+use vm::representations::{ClarityName, SymbolicExpression};
+use vm::types::{QualifiedContractIdentifier, Value};
+
+/// An EvalHook is notified by `vm::eval` before and after every expression is
+///   evaluated. It is installed in the `GlobalContext`, and is handed the
+///   same environment and local context as the expression being evaluated.
+pub trait EvalHook {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    );
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+        res: &Result<Value>,
+    );
+}
+
+/// Allows a hook to be installed in a `GlobalContext` while the caller
+///   retains a handle to it (e.g., to add breakpoints between executions).
+impl<T: EvalHook> EvalHook for Rc<RefCell<T>> {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        self.borrow_mut().will_begin_eval(env, context, expr)
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+        res: &Result<Value>,
+    ) {
+        self.borrow_mut().did_finish_eval(env, context, expr, res)
+    }
+}
+
+pub struct Breakpoint {
+    pub id: usize,
+    /// If None, the breakpoint applies to the given line of every contract
+    pub contract: Option<QualifiedContractIdentifier>,
+    pub line: u32,
+}
+
+impl Breakpoint {
+    fn matches(&self, contract: &QualifiedContractIdentifier, line: u32) -> bool {
+        self.line == line
+            && self
+                .contract
+                .as_ref()
+                .map(|bp_contract| bp_contract == contract)
+                .unwrap_or(true)
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.contract {
+            Some(ref contract) => write!(f, "#{} at {}:{}", self.id, contract, self.line),
+            None => write!(f, "#{} at line {}", self.id, self.line),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StepMode {
+    /// Run until a breakpoint or an error
+    Continue,
+    /// Pause before the next expression
+    StepIn,
+    /// Pause before the next expression at or above the given depth
+    StepOver(usize),
+    /// Pause before the next expression above the given depth
+    StepOut(usize),
+}
+
+const DEBUGGER_HELP: &str = "Debugger commands:
+  s, step              step into the next expression
+  n, next              step over the current expression
+  f, finish            step out of the current expression
+  c, continue          continue until the next breakpoint or error
+  l, locals            print the local bindings
+  v, vars              print the current contract's constants and data vars
+  bt, backtrace        print the call stack
+  b, break [CONTRACT:]LINE
+                       set a breakpoint
+  d, delete ID         delete a breakpoint
+  i, breakpoints       list the breakpoints
+  h, help              print this message";
+
+/// An interactive debugger for Clarity code. It pauses evaluation at breakpoints,
+///   when stepping, and where a runtime error originates, and reads commands
+///   from its input until it is told to resume.
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint_id: usize,
+    mode: StepMode,
+    /// Number of expressions currently being evaluated
+    depth: usize,
+    /// Location of the last expression that began evaluation, so a breakpoint
+    ///   only fires when evaluation enters its line
+    last_location: Option<(QualifiedContractIdentifier, u32)>,
+    /// Set once a failing expression has been reported, so the error is only
+    ///   reported where it originated rather than at every enclosing expression
+    unwinding: bool,
+    input: Box<dyn FnMut() -> Option<String>>,
+    output: Box<dyn Write>,
+}
+
+impl Debugger {
+    /// Create a debugger that reads commands by calling `input` (which returns None
+    ///   once there is no more input) and writes to `output`.
+    pub fn new(input: Box<dyn FnMut() -> Option<String>>, output: Box<dyn Write>) -> Debugger {
+        Debugger {
+            breakpoints: vec![],
+            next_breakpoint_id: 1,
+            mode: StepMode::Continue,
+            depth: 0,
+            last_location: None,
+            unwinding: false,
+            input,
+            output,
+        }
+    }
+
+    pub fn add_breakpoint(
+        &mut self,
+        contract: Option<QualifiedContractIdentifier>,
+        line: u32,
+    ) -> usize {
+        let id = self.next_breakpoint_id;
+        self.next_breakpoint_id += 1;
+        self.breakpoints.push(Breakpoint { id, contract, line });
+        id
+    }
+
+    /// Add a breakpoint from a `[CONTRACT:]LINE` spec. CONTRACT is either a fully
+    ///   qualified contract identifier, or a contract name deployed by the
+    ///   transient principal.
+    pub fn add_breakpoint_spec(&mut self, spec: &str) -> std::result::Result<usize, String> {
+        let mut parts = spec.rsplitn(2, ':');
+        let line = parts
+            .next()
+            .and_then(|line| line.parse::<u32>().ok())
+            .ok_or_else(|| format!("Invalid line number in breakpoint '{}'", spec))?;
+        let contract = match parts.next() {
+            Some(contract) if contract.contains('.') => Some(
+                QualifiedContractIdentifier::parse(contract)
+                    .map_err(|e| format!("Invalid contract identifier '{}': {}", contract, e))?,
+            ),
+            Some(contract) => Some(
+                QualifiedContractIdentifier::local(contract)
+                    .map_err(|e| format!("Invalid contract name '{}': {}", contract, e))?,
+            ),
+            None => None,
+        };
+        Ok(self.add_breakpoint(contract, line))
+    }
+
+    pub fn delete_breakpoint(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|bp| bp.id != id);
+        self.breakpoints.len() != count
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Pause before the next expression is evaluated.
+    pub fn step_into_next(&mut self) {
+        self.mode = StepMode::StepIn;
+    }
+
+    fn print(&mut self, msg: &str) {
+        let _ = writeln!(self.output, "{}", msg);
+    }
+
+    fn print_locals(&mut self, context: &LocalContext) {
+        let mut shadowed = HashSet::new();
+        let mut current = Some(context);
+        let mut lines = vec![];
+        while let Some(ctx) = current {
+            let mut names: Vec<&ClarityName> = ctx.variables.keys().collect();
+            names.sort();
+            for name in names.into_iter() {
+                if shadowed.insert(name.clone()) {
+                    lines.push(format!("  {} = {}", name, ctx.variables[name]));
+                }
+            }
+            let mut names: Vec<&ClarityName> = ctx.callable_contracts.keys().collect();
+            names.sort();
+            for name in names.into_iter() {
+                if shadowed.insert(name.clone()) {
+                    let (contract, _) = &ctx.callable_contracts[name];
+                    lines.push(format!("  {} = {}", name, contract));
+                }
+            }
+            current = ctx.parent;
+        }
+        if lines.len() == 0 {
+            self.print("No local bindings.");
+        } else {
+            for line in lines.iter() {
+                self.print(line);
+            }
+        }
+    }
+
+    fn print_data_vars(&mut self, env: &mut Environment) {
+        let contract_id = env.contract_context.contract_identifier.clone();
+        let mut lines = vec![];
+
+        let mut names: Vec<&ClarityName> = env.contract_context.variables.keys().collect();
+        names.sort();
+        for name in names.into_iter() {
+            lines.push(format!(
+                "  {} = {} (constant)",
+                name, env.contract_context.variables[name]
+            ));
+        }
+
+        let mut names: Vec<&ClarityName> = env.contract_context.meta_data_var.keys().collect();
+        names.sort();
+        for name in names.into_iter() {
+            let metadata = &env.contract_context.meta_data_var[name];
+            let value =
+                match env
+                    .global_context
+                    .database
+                    .lookup_variable(&contract_id, name, metadata)
+                {
+                    Ok(value) => format!("{}", value),
+                    Err(e) => format!("<failed to load: {}>", e),
+                };
+            lines.push(format!("  {} = {}", name, value));
+        }
+
+        if lines.len() == 0 {
+            self.print(&format!("No constants or data vars in {}.", contract_id));
+        } else {
+            for line in lines.iter() {
+                self.print(line);
+            }
+        }
+    }
+
+    fn print_backtrace(&mut self, env: &Environment, expr: &SymbolicExpression) {
+        self.print(&format!(
+            "  at {}:{}: {}",
+            env.contract_context.contract_identifier,
+            expr.start_line(),
+            expr
+        ));
+        let frames: Vec<String> = env
+            .call_stack
+            .frames()
+            .iter()
+            .rev()
+            .map(|frame| format!("  in {}", frame))
+            .collect();
+        for frame in frames.iter() {
+            self.print(frame);
+        }
+    }
+
+    /// Read and run commands until one of them resumes evaluation.
+    fn pause(&mut self, env: &mut Environment, context: &LocalContext, expr: &SymbolicExpression) {
+        loop {
+            let _ = write!(self.output, "(debug) ");
+            let _ = self.output.flush();
+
+            let line = match (self.input)() {
+                Some(line) => line,
+                None => {
+                    // no more input, so just run to completion
+                    self.breakpoints.clear();
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+            };
+
+            let mut parts = line.split_whitespace();
+            let command = match parts.next() {
+                Some(command) => command,
+                None => continue,
+            };
+            match command {
+                "s" | "step" => {
+                    self.mode = StepMode::StepIn;
+                    return;
+                }
+                "n" | "next" => {
+                    self.mode = StepMode::StepOver(self.depth);
+                    return;
+                }
+                "f" | "finish" => {
+                    self.mode = StepMode::StepOut(self.depth);
+                    return;
+                }
+                "c" | "continue" => {
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+                "l" | "locals" => self.print_locals(context),
+                "v" | "vars" => self.print_data_vars(env),
+                "bt" | "backtrace" => self.print_backtrace(env, expr),
+                "b" | "break" => match parts.next() {
+                    Some(spec) => match self.add_breakpoint_spec(spec) {
+                        Ok(_) => {
+                            let msg = format!(
+                                "Set breakpoint {}",
+                                self.breakpoints
+                                    .last()
+                                    .expect("FATAL: breakpoint not added")
+                            );
+                            self.print(&msg);
+                        }
+                        Err(e) => self.print(&e),
+                    },
+                    None => self.print("Usage: break [CONTRACT:]LINE"),
+                },
+                "d" | "delete" => match parts.next().and_then(|id| id.parse::<usize>().ok()) {
+                    Some(id) => {
+                        if self.delete_breakpoint(id) {
+                            self.print(&format!("Deleted breakpoint #{}", id));
+                        } else {
+                            self.print(&format!("No such breakpoint #{}", id));
+                        }
+                    }
+                    None => self.print("Usage: delete ID"),
+                },
+                "i" | "breakpoints" => {
+                    let lines: Vec<String> = self
+                        .breakpoints
+                        .iter()
+                        .map(|bp| format!("  {}", bp))
+                        .collect();
+                    if lines.len() == 0 {
+                        self.print("No breakpoints.");
+                    }
+                    for line in lines.iter() {
+                        self.print(line);
+                    }
+                }
+                "h" | "help" => self.print(DEBUGGER_HELP),
+                _ => self.print(&format!(
+                    "Unknown command '{}'. Type 'help' for a list of commands.",
+                    command
+                )),
+            }
+        }
+    }
+}
+
+impl EvalHook for Debugger {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        self.unwinding = false;
+
+        let contract = &env.contract_context.contract_identifier;
+        let line = expr.start_line();
+        let entered_line = match self.last_location {
+            Some((ref last_contract, last_line)) => last_contract != contract || last_line != line,
+            None => true,
+        };
+        let at_breakpoint =
+            entered_line && self.breakpoints.iter().any(|bp| bp.matches(contract, line));
+        self.last_location = Some((contract.clone(), line));
+
+        let stepped = match self.mode {
+            StepMode::Continue => false,
+            StepMode::StepIn => true,
+            StepMode::StepOver(depth) => self.depth <= depth,
+            StepMode::StepOut(depth) => self.depth < depth,
+        };
+
+        if at_breakpoint || stepped {
+            let msg = format!(
+                "{} {}:{}: {}",
+                if stepped {
+                    "Paused at"
+                } else {
+                    "Breakpoint at"
+                },
+                env.contract_context.contract_identifier,
+                line,
+                expr
+            );
+            self.print(&msg);
+            self.pause(env, context, expr);
+        }
+
+        self.depth += 1;
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+        res: &Result<Value>,
+    ) {
+        self.depth -= 1;
+
+        match res {
+            // short returns are control flow, not failures
+            Err(Error::ShortReturn(_)) | Ok(_) => {}
+            Err(e) => {
+                if !self.unwinding {
+                    self.unwinding = true;
+                    let msg = format!(
+                        "Error at {}:{}: {}\n  {}",
+                        env.contract_context.contract_identifier,
+                        expr.start_line(),
+                        expr,
+                        e
+                    );
+                    self.print(&msg);
+                    self.pause(env, context, expr);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clarity_vm::database::MemoryBackingStore;
+    use core::StacksEpochId;
+    use std::collections::VecDeque;
+    use vm::contexts::OwnedEnvironment;
+    use vm::types::PrincipalData;
+
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn scripted_debugger(commands: &[&str]) -> (Rc<RefCell<Debugger>>, Rc<RefCell<Vec<u8>>>) {
+        let mut commands: VecDeque<String> = commands.iter().map(|c| c.to_string()).collect();
+        let output = Rc::new(RefCell::new(vec![]));
+        let debugger = Debugger::new(
+            Box::new(move || commands.pop_front()),
+            Box::new(SharedOutput(output.clone())),
+        );
+        (Rc::new(RefCell::new(debugger)), output)
+    }
+
+    const CONTRACT: &str = "(define-data-var counter int 7)
+(define-private (inner (x int))
+  (begin
+    (var-set counter x)
+    (/ x (- x x))))
+(define-public (outer (y int))
+  (ok (inner (+ y 1))))";
+
+    #[test]
+    fn test_debugger_breakpoints_and_errors() {
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env =
+            OwnedEnvironment::new_free(false, marf.as_clarity_db(), StacksEpochId::Epoch2_05);
+        let contract_id = QualifiedContractIdentifier::local("debugged").unwrap();
+        owned_env
+            .initialize_contract(contract_id.clone(), CONTRACT)
+            .unwrap();
+
+        let (debugger, output) = scripted_debugger(&[
+            "locals", "vars", "bt", "step", "continue", "locals", "continue",
+        ]);
+        debugger
+            .borrow_mut()
+            .add_breakpoint_spec("debugged:4")
+            .unwrap();
+        owned_env.set_eval_hook(Box::new(debugger.clone()));
+
+        let sender = PrincipalData::from(contract_id.issuer.clone());
+        let res = owned_env.execute_transaction(
+            sender,
+            contract_id.clone(),
+            "outer",
+            &[SymbolicExpression::atom_value(Value::Int(2))],
+        );
+        assert!(res.is_err());
+        owned_env.take_eval_hook().unwrap();
+
+        let output = String::from_utf8(output.borrow().clone()).unwrap();
+        let breakpoint_at = output.find("Breakpoint at S1G2081040G2081040G2081040G208105NK8PE5.debugged:4: ( var-set counter x )").unwrap();
+        let local_x = output.find("  x = 3").unwrap();
+        let data_var = output.find("  counter = 7").unwrap();
+        let backtrace = output
+            .find("  in S1G2081040G2081040G2081040G208105NK8PE5.debugged:inner")
+            .unwrap();
+        let stepped = output
+            .find("Paused at S1G2081040G2081040G2081040G208105NK8PE5.debugged:4: x")
+            .unwrap();
+        let error_at = output
+            .find("Error at S1G2081040G2081040G2081040G208105NK8PE5.debugged:5: ( / x ( - x x ) )")
+            .unwrap();
+
+        assert!(breakpoint_at < local_x);
+        assert!(local_x < data_var);
+        assert!(data_var < backtrace);
+        assert!(backtrace < stepped);
+        assert!(stepped < error_at);
+        // the error is only reported where it originated
+        assert_eq!(output.matches("Error at").count(), 1);
+    }
+
+    #[test]
+    fn test_debugger_step_over_and_out() {
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env =
+            OwnedEnvironment::new_free(false, marf.as_clarity_db(), StacksEpochId::Epoch2_05);
+
+        let (debugger, output) = scripted_debugger(&["step", "next", "step", "finish", "continue"]);
+        debugger.borrow_mut().step_into_next();
+        owned_env.set_eval_hook(Box::new(debugger.clone()));
+
+        let (value, _, _) = owned_env
+            .eval_raw("(+ (* 2 3)\n (- 10 (* 2 2))\n 1)")
+            .unwrap();
+        assert_eq!(value, Value::Int(13));
+
+        let output = String::from_utf8(output.borrow().clone()).unwrap();
+        let pauses: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.split(": ").nth(1))
+            .collect();
+        assert_eq!(
+            pauses,
+            vec![
+                "( + ( * 2 3 ) ( - 10 ( * 2 2 ) ) 1 )",
+                // step into the first argument
+                "( * 2 3 )",
+                // step over (* 2 3)'s arguments
+                "( - 10 ( * 2 2 ) )",
+                "10",
+                // step out of (- ...)
+                "1",
+            ]
+        );
+    }
+}
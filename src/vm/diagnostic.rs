@@ -17,11 +17,12 @@
 use std::fmt;
 use vm::representations::Span;
 
-/// In a near future, we can go further in our static analysis and provide different levels
-/// of diagnostics, such as warnings, hints, best practices, etc.
+/// Errors are reported by the analysis passes that reject a contract. Warnings are
+/// reported by opt-in passes, like the linter, that never reject a contract.
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub enum Level {
     Error,
+    Warning,
 }
 
 pub trait DiagnosableError {
@@ -47,6 +48,15 @@ impl Diagnostic {
         }
     }
 
+    pub fn warning(error: &dyn DiagnosableError) -> Diagnostic {
+        Diagnostic {
+            spans: vec![],
+            level: Level::Warning,
+            message: error.message(),
+            suggestion: error.suggestion(),
+        }
+    }
+
     pub fn add_span(&mut self, start_line: u32, start_column: u32, end_line: u32, end_column: u32) {
         self.spans.push(Span {
             start_line,
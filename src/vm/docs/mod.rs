@@ -25,27 +25,27 @@ use vm::variables::NativeVariables;
 pub mod contracts;
 
 #[derive(Serialize)]
-struct ReferenceAPIs {
-    functions: Vec<FunctionAPI>,
-    keywords: Vec<KeywordAPI>,
+pub struct ReferenceAPIs {
+    pub functions: Vec<FunctionAPI>,
+    pub keywords: Vec<KeywordAPI>,
 }
 
 #[derive(Serialize, Clone)]
-struct KeywordAPI {
-    name: &'static str,
-    output_type: &'static str,
-    description: &'static str,
-    example: &'static str,
+pub struct KeywordAPI {
+    pub name: &'static str,
+    pub output_type: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
 }
 
 #[derive(Serialize)]
-struct FunctionAPI {
-    name: String,
-    input_type: String,
-    output_type: String,
-    signature: String,
-    description: String,
-    example: String,
+pub struct FunctionAPI {
+    pub name: String,
+    pub input_type: String,
+    pub output_type: String,
+    pub signature: String,
+    pub description: String,
+    pub example: String,
 }
 
 struct SimpleFunctionAPI {
@@ -1616,7 +1616,7 @@ one of the following error codes:
 "
 };
 
-fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
+pub fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
     use vm::functions::NativeFunctions::*;
     let name = function.get_name();
     match function {
@@ -1705,7 +1705,7 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
     }
 }
 
-fn make_keyword_reference(variable: &NativeVariables) -> Option<KeywordAPI> {
+pub fn make_keyword_reference(variable: &NativeVariables) -> Option<KeywordAPI> {
     match variable {
         NativeVariables::TxSender => Some(TX_SENDER_KEYWORD.clone()),
         NativeVariables::ContractCaller => Some(CONTRACT_CALLER_KEYWORD.clone()),
@@ -1741,7 +1741,7 @@ fn make_for_define(api: &DefineAPI, name: String) -> FunctionAPI {
     }
 }
 
-fn make_define_reference(define_type: &DefineFunctions) -> FunctionAPI {
+pub fn make_define_reference(define_type: &DefineFunctions) -> FunctionAPI {
     use vm::functions::define::DefineFunctions::*;
     let name = define_type.get_name();
     match define_type {
@@ -1759,7 +1759,7 @@ fn make_define_reference(define_type: &DefineFunctions) -> FunctionAPI {
     }
 }
 
-fn make_all_api_reference() -> ReferenceAPIs {
+pub fn make_all_api_reference() -> ReferenceAPIs {
     let mut functions: Vec<_> = NativeFunctions::ALL
         .iter()
         .map(|x| make_api_reference(x))
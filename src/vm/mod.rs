@@ -39,6 +39,7 @@ pub mod analysis;
 pub mod docs;
 
 pub mod coverage;
+pub mod debug;
 
 #[cfg(test)]
 pub mod tests;
@@ -66,7 +67,10 @@ pub use vm::representations::{
 use std::convert::{TryFrom, TryInto};
 pub use vm::contexts::MAX_CONTEXT_DEPTH;
 use vm::costs::cost_functions::ClarityCostFunction;
+pub use vm::functions::define::DefineFunctions;
 pub use vm::functions::stx_transfer_consolidated;
+pub use vm::functions::NativeFunctions;
+pub use vm::variables::NativeVariables;
 
 const MAX_CALL_STACK_DEPTH: usize = 64;
 
@@ -215,14 +219,44 @@ pub fn eval<'a>(
     env: &'a mut Environment,
     context: &LocalContext,
 ) -> Result<Value> {
-    use vm::representations::SymbolicExpressionType::{
-        Atom, AtomValue, Field, List, LiteralValue, TraitReference,
-    };
-
     if let Some(ref mut coverage_tracker) = env.global_context.coverage_reporting {
         coverage_tracker.report_eval(exp, &env.contract_context.contract_identifier);
     }
 
+    // the hook is taken out of the global context while it runs, so that it
+    //  can be handed the environment mutably.
+    if let Some(mut eval_hook) = env.global_context.eval_hook.take() {
+        eval_hook.will_begin_eval(env, context, exp);
+        env.global_context.eval_hook = Some(eval_hook);
+    }
+
+    if let Some(ref mut profiler) = env.global_context.cost_profiler {
+        profiler.enter_expression(exp, &env.contract_context.contract_identifier);
+    }
+
+    let res = eval_expression(exp, env, context);
+
+    if let Some(ref mut profiler) = env.global_context.cost_profiler {
+        profiler.exit_expression();
+    }
+
+    if let Some(mut eval_hook) = env.global_context.eval_hook.take() {
+        eval_hook.did_finish_eval(env, context, exp, &res);
+        env.global_context.eval_hook = Some(eval_hook);
+    }
+
+    res
+}
+
+fn eval_expression(
+    exp: &SymbolicExpression,
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    use vm::representations::SymbolicExpressionType::{
+        Atom, AtomValue, Field, List, LiteralValue, TraitReference,
+    };
+
     match exp.expr {
         AtomValue(ref value) | LiteralValue(ref value) => Ok(value.clone()),
         Atom(ref value) => lookup_variable(&value, context, env),
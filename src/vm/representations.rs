@@ -108,6 +108,8 @@ pub enum PreSymbolicExpressionType {
     SugaredFieldIdentifier(ContractName, ClarityName),
     FieldIdentifier(TraitIdentifier),
     TraitReference(ClarityName),
+    /// Only produced by `parser::parse_with_comments`, for tools that work on source code.
+    Comment(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -235,6 +237,13 @@ impl PreSymbolicExpression {
         }
     }
 
+    pub fn comment(text: String) -> PreSymbolicExpression {
+        PreSymbolicExpression {
+            pre_expr: PreSymbolicExpressionType::Comment(text),
+            ..PreSymbolicExpression::cons()
+        }
+    }
+
     pub fn list(val: Box<[PreSymbolicExpression]>) -> PreSymbolicExpression {
         PreSymbolicExpression {
             pre_expr: PreSymbolicExpressionType::List(val),
@@ -288,6 +297,14 @@ impl PreSymbolicExpression {
             None
         }
     }
+
+    pub fn match_comment(&self) -> Option<&str> {
+        if let PreSymbolicExpressionType::Comment(ref value) = self.pre_expr {
+            Some(value)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -377,6 +394,17 @@ impl SymbolicExpression {
     ) {
     }
 
+    /// The line this expression starts on, or 0 if spans are not tracked.
+    #[cfg(feature = "developer-mode")]
+    pub fn start_line(&self) -> u32 {
+        self.span.start_line
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn start_line(&self) -> u32 {
+        0
+    }
+
     pub fn atom_value(val: Value) -> SymbolicExpression {
         SymbolicExpression {
             expr: SymbolicExpressionType::AtomValue(val),
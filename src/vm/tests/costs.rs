@@ -31,6 +31,7 @@ use vm::contexts::Environment;
 use vm::contexts::{AssetMap, AssetMapEntry, GlobalContext, OwnedEnvironment};
 use vm::contracts::Contract;
 use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::profiler::CostProfiler;
 use vm::costs::{ClarityCostFunctionReference, ExecutionCost, LimitedCostTracker};
 use vm::database::ClarityDatabase;
 use vm::errors::{CheckErrors, Error, RuntimeErrorType};
@@ -1534,3 +1535,97 @@ fn test_cost_voting_integration(use_mainnet: bool) {
 fn test_cost_voting_integration_testnet() {
     test_cost_voting_integration(false)
 }
+
+#[test]
+fn test_cost_profiler() {
+    let contract = "(define-data-var total int 0)
+(define-private (square (x int))
+  (* x x))
+(define-public (add-squares (a int) (b int))
+  (begin
+    (var-set total (+ (square a) (square b)))
+    (ok (var-get total))))";
+
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+    let p1_principal = match p1 {
+        Value::Principal(PrincipalData::Standard(ref data)) => data.clone(),
+        _ => panic!(),
+    };
+    let contract_id = QualifiedContractIdentifier::new(p1_principal.clone(), "profiled".into());
+    let add_squares = format!("{}:add-squares", &contract_id);
+    let square = format!("{}:square", &contract_id);
+
+    with_owned_env(StacksEpochId::Epoch2_05, false, |mut owned_env| {
+        owned_env
+            .initialize_contract(contract_id.clone(), contract)
+            .unwrap();
+
+        let cost_before = owned_env.get_cost_total();
+        owned_env.set_cost_profiler(CostProfiler::new());
+        execute_transaction(
+            &mut owned_env,
+            p1_principal.clone().into(),
+            &contract_id,
+            "add-squares",
+            &symbols_from_values(vec![Value::Int(3), Value::Int(4)]),
+        )
+        .unwrap();
+        let profiler = owned_env.take_cost_profiler().unwrap();
+
+        // every cost charged to the transaction was profiled
+        let mut cost_after = owned_env.get_cost_total();
+        cost_after.sub(&cost_before).unwrap();
+        assert_eq!(profiler.get_total(), &cost_after);
+
+        let report = profiler.report();
+        assert_eq!(report.total, cost_after);
+
+        let mul = report
+            .cost_functions
+            .iter()
+            .find(|profile| profile.name == "cost_mul")
+            .unwrap();
+        assert_eq!(mul.invocations, 2);
+
+        let square_profile = report
+            .functions
+            .iter()
+            .find(|profile| profile.function == square)
+            .unwrap();
+        let add_squares_profile = report
+            .functions
+            .iter()
+            .find(|profile| profile.function == add_squares)
+            .unwrap();
+        assert_eq!(square_profile.self_cost, square_profile.total_cost);
+        assert!(square_profile.self_cost.runtime >= mul.cost.runtime);
+        assert!(add_squares_profile
+            .total_cost
+            .exceeds(&add_squares_profile.self_cost));
+        assert_eq!(
+            add_squares_profile.total_cost.runtime,
+            add_squares_profile.self_cost.runtime + square_profile.total_cost.runtime
+        );
+
+        // the var-set on line 6 is charged to that line
+        let var_set_line = report
+            .lines
+            .iter()
+            .find(|profile| profile.line == 6)
+            .unwrap();
+        assert_eq!(var_set_line.contract, contract_id.to_string());
+        assert!(var_set_line.cost.write_count > 0);
+
+        let folded = profiler.to_folded("runtime").unwrap();
+        assert!(folded
+            .lines()
+            .any(|line| line.starts_with(&format!("{};{};cost_mul ", &add_squares, &square))));
+        let runtime_total: u64 = folded
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap().parse::<u64>().unwrap())
+            .sum();
+        assert_eq!(runtime_total, cost_after.runtime);
+
+        assert!(profiler.to_folded("no-such-dimension").is_none());
+    });
+}
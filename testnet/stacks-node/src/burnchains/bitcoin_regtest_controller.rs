@@ -23,6 +23,7 @@ use stacks::burnchains::bitcoin::indexer::{
     BitcoinIndexer, BitcoinIndexerConfig, BitcoinIndexerRuntime,
 };
 use stacks::burnchains::bitcoin::spv::SpvClient;
+use stacks::burnchains::bitcoin::wallet::{BitcoinWallet, WalletUTXO};
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::db::BurnchainDB;
 use stacks::burnchains::indexer::BurnchainIndexer;
@@ -65,6 +66,14 @@ use stacks::types::chainstate::BurnchainHeaderHash;
 const UTXO_CACHE_STALENESS_LIMIT: u64 = 6;
 const DUST_UTXO_LIMIT: u64 = 5500;
 
+/// Internal wallet metadata key under which the ongoing block-commit is saved, so that its chain
+/// of RBF replacements can be continued after a restart.
+const ONGOING_BLOCK_COMMIT_KEY: &'static str = "ongoing_block_commit";
+
+/// The most blocks the internal wallet downloads and scans each time the miner asks it for
+/// UTXOs, so that a rescan never holds up building a block-commit for long.
+const WALLET_MAX_SYNC_BLOCKS: u64 = 10;
+
 pub struct BitcoinRegtestController {
     config: Config,
     indexer_config: BitcoinIndexerConfig,
@@ -75,6 +84,7 @@ pub struct BitcoinRegtestController {
     burnchain_config: Option<Burnchain>,
     ongoing_block_commit: Option<OngoingBlockCommit>,
     should_keep_running: Option<Arc<AtomicBool>>,
+    wallet: Option<BitcoinWallet>,
 }
 
 struct OngoingBlockCommit {
@@ -90,7 +100,17 @@ impl OngoingBlockCommit {
     }
 }
 
-#[derive(Clone)]
+/// What the internal wallet remembers about the ongoing block-commit.  Its UTXOs are the
+/// wallet's outputs spent by the last transaction in `txids`.
+#[derive(Serialize, Deserialize)]
+struct SavedBlockCommit {
+    payload: LeaderBlockCommitOp,
+    fees: LeaderBlockCommitFees,
+    txids: Vec<Txid>,
+    utxos_bhh: BurnchainHeaderHash,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct LeaderBlockCommitFees {
     sunset_fee: u64,
     fee_rate: u64,
@@ -252,6 +272,7 @@ impl BitcoinRegtestController {
             burnchain_config,
             ongoing_block_commit: None,
             should_keep_running,
+            wallet: None,
         }
     }
 
@@ -289,6 +310,7 @@ impl BitcoinRegtestController {
             burnchain_config: None,
             ongoing_block_commit: None,
             should_keep_running: None,
+            wallet: None,
         }
     }
 
@@ -685,6 +707,296 @@ impl BitcoinRegtestController {
         Some(utxos)
     }
 
+    /// Open the internal wallet and make sure it tracks the given public key's address.  If the
+    /// node stopped while RBF'ing a block-commit, pick the commit back up.
+    fn open_wallet(&mut self, public_key: &Secp256k1PublicKey) -> Option<()> {
+        if self.wallet.is_some() {
+            return Some(());
+        }
+
+        let (_, network_id) = self.config.burnchain.get_bitcoin_network();
+        let wallet_path = self.config.get_wallet_db_path();
+
+        // a new wallet starts scanning at the burnchain tip, unless told otherwise
+        let headers_height = match SpvClient::new(
+            &self.indexer_config.spv_headers_path,
+            0,
+            None,
+            network_id,
+            false,
+            false,
+        )
+        .and_then(|spv_client| spv_client.get_highest_header_height())
+        {
+            Ok(height) => height,
+            Err(e) => {
+                error!("Failed to read burnchain headers for wallet: {:?}", &e);
+                return None;
+            }
+        };
+
+        let mut wallet = match BitcoinWallet::new(&wallet_path, network_id, headers_height, true) {
+            Ok(wallet) => wallet,
+            Err(e) => {
+                error!("Failed to open wallet {}: {:?}", &wallet_path, &e);
+                return None;
+            }
+        };
+        if let Some(birth_height) = self.config.burnchain.wallet_birth_height {
+            if birth_height != wallet.birth_height {
+                if let Err(e) = wallet.set_birth_height(birth_height) {
+                    error!("Failed to set wallet birth height: {:?}", &e);
+                    return None;
+                }
+            }
+        }
+        info!(
+            "Internal wallet {} tracks UTXOs from burnchain height {}",
+            &wallet_path, wallet.birth_height
+        );
+
+        let pkh = Hash160::from_data(&public_key.to_bytes());
+        if let Err(e) = wallet.track_address(&pkh) {
+            error!("Failed to track {} in wallet: {:?}", &pkh, &e);
+            return None;
+        }
+
+        if self.ongoing_block_commit.is_none() {
+            self.ongoing_block_commit =
+                BitcoinRegtestController::load_ongoing_block_commit(&wallet);
+        }
+
+        self.wallet = Some(wallet);
+        Some(())
+    }
+
+    fn wallet_utxo_to_utxo(utxo: &WalletUTXO, tip_height: u64) -> UTXO {
+        UTXO {
+            txid: utxo.txid.clone(),
+            vout: utxo.vout,
+            script_pub_key: utxo.script_pubkey.clone(),
+            amount: utxo.amount,
+            confirmations: utxo.confirmations(tip_height),
+        }
+    }
+
+    fn txid_to_sha256d(txid: &Txid) -> Sha256dHash {
+        let mut bytes = txid.as_bytes().to_vec();
+        bytes.reverse();
+        Sha256dHash::from(&bytes[..])
+    }
+
+    /// Restore the block-commit that was ongoing when the node stopped, if its last attempt is
+    /// still one of the wallet's pending transactions.
+    fn load_ongoing_block_commit(wallet: &BitcoinWallet) -> Option<OngoingBlockCommit> {
+        let saved_json = match wallet.get_metadata(ONGOING_BLOCK_COMMIT_KEY) {
+            Ok(Some(saved_json)) => saved_json,
+            Ok(None) => {
+                return None;
+            }
+            Err(e) => {
+                warn!("Failed to load ongoing block-commit: {:?}", &e);
+                return None;
+            }
+        };
+        let saved: SavedBlockCommit = match serde_json::from_str(&saved_json) {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!("Failed to parse ongoing block-commit: {:?}", &e);
+                return None;
+            }
+        };
+
+        let last_txid = saved.txids.last()?;
+        let tip_height = wallet.get_scanned_height().ok()?.unwrap_or(0);
+        let utxos: Vec<UTXO> = wallet
+            .get_utxos_spent_by(&BitcoinRegtestController::txid_to_sha256d(last_txid))
+            .ok()?
+            .iter()
+            .map(|utxo| BitcoinRegtestController::wallet_utxo_to_utxo(utxo, tip_height))
+            .collect();
+
+        if utxos.is_empty() {
+            debug!(
+                "Saved block-commit {} is no longer pending",
+                last_txid.to_hex()
+            );
+            return None;
+        }
+
+        info!(
+            "Resuming ongoing leader_block_commit (txid: {}, attempts: {})",
+            last_txid.to_hex(),
+            saved.txids.len()
+        );
+        Some(OngoingBlockCommit {
+            payload: saved.payload,
+            utxos: UTXOSet {
+                bhh: saved.utxos_bhh,
+                utxos,
+                spend_all: true,
+            },
+            fees: saved.fees,
+            txids: saved.txids,
+        })
+    }
+
+    fn save_ongoing_block_commit(&mut self) {
+        let saved_json = match self.ongoing_block_commit {
+            Some(ref ongoing_op) => {
+                let saved = SavedBlockCommit {
+                    payload: ongoing_op.payload.clone(),
+                    fees: ongoing_op.fees.clone(),
+                    txids: ongoing_op.txids.clone(),
+                    utxos_bhh: ongoing_op.utxos.bhh.clone(),
+                };
+                serde_json::to_string(&saved).expect("FATAL: failed to serialize block-commit")
+            }
+            None => {
+                return;
+            }
+        };
+
+        if let Some(ref mut wallet) = self.wallet {
+            if let Err(e) = wallet.put_metadata(ONGOING_BLOCK_COMMIT_KEY, &saved_json) {
+                warn!("Failed to save ongoing block-commit: {:?}", &e);
+            }
+        }
+    }
+
+    /// Bring the internal wallet all the way up to date with the downloaded headers, and get its
+    /// balance.  This can take a while if the wallet's birth height is far back, so it is meant
+    /// to run once at startup, before the miner asks the wallet for UTXOs.
+    pub fn sync_wallet_balance(&mut self, public_key: &Secp256k1PublicKey) -> Option<u64> {
+        self.open_wallet(public_key)?;
+
+        let (_, indexer) = self.setup_indexer_runtime();
+        let wallet = self.wallet.as_mut().expect("BUG: wallet not opened");
+        if let Err(e) = wallet.sync(&indexer, None) {
+            error!(
+                "Failed to sync wallet from burnchain height {}: {:?}. If bitcoind is pruned, set burnchain.wallet_birth_height above its prune height.",
+                wallet.birth_height, &e
+            );
+            return None;
+        }
+        match wallet.get_balance() {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                error!("Failed to get wallet balance: {:?}", &e);
+                None
+            }
+        }
+    }
+
+    /// Find UTXOs worth at least `total_required` with the internal wallet, after bringing it up
+    /// to date with the downloaded headers.  The wallet only scans a few blocks per call; while
+    /// it is still catching up, no UTXOs are returned.
+    fn get_wallet_utxos(
+        &mut self,
+        public_key: &Secp256k1PublicKey,
+        total_required: u64,
+        utxos_to_exclude: Option<UTXOSet>,
+    ) -> Option<UTXOSet> {
+        // if mock mining, do not even both requesting UTXOs
+        if self.config.node.mock_mining {
+            return None;
+        }
+
+        self.open_wallet(public_key)?;
+
+        let (_, indexer) = self.setup_indexer_runtime();
+        let fee_rate = self.config.burnchain.satoshis_per_byte;
+        let wallet = self.wallet.as_mut().expect("BUG: wallet not opened");
+
+        let (scanned_height, tip_height) = match wallet.sync(&indexer, Some(WALLET_MAX_SYNC_BLOCKS))
+        {
+            Ok(heights) => heights,
+            Err(e) => {
+                error!(
+                        "Failed to sync wallet from burnchain height {}: {:?}. If bitcoind is pruned, set burnchain.wallet_birth_height above its prune height.",
+                        wallet.birth_height, &e
+                    );
+                return None;
+            }
+        };
+        if scanned_height < tip_height {
+            info!(
+                "Internal wallet has scanned up to burnchain height {} of {}; will not spend until it catches up",
+                scanned_height, tip_height
+            );
+            return None;
+        }
+        let bhh = match wallet.get_scanned_block_hash(scanned_height) {
+            Ok(Some(block_hash)) => BurnchainHeaderHash::from_bitcoin_hash(&block_hash),
+            _ => BurnchainHeaderHash::zero(),
+        };
+
+        let exclude: Vec<(Sha256dHash, u32)> = match utxos_to_exclude {
+            Some(utxos_to_exclude) => utxos_to_exclude
+                .utxos
+                .iter()
+                .map(|utxo| (utxo.txid.clone(), utxo.vout))
+                .collect(),
+            None => vec![],
+        };
+
+        let selected = match wallet.select_utxos(total_required, fee_rate, &exclude) {
+            Ok(Some(selected)) => selected,
+            Ok(None) => {
+                warn!(
+                    "Total unspent {} < {} for {:?}",
+                    wallet.get_balance().unwrap_or(0),
+                    total_required,
+                    &public_key.to_hex()
+                );
+                return None;
+            }
+            Err(e) => {
+                error!("Failed to select UTXOs from wallet: {:?}", &e);
+                return None;
+            }
+        };
+
+        Some(UTXOSet {
+            bhh,
+            utxos: selected
+                .iter()
+                .map(|utxo| BitcoinRegtestController::wallet_utxo_to_utxo(utxo, tip_height))
+                .collect(),
+            spend_all: true,
+        })
+    }
+
+    /// Tell the internal wallet about a transaction we just broadcast.  A block-commit replaces
+    /// the previous attempt of the ongoing commit, if there is one.
+    fn record_pending_tx(&mut self, transaction: &Transaction, is_block_commit: bool) {
+        let replaces = if is_block_commit {
+            match self.ongoing_block_commit {
+                Some(ref ongoing_op) if ongoing_op.txids.len() >= 2 => {
+                    let replaced_txid = &ongoing_op.txids[ongoing_op.txids.len() - 2];
+                    Some(BitcoinRegtestController::txid_to_sha256d(replaced_txid))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        if let Some(ref mut wallet) = self.wallet {
+            if let Err(e) = wallet.record_pending_tx(transaction, replaces.as_ref()) {
+                warn!(
+                    "Failed to record transaction {} in wallet: {:?}",
+                    transaction.txid().be_hex_string(),
+                    &e
+                );
+            }
+        }
+
+        if is_block_commit {
+            self.save_ongoing_block_commit();
+        }
+    }
+
     fn build_leader_key_register_tx(
         &mut self,
         payload: LeaderKeyRegisterOp,
@@ -816,6 +1128,7 @@ impl BitcoinRegtestController {
                 UTXOSet {
                     bhh: BurnchainHeaderHash::zero(),
                     utxos: vec![utxo],
+                    spend_all: false,
                 },
             )
         } else {
@@ -1147,6 +1460,14 @@ impl BitcoinRegtestController {
         let utxos = if let Some(utxos) = utxos_to_include {
             // in RBF, you have to consume the same UTXOs
             utxos
+        } else if self.config.burnchain.use_internal_wallet {
+            match self.get_wallet_utxos(&public_key, total_required, utxos_to_exclude) {
+                Some(utxos) => utxos,
+                None => {
+                    debug!("No UTXOs for {}", &public_key.to_hex());
+                    return None;
+                }
+            }
         } else {
             // Fetch some UTXOs
             let utxos =
@@ -1239,14 +1560,15 @@ impl BitcoinRegtestController {
         let public_key = signer.get_public_key();
         let mut total_consumed = 0;
 
-        // select UTXOs until we have enough to cover the cost
+        // select UTXOs until we have enough to cover the cost (or spend them all, if they were
+        // chosen to consolidate them)
         let mut available_utxos = vec![];
         available_utxos.append(&mut utxos_set.utxos);
         for utxo in available_utxos.into_iter() {
             total_consumed += utxo.amount;
             utxos_set.utxos.push(utxo);
 
-            if total_consumed >= total_to_spend {
+            if total_consumed >= total_to_spend && !utxos_set.spend_all {
                 break;
             }
         }
@@ -1470,6 +1792,17 @@ impl BurnchainController for BitcoinRegtestController {
         }
     }
 
+    fn get_burnchain(&self) -> Burnchain {
+        BitcoinRegtestController::get_burnchain(self)
+    }
+
+    fn wait_for_sortitions(
+        &self,
+        height_to_wait: Option<u64>,
+    ) -> Result<BurnchainTip, BurnchainControllerError> {
+        BitcoinRegtestController::wait_for_sortitions(self, height_to_wait)
+    }
+
     fn connect_dbs(&mut self) -> Result<(), BurnchainControllerError> {
         let (burnchain, burnchain_indexer) = self.setup_indexer_runtime();
         burnchain.connect_db(
@@ -1532,6 +1865,15 @@ impl BurnchainController for BitcoinRegtestController {
         op_signer: &mut BurnchainOpSigner,
         attempt: u64,
     ) -> bool {
+        if self.config.burnchain.use_internal_wallet {
+            self.open_wallet(&op_signer.get_public_key());
+        }
+
+        let is_block_commit = match operation {
+            BlockstackOperationType::LeaderBlockCommit(_) => true,
+            _ => false,
+        };
+
         let transaction = match operation {
             BlockstackOperationType::LeaderBlockCommit(payload) => {
                 self.build_leader_block_commit_tx(payload, op_signer, attempt)
@@ -1552,11 +1894,18 @@ impl BurnchainController for BitcoinRegtestController {
         };
 
         let transaction = match transaction {
-            Some(tx) => SerializedTx::new(tx),
+            Some(tx) => tx,
             _ => return false,
         };
 
-        self.send_transaction(transaction)
+        if !self.send_transaction(SerializedTx::new(transaction.clone())) {
+            return false;
+        }
+
+        if self.config.burnchain.use_internal_wallet {
+            self.record_pending_tx(&transaction, is_block_commit);
+        }
+        true
     }
 
     #[cfg(test)]
@@ -1597,6 +1946,8 @@ impl BurnchainController for BitcoinRegtestController {
 pub struct UTXOSet {
     bhh: BurnchainHeaderHash,
     utxos: Vec<UTXO>,
+    /// Spend every UTXO in the set, even if fewer would cover the transaction
+    spend_all: bool,
 }
 
 impl UTXOSet {
@@ -1891,7 +2242,11 @@ impl BitcoinRPCRequest {
             }
         };
 
-        Ok(UTXOSet { bhh, utxos })
+        Ok(UTXOSet {
+            bhh,
+            utxos,
+            spend_all: false,
+        })
     }
 
     pub fn send_raw_transaction(config: &Config, tx: String) -> RPCResult<()> {
@@ -86,6 +86,18 @@ impl BurnchainController for MocknetController {
         }
     }
 
+    fn get_burnchain(&self) -> Burnchain {
+        self.burnchain.clone()
+    }
+
+    /// Sortitions are evaluated as each block is mocked, so there is nothing to wait for
+    fn wait_for_sortitions(
+        &self,
+        _height_to_wait: Option<u64>,
+    ) -> Result<BurnchainTip, BurnchainControllerError> {
+        Ok(self.get_chain_tip())
+    }
+
     fn get_stacks_epochs(&self) -> Vec<StacksEpoch> {
         match &self.config.burnchain.epochs {
             Some(epochs) => epochs.clone(),
@@ -1,8 +1,10 @@
 pub mod bitcoin_regtest_controller;
 pub mod mocknet_controller;
+pub mod replay_controller;
 
 pub use self::bitcoin_regtest_controller::BitcoinRegtestController;
 pub use self::mocknet_controller::MocknetController;
+pub use self::replay_controller::ReplayController;
 
 use super::operations::BurnchainOpSigner;
 
@@ -10,6 +12,7 @@ use std::fmt;
 use std::time::Instant;
 
 use stacks::burnchains;
+use stacks::burnchains::Burnchain;
 use stacks::burnchains::BurnchainStateTransitionOps;
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::burn::operations::BlockstackOperationType;
@@ -21,6 +24,8 @@ use stacks::core::StacksEpoch;
 pub enum Error {
     CoordinatorClosed,
     IndexerError(burnchains::Error),
+    /// The burnchain replay fixture does not start at the network's first burnchain block
+    ReplayFixtureMismatch(String),
 }
 
 impl fmt::Display for Error {
@@ -28,6 +33,9 @@ impl fmt::Display for Error {
         match self {
             Error::CoordinatorClosed => write!(f, "ChainsCoordinator closed"),
             Error::IndexerError(ref e) => write!(f, "Indexer error: {:?}", e),
+            Error::ReplayFixtureMismatch(ref msg) => {
+                write!(f, "Replay fixture mismatch: {}", msg)
+            }
         }
     }
 }
@@ -51,6 +59,10 @@ pub trait BurnchainController {
     fn sortdb_ref(&self) -> &SortitionDB;
     fn sortdb_mut(&mut self) -> &mut SortitionDB;
     fn get_chain_tip(&self) -> BurnchainTip;
+    fn get_burnchain(&self) -> Burnchain;
+    /// Block until the chains coordinator has processed the sortitions for the burnchain blocks
+    ///  downloaded so far (or at least up to `height_to_wait`)
+    fn wait_for_sortitions(&self, height_to_wait: Option<u64>) -> Result<BurnchainTip, Error>;
     /// Invoke connect() on underlying burnchain and sortition databases, to perform any migration
     ///  or instantiation before other callers may use open()
     fn connect_dbs(&mut self) -> Result<(), Error>;
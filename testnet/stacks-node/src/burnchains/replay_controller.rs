@@ -0,0 +1,384 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Instant;
+
+use stacks::burnchains::bitcoin::BitcoinNetworkType;
+use stacks::burnchains::db::BurnchainDB;
+use stacks::burnchains::indexer::BurnchainIndexer;
+use stacks::burnchains::replay::ReplayIndexer;
+use stacks::burnchains::Burnchain;
+use stacks::burnchains::Error as burnchain_error;
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::burn::operations::BlockstackOperationType;
+use stacks::chainstate::coordinator::comm::CoordinatorChannels;
+use stacks::core::{
+    StacksEpoch, STACKS_EPOCHS_MAINNET, STACKS_EPOCHS_REGTEST, STACKS_EPOCHS_TESTNET,
+};
+use stacks::util::sleep_ms;
+
+use stacks::monitoring::increment_btc_blocks_received_counter;
+
+use super::super::operations::BurnchainOpSigner;
+use super::super::Config;
+use super::{BurnchainController, BurnchainTip, Error as BurnchainControllerError};
+
+/// ReplayController feeds the chains coordinator the burnchain blocks and operations stored in a
+/// replay fixture, instead of the ones reported by bitcoind.  The fixture's history is fixed, so
+/// a node booted from it reaches the same sortitions every time.  Nothing can be mined.
+pub struct ReplayController {
+    config: Config,
+    indexer: ReplayIndexer,
+    burnchain: Burnchain,
+    db: Option<SortitionDB>,
+    burnchain_db: Option<BurnchainDB>,
+    chain_tip: Option<BurnchainTip>,
+    use_coordinator: CoordinatorChannels,
+    should_keep_running: Option<Arc<AtomicBool>>,
+}
+
+impl ReplayController {
+    pub fn generic(
+        config: Config,
+        coordinator_channel: CoordinatorChannels,
+        burnchain_config: Option<Burnchain>,
+        should_keep_running: Option<Arc<AtomicBool>>,
+    ) -> Result<Box<dyn BurnchainController>, BurnchainControllerError> {
+        let controller = Self::new(
+            config,
+            coordinator_channel,
+            burnchain_config,
+            should_keep_running,
+        )?;
+        Ok(Box::new(controller))
+    }
+
+    pub fn new(
+        config: Config,
+        coordinator_channel: CoordinatorChannels,
+        burnchain_config: Option<Burnchain>,
+        should_keep_running: Option<Arc<AtomicBool>>,
+    ) -> Result<Self, BurnchainControllerError> {
+        std::fs::create_dir_all(&config.get_burnchain_path_str())
+            .expect("Unable to create workdir");
+
+        let fixture_path = config
+            .burnchain
+            .replay_fixture_path
+            .clone()
+            .expect("BUG: no burnchain replay fixture configured");
+
+        let (network_name, network_id) = config.burnchain.get_bitcoin_network();
+        let epochs = match config.burnchain.epochs {
+            Some(ref epochs) => {
+                if network_id == BitcoinNetworkType::Mainnet {
+                    panic!("It is an error to set custom epochs while running on Mainnet: network_id {:?} config.burnchain {:#?}",
+                           &network_id, &config.burnchain);
+                }
+                epochs.clone()
+            }
+            None => match network_id {
+                BitcoinNetworkType::Mainnet => STACKS_EPOCHS_MAINNET.to_vec(),
+                BitcoinNetworkType::Testnet => STACKS_EPOCHS_TESTNET.to_vec(),
+                BitcoinNetworkType::Regtest => STACKS_EPOCHS_REGTEST.to_vec(),
+            },
+        };
+
+        let indexer = match ReplayIndexer::new(&fixture_path, epochs) {
+            Ok(indexer) => indexer,
+            Err(e) => {
+                error!(
+                    "Failed to load burnchain replay fixture {}: {}",
+                    &fixture_path, e
+                );
+                panic!()
+            }
+        };
+
+        let mut burnchain = match burnchain_config {
+            Some(burnchain) => burnchain,
+            None => {
+                let working_dir = config.get_burn_db_path();
+                match Burnchain::new(&working_dir, &config.burnchain.chain, &network_name) {
+                    Ok(burnchain) => burnchain,
+                    Err(e) => {
+                        error!("Failed to instantiate burnchain: {}", e);
+                        panic!()
+                    }
+                }
+            }
+        };
+
+        // the fixture's first block is the burnchain's first block.  Only regtest-style networks
+        // (regtest and mocknet modes) may move it; mainnet and testnet fixtures must start at the
+        // network's own first block.
+        let first_block = indexer.first_block().clone();
+        if burnchain.first_block_height != first_block.block_height
+            || burnchain.first_block_hash != first_block.block_hash
+        {
+            if network_id != BitcoinNetworkType::Regtest {
+                let msg = format!(
+                    "fixture {} starts at block {} (height {}), but {} starts at block {} (height {})",
+                    &fixture_path,
+                    &first_block.block_hash,
+                    first_block.block_height,
+                    &network_name,
+                    &burnchain.first_block_hash,
+                    burnchain.first_block_height
+                );
+                return Err(BurnchainControllerError::ReplayFixtureMismatch(msg));
+            }
+            info!(
+                "Burnchain replay fixture starts at block {} (height {}) instead of {} (height {})",
+                &first_block.block_hash,
+                first_block.block_height,
+                &burnchain.first_block_hash,
+                burnchain.first_block_height
+            );
+            burnchain.first_block_height = first_block.block_height;
+            burnchain.first_block_hash = first_block.block_hash;
+            burnchain.first_block_timestamp = first_block.timestamp as u32;
+            burnchain.initial_reward_start_block = first_block.block_height;
+        }
+
+        Ok(Self {
+            config,
+            indexer,
+            burnchain,
+            db: None,
+            burnchain_db: None,
+            chain_tip: None,
+            use_coordinator: coordinator_channel,
+            should_keep_running,
+        })
+    }
+
+    fn receive_blocks(
+        &mut self,
+        block_for_sortitions: bool,
+        target_block_height_opt: Option<u64>,
+    ) -> Result<(BurnchainTip, u64), BurnchainControllerError> {
+        let coordinator_comms = self.use_coordinator.clone();
+        let mut burnchain = self.burnchain.clone();
+        let mut burnchain_indexer = self.indexer.clone();
+
+        let (block_snapshot, burnchain_height, state_transition) = loop {
+            if !self.should_keep_running() {
+                return Err(BurnchainControllerError::CoordinatorClosed);
+            }
+            match burnchain.sync_with_indexer(
+                &mut burnchain_indexer,
+                coordinator_comms.clone(),
+                target_block_height_opt,
+                Some(burnchain.pox_constants.reward_cycle_length as u64),
+                self.should_keep_running.clone(),
+            ) {
+                Ok(x) => {
+                    increment_btc_blocks_received_counter();
+
+                    // initialize the dbs...
+                    self.sortdb_mut();
+
+                    // wait for the chains coordinator to catch up with us
+                    if block_for_sortitions {
+                        self.wait_for_sortitions(Some(x.block_height))?;
+                    }
+
+                    let sort_tip =
+                        SortitionDB::get_canonical_burn_chain_tip(self.sortdb_ref().conn())
+                            .expect("Sortition DB error.");
+
+                    let (snapshot, state_transition) = self
+                        .sortdb_ref()
+                        .get_sortition_result(&sort_tip.sortition_id)
+                        .expect("Sortition DB error.")
+                        .expect("BUG: no data for the canonical chain tip");
+
+                    let burnchain_height = burnchain_indexer
+                        .get_highest_header_height()
+                        .map_err(BurnchainControllerError::IndexerError)?;
+                    break (snapshot, burnchain_height, state_transition);
+                }
+                Err(e) => {
+                    error!("Unable to replay burnchain: {}", e);
+                    match e {
+                        burnchain_error::CoordinatorClosed => {
+                            return Err(BurnchainControllerError::CoordinatorClosed)
+                        }
+                        burnchain_error::TrySyncAgain => {
+                            // try again immediately
+                            continue;
+                        }
+                        e => {
+                            // the fixture won't change, so retrying won't help
+                            return Err(BurnchainControllerError::IndexerError(e));
+                        }
+                    }
+                }
+            }
+        };
+
+        if let Some(ref chain_tip) = self.chain_tip {
+            if chain_tip.block_snapshot.block_height < burnchain_height
+                && block_snapshot.block_height == burnchain_height
+            {
+                info!(
+                    "Replayed all burnchain blocks in fixture, up to height {}",
+                    burnchain_height
+                );
+            }
+        }
+
+        let burnchain_tip = BurnchainTip {
+            block_snapshot: block_snapshot,
+            state_transition: state_transition,
+            received_at: Instant::now(),
+        };
+
+        self.chain_tip = Some(burnchain_tip.clone());
+        debug!("Done replaying blocks");
+
+        Ok((burnchain_tip, burnchain_height))
+    }
+
+    fn should_keep_running(&self) -> bool {
+        match self.should_keep_running {
+            Some(ref should_keep_running) => should_keep_running.load(Ordering::SeqCst),
+            _ => true,
+        }
+    }
+}
+
+impl BurnchainController for ReplayController {
+    fn sortdb_ref(&self) -> &SortitionDB {
+        self.db
+            .as_ref()
+            .expect("BUG: did not instantiate the burn DB")
+    }
+
+    fn sortdb_mut(&mut self) -> &mut SortitionDB {
+        let (db, burnchain_db) = self.burnchain.open_db(true).unwrap();
+        self.db = Some(db);
+        self.burnchain_db = Some(burnchain_db);
+
+        match self.db {
+            Some(ref mut sortdb) => sortdb,
+            None => unreachable!(),
+        }
+    }
+
+    fn get_chain_tip(&self) -> BurnchainTip {
+        match &self.chain_tip {
+            Some(chain_tip) => chain_tip.clone(),
+            None => {
+                unreachable!();
+            }
+        }
+    }
+
+    fn get_burnchain(&self) -> Burnchain {
+        self.burnchain.clone()
+    }
+
+    fn wait_for_sortitions(
+        &self,
+        height_to_wait: Option<u64>,
+    ) -> Result<BurnchainTip, BurnchainControllerError> {
+        loop {
+            let canonical_burnchain_tip = self
+                .burnchain_db
+                .as_ref()
+                .expect("BurnchainDB not opened")
+                .get_canonical_chain_tip()
+                .unwrap();
+            let canonical_sortition_tip =
+                SortitionDB::get_canonical_burn_chain_tip(self.sortdb_ref().conn()).unwrap();
+            let caught_up = canonical_burnchain_tip.block_height
+                == canonical_sortition_tip.block_height
+                || height_to_wait
+                    .map(|height| canonical_sortition_tip.block_height >= height)
+                    .unwrap_or(false);
+
+            if caught_up {
+                let (_, state_transition) = self
+                    .sortdb_ref()
+                    .get_sortition_result(&canonical_sortition_tip.sortition_id)
+                    .expect("Sortition DB error.")
+                    .expect("BUG: no data for the canonical chain tip");
+                return Ok(BurnchainTip {
+                    block_snapshot: canonical_sortition_tip,
+                    received_at: Instant::now(),
+                    state_transition,
+                });
+            }
+            if !self.should_keep_running() {
+                return Err(BurnchainControllerError::CoordinatorClosed);
+            }
+            // yield some time
+            sleep_ms(100);
+        }
+    }
+
+    fn connect_dbs(&mut self) -> Result<(), BurnchainControllerError> {
+        self.burnchain.connect_db(
+            &self.indexer,
+            true,
+            self.indexer.get_first_block_header_hash()?,
+            self.indexer.get_first_block_header_timestamp()?,
+        )?;
+        Ok(())
+    }
+
+    fn get_stacks_epochs(&self) -> Vec<StacksEpoch> {
+        self.indexer.get_stacks_epochs()
+    }
+
+    fn start(
+        &mut self,
+        target_block_height_opt: Option<u64>,
+    ) -> Result<(BurnchainTip, u64), BurnchainControllerError> {
+        // if no target block height is given, just replay the first burnchain block.
+        self.receive_blocks(
+            false,
+            target_block_height_opt.map_or_else(|| Some(1), |x| Some(x)),
+        )
+    }
+
+    fn sync(
+        &mut self,
+        target_block_height_opt: Option<u64>,
+    ) -> Result<(BurnchainTip, u64), BurnchainControllerError> {
+        let (burnchain_tip, burnchain_height) =
+            self.receive_blocks(true, target_block_height_opt)?;
+
+        // Evaluate process_exit_at_block_height setting
+        if let Some(cap) = self.config.burnchain.process_exit_at_block_height {
+            if burnchain_tip.block_snapshot.block_height >= cap {
+                info!(
+                    "Node replayed the burnchain up to block height {}; exiting",
+                    cap
+                );
+                std::process::exit(0);
+            }
+        }
+        Ok((burnchain_tip, burnchain_height))
+    }
+
+    /// A replayed burnchain is read-only
+    fn submit_operation(
+        &mut self,
+        operation: BlockstackOperationType,
+        _op_signer: &mut BurnchainOpSigner,
+        _attempt: u64,
+    ) -> bool {
+        warn!(
+            "Cannot submit burnchain operation {} to a replayed burnchain",
+            &operation.txid()
+        );
+        false
+    }
+
+    #[cfg(test)]
+    fn bootstrap_chain(&mut self, _num_blocks: u64) {}
+}
@@ -7,9 +7,13 @@ use rand::RngCore;
 
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{MagicBytes, BLOCKSTACK_MAGIC_MAINNET};
+use stacks::chainstate::stacks::db::blocks::MINIMUM_PRUNE_DEPTH;
 use stacks::chainstate::stacks::miner::BlockBuilderSettings;
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
-use stacks::core::mempool::MemPoolWalkSettings;
+use stacks::core::mempool::{
+    MemPoolLimits, MemPoolWalkSettings, MEMPOOL_DEFAULT_MAX_BYTES, MEMPOOL_DEFAULT_MAX_TX_COUNT,
+    MEMPOOL_DEFAULT_REPLACE_BY_FEE_RATE_BUMP,
+};
 use stacks::core::StacksEpoch;
 use stacks::core::{
     CHAIN_ID_MAINNET, CHAIN_ID_TESTNET, PEER_VERSION_MAINNET, PEER_VERSION_TESTNET,
@@ -20,7 +24,11 @@ use stacks::cost_estimates::metrics::ProportionalDotProduct;
 use stacks::cost_estimates::CostEstimator;
 use stacks::cost_estimates::FeeEstimator;
 use stacks::cost_estimates::PessimisticEstimator;
+use stacks::net::atlas::AtlasConfig;
 use stacks::net::connection::ConnectionOptions;
+use stacks::net::event_stream::{
+    EventStreamHub, DEFAULT_MAX_EVENT_STREAM_BACKLOG, DEFAULT_MAX_EVENT_STREAM_SUBSCRIBERS,
+};
 use stacks::net::{Neighbor, NeighborKey, PeerAddress};
 use stacks::util::get_epoch_time_ms;
 use stacks::util::hash::hex_bytes;
@@ -28,6 +36,8 @@ use stacks::util::secp256k1::Secp256k1PrivateKey;
 use stacks::util::secp256k1::Secp256k1PublicKey;
 use stacks::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
 
+use crate::event_queue::DEFAULT_EVENT_QUEUE_MAX_SIZE;
+
 const DEFAULT_SATS_PER_VB: u64 = 50;
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
@@ -44,6 +54,7 @@ pub struct ConfigFile {
     pub connection_options: Option<ConnectionOptionsFile>,
     pub fee_estimation: Option<FeeEstimationConfigFile>,
     pub miner: Option<MinerConfigFile>,
+    pub atlas: Option<AtlasConfigFile>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -98,6 +109,64 @@ mod tests {
             "ST2TFVBMRPS5SSNP98DQKQ5JNB2B6NZM91C4K3P7B"
         );
     }
+
+    #[test]
+    fn should_load_atlas_contracts_toml() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [atlas]
+            attachments_max_size = 4096
+
+            [[atlas.contracts]]
+            contract_id = "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.registry"
+            max_attachment_size = 1024
+
+            [[atlas.contracts]]
+            contract_id = "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.profiles"
+            "#,
+        ));
+        let registry = QualifiedContractIdentifier::parse(
+            "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.registry",
+        )
+        .unwrap();
+        let profiles = QualifiedContractIdentifier::parse(
+            "ST2ZRX0K27GW0SP3GJCEMHD95TQGJMKB7G9Y0X1MH.profiles",
+        )
+        .unwrap();
+        assert_eq!(config.atlas.contracts.len(), 3);
+        assert!(config.atlas.contracts.contains(&registry));
+        assert!(config.atlas.contracts.contains(&profiles));
+        assert_eq!(config.atlas.max_attachment_size(&registry), 1024);
+        assert_eq!(config.atlas.max_attachment_size(&profiles), 4096);
+    }
+
+    #[test]
+    fn should_load_admin_api_toml() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            admin_bind = "127.0.0.1:20445"
+            admin_auth_token = "hunter2"
+            "#,
+        ));
+        assert_eq!(config.node.admin_bind, Some("127.0.0.1:20445".to_string()));
+        assert_eq!(config.node.admin_auth_token, Some("hunter2".to_string()));
+
+        let config = Config::from_config_file(ConfigFile::from_str(""));
+        assert_eq!(config.node.admin_bind, None);
+        assert_eq!(config.node.admin_auth_token, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_refuse_admin_bind_without_auth_token() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            admin_bind = "127.0.0.1:20445"
+            "#,
+        ));
+    }
 }
 
 impl ConfigFile {
@@ -289,6 +358,7 @@ pub struct Config {
     pub connection_options: ConnectionOptions,
     pub miner: MinerConfig,
     pub estimation: FeeEstimationConfig,
+    pub atlas: AtlasConfig,
 }
 
 lazy_static! {
@@ -371,6 +441,42 @@ impl Config {
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
                     use_test_genesis_chainstate: node.use_test_genesis_chainstate,
+                    event_observer_max_queue_size: node
+                        .event_observer_max_queue_size
+                        .unwrap_or(default_node_config.event_observer_max_queue_size),
+                    enable_event_stream: node
+                        .enable_event_stream
+                        .unwrap_or(default_node_config.enable_event_stream),
+                    mempool_max_tx_count: node
+                        .mempool_max_tx_count
+                        .unwrap_or(default_node_config.mempool_max_tx_count),
+                    mempool_max_bytes: node
+                        .mempool_max_bytes
+                        .unwrap_or(default_node_config.mempool_max_bytes),
+                    mempool_replace_by_fee_rate_bump: node
+                        .mempool_replace_by_fee_rate_bump
+                        .unwrap_or(default_node_config.mempool_replace_by_fee_rate_bump),
+                    prune_depth: match node.prune_depth {
+                        Some(depth) if depth < MINIMUM_PRUNE_DEPTH => {
+                            panic!(
+                                "Config setting `node.prune_depth` must be at least {}",
+                                MINIMUM_PRUNE_DEPTH
+                            );
+                        }
+                        depth => depth,
+                    },
+                    admin_auth_token: match (&node.admin_bind, node.admin_auth_token) {
+                        (Some(_), None) => {
+                            panic!(
+                                "Config setting `node.admin_bind` requires `node.admin_auth_token`"
+                            );
+                        }
+                        (_, Some(token)) if token.is_empty() => {
+                            panic!("Config setting `node.admin_auth_token` must not be empty");
+                        }
+                        (_, token) => token,
+                    },
+                    admin_bind: node.admin_bind,
                 };
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
@@ -490,6 +596,11 @@ impl Config {
                         Some(epochs) => Some(epochs),
                         None => default_burnchain_config.epochs,
                     },
+                    replay_fixture_path: burnchain.replay_fixture_path,
+                    use_internal_wallet: burnchain
+                        .use_internal_wallet
+                        .unwrap_or(default_burnchain_config.use_internal_wallet),
+                    wallet_birth_height: burnchain.wallet_birth_height,
                 }
             }
             None => default_burnchain_config,
@@ -619,8 +730,30 @@ impl Config {
                 opts.read_only_call_limit_runtime.map(|x| {
                     read_only_call_limit.runtime = x;
                 });
+                let mut simulate_transaction_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
+                    .simulate_transaction_limit
+                    .clone();
+                opts.simulate_transaction_limit_write_length.map(|x| {
+                    simulate_transaction_limit.write_length = x;
+                });
+                opts.simulate_transaction_limit_write_count.map(|x| {
+                    simulate_transaction_limit.write_count = x;
+                });
+                opts.simulate_transaction_limit_read_length.map(|x| {
+                    simulate_transaction_limit.read_length = x;
+                });
+                opts.simulate_transaction_limit_read_count.map(|x| {
+                    simulate_transaction_limit.read_count = x;
+                });
+                opts.simulate_transaction_limit_runtime.map(|x| {
+                    simulate_transaction_limit.runtime = x;
+                });
                 ConnectionOptions {
                     read_only_call_limit,
+                    simulate_transaction_limit,
+                    enable_transaction_simulation: opts
+                        .enable_transaction_simulation
+                        .unwrap_or(false),
                     inbox_maxlen: opts
                         .inbox_maxlen
                         .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.inbox_maxlen.clone()),
@@ -738,6 +871,9 @@ impl Config {
                     handshake_timeout: opts.connect_timeout.unwrap_or(5),
                     max_sockets: opts.max_sockets.unwrap_or(800) as usize,
                     antientropy_public: opts.antientropy_public.unwrap_or(true),
+                    mempool_sync_interval: opts
+                        .mempool_sync_interval
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.mempool_sync_interval),
                     ..ConnectionOptions::default()
                 }
             }
@@ -749,6 +885,23 @@ impl Config {
             None => FeeEstimationConfig::default(),
         };
 
+        let mut atlas = AtlasConfig::default(burnchain.mode == "mainnet");
+        if let Some(atlas_file) = config_file.atlas {
+            if let Some(attachments_max_size) = atlas_file.attachments_max_size {
+                atlas.attachments_max_size = attachments_max_size;
+            }
+            for contract in atlas_file.contracts.unwrap_or_default() {
+                let contract_id = QualifiedContractIdentifier::parse(&contract.contract_id)
+                    .unwrap_or_else(|_| {
+                        panic!(
+                            "Setting atlas.contracts contract_id is not a contract: {}",
+                            contract.contract_id
+                        )
+                    });
+                atlas.add_contract(contract_id, contract.max_attachment_size);
+            }
+        }
+
         Config {
             node,
             burnchain,
@@ -757,6 +910,7 @@ impl Config {
             connection_options,
             estimation,
             miner,
+            atlas,
         }
     }
 
@@ -807,6 +961,12 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    pub fn get_wallet_db_path(&self) -> String {
+        let mut path = self.get_burnchain_path();
+        path.set_file_name("wallet.sqlite");
+        path.to_str().expect("Unable to produce path").to_string()
+    }
+
     pub fn get_peer_db_file_path(&self) -> String {
         let mut path = self.get_chainstate_path();
         path.set_file_name("peer.sqlite");
@@ -819,6 +979,18 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    /// Path to the on-disk delivery queue for the event observer at `endpoint`
+    pub fn get_event_observer_queue_path(&self, endpoint: &str) -> String {
+        let mut path = self.get_chainstate_path();
+        path.set_file_name("event_observers");
+        let file_name: String = endpoint
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        path.push(format!("{}.sqlite", file_name));
+        path.to_str().expect("Unable to produce path").to_string()
+    }
+
     pub fn add_initial_balance(&mut self, address: String, amount: u64) {
         let new_balance = InitialBalance {
             address: PrincipalData::parse_standard_principal(&address)
@@ -894,6 +1066,7 @@ impl std::default::Default for Config {
             connection_options,
             estimation,
             miner: MinerConfig::default(),
+            atlas: AtlasConfig::default(false),
         }
     }
 }
@@ -925,6 +1098,15 @@ pub struct BurnchainConfig {
     /// Custom override for the definitions of the epochs. This will only be applied for testnet and
     /// regtest nodes.
     pub epochs: Option<Vec<StacksEpoch>>,
+    /// Replay the burnchain blocks and operations stored in this fixture (a JSON file, or the
+    /// `burnchain.sqlite` of another node) instead of following bitcoind.  Neon mode only.
+    pub replay_fixture_path: Option<String>,
+    /// Find the miner's UTXOs by scanning downloaded blocks, instead of asking bitcoind's wallet
+    pub use_internal_wallet: bool,
+    /// Height of the first block the internal wallet scans.  Defaults to the burnchain tip when
+    /// the wallet is first created; UTXOs created before this height are not found, and on a
+    /// pruned bitcoind it must be above the prune height.
+    pub wallet_birth_height: Option<u64>,
 }
 
 impl BurnchainConfig {
@@ -953,6 +1135,9 @@ impl BurnchainConfig {
             block_commit_tx_estimated_size: BLOCK_COMMIT_TX_ESTIM_SIZE,
             rbf_fee_increment: DEFAULT_RBF_FEE_RATE_INCREMENT,
             epochs: None,
+            replay_fixture_path: None,
+            use_internal_wallet: false,
+            wallet_birth_height: None,
         }
     }
 
@@ -1007,6 +1192,9 @@ pub struct BurnchainConfigFile {
     pub rbf_fee_increment: Option<u64>,
     pub max_rbf: Option<u64>,
     pub epochs: Option<Vec<StacksEpoch>>,
+    pub replay_fixture_path: Option<String>,
+    pub use_internal_wallet: Option<bool>,
+    pub wallet_birth_height: Option<u64>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1030,6 +1218,22 @@ pub struct NodeConfig {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: u64,
     pub use_test_genesis_chainstate: Option<bool>,
+    /// Maximum number of undelivered payloads kept on disk for each event observer
+    pub event_observer_max_queue_size: u64,
+    /// Serve the observer events to RPC clients at `/v2/events/stream`
+    pub enable_event_stream: bool,
+    /// Maximum number of transactions in the mempool (0 for no limit)
+    pub mempool_max_tx_count: u64,
+    /// Maximum total size of the transactions in the mempool, in bytes (0 for no limit)
+    pub mempool_max_bytes: u64,
+    /// Minimum fee-rate increase, in percent, for a transaction to replace another
+    pub mempool_replace_by_fee_rate_bump: u64,
+    /// If set, delete block and microblock bodies this many blocks behind the canonical tip
+    pub prune_depth: Option<u64>,
+    /// If set, serve the admin RPC API on this address
+    pub admin_bind: Option<String>,
+    /// Bearer token that clients of the admin RPC API must present
+    pub admin_auth_token: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -1175,6 +1379,16 @@ impl Config {
         Some(metric)
     }
 
+    pub fn make_event_stream_hub(&self) -> Option<EventStreamHub> {
+        if !self.node.enable_event_stream {
+            return None;
+        }
+        Some(EventStreamHub::new(
+            DEFAULT_MAX_EVENT_STREAM_SUBSCRIBERS,
+            DEFAULT_MAX_EVENT_STREAM_BACKLOG,
+        ))
+    }
+
     pub fn make_fee_estimator(&self) -> Option<Box<dyn FeeEstimator>> {
         let metric = self.make_cost_metric()?;
         let fee_estimator: Box<dyn FeeEstimator> = match self.estimation.fee_estimator.as_ref()? {
@@ -1256,6 +1470,22 @@ impl NodeConfig {
             prometheus_bind: None,
             pox_sync_sample_secs: 30,
             use_test_genesis_chainstate: None,
+            event_observer_max_queue_size: DEFAULT_EVENT_QUEUE_MAX_SIZE,
+            enable_event_stream: false,
+            mempool_max_tx_count: MEMPOOL_DEFAULT_MAX_TX_COUNT,
+            mempool_max_bytes: MEMPOOL_DEFAULT_MAX_BYTES,
+            mempool_replace_by_fee_rate_bump: MEMPOOL_DEFAULT_REPLACE_BY_FEE_RATE_BUMP,
+            prune_depth: None,
+            admin_bind: None,
+            admin_auth_token: None,
+        }
+    }
+
+    pub fn mempool_limits(&self) -> MemPoolLimits {
+        MemPoolLimits {
+            max_tx_count: self.mempool_max_tx_count,
+            max_bytes: self.mempool_max_bytes,
+            replace_by_fee_rate_bump: self.mempool_replace_by_fee_rate_bump,
         }
     }
 
@@ -1385,6 +1615,12 @@ pub struct ConnectionOptionsFile {
     pub read_only_call_limit_read_count: Option<u64>,
     pub read_only_call_limit_runtime: Option<u64>,
     pub maximum_call_argument_size: Option<u32>,
+    pub enable_transaction_simulation: Option<bool>,
+    pub simulate_transaction_limit_write_length: Option<u64>,
+    pub simulate_transaction_limit_read_length: Option<u64>,
+    pub simulate_transaction_limit_write_count: Option<u64>,
+    pub simulate_transaction_limit_read_count: Option<u64>,
+    pub simulate_transaction_limit_runtime: Option<u64>,
     pub download_interval: Option<u64>,
     pub inv_sync_interval: Option<u64>,
     pub full_inv_sync_interval: Option<u64>,
@@ -1395,6 +1631,7 @@ pub struct ConnectionOptionsFile {
     pub disable_block_download: Option<bool>,
     pub force_disconnect_interval: Option<u64>,
     pub antientropy_public: Option<bool>,
+    pub mempool_sync_interval: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -1418,6 +1655,14 @@ pub struct NodeConfigFile {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: Option<u64>,
     pub use_test_genesis_chainstate: Option<bool>,
+    pub event_observer_max_queue_size: Option<u64>,
+    pub enable_event_stream: Option<bool>,
+    pub mempool_max_tx_count: Option<u64>,
+    pub mempool_max_bytes: Option<u64>,
+    pub mempool_replace_by_fee_rate_bump: Option<u64>,
+    pub prune_depth: Option<u64>,
+    pub admin_bind: Option<String>,
+    pub admin_auth_token: Option<String>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -1449,6 +1694,19 @@ pub struct MinerConfigFile {
     pub probability_pick_no_estimate_tx: Option<u8>,
 }
 
+#[derive(Clone, Deserialize, Default)]
+pub struct AtlasConfigFile {
+    pub attachments_max_size: Option<u32>,
+    pub contracts: Option<Vec<AtlasContractConfigFile>>,
+}
+
+/// A contract, other than BNS, whose attachments the node should sync
+#[derive(Clone, Deserialize, Default)]
+pub struct AtlasContractConfigFile {
+    pub contract_id: String,
+    pub max_attachment_size: Option<u32>,
+}
+
 #[derive(Clone, Deserialize, Default)]
 pub struct EventObserverConfigFile {
     pub endpoint: String,
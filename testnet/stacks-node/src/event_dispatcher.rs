@@ -1,10 +1,12 @@
+use std::cmp;
 use std::collections::hash_map::Entry;
-use std::thread::sleep;
+use std::thread::{self, sleep};
 use std::time::Duration;
 use std::{
     collections::{HashMap, HashSet},
     sync::{
         atomic::{AtomicBool, Ordering},
+        mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError},
         Arc, Mutex,
     },
 };
@@ -28,6 +30,7 @@ use stacks::chainstate::stacks::{
 use stacks::codec::StacksMessageCodec;
 use stacks::core::mempool::{MemPoolDropReason, MemPoolEventDispatcher};
 use stacks::net::atlas::{Attachment, AttachmentInstance};
+use stacks::net::event_stream::{EventStreamHub, EventStreamKey};
 use stacks::types::chainstate::{BurnchainHeaderHash, StacksAddress, StacksBlockId};
 use stacks::util::hash::bytes_to_hex;
 use stacks::vm::analysis::contract_interface_builder::build_contract_interface;
@@ -35,13 +38,15 @@ use stacks::vm::costs::ExecutionCost;
 use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
 
 use super::config::{EventKeyType, EventObserverConfig};
+use super::event_queue::EventQueueDB;
 use super::node::ChainTip;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
 
 #[derive(Debug, Clone)]
 struct EventObserver {
     endpoint: String,
-    should_keep_running: Arc<AtomicBool>,
+    queue: Arc<Mutex<EventQueueDB>>,
+    wakeup_tx: SyncSender<()>,
 }
 
 struct ReceiptPayloadInfo<'a> {
@@ -75,9 +80,63 @@ pub struct MinedBlockEvent {
     pub confirmed_microblocks_cost: ExecutionCost,
 }
 
+/// Initial delay before retrying a failed delivery to an observer
+const DELIVERY_BACKOFF_INITIAL_MS: u64 = 1_000;
+/// Upper bound on the delay between delivery retries to an observer
+const DELIVERY_BACKOFF_MAX_MS: u64 = 60_000;
+/// How long an idle delivery thread waits for a wakeup before re-checking its queue
+const DELIVERY_IDLE_POLL_MS: u64 = 1_000;
+
 impl EventObserver {
+    /// Open (or resume) the durable queue for this observer, and spawn the thread that
+    /// delivers its contents in order.  Any payloads left over from a previous run are
+    /// delivered before new ones.
+    fn new(
+        endpoint: &str,
+        queue_path: &str,
+        max_queue_size: u64,
+        should_keep_running: Arc<AtomicBool>,
+    ) -> EventObserver {
+        let queue = EventQueueDB::connect(queue_path, max_queue_size).expect(&format!(
+            "FATAL: failed to open event observer queue at {}",
+            queue_path
+        ));
+        match queue.len() {
+            Ok(pending) if pending > 0 => info!(
+                "Event dispatcher: resuming delivery of {} queued payloads to {}",
+                pending, endpoint
+            ),
+            _ => {}
+        }
+
+        let queue = Arc::new(Mutex::new(queue));
+        let (wakeup_tx, wakeup_rx) = sync_channel(1);
+
+        let thread_endpoint = endpoint.to_string();
+        let thread_queue = queue.clone();
+        thread::Builder::new()
+            .name(format!("event-observer:{}", endpoint))
+            .spawn(move || {
+                EventObserver::delivery_loop(
+                    thread_endpoint,
+                    thread_queue,
+                    wakeup_rx,
+                    should_keep_running,
+                )
+            })
+            .expect("FATAL: failed to spawn event observer delivery thread");
+
+        EventObserver {
+            endpoint: endpoint.to_string(),
+            queue,
+            wakeup_tx,
+        }
+    }
+
+    /// Persist a payload to this observer's queue, and wake up its delivery thread.
+    /// Never blocks on the observer itself.
     fn send_payload(&self, payload: &serde_json::Value, path: &str) {
-        let body = match serde_json::to_vec(&payload) {
+        let body = match serde_json::to_string(&payload) {
             Ok(body) => body,
             Err(err) => {
                 error!("Event dispatcher: serialization failed  - {:?}", err);
@@ -85,61 +144,151 @@ impl EventObserver {
             }
         };
 
-        let url = {
-            let joined_components = match path.starts_with("/") {
-                true => format!("{}{}", &self.endpoint, path),
-                false => format!("{}/{}", &self.endpoint, path),
-            };
-            let url = format!("http://{}", joined_components);
-            Url::parse(&url).expect(&format!(
-                "Event dispatcher: unable to parse {} as a URL",
-                url
-            ))
-        };
+        {
+            let mut queue = self
+                .queue
+                .lock()
+                .expect("FATAL: event observer queue lock poisoned");
+            match queue.push(path, &body) {
+                Ok(0) => {}
+                Ok(num_evicted) => {
+                    error!(
+                        "Event dispatcher: queue for {} is full; discarded {} undelivered payloads",
+                        &self.endpoint, num_evicted
+                    );
+                }
+                Err(err) => {
+                    error!(
+                        "Event dispatcher: failed to queue payload for {}/{} - {:?}",
+                        &self.endpoint, path, err
+                    );
+                    return;
+                }
+            }
+        }
 
-        let backoff = Duration::from_millis((1.0 * 1_000.0) as u64);
+        // a full channel means the delivery thread already has a pending wakeup
+        match self.wakeup_tx.try_send(()) {
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => {
+                debug!(
+                    "Event dispatcher: delivery thread for {} has exited",
+                    &self.endpoint
+                );
+            }
+        }
+    }
 
+    /// Deliver queued payloads to `endpoint` one at a time, oldest first.  A payload is only
+    /// removed from the queue once the observer responds with a success status; until then,
+    /// it is retried with exponential backoff.
+    fn delivery_loop(
+        endpoint: String,
+        queue: Arc<Mutex<EventQueueDB>>,
+        wakeup_rx: Receiver<()>,
+        should_keep_running: Arc<AtomicBool>,
+    ) {
+        let mut backoff_ms = DELIVERY_BACKOFF_INITIAL_MS;
         loop {
-            if !self.should_keep_running.load(Ordering::SeqCst) {
+            if !should_keep_running.load(Ordering::SeqCst) {
                 info!("Terminating event observer");
                 return;
             }
 
-            let body = body.clone();
-            let mut req = Request::new(Method::Post, url.clone());
-            req.append_header("Content-Type", "application/json")
-                .expect("Unable to set header");
-            req.set_body(body);
-
-            let response = async_std::task::block_on(async {
-                let stream = match TcpStream::connect(self.endpoint.clone()).await {
-                    Ok(stream) => stream,
-                    Err(err) => {
-                        println!("Event dispatcher: connection failed  - {:?}", err);
-                        return None;
-                    }
-                };
-
-                match client::connect(stream, req).await {
-                    Ok(response) => Some(response),
-                    Err(err) => {
-                        println!("Event dispatcher: rpc invokation failed  - {:?}", err);
-                        return None;
+            let next = queue
+                .lock()
+                .expect("FATAL: event observer queue lock poisoned")
+                .peek();
+
+            let entry = match next {
+                Ok(Some(entry)) => entry,
+                Ok(None) => {
+                    match wakeup_rx.recv_timeout(Duration::from_millis(DELIVERY_IDLE_POLL_MS)) {
+                        Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => {
+                            // dispatcher is gone; nothing more will be queued
+                            return;
+                        }
                     }
+                    continue;
                 }
-            });
+                Err(err) => {
+                    error!(
+                        "Event dispatcher: failed to read queue for {} - {:?}",
+                        &endpoint, err
+                    );
+                    sleep(Duration::from_millis(backoff_ms));
+                    continue;
+                }
+            };
 
-            if let Some(response) = response {
-                if response.status().is_success() {
-                    break;
-                } else {
+            if EventObserver::post_payload(&endpoint, &entry.path, entry.payload.into_bytes()) {
+                if let Err(err) = queue
+                    .lock()
+                    .expect("FATAL: event observer queue lock poisoned")
+                    .remove(entry.id)
+                {
                     error!(
-                        "Event dispatcher: POST {}/{} failed with error {:?}",
-                        self.endpoint, &url, response
+                        "Event dispatcher: failed to dequeue delivered payload for {} - {:?}",
+                        &endpoint, err
                     );
                 }
+                backoff_ms = DELIVERY_BACKOFF_INITIAL_MS;
+            } else {
+                sleep(Duration::from_millis(backoff_ms));
+                backoff_ms = cmp::min(backoff_ms.saturating_mul(2), DELIVERY_BACKOFF_MAX_MS);
+            }
+        }
+    }
+
+    /// Make a single attempt to POST a payload to the observer.
+    /// Returns true if the observer acknowledged it.
+    fn post_payload(endpoint: &str, path: &str, body: Vec<u8>) -> bool {
+        let url = {
+            let joined_components = match path.starts_with("/") {
+                true => format!("{}{}", endpoint, path),
+                false => format!("{}/{}", endpoint, path),
+            };
+            let url = format!("http://{}", joined_components);
+            Url::parse(&url).expect(&format!(
+                "Event dispatcher: unable to parse {} as a URL",
+                url
+            ))
+        };
+
+        let mut req = Request::new(Method::Post, url.clone());
+        req.append_header("Content-Type", "application/json")
+            .expect("Unable to set header");
+        req.set_body(body);
+
+        let response = async_std::task::block_on(async {
+            let stream = match TcpStream::connect(endpoint.to_string()).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("Event dispatcher: connection failed - {:?}", err);
+                    return None;
+                }
+            };
+
+            match client::connect(stream, req).await {
+                Ok(response) => Some(response),
+                Err(err) => {
+                    warn!("Event dispatcher: rpc invocation failed - {:?}", err);
+                    return None;
+                }
+            }
+        });
+
+        match response {
+            Some(response) if response.status().is_success() => true,
+            Some(response) => {
+                error!(
+                    "Event dispatcher: POST {}/{} failed with error {:?}",
+                    endpoint, &url, response
+                );
+                false
             }
-            sleep(backoff);
+            None => false,
         }
     }
 
@@ -273,16 +422,15 @@ impl EventObserver {
         self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
     }
 
-    /// Serializes new microblocks data into a JSON payload and sends it off to the correct path
-    fn send_new_microblocks(
-        &self,
+    /// Serializes new microblocks data into a JSON payload
+    fn make_new_microblocks_payload(
         parent_index_block_hash: StacksBlockId,
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
         serialized_txs: &Vec<serde_json::Value>,
         burn_block_hash: BurnchainHeaderHash,
         burn_block_height: u32,
         burn_block_timestamp: u64,
-    ) {
+    ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
             .iter()
@@ -291,16 +439,18 @@ impl EventObserver {
             })
             .collect();
 
-        let payload = json!({
+        json!({
             "parent_index_block_hash": format!("0x{}", parent_index_block_hash),
             "events": serialized_events,
             "transactions": serialized_txs,
             "burn_block_hash": format!("0x{}", burn_block_hash),
             "burn_block_height": burn_block_height,
             "burn_block_timestamp": burn_block_timestamp,
-        });
+        })
+    }
 
-        self.send_payload(&payload, PATH_MICROBLOCK_SUBMIT);
+    fn send_new_microblocks(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MICROBLOCK_SUBMIT);
     }
 
     fn send_dropped_mempool_txs(&self, payload: &serde_json::Value) {
@@ -315,8 +465,9 @@ impl EventObserver {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
-    fn send(
-        &self,
+    /// Serializes a newly-processed block, along with the given subset of its events, into a
+    /// JSON payload
+    fn make_new_block_processed_payload(
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
         chain_tip: &ChainTip,
         parent_index_hash: &StacksBlockId,
@@ -328,7 +479,7 @@ impl EventObserver {
         parent_burn_block_timestamp: u64,
         anchored_consumed: &ExecutionCost,
         mblock_confirmed_consumed: &ExecutionCost,
-    ) {
+    ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
             .iter()
@@ -347,7 +498,7 @@ impl EventObserver {
         }
 
         // Wrap events
-        let payload = json!({
+        json!({
             "block_hash": format!("0x{}", chain_tip.block.block_hash()),
             "block_height": chain_tip.metadata.block_height,
             "burn_block_hash": format!("0x{}", chain_tip.metadata.burn_header_hash),
@@ -367,13 +518,66 @@ impl EventObserver {
             "parent_burn_block_timestamp": parent_burn_block_timestamp,
             "anchored_cost": anchored_consumed,
             "confirmed_microblocks_cost": mblock_confirmed_consumed,
-        });
+        })
+    }
 
-        // Send payload
-        self.send_payload(&payload, PATH_BLOCK_PROCESSED);
+    fn send(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_BLOCK_PROCESSED);
     }
 }
 
+/// Is a client of the RPC event stream that subscribed with `keys` interested in events of the
+/// given kind?
+fn event_stream_wants(keys: &[EventStreamKey], kind: &EventStreamKey) -> bool {
+    keys.iter()
+        .any(|key| *key == EventStreamKey::AnyEvent || key == kind)
+}
+
+/// Should a transaction event be included in the payloads sent to a client of the RPC event
+/// stream that subscribed with `keys`?  This applies the same filtering as is applied to
+/// registered observers.
+fn event_stream_wants_event(keys: &[EventStreamKey], event: &StacksTransactionEvent) -> bool {
+    let asset_identifier = match event {
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+            Some(&event_data.asset_identifier)
+        }
+        _ => None,
+    };
+
+    keys.iter().any(|key| match key {
+        EventStreamKey::AnyEvent => true,
+        EventStreamKey::SmartContractEvent(contract_identifier, topic) => match event {
+            StacksTransactionEvent::SmartContractEvent(event_data) => {
+                event_data.key.0 == *contract_identifier && event_data.key.1 == *topic
+            }
+            _ => false,
+        },
+        EventStreamKey::STXEvent => match event {
+            StacksTransactionEvent::STXEvent(_) => true,
+            _ => false,
+        },
+        EventStreamKey::AssetEvent(key_asset_identifier) => {
+            asset_identifier == Some(key_asset_identifier)
+        }
+        _ => false,
+    })
+}
+
 #[derive(Clone)]
 pub struct EventDispatcher {
     registered_observers: Vec<EventObserver>,
@@ -386,6 +590,7 @@ pub struct EventDispatcher {
     any_event_observers_lookup: HashSet<u16>,
     miner_observers_lookup: HashSet<u16>,
     boot_receipts: Arc<Mutex<Option<Vec<StacksTransactionReceipt>>>>,
+    event_stream: Option<EventStreamHub>,
 }
 
 impl MemPoolEventDispatcher for EventDispatcher {
@@ -483,6 +688,15 @@ impl EventDispatcher {
             microblock_observers_lookup: HashSet::new(),
             boot_receipts: Arc::new(Mutex::new(None)),
             miner_observers_lookup: HashSet::new(),
+            event_stream: None,
+        }
+    }
+
+    /// Are any RPC clients currently subscribed to the event stream?
+    fn has_event_stream_clients(&self) -> bool {
+        match self.event_stream {
+            Some(ref hub) => hub.num_subscribers() > 0,
+            None => false,
         }
     }
 
@@ -504,7 +718,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_event_stream_clients() {
             return;
         }
 
@@ -519,6 +733,16 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_burn_block(&payload);
         }
+
+        if let Some(ref hub) = self.event_stream {
+            hub.publish(PATH_BURN_BLOCK_SUBMIT, |keys| {
+                if event_stream_wants(keys, &EventStreamKey::BurnchainBlocks) {
+                    Some(payload.clone())
+                } else {
+                    None
+                }
+            });
+        }
     }
 
     /// Iterates through tx receipts, and then the events corresponding to each receipt to
@@ -655,7 +879,7 @@ impl EventDispatcher {
 
         let (dispatch_matrix, events) = self.create_dispatch_matrix_and_event_vector(&receipts);
 
-        if dispatch_matrix.len() > 0 {
+        if dispatch_matrix.len() > 0 || self.has_event_stream_clients() {
             let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
                 mature_rewards
                     .iter()
@@ -684,7 +908,7 @@ impl EventDispatcher {
                     .map(|event_id| (*event_id, &events[*event_id]))
                     .collect();
 
-                self.registered_observers[observer_id].send(
+                let payload = EventObserver::make_new_block_processed_payload(
                     filtered_events,
                     chain_tip,
                     parent_index_hash,
@@ -697,6 +921,31 @@ impl EventDispatcher {
                     anchored_consumed,
                     mblock_confirmed_consumed,
                 );
+                self.registered_observers[observer_id].send(&payload);
+            }
+
+            if let Some(ref hub) = self.event_stream {
+                hub.publish(PATH_BLOCK_PROCESSED, |keys| {
+                    let filtered_events: Vec<_> = events
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (_, _, event))| event_stream_wants_event(keys, event))
+                        .collect();
+
+                    Some(EventObserver::make_new_block_processed_payload(
+                        filtered_events,
+                        chain_tip,
+                        parent_index_hash,
+                        &boot_receipts,
+                        &winner_txid,
+                        &mature_rewards,
+                        parent_burn_block_hash,
+                        parent_burn_block_height,
+                        parent_burn_block_timestamp,
+                        anchored_consumed,
+                        mblock_confirmed_consumed,
+                    ))
+                });
             }
         }
     }
@@ -719,7 +968,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_event_stream_clients() {
             return;
         }
         let flattened_receipts = processed_unconfirmed_state
@@ -750,7 +999,7 @@ impl EventDispatcher {
                 .map(|event_id| (*event_id, &events[*event_id]))
                 .collect();
 
-            observer.send_new_microblocks(
+            let payload = EventObserver::make_new_microblocks_payload(
                 parent_index_block_hash,
                 filtered_events,
                 &serialized_txs,
@@ -758,6 +1007,29 @@ impl EventDispatcher {
                 processed_unconfirmed_state.burn_block_height,
                 processed_unconfirmed_state.burn_block_timestamp,
             );
+            observer.send_new_microblocks(&payload);
+        }
+
+        if let Some(ref hub) = self.event_stream {
+            hub.publish(PATH_MICROBLOCK_SUBMIT, |keys| {
+                if !event_stream_wants(keys, &EventStreamKey::Microblocks) {
+                    return None;
+                }
+                let filtered_events: Vec<_> = events
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, _, event))| event_stream_wants_event(keys, event))
+                    .collect();
+
+                Some(EventObserver::make_new_microblocks_payload(
+                    parent_index_block_hash,
+                    filtered_events,
+                    &serialized_txs,
+                    processed_unconfirmed_state.burn_block_hash,
+                    processed_unconfirmed_state.burn_block_height,
+                    processed_unconfirmed_state.burn_block_timestamp,
+                ))
+            });
         }
     }
 
@@ -772,7 +1044,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_event_stream_clients() {
             return;
         }
 
@@ -781,6 +1053,16 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_mempool_txs(&payload);
         }
+
+        if let Some(ref hub) = self.event_stream {
+            hub.publish(PATH_MEMPOOL_TX_SUBMIT, |keys| {
+                if event_stream_wants(keys, &EventStreamKey::MemPoolTransactions) {
+                    Some(payload.clone())
+                } else {
+                    None
+                }
+            });
+        }
     }
 
     pub fn process_mined_block_event(
@@ -827,7 +1109,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && !self.has_event_stream_clients() {
             return;
         }
 
@@ -844,6 +1126,16 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_dropped_mempool_txs(&payload);
         }
+
+        if let Some(ref hub) = self.event_stream {
+            hub.publish(PATH_MEMPOOL_TX_DROP, |keys| {
+                if event_stream_wants(keys, &EventStreamKey::MemPoolTransactions) {
+                    Some(payload.clone())
+                } else {
+                    None
+                }
+            });
+        }
     }
 
     pub fn process_new_attachments(&self, attachments: &Vec<(AttachmentInstance, Attachment)>) {
@@ -880,16 +1172,33 @@ impl EventDispatcher {
         }
     }
 
+    /// Publish events to RPC clients of `/v2/events/stream` through the given hub, in addition
+    /// to any registered observers.
+    pub fn register_event_stream(&mut self, hub: EventStreamHub) {
+        info!("Serving events to RPC event stream clients");
+        self.event_stream = Some(hub);
+    }
+
+    pub fn get_event_stream(&self) -> Option<&EventStreamHub> {
+        self.event_stream.as_ref()
+    }
+
+    /// Register an observer whose payloads are persisted to the queue database at `queue_path`
+    /// before being delivered by a dedicated thread.
     pub fn register_observer(
         &mut self,
         conf: &EventObserverConfig,
+        queue_path: &str,
+        max_queue_size: u64,
         should_keep_running: Arc<AtomicBool>,
     ) {
         info!("Registering event observer at: {}", conf.endpoint);
-        let event_observer = EventObserver {
-            endpoint: conf.endpoint.clone(),
+        let event_observer = EventObserver::new(
+            &conf.endpoint,
+            queue_path,
+            max_queue_size,
             should_keep_running,
-        };
+        );
 
         let observer_index = self.registered_observers.len() as u16;
 
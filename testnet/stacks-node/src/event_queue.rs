@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::Path;
+
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, OpenFlags, Row, NO_PARAMS};
+
+use stacks::util::db::Error as db_error;
+use stacks::util::db::{
+    query_count, query_row, sqlite_open, tx_begin_immediate, u64_to_sql, FromRow,
+};
+use stacks::util::get_epoch_time_secs;
+
+pub const EVENT_QUEUE_DB_VERSION: &'static str = "1";
+
+/// Default upper bound on the number of undelivered payloads kept per observer
+pub const DEFAULT_EVENT_QUEUE_MAX_SIZE: u64 = 100_000;
+
+const EVENT_QUEUE_DB_SCHEMA: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE pending_payloads(
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        path TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );"#,
+    "CREATE TABLE db_config(version TEXT NOT NULL);",
+];
+
+/// A payload that has been accepted for an observer, but not yet acknowledged by it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedPayload {
+    pub id: i64,
+    pub path: String,
+    pub payload: String,
+}
+
+impl FromRow<QueuedPayload> for QueuedPayload {
+    fn from_row<'a>(row: &'a Row) -> Result<QueuedPayload, db_error> {
+        let id: i64 = row.get_unwrap("id");
+        let path: String = row.get_unwrap("path");
+        let payload: String = row.get_unwrap("payload");
+        Ok(QueuedPayload { id, path, payload })
+    }
+}
+
+/// Durable, ordered queue of payloads destined for a single event observer.
+/// Payloads are written here before delivery is attempted, and are only removed
+/// once the observer has acknowledged them, so they survive node restarts.
+#[derive(Debug)]
+pub struct EventQueueDB {
+    conn: Connection,
+    max_size: u64,
+}
+
+impl EventQueueDB {
+    fn instantiate(&mut self) -> Result<(), db_error> {
+        let tx = tx_begin_immediate(&mut self.conn)?;
+
+        for row_text in EVENT_QUEUE_DB_SCHEMA {
+            tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
+        }
+
+        tx.execute(
+            "INSERT INTO db_config (version) VALUES (?1)",
+            &[&EVENT_QUEUE_DB_VERSION],
+        )
+        .map_err(db_error::SqliteError)?;
+
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Open the queue database at the given path, creating it (and its parent directory)
+    /// if it does not exist yet.
+    pub fn connect(path: &str, max_size: u64) -> Result<EventQueueDB, db_error> {
+        let create_flag = fs::metadata(path).is_err();
+        if create_flag {
+            if let Some(parent) = Path::new(path).parent() {
+                fs::create_dir_all(parent).map_err(db_error::IOError)?;
+            }
+        }
+
+        let open_flags = if create_flag {
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE
+        } else {
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+        };
+
+        let conn = sqlite_open(path, open_flags, false)?;
+        let mut db = EventQueueDB { conn, max_size };
+        if create_flag {
+            db.instantiate()?;
+        }
+        Ok(db)
+    }
+
+    /// Append a payload to the back of the queue.  If the queue is full, the oldest
+    /// payloads are discarded to make room, so that a dead observer can never stall the node.
+    /// Returns the number of payloads that were discarded.
+    pub fn push(&mut self, path: &str, payload: &str) -> Result<u64, db_error> {
+        let max_size = u64_to_sql(self.max_size)?;
+        let now = u64_to_sql(get_epoch_time_secs())?;
+        let tx = tx_begin_immediate(&mut self.conn)?;
+
+        let pending = query_count(&tx, "SELECT COUNT(*) FROM pending_payloads", NO_PARAMS)?;
+        let num_evicted = if max_size > 0 && pending >= max_size {
+            let num_evicted = pending - max_size + 1;
+            tx.execute(
+                "DELETE FROM pending_payloads WHERE id IN (SELECT id FROM pending_payloads ORDER BY id ASC LIMIT ?1)",
+                &[&num_evicted as &dyn ToSql],
+            )?;
+            num_evicted as u64
+        } else {
+            0
+        };
+
+        tx.execute(
+            "INSERT INTO pending_payloads (path, payload, created_at) VALUES (?1, ?2, ?3)",
+            &[&path as &dyn ToSql, &payload, &now],
+        )?;
+
+        tx.commit()?;
+        Ok(num_evicted)
+    }
+
+    /// Get the oldest payload that has not yet been delivered, if any.
+    pub fn peek(&self) -> Result<Option<QueuedPayload>, db_error> {
+        query_row(
+            &self.conn,
+            "SELECT id, path, payload FROM pending_payloads ORDER BY id ASC LIMIT 1",
+            NO_PARAMS,
+        )
+    }
+
+    /// Remove a payload once it has been acknowledged by the observer.
+    pub fn remove(&mut self, id: i64) -> Result<(), db_error> {
+        self.conn
+            .execute("DELETE FROM pending_payloads WHERE id = ?1", &[&id])?;
+        Ok(())
+    }
+
+    /// Number of payloads still awaiting delivery.
+    pub fn len(&self) -> Result<u64, db_error> {
+        let count = query_count(
+            &self.conn,
+            "SELECT COUNT(*) FROM pending_payloads",
+            NO_PARAMS,
+        )?;
+        Ok(count as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fresh_db_path(name: &str) -> String {
+        let dir = format!("/tmp/stacks-node-tests/event_queue/{}", name);
+        if fs::metadata(&dir).is_ok() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        format!("{}/queue.sqlite", dir)
+    }
+
+    #[test]
+    fn test_event_queue_fifo() {
+        let path = fresh_db_path("test_event_queue_fifo");
+        let mut db = EventQueueDB::connect(&path, 10).unwrap();
+
+        assert_eq!(db.peek().unwrap(), None);
+        db.push("new_block", "{\"a\":1}").unwrap();
+        db.push("new_burn_block", "{\"b\":2}").unwrap();
+        assert_eq!(db.len().unwrap(), 2);
+
+        let first = db.peek().unwrap().unwrap();
+        assert_eq!(first.path, "new_block");
+        assert_eq!(first.payload, "{\"a\":1}");
+
+        // still at the head until removed
+        assert_eq!(db.peek().unwrap().unwrap(), first);
+        db.remove(first.id).unwrap();
+
+        let second = db.peek().unwrap().unwrap();
+        assert_eq!(second.path, "new_burn_block");
+        db.remove(second.id).unwrap();
+        assert_eq!(db.peek().unwrap(), None);
+    }
+
+    #[test]
+    fn test_event_queue_survives_reopen() {
+        let path = fresh_db_path("test_event_queue_survives_reopen");
+        {
+            let mut db = EventQueueDB::connect(&path, 10).unwrap();
+            db.push("new_mempool_tx", "[]").unwrap();
+        }
+
+        let db = EventQueueDB::connect(&path, 10).unwrap();
+        let entry = db.peek().unwrap().unwrap();
+        assert_eq!(entry.path, "new_mempool_tx");
+        assert_eq!(entry.payload, "[]");
+    }
+
+    #[test]
+    fn test_event_queue_evicts_oldest() {
+        let path = fresh_db_path("test_event_queue_evicts_oldest");
+        let mut db = EventQueueDB::connect(&path, 3).unwrap();
+
+        for i in 0..3 {
+            assert_eq!(db.push("new_block", &format!("{}", i)).unwrap(), 0);
+        }
+        assert_eq!(db.push("new_block", "3").unwrap(), 1);
+        assert_eq!(db.len().unwrap(), 3);
+        assert_eq!(db.peek().unwrap().unwrap().payload, "1");
+    }
+}
@@ -23,6 +23,7 @@ pub mod monitoring;
 pub mod burnchains;
 pub mod config;
 pub mod event_dispatcher;
+pub mod event_queue;
 pub mod genesis_data;
 pub mod keychain;
 pub mod neon_node;
@@ -34,6 +35,7 @@ pub mod tenure;
 
 pub use self::burnchains::{
     BitcoinRegtestController, BurnchainController, BurnchainTip, MocknetController,
+    ReplayController,
 };
 pub use self::config::{Config, ConfigFile};
 pub use self::event_dispatcher::EventDispatcher;
@@ -45,6 +47,8 @@ pub use self::tenure::Tenure;
 
 use pico_args::Arguments;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use std::convert::TryInto;
 use std::panic;
@@ -52,6 +56,8 @@ use std::process;
 
 use backtrace::Backtrace;
 
+use stacks::chainstate::snapshot;
+
 fn main() {
     panic::set_hook(Box::new(|panic_info| {
         eprintln!("Process abort due to thread panic: {}", panic_info);
@@ -144,6 +150,24 @@ fn main() {
             );
             return;
         }
+        "export-snapshot" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            let snapshot_dir: String = args.value_from_str("--snapshot").unwrap();
+            let stacks_block_height: Option<u64> = args.opt_value_from_str("--height").unwrap();
+            args.finish().unwrap();
+            let conf = Config::from_config_file(ConfigFile::from_path(&config_path));
+            export_snapshot(&conf, &snapshot_dir, stacks_block_height);
+            return;
+        }
+        "import-snapshot" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            let snapshot_dir: String = args.value_from_str("--snapshot").unwrap();
+            let trusted_sortdb_path: String = args.value_from_str("--trusted-sortdb").unwrap();
+            args.finish().unwrap();
+            let conf = Config::from_config_file(ConfigFile::from_path(&config_path));
+            import_snapshot(&conf, &snapshot_dir, &trusted_sortdb_path);
+            return;
+        }
         _ => {
             print_help();
             return;
@@ -175,6 +199,88 @@ fn main() {
     }
 }
 
+/// The directory that holds the node's chain state, sortition DB, and burnchain headers
+fn snapshot_state_dir(conf: &Config) -> PathBuf {
+    let mut state_dir = conf.get_chainstate_path();
+    state_dir.pop();
+    state_dir
+}
+
+/// Snapshot the node's chain state, sortition DB, and burnchain headers (if it has any), with a
+/// manifest for the Stacks block at the given height on the canonical fork (or the canonical
+/// chain tip).  The node must not be running.
+fn export_snapshot(conf: &Config, snapshot_dir: &str, stacks_block_height: Option<u64>) {
+    let state_dir = snapshot_state_dir(conf);
+    let entries: Vec<&str> = ["chainstate", "burnchain", "headers.sqlite"]
+        .iter()
+        .filter(|entry| fs::metadata(state_dir.join(entry)).is_ok())
+        .map(|entry| *entry)
+        .collect();
+
+    match snapshot::export_snapshot(
+        &state_dir,
+        &entries,
+        &conf.get_burn_db_file_path(),
+        &conf.get_chainstate_path_str(),
+        conf.is_mainnet(),
+        conf.burnchain.chain_id,
+        stacks_block_height,
+        Path::new(snapshot_dir),
+    ) {
+        Ok(manifest) => {
+            println!(
+                "Exported snapshot of Stacks block {} (height {}) at burn block {} (height {}) to {}",
+                &manifest.index_block_hash,
+                manifest.stacks_block_height,
+                &manifest.burn_header_hash,
+                manifest.burn_block_height,
+                snapshot_dir
+            );
+            println!("Consensus hash: {}", &manifest.consensus_hash);
+            println!("MARF root hash: {}", &manifest.marf_root_hash);
+        }
+        Err(e) => {
+            eprintln!("Failed to export snapshot: {}", &e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Import a snapshot made by `export-snapshot` into the node's working directory, verifying it
+/// against its manifest and the manifest against a sortition DB synced from our own bitcoind.
+fn import_snapshot(conf: &Config, snapshot_dir: &str, trusted_sortdb_path: &str) {
+    let state_dir = snapshot_state_dir(conf);
+    if let Err(e) = fs::create_dir_all(&state_dir) {
+        eprintln!("Failed to create {:?}: {:?}", &state_dir, &e);
+        process::exit(1);
+    }
+
+    match snapshot::import_snapshot(
+        Path::new(snapshot_dir),
+        &state_dir,
+        &conf.get_burn_db_file_path(),
+        &conf.get_chainstate_path_str(),
+        trusted_sortdb_path,
+        conf.is_mainnet(),
+        conf.burnchain.chain_id,
+    ) {
+        Ok(manifest) => {
+            println!(
+                "Imported snapshot of Stacks block {} (height {}) at burn block {} (height {}) into {:?}",
+                &manifest.index_block_hash,
+                manifest.stacks_block_height,
+                &manifest.burn_header_hash,
+                manifest.burn_block_height,
+                &state_dir
+            );
+        }
+        Err(e) => {
+            eprintln!("Failed to import snapshot: {}", &e);
+            process::exit(1);
+        }
+    }
+}
+
 fn version() -> String {
     stacks::version_string(
         "stacks-node",
@@ -224,6 +330,28 @@ key-for-seed\tOutput the associated secret key for a burnchain signer created wi
 \t\tCan be passed a config file for the seed via the `--config=<file>` option *or* by supplying the hex seed on
 \t\tthe command line directly.
 
+export-snapshot\tExport a snapshot of the node's chain state, sortition DB, and burnchain headers, along with a
+\t\tmanifest of their hashes and of a Stacks block on the canonical fork. The node must not be running.
+\t\tArguments:
+\t\t  --config: path of the node's config.
+\t\t  --snapshot: directory to write the snapshot to. Must not already exist.
+\t\t  --height: (optional) height of the Stacks block the manifest describes. Defaults to the
+\t\t    canonical chain tip. Pick a block that importers' own sortition DBs have already seen.
+\t\tExample:
+\t\t  stacks-node export-snapshot --config=/path/to/config.toml --snapshot=/path/to/snapshot --height=40000
+
+import-snapshot\tBootstrap a node from a snapshot made by `export-snapshot`. The snapshot is verified against
+\t\tits manifest, and the manifest against a sortition DB that you synced from your own bitcoind.
+\t\tThe node's working directory must not already have chain state.
+\t\tArguments:
+\t\t  --config: path of the node's config.
+\t\t  --snapshot: directory to read the snapshot from.
+\t\t  --trusted-sortdb: path of a sortition DB synced from your own bitcoind past the snapshot's
+\t\t    burn block, e.g. another node's burnchain/sortition directory.
+\t\tExample:
+\t\t  stacks-node import-snapshot --config=/path/to/config.toml --snapshot=/path/to/snapshot \\
+\t\t    --trusted-sortdb=/path/to/synced/burnchain/sortition
+
 help\t\tDisplay this help.
 
 OPTIONAL ARGUMENTS:
@@ -108,6 +108,8 @@ pub struct InitializedNeonNode {
     is_miner: bool,
     pub atlas_config: AtlasConfig,
     leader_key_registration_state: LeaderKeyRegistrationState,
+    /// set through the admin RPC API to stop issuing tenures
+    mining_paused: Arc<AtomicBool>,
     pub p2p_thread_handle: JoinHandle<()>,
     pub relayer_thread_handle: JoinHandle<()>,
 }
@@ -624,12 +626,22 @@ fn spawn_peer(
     unconfirmed_txs: Arc<Mutex<UnconfirmedTxMap>>,
     event_observer: EventDispatcher,
     should_keep_running: Arc<AtomicBool>,
+    mining_paused: Arc<AtomicBool>,
 ) -> Result<JoinHandle<()>, NetError> {
     let burn_db_path = config.get_burn_db_file_path();
     let stacks_chainstate_path = config.get_chainstate_path_str();
     let exit_at_block_height = config.burnchain.process_exit_at_block_height;
 
     this.bind(p2p_sock, rpc_sock).unwrap();
+    if let (Some(admin_bind), Some(admin_auth_token)) =
+        (&config.node.admin_bind, &config.node.admin_auth_token)
+    {
+        let admin_sock: SocketAddr = admin_bind
+            .parse()
+            .expect(&format!("Failed to parse socket: {}", admin_bind));
+        this.bind_admin(&admin_sock, admin_auth_token.clone())
+            .unwrap();
+    }
     let (mut dns_resolver, mut dns_client) = DNSResolver::new(10);
     let sortdb = SortitionDB::open(&burn_db_path, false).map_err(NetError::DBError)?;
 
@@ -661,6 +673,7 @@ fn spawn_peer(
                 metric,
             )
             .expect("Database failure opening mempool");
+            mem_pool.set_limits(config.node.mempool_limits());
 
             // create estimators, metric instances for RPC handler
             let cost_estimator = config
@@ -679,6 +692,8 @@ fn spawn_peer(
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(metric.as_ref()),
                 fee_estimator: fee_estimator.as_ref().map(|x| x.as_ref()),
+                event_stream: event_observer.get_event_stream(),
+                mining_paused: Some(mining_paused.as_ref()),
                 ..RPCHandlerArgs::default()
             };
 
@@ -751,7 +766,9 @@ fn spawn_peer(
 
                         // only do this on the Ok() path, even if we're mining, because an error in
                         // network dispatching is likely due to resource exhaustion
-                        if mblock_deadline < get_epoch_time_ms() {
+                        if mblock_deadline < get_epoch_time_ms()
+                            && !mining_paused.load(Ordering::SeqCst)
+                        {
                             debug!("P2P: schedule microblock tenure");
                             results_with_data.push_back(RelayerDirective::RunMicroblockTenure(
                                 get_epoch_time_ms(),
@@ -861,6 +878,7 @@ fn spawn_miner_relayer(
 
         let mut mem_pool = MemPoolDB::open(is_mainnet, chain_id, &stacks_chainstate_path, cost_estimator, metric)
             .expect("Database failure opening mempool");
+        mem_pool.set_limits(config.node.mempool_limits());
 
         while let Ok(mut directive) = relay_channel.recv() {
             match directive {
@@ -1360,6 +1378,7 @@ impl InitializedNeonNode {
         )
         .expect("Failed to initialize mine/relay thread");
 
+        let mining_paused = Arc::new(AtomicBool::new(false));
         let p2p_thread_handle = spawn_peer(
             config.is_mainnet(),
             p2p_net,
@@ -1373,17 +1392,21 @@ impl InitializedNeonNode {
             shared_unconfirmed_txs,
             event_dispatcher,
             should_keep_running,
+            mining_paused.clone(),
         )
         .expect("Failed to initialize p2p thread");
 
         info!("Start HTTP server on: {}", &config.node.rpc_bind);
         info!("Start P2P server on: {}", &config.node.p2p_bind);
+        if let Some(ref admin_bind) = config.node.admin_bind {
+            info!("Start admin API server on: {}", admin_bind);
+        }
 
         let last_burn_block = last_burn_block.map(|x| x.block_snapshot);
 
         let is_miner = miner;
 
-        let atlas_config = AtlasConfig::default(config.is_mainnet());
+        let atlas_config = config.atlas.clone();
         InitializedNeonNode {
             config,
             relay_channel: relay_send,
@@ -1392,6 +1415,7 @@ impl InitializedNeonNode {
             is_miner,
             atlas_config,
             leader_key_registration_state,
+            mining_paused,
             p2p_thread_handle,
             relayer_thread_handle,
         }
@@ -1405,6 +1429,11 @@ impl InitializedNeonNode {
             return true;
         }
 
+        if self.mining_paused.load(Ordering::SeqCst) {
+            debug!("Tenure: skipped tenure because mining is paused");
+            return true;
+        }
+
         if let Some(burnchain_tip) = self.last_burn_block.clone() {
             match self.leader_key_registration_state {
                 LeaderKeyRegistrationState::Active(ref key) => {
@@ -188,6 +188,7 @@ fn spawn_peer(
             cost_metric: Some(metric.as_ref()),
             fee_estimator: fee_estimator.as_ref().map(|x| x.as_ref()),
             genesis_chainstate_hash,
+            event_stream: event_dispatcher.get_event_stream(),
             ..RPCHandlerArgs::default()
         };
 
@@ -227,6 +228,7 @@ fn spawn_peer(
                     continue;
                 }
             };
+            mem_pool.set_limits(config.node.mempool_limits());
 
             let mut expected_attachments = match attachments_rx.try_recv() {
                 Ok(expected_attachments) => expected_attachments,
@@ -350,7 +352,15 @@ impl Node {
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer, Arc::new(AtomicBool::new(true)));
+            event_dispatcher.register_observer(
+                observer,
+                &config.get_event_observer_queue_path(&observer.endpoint),
+                config.node.event_observer_max_queue_size,
+                Arc::new(AtomicBool::new(true)),
+            );
+        }
+        if let Some(hub) = config.make_event_stream_hub() {
+            event_dispatcher.register_event_stream(hub);
         }
 
         event_dispatcher.process_boot_receipts(receipts);
@@ -381,7 +391,15 @@ impl Node {
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer, Arc::new(AtomicBool::new(true)));
+            event_dispatcher.register_observer(
+                observer,
+                &config.get_event_observer_queue_path(&observer.endpoint),
+                config.node.event_observer_max_queue_size,
+                Arc::new(AtomicBool::new(true)),
+            );
+        }
+        if let Some(hub) = config.make_event_stream_hub() {
+            event_dispatcher.register_event_stream(hub);
         }
 
         let chainstate_path = config.get_chainstate_path_str();
@@ -515,7 +533,7 @@ impl Node {
             }
             tx.commit().unwrap();
         }
-        let atlas_config = AtlasConfig::default(false);
+        let atlas_config = self.config.atlas.clone();
         let atlasdb =
             AtlasDB::connect(atlas_config, &self.config.get_atlas_db_file_path(), true).unwrap();
 
@@ -838,7 +856,7 @@ impl Node {
                 }
             };
 
-        let atlas_config = AtlasConfig::default(false);
+        let atlas_config = self.config.atlas.clone();
         let mut processed_blocks = vec![];
         loop {
             let mut process_blocks_at_tip = {
@@ -25,7 +25,7 @@ use crate::syncctl::PoxSyncWatchdog;
 use crate::{
     node::{get_account_balances, get_account_lockups, get_names, get_namespaces},
     BitcoinRegtestController, BurnchainController, Config, EventDispatcher, Keychain,
-    NeonGenesisNode,
+    NeonGenesisNode, ReplayController,
 };
 
 use super::RunLoopCallbacks;
@@ -159,14 +159,30 @@ impl RunLoop {
         }
 
         // Initialize and start the burnchain.
-        let mut burnchain = BitcoinRegtestController::with_burnchain(
-            self.config.clone(),
-            Some(coordinator_senders.clone()),
-            burnchain_opt,
-            Some(should_keep_running.clone()),
-        );
+        let mut burnchain: Box<dyn BurnchainController> =
+            if self.config.burnchain.replay_fixture_path.is_some() {
+                match ReplayController::generic(
+                    self.config.clone(),
+                    coordinator_senders.clone(),
+                    burnchain_opt,
+                    Some(should_keep_running.clone()),
+                ) {
+                    Ok(controller) => controller,
+                    Err(e) => {
+                        error!("Failed to start burnchain replay: {}", e);
+                        panic!()
+                    }
+                }
+            } else {
+                Box::new(BitcoinRegtestController::with_burnchain(
+                    self.config.clone(),
+                    Some(coordinator_senders.clone()),
+                    burnchain_opt,
+                    Some(should_keep_running.clone()),
+                ))
+            };
 
-        let pox_constants = burnchain.get_pox_constants();
+        let pox_constants = burnchain.get_burnchain().pox_constants;
         let epochs = burnchain.get_stacks_epochs();
         if !check_chainstate_db_versions(
             &epochs,
@@ -180,7 +196,14 @@ impl RunLoop {
             );
         }
 
-        let is_miner = if self.config.node.miner {
+        let is_miner = if self.config.burnchain.replay_fixture_path.is_some() {
+            info!("Replaying burnchain fixture - will run as a Follower node");
+            false
+        } else if self.config.node.miner && self.config.burnchain.use_internal_wallet {
+            // the internal wallet can only look for UTXOs once the headers are synced
+            info!("Miner node: will check UTXOs with the internal wallet once headers are synced");
+            true
+        } else if self.config.node.miner {
             let keychain = Keychain::default(self.config.node.seed.clone());
             let node_address = Keychain::address_from_burnchain_signer(
                 &keychain.get_burnchain_signer(),
@@ -194,13 +217,18 @@ impl RunLoop {
             .unwrap();
             info!("Miner node: checking UTXOs at address: {}", btc_addr);
 
-            match burnchain.create_wallet_if_dne() {
+            let bitcoin_controller = BitcoinRegtestController::new_dummy(self.config.clone());
+            match bitcoin_controller.create_wallet_if_dne() {
                 Err(e) => warn!("Error when creating wallet: {:?}", e),
                 _ => {}
             }
 
-            let utxos =
-                burnchain.get_utxos(&keychain.generate_op_signer().get_public_key(), 1, None, 0);
+            let utxos = bitcoin_controller.get_utxos(
+                &keychain.generate_op_signer().get_public_key(),
+                1,
+                None,
+                0,
+            );
             if utxos.is_none() {
                 if self.config.node.mock_mining {
                     info!("No UTXOs found, but configured to mock mine");
@@ -230,6 +258,42 @@ impl RunLoop {
             }
         };
 
+        let is_miner = if is_miner && self.config.burnchain.use_internal_wallet {
+            let keychain = Keychain::default(self.config.node.seed.clone());
+            let node_address = Keychain::address_from_burnchain_signer(
+                &keychain.get_burnchain_signer(),
+                self.config.is_mainnet(),
+            );
+            let btc_addr = BitcoinAddress::from_bytes(
+                self.config.burnchain.get_bitcoin_network().1,
+                BitcoinAddressType::PublicKeyHash,
+                &node_address.to_bytes(),
+            )
+            .unwrap();
+            info!(
+                "Miner node: checking UTXOs at address {} with the internal wallet",
+                btc_addr
+            );
+
+            // scan the wallet's whole history here, so the miner thread only ever has to scan
+            // the few blocks that arrive between block-commits
+            let mut bitcoin_controller = BitcoinRegtestController::new_dummy(self.config.clone());
+            let balance = bitcoin_controller
+                .sync_wallet_balance(&keychain.generate_op_signer().get_public_key());
+            if balance.unwrap_or(0) > 0 {
+                info!("UTXOs found - will run as a Miner node");
+                true
+            } else if self.config.node.mock_mining {
+                info!("No UTXOs found, but configured to mock mine");
+                true
+            } else {
+                error!("UTXOs not found - switching off mining, will run as a Follower node. If this is unexpected, please ensure that the address {} was funded after burnchain.wallet_birth_height", btc_addr);
+                false
+            }
+        } else {
+            is_miner
+        };
+
         // Invoke connect() to perform any db instantiation early
         if let Err(e) = burnchain.connect_dbs() {
             error!("Failed to connect to burnchain databases: {}", e);
@@ -248,7 +312,17 @@ impl RunLoop {
         // setup dispatcher
         let mut event_dispatcher = EventDispatcher::new();
         for observer in self.config.events_observers.iter() {
-            event_dispatcher.register_observer(observer, should_keep_running.clone());
+            event_dispatcher.register_observer(
+                observer,
+                &self
+                    .config
+                    .get_event_observer_queue_path(&observer.endpoint),
+                self.config.node.event_observer_max_queue_size,
+                should_keep_running.clone(),
+            );
+        }
+        if let Some(hub) = self.config.make_event_stream_hub() {
+            event_dispatcher.register_event_stream(hub);
         }
 
         let use_test_genesis_data = use_test_genesis_chainstate(&self.config);
@@ -287,16 +361,19 @@ impl RunLoop {
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
         };
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             mainnet,
             chainid,
             &chainstate_path,
             Some(&mut boot_data),
         )
         .unwrap();
+        chain_state_db
+            .set_prune_depth(self.config.node.prune_depth)
+            .expect("FATAL: failed to set up chainstate pruning");
         coordinator_dispatcher.dispatch_boot_receipts(receipts);
 
-        let atlas_config = AtlasConfig::default(mainnet);
+        let atlas_config = self.config.atlas.clone();
         let moved_atlas_config = atlas_config.clone();
         let moved_config = self.config.clone();
 
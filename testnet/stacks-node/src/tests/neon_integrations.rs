@@ -777,7 +777,7 @@ fn liquid_ustx_integration() {
 
     let replaced_txid = submit_tx(&http_origin, &publish);
 
-    let publish = make_contract_publish(&spender_sk, 0, 1100, "caller", caller_src);
+    let publish = make_contract_publish(&spender_sk, 0, 1200, "caller", caller_src);
     submit_tx(&http_origin, &publish);
 
     let dropped_txs = test_observer::get_memtx_drops();